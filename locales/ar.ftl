@@ -0,0 +1,109 @@
+# Arabic. See en.ftl for the format.
+
+language-picker-label = اللغة:
+greeting = مرحباً @{ $login }
+
+## me.html
+
+me-title = أهدافي
+me-see-progress = اطّلع على تقدّمك
+me-saved = شكراً - تم حفظ أهدافك.
+me-set-goals = حدّد أهدافك لهذا السبرنت
+me-set-goals-help = ما الذي تريد إنجازه في هذا السبرنت، وما مدى ثقتك بذلك؟ سيرى مرشدوك هذا إلى جانب واجباتك، ليعرفوا أفضل طريقة لمساعدتك.
+me-module = الوحدة
+me-sprint = السبرنت
+me-goals = أهدافي
+me-confidence = ما مدى ثقتك؟
+me-save = حفظ
+me-previous-goals = أهدافك السابقة
+me-previous-goal = { $module } السبرنت { $sprint } ({ $date }) - { $confidence }: { $goals }
+confidence-1 = غير متأكد أبداً
+confidence-2 = غير متأكد
+confidence-3 = لا بأس
+confidence-4 = واثق
+confidence-5 = واثق جداً
+
+## me-progress.html
+
+progress-title = تقدّمي
+progress-heading = تقدّم @{ $login }
+progress-on-track = على المسار الصحيح
+progress-behind = متأخر قليلاً
+progress-at-risk = في خطر - يرجى التحدث إلى أحد المتطوعين
+progress-attended = حضرت { $attended } من أصل { $expected } حصة.
+progress-coursework = الواجبات
+progress-complete = { $count } مكتمل
+progress-submitted = { $count } تم تسليمه، بانتظار المراجعة أو التعديلات
+progress-missing = { $count } ناقص
+progress-missing-stretch = { $count } من المهام الإضافية لم يُنجز
+progress-not-due = { $count } لم يحن موعده بعد
+progress-back = العودة إلى أهدافك
+
+## check-in.html
+
+check-in-title = تسجيل الحضور
+check-in-done = شكراً - تم تسجيل حضورك في { $module } السبرنت { $sprint }.
+check-in-code-label = أدخل الرمز المعروض في الصف
+check-in-submit = سجّل حضوري
+check-in-wrong-code = هذا الرمز غير صحيح أو انتهت صلاحيته - تحقق من الشاشة للحصول على أحدث رمز.
+check-in-unknown-trainee = لا نعرف أي متدرب هو @{ $login } - يرجى أن تطلب من أحد المتطوعين إضافتك إلى سجل الحضور بدلاً من ذلك.
+check-in-too-many-attempts = لقد أدخلت عدداً كبيراً من الرموز الخاطئة - يرجى الانتظار 10 دقائق قبل المحاولة مرة أخرى، أو اطلب من أحد المتطوعين إضافتك إلى سجل الحضور بدلاً من ذلك.
+check-in-not-in-batch = @{ $login } ليس ضمن الدفعة التي يخص هذا الرمز - تأكد من أنك أدخلت الرمز المعروض في صفك، أو اطلب من أحد المتطوعين إضافتك إلى سجل الحضور بدلاً من ذلك.
+
+## me-leaderboard.html
+
+leaderboard-title = لوحة المتصدرين
+leaderboard-heading = لوحة المتصدرين لـ { $batch }
+leaderboard-explanation = السلسلة هي عدد السبرنتات المتتالية التي سلّمت فيها جميع واجباتك الإلزامية. حافظ على سلسلتك!
+leaderboard-trainee = المتدرب
+leaderboard-current-streak = السلسلة الحالية
+leaderboard-longest-streak = أطول سلسلة
+leaderboard-not-opted-in = دفعتك ليس لديها لوحة متصدرين.
+
+## PR validator comments
+
+validator-could-not-match = لم نتمكن من ربط طلب الدمج (PR) الخاص بك بأي واجب في هذه الوحدة.
+
+    يرجى التحقق من أن عنوانه بالصيغة الصحيحة، وأن لديك طلب دمج واحداً فقط لكل واجب.
+validator-body-template-not-filled-out = وصف طلب الدمج الخاص بك يحتوي على حقول من القالب لم تُملأ.
+
+    تأكد من أنك وضعت علامة على كل بنود قائمة التحقق الذاتي، وأن كل قسم يطلب منك كتابة إجابة إما مملوء أو محذوف.
+validator-body-template-not-filled-out-specific = وصف طلب الدمج الخاص بك غير مكتمل.
+
+    عندما أنشأت طلب الدمج هذا ملأت قالباً (يبدأ بـ "Learners, PR Template") - يمكنك رؤيته أعلى هذه الصفحة.
+
+    تأكد من ملء جميع الحقول في القالب.
+
+    يرجى التأكد من:
+    - [ ] وضع علامة على كل بنود قائمة التحقق الذاتي (باستخدام `[x]`)
+    - [ ] ملء قسم "Changelist" بتفاصيل ما يفعله طلب الدمج.
+    - [ ] ملء قسم "Questions" (إذا كانت لديك أسئلة) أو حذفه (إذا لم تكن لديك أسئلة).
+validator-template-missing-section = قسم "{ $heading }" غير موجود.
+validator-template-unticked-item = لم توضع علامة على "{ $item }".
+validator-template-prompt-left-in = يجب استبدال "{ $prompt }" بإجابتك، أو حذف القسم الذي يحتويه.
+validator-template-broken-link = الرابط { $url } لا يعمل.
+validator-bad-title = عنوان طلب الدمج الخاص بك ليس بالصيغة المتوقعة.
+
+    يرجى مراجعة صيغة العنوان المتوقعة وتعديل عنوانك ليطابقها.
+
+    السبب: { $reason }
+validator-title-wrong-number-of-parts = عدد الأجزاء المفصولة بعلامة | غير صحيح
+validator-title-bad-sprint = جزء السبرنت ({ $sprint }) لا يطابق الصيغة المتوقعة (مثال: 'Sprint 2' بدون علامات الاقتباس)
+validator-title-uppercase = يجب ألا يكون عنوان طلب الدمج كله بأحرف كبيرة
+validator-unknown-region = عنوان طلب الدمج الخاص بك لا يحتوي على منطقة معروفة.
+
+    يرجى مراجعة صيغة العنوان المتوقعة، والتأكد من أن منطقتك في المكان الصحيح ومكتوبة بشكل صحيح.
+validator-wrong-files = الملفات التي تغيّرت في طلب الدمج هذا لا تطابق المتوقع لهذا الواجب.
+
+    يرجى التحقق من أنك أضفت الملفات الصحيحة للواجب، وأنه لا توجد ملفات من سبرنتات أخرى أُضيفت بالخطأ.
+
+    يرجى مراجعة تبويب الملفات المتغيرة أعلى الصفحة، فنحن نتوقع تغييرات في هذا المجلد فقط: `{ $expected_files }`
+validator-no-files = طلب الدمج هذا لا يحتوي على أي ملفات.
+
+    يرجى التحقق من أنك أضفت الملفات الصحيحة ورفعتها (push) إلى المستودع
+validator-too-many-files = هناك ملفات كثيرة جداً في طلب الدمج هذا.
+
+    يرجى التأكد من أنك لم تُضف بالخطأ مجلد ذاكرة مؤقتة (cache) أو بيئة افتراضية (virtual environment) أو حزم npm.
+validator-footer = إذا لم يكن طلب الدمج هذا من الواجبات، يرجى إضافة التصنيف NotCoursework (وإرسال رسالة على Slack في #cyf-curriculum وإلا فلن ينتبه إليه أحد على الأرجح).
+
+    إذا كان طلب الدمج هذا يحتاج إلى مراجعة، يرجى إضافة التصنيف 'Needs Review' إليه بعد حل المشاكل المذكورة أعلاه.
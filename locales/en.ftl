@@ -0,0 +1,111 @@
+# Text on the pages trainees use and the PR validator's comments, in a small subset of Fluent (https://projectfluent.org/) syntax:
+# one `message-id = text` per line (continued on any indented lines after it), with `{ $name }` placeholders.
+# Every message here should also be in each of the other catalogues in this directory.
+
+language-picker-label = Language:
+greeting = Hi @{ $login }
+
+## me.html
+
+me-title = My goals
+me-see-progress = See your progress
+me-saved = Thanks - your goals have been saved.
+me-set-goals = Set your goals for this sprint
+me-set-goals-help = What do you want to get done this sprint, and how confident do you feel about it? Your mentors will see this alongside your coursework, so they know how best to help.
+me-module = Module
+me-sprint = Sprint
+me-goals = My goals
+me-confidence = How confident do you feel?
+me-save = Save
+me-previous-goals = Your previous goals
+me-previous-goal = { $module } sprint { $sprint } ({ $date }) - { $confidence }: { $goals }
+confidence-1 = Very unsure
+confidence-2 = Unsure
+confidence-3 = Okay
+confidence-4 = Confident
+confidence-5 = Very confident
+
+## me-progress.html
+
+progress-title = My progress
+progress-heading = @{ $login }'s progress
+progress-on-track = on track
+progress-behind = a bit behind
+progress-at-risk = at risk - please talk to a volunteer
+progress-attended = Attended { $attended } of { $expected } classes.
+progress-coursework = Coursework
+progress-complete = { $count } complete
+progress-submitted = { $count } submitted, waiting for review or changes
+progress-missing = { $count } missing
+progress-missing-stretch = { $count } stretch not done
+progress-not-due = { $count } not due yet
+progress-back = Back to your goals
+
+## check-in.html
+
+check-in-title = Check in
+check-in-done = Thanks - you're checked in to { $module } sprint { $sprint }.
+check-in-code-label = Enter the code shown in class
+check-in-submit = Check in
+check-in-wrong-code = That code isn't right, or has expired - check the screen for the latest code.
+check-in-unknown-trainee = We don't know which trainee @{ $login } is - please ask a volunteer to add you to the register instead.
+check-in-too-many-attempts = You've entered too many wrong codes - please wait 10 minutes before trying again, or ask a volunteer to add you to the register instead.
+check-in-not-in-batch = @{ $login } isn't in the batch this code is for - check you've entered the code shown in your own class, or ask a volunteer to add you to the register instead.
+
+## me-leaderboard.html
+
+leaderboard-title = Leaderboard
+leaderboard-heading = { $batch } leaderboard
+leaderboard-explanation = A streak is how many sprints in a row you've submitted all of your mandatory coursework for. Keep yours going!
+leaderboard-trainee = Trainee
+leaderboard-current-streak = Current streak
+leaderboard-longest-streak = Longest streak
+leaderboard-not-opted-in = Your batch doesn't have a leaderboard.
+
+## PR validator comments
+
+validator-could-not-match = Your PR couldn't be matched to an assignment in this module.
+
+    Please check its title is in the correct format, and that you only have one PR per assignment.
+validator-body-template-not-filled-out = Your PR description contained template fields which weren't filled in.
+
+    Check you've ticked everything in the self checklist, and that any sections which prompt you to fill in an answer are either filled in or removed.
+validator-body-template-not-filled-out-specific = Your PR description is incomplete.
+
+    You filled out a template (that starts "Learners, PR Template") when you created this PR - you can see it at the top of this page.
+
+    Make sure to fill in all fields in the template.
+
+    Please ensure:
+    - [ ] All self checklist items are ticked (with a `[x]`)
+    - [ ] The "Changelist" section is filled with details of what your PR does.
+    - [ ] The "Questions" section is either filled in (if you have questions) or is removed (if you don't).
+validator-template-missing-section = The "{ $heading }" section is missing.
+validator-template-unticked-item = "{ $item }" isn't ticked.
+validator-template-prompt-left-in = "{ $prompt }" needs replacing with your answer, or its section removing.
+validator-template-broken-link = The link { $url } doesn't work.
+validator-bad-title = Your PR's title isn't in the expected format.
+
+    Please check the expected title format, and update yours to match.
+
+    Reason: { $reason }
+validator-title-wrong-number-of-parts = Wrong number of parts separated by |s
+validator-title-bad-sprint = Sprint part ({ $sprint }) doesn't match expected format (example: 'Sprint 2', without quotes)
+validator-title-uppercase = PR title should not all be in uppercase
+validator-unknown-region = Your PR's title didn't contain a known region.
+
+    Please check the expected title format, and make sure your region is in the correct place and spelled correctly.
+validator-wrong-files = The changed files in this PR don't match what is expected for this task.
+
+    Please check that you committed the right files for the task, and that there are no accidentally committed files from other sprints.
+
+    Please review the changed files tab at the top of the page, we are only expecting changes in this directory: `{ $expected_files }`
+validator-no-files = This PR is missing any submitted files.
+
+    Please check that you committed the right files and pushed to the repository
+validator-too-many-files = There are too many files committed in this pull request.
+
+    Please check and make sure you have not accidentally committed a cache, virtual environment, or npm package directory.
+validator-footer = If this PR is not coursework, please add the NotCoursework label (and message on Slack in #cyf-curriculum or it will probably not be noticed).
+
+    If this PR needs reviewed, please add the 'Needs Review' label to this PR after you have resolved the issues listed above.
@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::course::{Attendance, Course, Submission, SubmissionState, TraineeWithSubmissions};
+
+/// When a run of missed classes gets its own badge and event.
+///
+/// Safeguarding policy treats consecutive absences differently from the same number of scattered ones,
+/// so this is kept apart from at-risk rules (which are only badges) and always counts the trainee's own region's classes in date order.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AbsenceStreakPolicy {
+    /// How many classes in a row a trainee needs to have missed to be flagged.
+    pub consecutive_absences: usize,
+}
+
+impl AbsenceStreakPolicy {
+    /// The trainee's current streak, if it's long enough to flag.
+    pub fn streak(&self, course: &Course, trainee: &TraineeWithSubmissions) -> Option<usize> {
+        let streak = absence_streak(course, trainee);
+        (self.consecutive_absences > 0 && streak >= self.consecutive_absences).then_some(streak)
+    }
+}
+
+/// How many of their region's classes in a row the trainee has missed, counting back from the most recent one.
+///
+/// Classes only held in other regions are skipped, as the trainee wasn't expected at them,
+/// and classes which haven't happened yet (or have no register) don't break a streak.
+pub fn absence_streak(course: &Course, trainee: &TraineeWithSubmissions) -> usize {
+    let region = &trainee.trainee.region;
+    let mut classes: Vec<(NaiveDate, bool)> = Vec::new();
+    for (module_name, module) in &course.modules {
+        let Some(trainee_module) = trainee.modules.get(module_name) else {
+            continue;
+        };
+        for (sprint, trainee_sprint) in module.sprints.iter().zip(&trainee_module.sprints) {
+            let Some(date) = sprint.dates.get(region) else {
+                continue;
+            };
+            for submission in &trainee_sprint.submissions {
+                if let SubmissionState::Some(Submission::Attendance(attendance)) = submission {
+                    classes.push((*date, matches!(attendance, Attendance::Absent { .. })));
+                }
+            }
+        }
+    }
+    // Stable, so classes on the same day stay in course order.
+    classes.sort_by_key(|(date, _)| *date);
+    classes
+        .iter()
+        .rev()
+        .take_while(|(_, absent)| *absent)
+        .count()
+}
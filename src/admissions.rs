@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use email_address::EmailAddress;
+use google_sheets4::api::CellData;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{
+    Error,
+    newtypes::new_case_insensitive_email_address,
+    sheets::{Sheet, SheetsClient, cell_formatted, cell_string},
+};
+
+/// Context from a trainee's application which helps mentors tailor their support.
+/// This may contain sensitive data, so must only be shown to staff.
+#[derive(Clone, Debug, Serialize)]
+pub struct EntryAssessment {
+    pub entry_test_score: String,
+    pub learning_support: String,
+    pub notes: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) enum MaybeEntryAssessment {
+    Some(EntryAssessment),
+    /// The viewer is allowed to see admissions data, but we don't have any for this trainee.
+    Unknown,
+    /// Either the viewer isn't allowed to see admissions data, or no admissions sheet is configured.
+    Hidden,
+}
+
+const EXPECTED_SHEET_NAME: &str = "Applications";
+
+const EMAIL_HEADING: &str = "Email";
+const ENTRY_TEST_SCORE_HEADING: &str = "Entry test score";
+const LEARNING_SUPPORT_HEADING: &str = "Learning support";
+const NOTES_HEADING: &str = "Notes";
+
+/// Reads entry assessments from the admissions sheet, keyed by trainee email.
+/// Returns None if the current user doesn't have access to the sheet, which means they aren't staff.
+pub(crate) async fn get_entry_assessments_if_permitted(
+    client: SheetsClient,
+    sheet_id: &str,
+) -> Result<Option<BTreeMap<EmailAddress, EntryAssessment>>, Error> {
+    let mut data = match client.get(sheet_id).await {
+        Ok(data) => data,
+        Err(Error::PermissionDenied(_)) => return Ok(None),
+        Err(err) => {
+            return Err(
+                err.with_context(|| format!("Failed to get admissions sheet with id {}", sheet_id))
+            );
+        }
+    };
+    let sheet = data.remove(EXPECTED_SHEET_NAME).ok_or_else(|| {
+        Error::Fatal(anyhow::anyhow!(
+            "Didn't find sheet '{}' in admissions sheet with id {}",
+            EXPECTED_SHEET_NAME,
+            sheet_id
+        ))
+    })?;
+    entry_assessments_from_sheet(&sheet)
+        .map(Some)
+        .map_err(|err| {
+            err.with_context(|| {
+                format!(
+                    "Failed to read entry assessments from sheet {}",
+                    EXPECTED_SHEET_NAME
+                )
+            })
+        })
+}
+
+fn entry_assessments_from_sheet(
+    sheet: &Sheet,
+) -> Result<BTreeMap<EmailAddress, EntryAssessment>, Error> {
+    let mut assessments = BTreeMap::new();
+
+    let Some(headings) = sheet.rows.first() else {
+        return Ok(assessments);
+    };
+    let headings: Vec<_> = headings.iter().map(cell_string).collect();
+    // The admissions team owns this sheet and adds columns as they need them, so look columns up by heading rather than position.
+    let column = |heading: &str| {
+        headings
+            .iter()
+            .position(|h| h.trim() == heading)
+            .ok_or_else(|| {
+                Error::Fatal(anyhow::anyhow!(
+                    "Admissions sheet didn't have a '{}' column",
+                    heading
+                ))
+            })
+    };
+    let email_column = column(EMAIL_HEADING)?;
+    let entry_test_score_column = column(ENTRY_TEST_SCORE_HEADING)?;
+    let learning_support_column = column(LEARNING_SUPPORT_HEADING)?;
+    let notes_column = column(NOTES_HEADING).ok();
+
+    let cell_at =
+        |cells: &[CellData], index: usize| cells.get(index).map(cell_formatted).unwrap_or_default();
+
+    for (row_index, cells) in sheet.rows.iter().enumerate().skip(1) {
+        let email = cell_at(cells, email_column);
+        if email.is_empty() {
+            continue;
+        }
+        let email = match new_case_insensitive_email_address(email.trim()) {
+            Ok(email) => email,
+            Err(err) => {
+                warn!(
+                    "Skipping admissions row {} with invalid email {}: {}",
+                    row_index + 1,
+                    email,
+                    err
+                );
+                continue;
+            }
+        };
+        assessments.insert(
+            email,
+            EntryAssessment {
+                entry_test_score: cell_at(cells, entry_test_score_column),
+                learning_support: cell_at(cells, learning_support_column),
+                notes: notes_column
+                    .map(|column| cell_at(cells, column))
+                    .unwrap_or_default(),
+            },
+        );
+    }
+    Ok(assessments)
+}
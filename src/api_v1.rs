@@ -0,0 +1,222 @@
+//! Versioned API endpoints, which other services (e.g. a trainee portal or mobile client) can rely on.
+//!
+//! Unlike the unversioned `/api` endpoints, which exist to back our own pages and change with them,
+//! fields here must not be removed or change meaning - add a `/api/v2` instead.
+
+use axum::{
+    Json,
+    extract::{OriginalUri, Path, State},
+};
+use http::HeaderMap;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tower_sessions::Session;
+use tracing::info;
+
+use crate::{
+    Config, Error, ServerState,
+    course::{
+        Assignment, AssignmentOptionality, Submission, SubmissionState, TraineeInBatch,
+        TraineeStatus, TraineeWithSubmissions, find_trainee_in_batches,
+    },
+    frontend::current_github_login,
+    newtypes::GithubLogin,
+    octocrab::{octocrab, octocrab_for_token},
+    prs::PrState,
+    sheets::SheetsClient,
+};
+
+#[derive(Serialize)]
+pub struct TraineeProgressV1 {
+    pub github_login: GithubLogin,
+    pub course: String,
+    pub batch: String,
+    /// One of `on_track`, `behind` or `at_risk`.
+    pub status: &'static str,
+    /// From 0 to 100.
+    pub progress_percent: u64,
+    pub attendance: AttendanceV1,
+    /// Ways the trainee falls short of the course's attendance policy, e.g. "Attended 75% of classes (minimum 80%)".
+    /// Empty if they meet it, or the course doesn't have one.
+    pub attendance_policy_breaches: Vec<String>,
+    pub modules: Vec<ModuleProgressV1>,
+}
+
+#[derive(Serialize)]
+pub struct AttendanceV1 {
+    pub attended: usize,
+    pub expected: usize,
+}
+
+/// Counts of the module's coursework (PRs and project repos) in each state.
+#[derive(Default, Serialize)]
+pub struct ModuleProgressV1 {
+    pub name: String,
+    /// Submitted and marked as complete by a reviewer.
+    pub complete: usize,
+    /// Submitted, but not yet complete.
+    pub submitted: usize,
+    /// Mandatory work which is due but hasn't been submitted.
+    pub missing: usize,
+    /// Stretch work which is due but hasn't been submitted.
+    pub missing_stretch: usize,
+    /// Work for sprints which haven't happened yet.
+    pub not_due: usize,
+}
+
+/// The trainee's progress through their course.
+///
+/// Trainees can see their own progress when signed in with GitHub.
+/// Other services can see anyone's by sending one of the configured `api_tokens`.
+pub async fn get_trainee_progress(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(github_login): Path<String>,
+) -> Result<Json<TraineeProgressV1>, Error> {
+    let github_login = GithubLogin::from(github_login);
+    match api_token_name(&server_state.config, &headers) {
+        Some(name) => info!(api_token = name, %github_login, "API token used"),
+        None => {
+            let octocrab = octocrab(&session, &server_state, original_uri).await?;
+            if current_github_login(&octocrab).await? != github_login {
+                return Err(Error::UserFacing(
+                    "You can only see your own progress".to_owned(),
+                ));
+            }
+        }
+    }
+    load_trainee_progress(&server_state, github_login)
+        .await
+        .map(Json)
+}
+
+/// Looks up a trainee's progress as the tracker itself, as trainees can't read the staff-only sheets it's worked out from.
+/// Callers must check the current user is allowed to see it.
+pub(crate) async fn load_trainee_progress(
+    server_state: &ServerState,
+    github_login: GithubLogin,
+) -> Result<TraineeProgressV1, Error> {
+    let Some(service_credentials) = &server_state.config.service_credentials else {
+        return Err(Error::UserFacing(
+            "The trainee tracker isn't configured with service credentials, so can't look up progress.".to_owned(),
+        ));
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await?;
+    let Some(trainee_in_batch) = find_trainee_in_batches(
+        &octocrab,
+        sheets_client,
+        &server_state.config,
+        &github_login,
+    )
+    .await?
+    else {
+        return Err(Error::UserFacing(format!(
+            "@{} isn't in any batch the tracker knows about",
+            github_login
+        )));
+    };
+    Ok(trainee_progress(&server_state.config, trainee_in_batch))
+}
+
+/// The name of the configured API token the request was authorised with, if any.
+fn api_token_name<'a>(config: &'a Config, headers: &HeaderMap) -> Option<&'a str> {
+    let token = headers
+        .get(http::header::AUTHORIZATION)?
+        .as_bytes()
+        .strip_prefix(b"Bearer ")?;
+    // Comparing digests rather than the tokens themselves means how long the comparison takes doesn't reveal how much of a token was right.
+    let digest = Sha256::digest(token);
+    config
+        .api_tokens
+        .iter()
+        .find(|api_token| Sha256::digest(api_token.token.as_bytes()) == digest)
+        .map(|api_token| api_token.name.as_str())
+}
+
+fn trainee_progress(config: &Config, trainee_in_batch: TraineeInBatch) -> TraineeProgressV1 {
+    let TraineeInBatch {
+        course_name,
+        batch_github_slug,
+        trainee,
+        ..
+    } = trainee_in_batch;
+    let attendance = trainee.attendance();
+    let attendance_policy_breaches = config
+        .attendance_policy(&course_name)
+        .map(|policy| {
+            policy
+                .breaches(&trainee)
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    TraineeProgressV1 {
+        github_login: trainee.trainee.github_login.clone(),
+        course: course_name,
+        batch: batch_github_slug,
+        status: match trainee.status() {
+            TraineeStatus::OnTrack => "on_track",
+            TraineeStatus::Behind => "behind",
+            TraineeStatus::AtRisk => "at_risk",
+        },
+        progress_percent: trainee.progress_score() / 100,
+        attendance: AttendanceV1 {
+            attended: attendance.numerator,
+            expected: attendance.denominator,
+        },
+        attendance_policy_breaches,
+        modules: module_progress(&trainee),
+    }
+}
+
+fn module_progress(trainee: &TraineeWithSubmissions) -> Vec<ModuleProgressV1> {
+    trainee
+        .modules
+        .iter()
+        .map(|(module_name, module)| {
+            let mut progress = ModuleProgressV1 {
+                name: module_name.clone(),
+                ..Default::default()
+            };
+            for submission in module.sprints.iter().flat_map(|sprint| &sprint.submissions) {
+                match submission {
+                    // Attendance is summarised across the whole course instead.
+                    SubmissionState::Some(Submission::Attendance(_))
+                    | SubmissionState::MissingButExpected(Assignment::Attendance { .. })
+                    | SubmissionState::MissingButNotExpected(Assignment::Attendance { .. }) => {}
+                    SubmissionState::Some(Submission::PullRequest { pull_request, .. }) => {
+                        if pull_request.state == PrState::Complete {
+                            progress.complete += 1;
+                        } else {
+                            progress.submitted += 1;
+                        }
+                    }
+                    SubmissionState::Some(Submission::ProjectRepo(project_repo)) => {
+                        if project_repo.is_complete() {
+                            progress.complete += 1;
+                        } else {
+                            progress.submitted += 1;
+                        }
+                    }
+                    SubmissionState::MissingButExpected(assignment) => {
+                        match assignment.optionality() {
+                            AssignmentOptionality::Mandatory => progress.missing += 1,
+                            AssignmentOptionality::Stretch => progress.missing_stretch += 1,
+                        }
+                    }
+                    SubmissionState::MissingStretch(_) => progress.missing_stretch += 1,
+                    SubmissionState::MissingButNotExpected(_) => progress.not_due += 1,
+                }
+            }
+            progress
+        })
+        .collect()
+}
@@ -0,0 +1,490 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use askama::Template;
+use axum::{
+    Form,
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::{DateTime, Utc};
+use http::HeaderMap;
+use serde::Deserialize;
+use serde_env_field::EnvField;
+use serde_json::json;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    audit_log::{AuditEvent, record_audit_event},
+    frontend::{current_github_login, get_course_and_batch, record_store},
+    github_accounts::Trainee,
+    impersonation::forbid_while_viewing_as,
+    newtypes::GithubLogin,
+    octocrab::octocrab,
+    persistence::RecordStore,
+};
+
+/// The audit log action for staff re-inviting a trainee, which counts as a nudge on the staff workload dashboard.
+pub const REINVITE_AUDIT_ACTION: &str = "re-invite to assessment";
+
+const ASSESSMENTS_TAB: &str = "Timed assessments";
+const ASSESSMENTS_HEADINGS: [&str; 5] = ["Email", "Assessment", "Event", "Recorded", "Score"];
+
+/// Timed assessments (e.g. Codility tests) trainees take, and how to invite them.
+#[derive(Clone, Deserialize)]
+pub struct TimedAssessmentConfig {
+    /// Codility API token, used to send invitations.
+    pub codility_api_token: EnvField<String>,
+    /// Codility test IDs, keyed by the name staff know each assessment by.
+    pub tests: BTreeMap<String, u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssessmentEventKind {
+    Invited,
+    Started,
+    Completed,
+}
+
+impl AssessmentEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssessmentEventKind::Invited => "invited",
+            AssessmentEventKind::Started => "started",
+            AssessmentEventKind::Completed => "completed",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        [
+            AssessmentEventKind::Invited,
+            AssessmentEventKind::Started,
+            AssessmentEventKind::Completed,
+        ]
+        .into_iter()
+        .find(|kind| kind.as_str() == s)
+    }
+
+    pub fn display_text(&self) -> &'static str {
+        match self {
+            AssessmentEventKind::Invited => "Invited",
+            AssessmentEventKind::Started => "Started",
+            AssessmentEventKind::Completed => "Completed",
+        }
+    }
+}
+
+/// Something which happened to a trainee's attempt at a timed assessment.
+/// Attempts are keyed by email, because that's what Codility knows trainees by.
+#[derive(Clone, Debug)]
+pub struct AssessmentEvent {
+    pub email: String,
+    pub assessment: String,
+    pub kind: AssessmentEventKind,
+    pub recorded_at: DateTime<Utc>,
+    /// Percentage, only for completed attempts.
+    pub score: Option<u32>,
+}
+
+pub async fn record_assessment_event(
+    store: &RecordStore,
+    event: &AssessmentEvent,
+) -> Result<(), Error> {
+    store
+        .append(
+            ASSESSMENTS_TAB,
+            &ASSESSMENTS_HEADINGS,
+            vec![
+                event.email.to_ascii_lowercase(),
+                event.assessment.clone(),
+                event.kind.as_str().to_owned(),
+                event.recorded_at.to_rfc3339(),
+                event
+                    .score
+                    .map(|score| score.to_string())
+                    .unwrap_or_default(),
+            ],
+        )
+        .await
+}
+
+/// Where a trainee has got to with an assessment, from the latest of its events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssessmentStatus {
+    pub kind: AssessmentEventKind,
+    pub since: DateTime<Utc>,
+    pub score: Option<u32>,
+}
+
+impl AssessmentStatus {
+    pub fn score_band(&self) -> Option<ScoreBand> {
+        self.score.map(ScoreBand::for_score)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScoreBand {
+    Below50,
+    From50To79,
+    AtLeast80,
+}
+
+impl ScoreBand {
+    pub const ALL: [ScoreBand; 3] = [
+        ScoreBand::Below50,
+        ScoreBand::From50To79,
+        ScoreBand::AtLeast80,
+    ];
+
+    pub fn for_score(score: u32) -> Self {
+        match score {
+            0..50 => ScoreBand::Below50,
+            50..80 => ScoreBand::From50To79,
+            _ => ScoreBand::AtLeast80,
+        }
+    }
+
+    pub fn display_text(&self) -> &'static str {
+        match self {
+            ScoreBand::Below50 => "Below 50%",
+            ScoreBand::From50To79 => "50-79%",
+            ScoreBand::AtLeast80 => "80%+",
+        }
+    }
+}
+
+/// Each attempt's status, keyed by (lower-cased email, assessment).
+/// A re-invitation starts a fresh attempt, so any earlier start or score no longer counts.
+pub fn assessment_statuses(
+    events: impl IntoIterator<Item = AssessmentEvent>,
+) -> BTreeMap<(String, String), AssessmentStatus> {
+    let mut statuses = BTreeMap::new();
+    for event in events {
+        statuses.insert(
+            (event.email.to_ascii_lowercase(), event.assessment),
+            AssessmentStatus {
+                kind: event.kind,
+                since: event.recorded_at,
+                score: event.score,
+            },
+        );
+    }
+    statuses
+}
+
+/// Every recorded assessment event, oldest first.
+pub async fn get_assessment_events(store: &RecordStore) -> Result<Vec<AssessmentEvent>, Error> {
+    Ok(store
+        .read(ASSESSMENTS_TAB)
+        .await?
+        .into_iter()
+        .map(|row| {
+            // The score is blank, and so missing from the row, unless the attempt was completed.
+            let (email, assessment, kind, recorded_at, score) = match row.as_slice() {
+                [email, assessment, kind, recorded_at] => {
+                    (email, assessment, kind, recorded_at, None)
+                }
+                [email, assessment, kind, recorded_at, score] => {
+                    (email, assessment, kind, recorded_at, Some(score))
+                }
+                _ => return Err(anyhow::anyhow!("Expected 5 columns, got {}", row.len())),
+            };
+            Ok(AssessmentEvent {
+                email: email.clone(),
+                assessment: assessment.clone(),
+                kind: AssessmentEventKind::from_str(kind)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown event {}", kind))?,
+                recorded_at: DateTime::parse_from_rfc3339(recorded_at)
+                    .context("Failed to parse recorded time")?
+                    .to_utc(),
+                score: score
+                    .filter(|score| !score.is_empty())
+                    .map(|score| score.parse())
+                    .transpose()
+                    .context("Failed to parse score")?,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .context("Failed to read timed assessments")?)
+}
+
+/// Sends the trainee a Codility invitation to the test.
+pub async fn send_codility_invitation(
+    client: &reqwest::Client,
+    config: &TimedAssessmentConfig,
+    test_id: u64,
+    trainee: &Trainee,
+) -> Result<(), Error> {
+    let (first_name, last_name) = trainee
+        .display_name()
+        .split_once(' ')
+        .unwrap_or((trainee.display_name(), ""));
+    client
+        .post(format!(
+            "https://codility.com/api/tests/{}/invite/",
+            test_id
+        ))
+        .bearer_auth(config.codility_api_token.as_str())
+        .json(&json!({
+            "candidates": [{
+                "email": trainee.email.to_string(),
+                "first_name": first_name,
+                "last_name": last_name,
+            }],
+        }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("Failed to send Codility invitation")?;
+    Ok(())
+}
+
+/// Counts for one assessment across a batch, for the top of the dashboard.
+pub struct AssessmentSummary {
+    pub assessment: String,
+    pub not_invited: usize,
+    pub invited: usize,
+    pub started: usize,
+    pub completed: usize,
+    pub bands: Vec<(ScoreBand, usize)>,
+}
+
+pub struct TraineeAssessments {
+    pub name: String,
+    pub github_login: GithubLogin,
+    /// One per configured assessment, in the same order as the summaries.
+    pub statuses: Vec<Option<AssessmentStatus>>,
+}
+
+fn timed_assessment_config(server_state: &ServerState) -> Result<&TimedAssessmentConfig, Error> {
+    server_state
+        .config
+        .timed_assessments
+        .as_ref()
+        .ok_or_else(|| {
+            Error::UserFacing(
+                "Timed assessments aren't set up - add timed_assessments to the config".to_owned(),
+            )
+        })
+}
+
+pub async fn get_assessments(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    let config = timed_assessment_config(&server_state)?;
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let store = record_store(&server_state).await?;
+    let statuses = assessment_statuses(get_assessment_events(&store).await?);
+
+    let trainees = batch
+        .trainees
+        .iter()
+        .map(|trainee| TraineeAssessments {
+            name: trainee.trainee.display_name().to_owned(),
+            github_login: trainee.trainee.github_login.clone(),
+            statuses: config
+                .tests
+                .keys()
+                .map(|assessment| {
+                    statuses
+                        .get(&(
+                            trainee.trainee.email.to_string().to_ascii_lowercase(),
+                            assessment.clone(),
+                        ))
+                        .cloned()
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let summaries = config
+        .tests
+        .keys()
+        .enumerate()
+        .map(|(index, assessment)| {
+            let statuses = trainees
+                .iter()
+                .map(|trainee| trainee.statuses[index].as_ref())
+                .collect::<Vec<_>>();
+            let count = |kind| {
+                statuses
+                    .iter()
+                    .filter(|status| status.is_some_and(|status| status.kind == kind))
+                    .count()
+            };
+            AssessmentSummary {
+                assessment: assessment.clone(),
+                not_invited: statuses.iter().filter(|status| status.is_none()).count(),
+                invited: count(AssessmentEventKind::Invited),
+                started: count(AssessmentEventKind::Started),
+                completed: count(AssessmentEventKind::Completed),
+                bands: ScoreBand::ALL
+                    .into_iter()
+                    .map(|band| {
+                        let in_band = statuses
+                            .iter()
+                            .filter(|status| {
+                                status.is_some_and(|status| status.score_band() == Some(band))
+                            })
+                            .count();
+                        (band, in_band)
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    Ok(Html(
+        AssessmentsTemplate {
+            course_name: course.name,
+            batch_name: batch.name,
+            batch_github_slug: batch.github_team_slug,
+            summaries,
+            trainees,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ReinviteForm {
+    github_login: GithubLogin,
+    assessment: String,
+}
+
+/// Sends a fresh invitation, e.g. because the trainee's last one expired before they took the test.
+pub async fn post_reinvite(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+    Form(form): Form<ReinviteForm>,
+) -> Result<axum::response::Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let config = timed_assessment_config(&server_state)?;
+    let Some(test_id) = config.tests.get(&form.assessment) else {
+        return Err(Error::UserFacing(format!(
+            "Unknown assessment {}",
+            form.assessment
+        )));
+    };
+    let staff =
+        current_github_login(&octocrab(&session, &server_state, original_uri.clone()).await?)
+            .await?;
+    // Only staff can load the batch, so this also stops trainees inviting themselves.
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let Some(trainee) = batch
+        .trainees
+        .iter()
+        .find(|trainee| trainee.trainee.github_login == form.github_login)
+    else {
+        return Err(Error::UserFacing(format!(
+            "{} isn't in {}",
+            form.github_login, batch.name
+        )));
+    };
+    send_codility_invitation(
+        &server_state.http_clients.codility,
+        config,
+        *test_id,
+        &trainee.trainee,
+    )
+    .await?;
+    let store = record_store(&server_state).await?;
+    record_audit_event(
+        &store,
+        &AuditEvent {
+            recorded_at: Utc::now(),
+            actor: staff,
+            action: REINVITE_AUDIT_ACTION,
+            detail: format!("@{} to {}", trainee.trainee.github_login, form.assessment),
+        },
+    )
+    .await?;
+    record_assessment_event(
+        &store,
+        &AssessmentEvent {
+            email: trainee.trainee.email.to_string(),
+            assessment: form.assessment,
+            kind: AssessmentEventKind::Invited,
+            recorded_at: Utc::now(),
+            score: None,
+        },
+    )
+    .await?;
+    Ok(axum::response::Redirect::to(&format!(
+        "/courses/{}/batches/{}/assessments",
+        course.name, batch_github_slug
+    )))
+}
+
+#[derive(Template)]
+#[template(path = "assessments.html")]
+struct AssessmentsTemplate {
+    course_name: String,
+    batch_name: String,
+    batch_github_slug: String,
+    summaries: Vec<AssessmentSummary>,
+    trainees: Vec<TraineeAssessments>,
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Utc};
+
+    use super::{AssessmentEvent, AssessmentEventKind, ScoreBand, assessment_statuses};
+
+    fn event(kind: AssessmentEventKind, recorded_at: &str, score: Option<u32>) -> AssessmentEvent {
+        AssessmentEvent {
+            email: "Trainee@Example.com".to_owned(),
+            assessment: "Entry test".to_owned(),
+            kind,
+            recorded_at: recorded_at.parse::<DateTime<Utc>>().unwrap(),
+            score,
+        }
+    }
+
+    #[test]
+    fn reinvitation_starts_a_fresh_attempt() {
+        let statuses = assessment_statuses([
+            event(AssessmentEventKind::Invited, "2026-09-01T10:00:00Z", None),
+            event(
+                AssessmentEventKind::Completed,
+                "2026-09-03T10:00:00Z",
+                Some(42),
+            ),
+            event(AssessmentEventKind::Invited, "2026-09-10T10:00:00Z", None),
+        ]);
+        let status = &statuses[&("trainee@example.com".to_owned(), "Entry test".to_owned())];
+        assert_eq!(status.kind, AssessmentEventKind::Invited);
+        assert_eq!(status.score_band(), None);
+    }
+
+    #[test]
+    fn bands_scores() {
+        assert_eq!(ScoreBand::for_score(49), ScoreBand::Below50);
+        assert_eq!(ScoreBand::for_score(50), ScoreBand::From50To79);
+        assert_eq!(ScoreBand::for_score(100), ScoreBand::AtLeast80);
+    }
+}
@@ -0,0 +1,404 @@
+//! Comparing a module's assignments between two points in time, or two repos (e.g. last year's copy of a module and this year's),
+//! so that staff can see why an older batch's data looks different after the curriculum changed.
+//!
+//! Issues' labels aren't versioned, so an assignment as it was at a point in time is its issue if it was open then, with the labels it has now.
+
+use axum::{
+    Json,
+    extract::{OriginalUri, Path, Query, State},
+};
+use chrono::NaiveDate;
+use octocrab::{
+    Octocrab,
+    models::{IssueState, issues::Issue},
+    params::State as IssueStateParam,
+};
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+use tracing::warn;
+
+use crate::{
+    Error, ServerState,
+    config::{SprintSource, SubmitLabelTaxonomy},
+    course::{Assignment, AssignmentOptionality, parse_issue},
+    endpoints::first_batch_course_schedule,
+    module_discovery::get_module_metadata,
+    octocrab::{all_pages, octocrab, octocrab_or_shared},
+};
+
+/// How similar two titles must be (from 0 to 1) for an assignment which disappeared and one which appeared to count as a rename.
+const RENAME_THRESHOLD: f64 = 0.6;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ParsedAssignment {
+    pub title: String,
+    pub sprint: usize,
+    pub optionality: AssignmentOptionality,
+    pub issue_number: u64,
+    pub issue_url: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RenamedAssignment {
+    pub from: ParsedAssignment,
+    pub to: ParsedAssignment,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AssignmentDiff {
+    pub added: Vec<ParsedAssignment>,
+    pub removed: Vec<ParsedAssignment>,
+    pub renamed: Vec<RenamedAssignment>,
+    /// How many assignments have the same title in both.
+    pub unchanged: usize,
+}
+
+/// An issue which looks like an assignment but couldn't be parsed, e.g. because it has two sprint labels.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct UnparseableIssue {
+    pub repo: String,
+    pub issue_number: u64,
+}
+
+#[derive(Serialize)]
+pub struct AssignmentDiffResponse {
+    #[serde(flatten)]
+    pub diff: AssignmentDiff,
+    /// Issues left out of the comparison. Old issues can be labelled in ways which are no longer valid, and shouldn't stop the rest being compared.
+    pub unparseable_issues: Vec<UnparseableIssue>,
+}
+
+/// What changed between two lists of a module's assignments.
+///
+/// Assignments with the same title are the same. Otherwise, within one repo an issue keeps its number when renamed;
+/// across repos numbers mean nothing, so a removed and an added assignment in the same sprint with similar titles are taken to be a rename.
+pub fn diff_assignments(
+    from: &[ParsedAssignment],
+    to: &[ParsedAssignment],
+    same_repo: bool,
+) -> AssignmentDiff {
+    let mut removed = from.to_vec();
+    let mut added = to.to_vec();
+    let mut diff = AssignmentDiff::default();
+
+    removed.retain(|old| {
+        match added
+            .iter()
+            .position(|new| normalise_title(&new.title) == normalise_title(&old.title))
+        {
+            Some(index) => {
+                added.remove(index);
+                diff.unchanged += 1;
+                false
+            }
+            None => true,
+        }
+    });
+
+    removed.retain(|old| {
+        let renamed_to = if same_repo {
+            added
+                .iter()
+                .position(|new| new.issue_number == old.issue_number)
+        } else {
+            added
+                .iter()
+                .enumerate()
+                .filter(|(_, new)| new.sprint == old.sprint)
+                .map(|(index, new)| {
+                    (
+                        index,
+                        strsim::normalized_levenshtein(
+                            &normalise_title(&old.title),
+                            &normalise_title(&new.title),
+                        ),
+                    )
+                })
+                .filter(|(_, similarity)| *similarity >= RENAME_THRESHOLD)
+                .max_by(|(_, left), (_, right)| left.total_cmp(right))
+                .map(|(index, _)| index)
+        };
+        match renamed_to {
+            Some(index) => {
+                diff.renamed.push(RenamedAssignment {
+                    from: old.clone(),
+                    to: added.remove(index),
+                });
+                false
+            }
+            None => true,
+        }
+    });
+
+    diff.added = added;
+    diff.removed = removed;
+    diff
+}
+
+fn normalise_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[derive(Deserialize)]
+pub struct AssignmentDiffParams {
+    /// The repo to compare from. Defaults to the module's own. Repos which aren't the module's need the caller to be signed in.
+    from_repo: Option<String>,
+    /// Compare from the assignments open on this date. Defaults to those open now.
+    from_date: Option<NaiveDate>,
+    /// The repo to compare to. Defaults to the module's own. Repos which aren't the module's need the caller to be signed in.
+    to_repo: Option<String>,
+    /// Compare to the assignments open on this date. Defaults to those open now.
+    to_date: Option<NaiveDate>,
+}
+
+/// How a module's assignments differ between two points in time and/or repos.
+pub async fn get_assignment_diff(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, module)): Path<(String, String)>,
+    Query(params): Query<AssignmentDiffParams>,
+) -> Result<Json<AssignmentDiffResponse>, Error> {
+    let course_schedule = first_batch_course_schedule(&server_state, &course)?;
+    if !course_schedule
+        .course_schedule
+        .sprints
+        .contains_key(&module)
+    {
+        return Err(Error::UserFacing(format!(
+            "Unknown module {module} in course {course}"
+        )));
+    }
+    let from_repo = params.from_repo.unwrap_or_else(|| module.clone());
+    let to_repo = params.to_repo.unwrap_or_else(|| module.clone());
+    if from_repo == to_repo && params.from_date == params.to_date {
+        return Err(Error::UserFacing(
+            "Nothing to compare - give a from_date or to_date, or a from_repo or to_repo which isn't the module's own".to_owned(),
+        ));
+    }
+
+    // The module's own repos are public course material, so anyone can compare them, as with the course pages.
+    // Any other repo could be private, so must be read with the caller's own token, not the shared ones.
+    let module_repos = course_schedule.module_repos(&module);
+    let octocrab = if module_repos.contains(&from_repo) && module_repos.contains(&to_repo) {
+        octocrab_or_shared(&session, &server_state, original_uri).await?
+    } else {
+        octocrab(&session, &server_state, original_uri).await?
+    };
+    let github_org = &server_state.config.github_org;
+    // As when tracking, the module's tracker.toml can add submit types.
+    let submit_labels = match get_module_metadata(&octocrab, github_org, &module).await? {
+        Some(metadata) => SubmitLabelTaxonomy(
+            metadata
+                .submit_types
+                .into_iter()
+                .chain(course_schedule.submit_labels.0.clone())
+                .collect(),
+        ),
+        None => course_schedule.submit_labels.clone(),
+    };
+    let sprint_source = course_schedule
+        .module_settings
+        .get(&module)
+        .map(|settings| settings.sprint_source)
+        .unwrap_or_default();
+
+    let (from_issues, to_issues) = if from_repo == to_repo {
+        let issues = get_all_issues(&octocrab, github_org, &from_repo).await?;
+        (issues.clone(), issues)
+    } else {
+        (
+            get_all_issues(&octocrab, github_org, &from_repo).await?,
+            get_all_issues(&octocrab, github_org, &to_repo).await?,
+        )
+    };
+    let (from, from_unparseable) = parse_assignments_at(
+        &from_issues,
+        params.from_date,
+        &module,
+        &submit_labels,
+        sprint_source,
+    );
+    let (to, to_unparseable) = parse_assignments_at(
+        &to_issues,
+        params.to_date,
+        &module,
+        &submit_labels,
+        sprint_source,
+    );
+    let mut unparseable_issues = from_unparseable
+        .into_iter()
+        .map(|issue_number| UnparseableIssue {
+            repo: from_repo.clone(),
+            issue_number,
+        })
+        .chain(
+            to_unparseable
+                .into_iter()
+                .map(|issue_number| UnparseableIssue {
+                    repo: to_repo.clone(),
+                    issue_number,
+                }),
+        )
+        .collect::<Vec<_>>();
+    // Comparing a repo with itself parses the same issues twice.
+    unparseable_issues.sort();
+    unparseable_issues.dedup();
+    Ok(Json(AssignmentDiffResponse {
+        diff: diff_assignments(&from, &to, from_repo == to_repo),
+        unparseable_issues,
+    }))
+}
+
+async fn get_all_issues(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo: &str,
+) -> Result<Vec<Issue>, Error> {
+    all_pages("issues", octocrab, async || {
+        octocrab
+            .issues(github_org, repo)
+            .list()
+            .state(IssueStateParam::All)
+            .send()
+            .await
+    })
+    .await
+    .map_err(|err| err.with_context(|| format!("Failed to fetch module issues from {}", repo)))
+}
+
+/// The assignments among issues which were open on date, or which are open now if there's no date,
+/// and the numbers of those which couldn't be parsed.
+fn parse_assignments_at(
+    issues: &[Issue],
+    date: Option<NaiveDate>,
+    module_name: &str,
+    submit_labels: &SubmitLabelTaxonomy,
+    sprint_source: SprintSource,
+) -> (Vec<ParsedAssignment>, Vec<u64>) {
+    let mut unhandled_submit_labels = Vec::new();
+    let mut assignments = Vec::new();
+    let mut unparseable = Vec::new();
+    for issue in issues {
+        let was_open = match date {
+            Some(date) => {
+                issue.created_at.date_naive() <= date
+                    && issue
+                        .closed_at
+                        .is_none_or(|closed_at| closed_at.date_naive() > date)
+            }
+            None => issue.state == IssueState::Open,
+        };
+        if !was_open {
+            continue;
+        }
+        match parse_issue(
+            issue,
+            module_name,
+            submit_labels,
+            sprint_source,
+            &mut unhandled_submit_labels,
+        ) {
+            Ok(Some((
+                sprint,
+                Assignment::ExpectedPullRequest {
+                    title,
+                    html_url,
+                    assignment_issue_id,
+                    optionality,
+                    ..
+                },
+            ))) => assignments.push(ParsedAssignment {
+                title,
+                sprint: sprint.get(),
+                optionality,
+                issue_number: assignment_issue_id,
+                issue_url: html_url.to_string(),
+            }),
+            Ok(_) => {}
+            // Errors can include the issue's URL, so are only logged, not returned to the caller.
+            Err(err) => {
+                warn!(
+                    "Leaving issue {} out of assignment diff: {:?}",
+                    issue.html_url, err
+                );
+                unparseable.push(issue.number);
+            }
+        }
+    }
+    (assignments, unparseable)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::course::AssignmentOptionality;
+
+    use super::{AssignmentDiff, ParsedAssignment, RenamedAssignment, diff_assignments};
+
+    fn assignment(title: &str, sprint: usize, issue_number: u64) -> ParsedAssignment {
+        ParsedAssignment {
+            title: title.to_owned(),
+            sprint,
+            optionality: AssignmentOptionality::Mandatory,
+            issue_number,
+            issue_url: format!(
+                "https://github.com/CodeYourFuture/Module-Data-Groups/issues/{issue_number}"
+            ),
+        }
+    }
+
+    #[test]
+    fn renames_within_a_repo_keep_their_issue_number() {
+        let from = vec![
+            assignment("Alarm clock app", 1, 10),
+            assignment("Quote generator", 1, 11),
+            assignment("Reading list", 2, 12),
+        ];
+        let to = vec![
+            assignment("Alarm clock app", 1, 10),
+            assignment("Random quote generator app", 1, 11),
+            assignment("Todo list", 2, 13),
+        ];
+        assert_eq!(
+            diff_assignments(&from, &to, true),
+            AssignmentDiff {
+                added: vec![assignment("Todo list", 2, 13)],
+                removed: vec![assignment("Reading list", 2, 12)],
+                renamed: vec![RenamedAssignment {
+                    from: assignment("Quote generator", 1, 11),
+                    to: assignment("Random quote generator app", 1, 11),
+                }],
+                unchanged: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn renames_across_repos_have_similar_titles() {
+        let from = vec![
+            assignment("Alarm Clock", 1, 3),
+            assignment("Slideshow", 2, 4),
+        ];
+        let to = vec![
+            assignment("alarm  clock", 1, 30),
+            assignment("Slideshow app", 2, 31),
+            assignment("Slideshow extra", 3, 32),
+        ];
+        assert_eq!(
+            diff_assignments(&from, &to, false),
+            AssignmentDiff {
+                added: vec![assignment("Slideshow extra", 3, 32)],
+                removed: vec![],
+                renamed: vec![RenamedAssignment {
+                    from: assignment("Slideshow", 2, 4),
+                    to: assignment("Slideshow app", 2, 31),
+                }],
+                unchanged: 1,
+            }
+        );
+    }
+}
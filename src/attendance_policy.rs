@@ -0,0 +1,102 @@
+use serde::Deserialize;
+
+use crate::course::{Attendance, Submission, SubmissionState, TraineeWithSubmissions};
+
+/// A course's formal attendance requirements, which progression boards check trainees against.
+///
+/// Unlike the progress score (which weighs attendance alongside coursework to give a rough status), this is pass/fail:
+/// a trainee either meets every requirement or is in breach.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AttendancePolicy {
+    /// e.g. 80 means trainees must attend at least 80% of their classes so far. Late arrivals count as attending.
+    #[serde(default)]
+    pub min_attendance_percent: Option<usize>,
+    /// The most classes in a row a trainee may miss at any point in the course.
+    #[serde(default)]
+    pub max_consecutive_absences: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyBreach {
+    LowAttendance {
+        attended_percent: usize,
+        min_percent: usize,
+    },
+    ConsecutiveAbsences {
+        absences: usize,
+        max_absences: usize,
+    },
+}
+
+impl std::fmt::Display for PolicyBreach {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyBreach::LowAttendance {
+                attended_percent,
+                min_percent,
+            } => write!(
+                f,
+                "Attended {}% of classes (minimum {}%)",
+                attended_percent, min_percent
+            ),
+            PolicyBreach::ConsecutiveAbsences {
+                absences,
+                max_absences,
+            } => write!(
+                f,
+                "Missed {} classes in a row (maximum {})",
+                absences, max_absences
+            ),
+        }
+    }
+}
+
+impl AttendancePolicy {
+    /// Every requirement the trainee doesn't meet, based on the classes which have happened so far.
+    pub fn breaches(&self, trainee: &TraineeWithSubmissions) -> Vec<PolicyBreach> {
+        let mut breaches = Vec::new();
+        if let Some(min_percent) = self.min_attendance_percent
+            && let Some(attended_percent) = trainee.attendance().percent()
+            && attended_percent < min_percent
+        {
+            breaches.push(PolicyBreach::LowAttendance {
+                attended_percent,
+                min_percent,
+            });
+        }
+        if let Some(max_absences) = self.max_consecutive_absences {
+            let absences = longest_absence_run(trainee);
+            if absences > max_absences {
+                breaches.push(PolicyBreach::ConsecutiveAbsences {
+                    absences,
+                    max_absences,
+                });
+            }
+        }
+        breaches
+    }
+}
+
+/// The most classes the trainee has missed in a row, at any point in the course.
+///
+/// Classes are taken in course order (module by module, sprint by sprint), and classes which haven't happened yet don't break a run.
+fn longest_absence_run(trainee: &TraineeWithSubmissions) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for submission in trainee
+        .modules
+        .values()
+        .flat_map(|module| module.sprints.iter())
+        .flat_map(|sprint| sprint.submissions.iter())
+    {
+        match submission {
+            SubmissionState::Some(Submission::Attendance(Attendance::Absent { .. })) => {
+                current += 1;
+                longest = longest.max(current);
+            }
+            SubmissionState::Some(Submission::Attendance(_)) => current = 0,
+            _ => {}
+        }
+    }
+    longest
+}
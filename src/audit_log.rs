@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+use crate::{Error, newtypes::GithubLogin, persistence::RecordStore};
+
+const AUDIT_LOG_TAB: &str = "Audit log";
+const AUDIT_LOG_HEADINGS: [&str; 4] = ["Recorded", "Actor", "Action", "Detail"];
+
+/// Something a user did with elevated access (e.g. viewing the tracker as someone else), which we want a record of.
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    pub recorded_at: DateTime<Utc>,
+    pub actor: GithubLogin,
+    pub action: &'static str,
+    pub detail: String,
+}
+
+pub async fn record_audit_event(store: &RecordStore, event: &AuditEvent) -> Result<(), Error> {
+    info!(
+        actor = %event.actor,
+        action = event.action,
+        detail = %event.detail,
+        "Audit event"
+    );
+    store
+        .append(
+            AUDIT_LOG_TAB,
+            &AUDIT_LOG_HEADINGS,
+            vec![
+                event.recorded_at.to_rfc3339(),
+                event.actor.to_string(),
+                event.action.to_owned(),
+                event.detail.clone(),
+            ],
+        )
+        .await
+}
+
+/// How many times each actor did the action since the given time.
+pub async fn count_audit_actions_since(
+    store: &RecordStore,
+    action: &str,
+    since: DateTime<Utc>,
+) -> Result<BTreeMap<GithubLogin, usize>, Error> {
+    let mut counts = BTreeMap::new();
+    for row in store.read(AUDIT_LOG_TAB).await? {
+        let [recorded_at, actor, row_action, ..] = row.as_slice() else {
+            continue;
+        };
+        let Ok(recorded_at) = DateTime::parse_from_rfc3339(recorded_at) else {
+            continue;
+        };
+        if row_action == action && recorded_at.to_utc() >= since {
+            *counts.entry(GithubLogin::from(actor.clone())).or_default() += 1;
+        }
+    }
+    Ok(counts)
+}
@@ -1,7 +1,7 @@
 use anyhow::{Context, anyhow};
 use askama::Template;
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     response::Html,
 };
 use http::Uri;
@@ -10,7 +10,9 @@ use tower_sessions::Session;
 use uuid::Uuid;
 
 use crate::{
-    Config, Error, ServerState,
+    Error, ServerState,
+    google_auth::{GoogleScope, make_redirect_uri, redirect_endpoint},
+    newtypes::GithubLogin,
     slack::{SLACK_ACCESS_TOKEN_SESSION_KEY, make_slack_redirect_uri},
 };
 
@@ -27,10 +29,9 @@ pub async fn handle_github_oauth_callback(
     session: Session,
     params: Query<OauthCallbackParams>,
 ) -> Result<Html<String>, Error> {
-    let access_token =
-        exchange_github_oauth_code_for_access_token(&server_state.config, &params.code)
-            .await
-            .context("Failed to exchange GitHub oauth token")?;
+    let access_token = exchange_github_oauth_code_for_access_token(&server_state, &params.code)
+        .await
+        .context("Failed to exchange GitHub oauth token")?;
     session
         .insert(GITHUB_ACCESS_TOKEN_SESSION_KEY, access_token)
         .await
@@ -68,13 +69,187 @@ pub(crate) async fn github_auth_redirect_url(
         .context("Statically known correct GitHub auth Uri couldn't be constructed")?)
 }
 
+/// A service whose token we hold in the session on the user's behalf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthProvider {
+    Github,
+    GoogleSheets,
+    GoogleGroups,
+}
+
+impl AuthProvider {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AuthProvider::Github => "GitHub",
+            AuthProvider::GoogleSheets => "Google Sheets",
+            AuthProvider::GoogleGroups => "Google Groups",
+        }
+    }
+
+    /// The path segment used in `/auth/reconnect/{provider}`.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            AuthProvider::Github => "github",
+            AuthProvider::GoogleSheets => "google-sheets",
+            AuthProvider::GoogleGroups => "google-groups",
+        }
+    }
+
+    fn token_session_key(&self) -> &'static str {
+        match self {
+            AuthProvider::Github => GITHUB_ACCESS_TOKEN_SESSION_KEY,
+            AuthProvider::GoogleSheets => GoogleScope::Sheets.token_session_key(),
+            AuthProvider::GoogleGroups => GoogleScope::Groups.token_session_key(),
+        }
+    }
+}
+
+/// A token we had in the session was rejected (e.g. because it expired or was revoked) part-way through the user's session.
+///
+/// Rather than failing the page, we ask the user to reconnect, and then send them back to where they were.
+#[derive(Debug)]
+pub struct ExpiredAuthorisation {
+    pub provider: AuthProvider,
+}
+
+impl std::fmt::Display for ExpiredAuthorisation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Your {} authorisation expired",
+            self.provider.display_name()
+        )
+    }
+}
+
+impl std::error::Error for ExpiredAuthorisation {}
+
+/// The signed-in user doesn't have access to something upstream, e.g. a Google Sheet which hasn't been shared with them.
+///
+/// Callers may treat this as "this user isn't staff" and show less, but where what's missing matters,
+/// they should show `user_facing_message` rather than silently degrading.
+#[derive(Debug)]
+pub struct PermissionDenied {
+    pub provider: AuthProvider,
+    /// What we tried to access, described for a user, e.g. "the Google Sheet https://docs.google.com/spreadsheets/d/...".
+    pub resource: String,
+    /// The access the user would need, e.g. "read" or "edit".
+    pub needed_scope: &'static str,
+    /// Who can grant access (the configured admins).
+    pub who_to_ask: Vec<GithubLogin>,
+    pub source: anyhow::Error,
+}
+
+impl PermissionDenied {
+    /// Safe to show to the user: it only describes things the user tried to access.
+    pub fn user_facing_message(&self) -> String {
+        let who_to_ask = if self.who_to_ask.is_empty() {
+            "whoever runs the tracker".to_owned()
+        } else {
+            self.who_to_ask
+                .iter()
+                .map(|login| format!("@{login}"))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        };
+        format!(
+            "You need {} access to {} in {} - ask {} to give you access.",
+            self.needed_scope,
+            self.resource,
+            self.provider.display_name(),
+            who_to_ask
+        )
+    }
+
+    pub fn map_source<F: FnOnce(anyhow::Error) -> anyhow::Error>(self, f: F) -> Self {
+        PermissionDenied {
+            source: f(self.source),
+            ..self
+        }
+    }
+}
+
+/// Finds which provider's token was rejected, if anything in the error chain says a token was rejected.
+pub(crate) fn expired_authorisation(err: &anyhow::Error) -> Option<AuthProvider> {
+    err.chain().find_map(|err| {
+        if let Some(expired) = err.downcast_ref::<ExpiredAuthorisation>() {
+            Some(expired.provider)
+        } else if let Some(octocrab::Error::GitHub { source, .. }) =
+            err.downcast_ref::<octocrab::Error>()
+            && source.status_code == 401
+        {
+            Some(AuthProvider::Github)
+        } else {
+            None
+        }
+    })
+}
+
+#[derive(Deserialize)]
+pub struct ReconnectParams {
+    return_to: String,
+}
+
+/// Forgets the rejected token, and sends the user to re-authorise, returning them to `return_to` afterwards.
+pub async fn reconnect(
+    State(server_state): State<ServerState>,
+    session: Session,
+    Path(provider): Path<AuthProvider>,
+    Query(params): Query<ReconnectParams>,
+) -> Result<Html<String>, Error> {
+    // Only allow returning to pages on this site.
+    if !params.return_to.starts_with('/') || params.return_to.starts_with("//") {
+        return Err(Error::UserFacing(
+            "Can only return to a page on this site after reconnecting".to_owned(),
+        ));
+    }
+    let return_to: Uri = params
+        .return_to
+        .parse()
+        .map_err(|_| Error::UserFacing("Invalid page to return to".to_owned()))?;
+
+    session
+        .remove::<String>(provider.token_session_key())
+        .await
+        .context("Session remove error")?;
+
+    let redirect_uri = match provider {
+        AuthProvider::Github => github_auth_redirect_url(&server_state, return_to).await?,
+        AuthProvider::GoogleSheets => {
+            make_redirect_uri(
+                &server_state,
+                return_to,
+                &redirect_endpoint(&server_state),
+                GoogleScope::Sheets,
+            )
+            .await?
+        }
+        AuthProvider::GoogleGroups => {
+            make_redirect_uri(
+                &server_state,
+                return_to,
+                &redirect_endpoint(&server_state),
+                GoogleScope::Groups,
+            )
+            .await?
+        }
+    };
+    Ok(Html(
+        crate::frontend::Redirect { redirect_uri }
+            .render()
+            .context("Failed to render")?,
+    ))
+}
+
 async fn exchange_github_oauth_code_for_access_token(
-    config: &Config,
+    server_state: &ServerState,
     code: &str,
 ) -> anyhow::Result<String> {
-    let client = reqwest::Client::new();
-
-    let response: GitHubOauthExchangeResponse = client
+    let config = &server_state.config;
+    let response: GitHubOauthExchangeResponse = server_state
+        .http_clients
+        .github_oauth
         .get(format!("https://github.com/login/oauth/access_token?client_id={client_id}&client_secret={client_secret}&code={code}", client_id = config.github_client_id, client_secret = config.github_client_secret, code = code))
         .header(reqwest::header::ACCEPT, "application/json")
         .send()
@@ -146,7 +321,7 @@ pub async fn handle_slack_oauth_callback(
     Query(params): Query<OauthCallbackParams>,
 ) -> Result<Html<String>, Error> {
     let client = slack_with_types::client::Client::new_without_auth(
-        reqwest::Client::new(),
+        server_state.http_clients.slack.clone(),
         slack_with_types::client::RateLimiter::new(),
     );
     let response: slack_with_types::oauth::OauthExchangeResponse = client
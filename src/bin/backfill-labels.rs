@@ -0,0 +1,102 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    label_backfill::{apply_label_backfill, find_label_backfills},
+    octocrab::octocrab_for_token,
+    setup_logging,
+};
+
+/// Adds missing sprint and priority labels to a module's coursework issues, working them out from issue titles and milestones.
+/// Issues which are missing these labels stop the whole module's assignments from being parsed.
+/// By default this only reports what it would do; pass --apply to add the labels.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// The module's repo name, e.g. Module-Data-Groups.
+    module: String,
+
+    /// Add the labels, rather than only reporting them.
+    #[arg(long)]
+    apply: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to label issues");
+        exit(1);
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+
+    let backfills = match find_label_backfills(&octocrab, &config.github_org, &args.module).await {
+        Ok(backfills) => backfills,
+        Err(err) => {
+            eprintln!("Failed to find issues in {}: {:?}", args.module, err);
+            exit(1);
+        }
+    };
+
+    let mut failure_count = 0;
+    let mut unresolved_count = 0;
+    for backfill in &backfills {
+        println!("{} ({})", backfill.html_url, backfill.title);
+        for (label, reason) in &backfill.labels_to_add {
+            println!("  add {} ({})", label, reason);
+        }
+        for missing in &backfill.unresolved {
+            println!(
+                "  couldn't work out {} label - please add it by hand",
+                missing
+            );
+            unresolved_count += 1;
+        }
+        if args.apply
+            && let Err(err) =
+                apply_label_backfill(&octocrab, &config.github_org, &args.module, backfill).await
+        {
+            eprintln!("  {:?}", err);
+            failure_count += 1;
+        }
+    }
+
+    println!(
+        "{} issues need labels, {} labels need adding by hand{}",
+        backfills.len(),
+        unresolved_count,
+        if args.apply {
+            ""
+        } else {
+            " (dry run - pass --apply to add labels)"
+        }
+    );
+
+    if failure_count > 0 {
+        exit(1);
+    }
+}
@@ -0,0 +1,129 @@
+use std::process::exit;
+
+use clap::{Parser, ValueEnum};
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    course::get_batch_with_submissions,
+    course_definitions::load_course_definitions,
+    endpoints::{flat_submissions_csv, flatten_submissions},
+    error_reports::init_error_reports,
+    frontend::render_trainee_batch_page,
+    octocrab::octocrab_for_token,
+    setup_logging,
+    sheets::SheetsClient,
+};
+
+/// Prints the same data as a batch's page in the web app, using the service credentials.
+/// Intended for scheduled exports (e.g. from cron).
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    #[arg(long)]
+    course: String,
+
+    /// The batch's GitHub team slug.
+    #[arg(long)]
+    batch: String,
+
+    #[arg(long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// One row per trainee per assignment, as served by /api/reports/flat/submissions.
+    Csv,
+    /// The same rows as csv.
+    Json,
+    /// The batch page.
+    Html,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to generate reports");
+        exit(1);
+    };
+    let Some(course_schedule) =
+        config.get_course_schedule_with_register_sheet_id(args.course.clone(), &args.batch)
+    else {
+        eprintln!("Unknown course {} batch {}", args.course, args.batch);
+        exit(1);
+    };
+
+    let server_state = ServerState::new(config);
+    let config = &server_state.config;
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+
+    let result = async {
+        let course = course_schedule
+            .with_assignments(&octocrab, &server_state.in_flight, &config.github_org)
+            .await?;
+        let batch = get_batch_with_submissions(
+            &octocrab,
+            sheets_client,
+            &config.github_email_mapping_sheet_id,
+            &config.mentoring_records_sheet_id,
+            &config.github_org,
+            &args.batch,
+            &course,
+        )
+        .await?;
+        let attendance_policy = config.attendance_policy(&course.name);
+        Ok::<_, trainee_tracker::Error>(match args.format {
+            Format::Csv => flat_submissions_csv(&flatten_submissions(&course, &batch)),
+            Format::Json => serde_json::to_string_pretty(&flatten_submissions(&course, &batch))
+                .expect("Failed to serialize report"),
+            Format::Html => render_trainee_batch_page(
+                course,
+                batch,
+                config.at_risk_rules.clone(),
+                config.absence_streak.clone(),
+                attendance_policy,
+                None,
+                Default::default(),
+            ),
+        })
+    }
+    .await;
+
+    match result {
+        Ok(report) => print!("{}", report),
+        Err(err) => {
+            eprintln!(
+                "Failed to generate report for {} {}: {:?}",
+                args.course, args.batch, err
+            );
+            exit(1);
+        }
+    }
+}
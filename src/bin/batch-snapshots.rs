@@ -0,0 +1,173 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    burndown::{burndown_point, current_sprint},
+    course::get_batch_with_submissions,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    funnel::funnel_points,
+    newtypes::CourseName,
+    octocrab::octocrab_for_token,
+    setup_logging,
+    sheets::SheetsClient,
+    snapshots::{write_batch_snapshot, write_burndown_point, write_funnel_points},
+};
+
+/// Writes a dated snapshot of each batch's submissions into the configured snapshot sheet, and records the current sprint's burndown and each module's funnel.
+/// Intended to be run weekly (e.g. from cron), and daily with --burndown-only to keep burndown charts up to date.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// Only snapshot batches of this course.
+    #[arg(long)]
+    course: Option<CourseName>,
+
+    /// Only record the current sprint's burndown, without writing a dated snapshot tab.
+    #[arg(long)]
+    burndown_only: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(snapshot_sheet_id) = config.snapshot_sheet_id.clone() else {
+        eprintln!("Config must contain snapshot_sheet_id to write snapshots");
+        exit(1);
+    };
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to write snapshots");
+        exit(1);
+    };
+    if let Some(course) = &args.course {
+        if !config.courses.contains_key(course) {
+            eprintln!("Unknown course {}", course);
+            exit(1);
+        }
+    }
+
+    let server_state = ServerState::new(config);
+    let config = &server_state.config;
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+    let today = chrono::Utc::now().date_naive();
+
+    let mut failure_count = 0;
+    for (course_name, course_info) in &config.courses {
+        if args
+            .course
+            .as_ref()
+            .is_some_and(|course| course != course_name)
+        {
+            continue;
+        }
+        for batch_github_slug in course_info.batches.keys() {
+            // UNWRAP: We're iterating over the config's own courses and batches.
+            let course_schedule = config
+                .get_course_schedule_with_register_sheet_id(
+                    course_name.to_string(),
+                    batch_github_slug,
+                )
+                .unwrap();
+            let result = async {
+                let course = course_schedule
+                    .with_assignments(&octocrab, &server_state.in_flight, &config.github_org)
+                    .await?;
+                let batch = get_batch_with_submissions(
+                    &octocrab,
+                    sheets_client.clone(),
+                    &config.github_email_mapping_sheet_id,
+                    &config.mentoring_records_sheet_id,
+                    &config.github_org,
+                    batch_github_slug,
+                    &course,
+                )
+                .await?;
+                let mut titles = Vec::new();
+                if let Some((module_name, sprint_number, _start)) = current_sprint(&course, today) {
+                    let point = burndown_point(&course, &batch, &module_name, sprint_number, today);
+                    titles.push(
+                        write_burndown_point(
+                            &sheets_client,
+                            &snapshot_sheet_id,
+                            course_name,
+                            batch_github_slug,
+                            &point,
+                        )
+                        .await?,
+                    );
+                }
+                if !args.burndown_only {
+                    titles.push(
+                        write_funnel_points(
+                            &sheets_client,
+                            &snapshot_sheet_id,
+                            course_name,
+                            batch_github_slug,
+                            today,
+                            &funnel_points(&course, &batch, today),
+                        )
+                        .await?,
+                    );
+                    titles.push(
+                        write_batch_snapshot(
+                            &sheets_client,
+                            &snapshot_sheet_id,
+                            &course,
+                            &batch,
+                            today,
+                        )
+                        .await?,
+                    );
+                }
+                Ok::<_, trainee_tracker::Error>(titles)
+            }
+            .await;
+            match result {
+                Ok(titles) => {
+                    for title in titles {
+                        println!("Wrote snapshot {}", title);
+                    }
+                }
+                Err(err) => {
+                    eprintln!(
+                        "Failed to snapshot {} {}: {:?}",
+                        course_name, batch_github_slug, err
+                    );
+                    failure_count += 1;
+                }
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        exit(1);
+    }
+}
@@ -0,0 +1,133 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    course_definitions::load_course_definitions,
+    data_health::{
+        HealthCheck, check_register, check_roster, lint_modules, slack_text, validate_config,
+    },
+    error_reports::init_error_reports,
+    octocrab::octocrab_for_token,
+    setup_logging,
+    sheets::SheetsClient,
+    slack::post_to_slack_webhook,
+};
+
+/// Checks the config, and each course's roster, module issues and register, and posts a single report of any problems to Slack.
+/// Intended to be run every morning (e.g. from cron), so the ops team can fix data problems before they show up on pages.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// Print the report instead of posting it to Slack.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to check data health");
+        exit(1);
+    };
+    let webhook_url = config
+        .data_health_slack_webhook_url
+        .as_ref()
+        .map(|webhook_url| webhook_url.to_string());
+    if webhook_url.is_none() && !args.dry_run {
+        eprintln!("Config must contain data_health_slack_webhook_url to post the report");
+        exit(1);
+    }
+
+    let server_state = ServerState::new(config);
+    let config = &server_state.config;
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+
+    let mut sections = vec![(
+        "Config".to_owned(),
+        vec![HealthCheck {
+            name: "Config",
+            outcome: Ok(validate_config(config)),
+        }],
+    )];
+    for (course_name, course_info) in &config.courses {
+        let (roster, modules, register) = futures::join!(
+            check_roster(&octocrab, sheets_client.clone(), config, course_info),
+            lint_modules(&octocrab, &config.github_org, course_info),
+            check_register(
+                &octocrab,
+                sheets_client.clone(),
+                config,
+                course_name,
+                course_info
+            ),
+        );
+        sections.push((
+            course_name.to_string(),
+            vec![
+                HealthCheck {
+                    name: "Roster",
+                    outcome: roster,
+                },
+                HealthCheck {
+                    name: "Module issues",
+                    outcome: modules,
+                },
+                HealthCheck {
+                    name: "Register",
+                    outcome: register,
+                },
+            ],
+        ));
+    }
+
+    let text = slack_text(chrono::Utc::now().date_naive(), &sections);
+    match &webhook_url {
+        Some(webhook_url) if !args.dry_run => {
+            if let Err(err) =
+                post_to_slack_webhook(&server_state.http_clients.slack, webhook_url, &text).await
+            {
+                eprintln!("Failed to post data health report: {:?}", err);
+                exit(1);
+            }
+        }
+        _ => println!("{}", text),
+    }
+
+    // Checks which couldn't run are in the report, but also fail the job so they're noticed if the report isn't.
+    if sections
+        .iter()
+        .flat_map(|(_, checks)| checks)
+        .any(|check| check.outcome.is_err())
+    {
+        exit(1);
+    }
+}
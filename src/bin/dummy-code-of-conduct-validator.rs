@@ -1,30 +1,81 @@
 /// This binary exists to be a lightweight teaching version of the pr-metadata-validator.
 /// Its purpose is to train trainees in the idea that bots will comment on their PRs, and they should follow their advice.
 /// It is installed in https://github.com/CodeYourFuture/github_issues_prs_practice as a GitHub Action.
-use std::process::exit;
-
+use clap::Parser;
 use const_format::concatcp;
 use trainee_tracker::{
+    Error,
+    error_reports::{ErrorContext, ErrorReport, init_error_reports, send_error_report},
     octocrab::octocrab_for_token,
     pr_comments::{PullRequest, close_existing_comments, leave_tagged_comment},
+    pr_validator::ValidatorExitCode,
 };
 
+#[derive(Parser)]
+struct Args {
+    pr_url: String,
+
+    /// Print `{"result": "Ok"}` or `{"result": "MissingCodeOfConductAgreement"}` on stdout, or `{"error": "..."}` if the PR couldn't be validated.
+    #[arg(long)]
+    json: bool,
+}
+
 #[tokio::main]
 async fn main() {
-    let Ok([_argv0, pr_url]) = <[_; _]>::try_from(std::env::args().collect::<Vec<_>>()) else {
-        eprintln!("Expected one arg - PR URL");
-        exit(1);
+    init_error_reports(
+        env!("CARGO_BIN_NAME"),
+        std::env::var("ERROR_REPORT_DSN").ok().as_ref(),
+    );
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        Err(err) => {
+            let _ = err.print();
+            if err.use_stderr() {
+                ValidatorExitCode::Error.exit();
+            }
+            ValidatorExitCode::Passed.exit();
+        }
     };
-    let pr_metadata = PullRequest::from_html_url(&pr_url).expect("Failed to parse PR URL");
-    let github_token =
-        std::env::var("GH_TOKEN").expect("GH_TOKEN wasn't set - must be set to a GitHub API token");
-    let octocrab = octocrab_for_token(github_token.to_owned()).expect("Failed to get octocrab");
+    match run(&args.pr_url).await {
+        Ok(passed) => {
+            if args.json {
+                let result = if passed {
+                    "Ok"
+                } else {
+                    "MissingCodeOfConductAgreement"
+                };
+                println!("{}", serde_json::json!({ "result": result }));
+            }
+            if passed {
+                ValidatorExitCode::Passed.exit();
+            }
+            ValidatorExitCode::FailedValidation.exit();
+        }
+        Err(err) => {
+            eprintln!("Failed to validate PR: {:?}", err);
+            if let Error::Fatal(err) = &err {
+                send_error_report(ErrorReport::fatal(err, ErrorContext::from_args())).await;
+            }
+            if args.json {
+                println!("{}", serde_json::json!({ "error": err.to_string() }));
+            }
+            ValidatorExitCode::Error.exit();
+        }
+    }
+}
+
+/// Returns whether the PR passed.
+async fn run(pr_url: &str) -> Result<bool, Error> {
+    let pr_metadata = PullRequest::from_html_url(pr_url)?;
+    let github_token = std::env::var("GH_TOKEN")
+        .map_err(|_| anyhow::anyhow!("GH_TOKEN wasn't set - must be set to a GitHub API token"))?;
+    let octocrab = octocrab_for_token(github_token)?;
 
     let pr_from_rest = octocrab
         .pulls(&pr_metadata.org, &pr_metadata.repo)
         .get(pr_metadata.number)
         .await
-        .expect("Failed to get PR");
+        .map_err(|err| anyhow::Error::from(err).context("Failed to get PR"))?;
     if pr_from_rest
         .body
         .unwrap_or_default()
@@ -34,10 +85,12 @@ async fn main() {
         if let Err(err) = result {
             eprintln!("Failed to close existing comments: {:?}", err);
         }
+        Ok(true)
     } else {
         leave_tagged_comment(&octocrab, &pr_metadata, &[TAG], COMMENT_TO_LEAVE.to_owned())
             .await
-            .expect("Failed to leave comment");
+            .map_err(|err| err.context("Failed to leave comment"))?;
+        Ok(false)
     }
 }
 
@@ -1,4 +1,4 @@
-use std::process::exit;
+use std::{collections::BTreeMap, process::exit};
 
 use chrono::NaiveDate;
 use indexmap::IndexMap;
@@ -9,6 +9,7 @@ use trainee_tracker::{
     octocrab::octocrab_for_token,
     prs::get_prs,
     setup_logging,
+    single_flight::InFlight,
 };
 
 #[tokio::main]
@@ -55,29 +56,42 @@ async fn main() {
     let mut sprints = IndexMap::new();
     sprints.insert(
         module_name.to_owned(),
-        std::iter::repeat_with(|| {
+        // Further sprints are added based on the sprint labels on the module's issues.
+        vec![
             regions
                 .iter()
                 .map(|region| (Region(region.to_string()), fixed_date))
-                .collect()
-        })
-        .take(5)
-        .collect(),
+                .collect(),
+        ],
     );
     let course_schedule = CourseSchedule {
         start: fixed_date,
         end: fixed_date,
         sprints,
+        groups: Vec::new(),
+        project_repos: Vec::new(),
+        deadline_extensions: Vec::new(),
+        onboarding_sessions: Vec::new(),
+        peer_review_slack_webhook_url: None,
+        slack_channels: Vec::new(),
+        slack_usergroup_handle: None,
+        leaderboard: false,
+        weekly_classes_from: BTreeMap::new(),
+        assessment_windows: Vec::new(),
     };
     let course = CourseScheduleWithRegisterSheetId {
         name: "itp".to_owned(),
-        register_sheet_id: "".to_owned(),
+        register_sheet_id: None,
         course_schedule,
+        submit_labels: Default::default(),
+        module_settings: Default::default(),
+        duplicate_register_entries: Default::default(),
+        lateness: Default::default(),
     }
-    .with_assignments(&octocrab, org_name)
+    .with_assignments(&octocrab, &InFlight::default(), org_name)
     .await
     .expect("Failed to get assignments");
-    let module_prs = get_prs(&octocrab, org_name, module_name, true)
+    let module_prs = get_prs(&octocrab, &InFlight::default(), org_name, module_name, true)
         .await
         .expect("Failed to get PRs");
     let pr_in_question = module_prs
@@ -0,0 +1,173 @@
+use std::process::exit;
+
+use anyhow::Context;
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    newtypes::TeamSlug,
+    octocrab::octocrab_for_token,
+    peer_review::{
+        check_peer_reviews, get_peer_review_pairings, pairings_issue_comment, pairings_slack_text,
+        peer_review_round, sprint_start,
+    },
+    setup_logging,
+    single_flight::InFlight,
+    slack::post_to_slack_webhook,
+};
+
+/// Pairs up a batch's trainees to review each other's PRs for a sprint, or checks whether the reviews happened.
+/// Pairings are worked out from the batch's GitHub team and the sprint, so checking later gives the same pairs that were posted.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    #[arg(long)]
+    course: String,
+
+    /// The batch's GitHub team slug.
+    #[arg(long)]
+    batch: TeamSlug,
+
+    #[arg(long)]
+    module: String,
+
+    /// 1-based, as in sprint labels.
+    #[arg(long)]
+    sprint: usize,
+
+    /// Post the pairings to the batch's peer_review_slack_webhook_url.
+    #[arg(long)]
+    post_to_slack: bool,
+
+    /// Post the pairings as a comment on this issue number in the module's repo (e.g. the sprint's peer review issue).
+    #[arg(long)]
+    issue: Option<u64>,
+
+    /// Rather than posting pairings, report which of them have resulted in a review since the sprint started.
+    #[arg(long)]
+    check: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to pair trainees");
+        exit(1);
+    };
+    let Some(course_schedule) =
+        config.get_course_schedule_with_register_sheet_id(args.course.clone(), &args.batch)
+    else {
+        eprintln!("Unknown course {} batch {}", args.course, args.batch);
+        exit(1);
+    };
+    let course_schedule = course_schedule.course_schedule;
+    let (Some(round), Some(since)) = (
+        peer_review_round(&course_schedule, &args.module, args.sprint),
+        sprint_start(&course_schedule, &args.module, args.sprint),
+    ) else {
+        eprintln!(
+            "Batch {} has no sprint {} of {}",
+            args.batch, args.sprint, args.module
+        );
+        exit(1);
+    };
+    let webhook_url = course_schedule
+        .peer_review_slack_webhook_url
+        .as_ref()
+        .map(|webhook_url| webhook_url.to_string());
+    if args.post_to_slack && webhook_url.is_none() {
+        eprintln!(
+            "Batch {} must have peer_review_slack_webhook_url to post to Slack",
+            args.batch
+        );
+        exit(1);
+    }
+
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+
+    let result = async {
+        let pairings =
+            get_peer_review_pairings(&octocrab, &config.github_org, &args.batch, round).await?;
+
+        if args.check {
+            let statuses = check_peer_reviews(
+                &octocrab,
+                &InFlight::default(),
+                &config.github_org,
+                &args.module,
+                since,
+                pairings,
+            )
+            .await?;
+            for status in &statuses {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    status.pairing.reviewer,
+                    status.pairing.author,
+                    if status.happened() {
+                        "reviewed"
+                    } else {
+                        "not reviewed"
+                    },
+                    status.reviewed_pr_urls.join(" ")
+                );
+            }
+            return Ok::<_, trainee_tracker::Error>(());
+        }
+
+        for pairing in &pairings {
+            println!("{}\t{}", pairing.reviewer, pairing.author);
+        }
+        if let Some(webhook_url) = webhook_url.as_deref().filter(|_| args.post_to_slack) {
+            post_to_slack_webhook(
+                &reqwest::Client::new(),
+                webhook_url,
+                &pairings_slack_text(&args.module, args.sprint, &pairings),
+            )
+            .await?;
+        }
+        if let Some(issue) = args.issue {
+            octocrab
+                .issues(&config.github_org, &args.module)
+                .create_comment(
+                    issue,
+                    pairings_issue_comment(&args.module, args.sprint, &pairings),
+                )
+                .await
+                .with_context(|| format!("Failed to comment on {} #{}", args.module, issue))?;
+        }
+        Ok(())
+    }
+    .await;
+
+    if let Err(err) = result {
+        eprintln!(
+            "Failed to pair trainees in {} {}: {:?}",
+            args.course, args.batch, err
+        );
+        exit(1);
+    }
+}
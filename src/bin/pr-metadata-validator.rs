@@ -1,123 +1,235 @@
-use std::{collections::BTreeMap, process::exit};
-
-use chrono::NaiveDate;
+use anyhow::Context;
 use clap::Parser;
-use indexmap::IndexMap;
-use maplit::btreemap;
 use octocrab::Octocrab;
-use regex::Regex;
+use serde::Serialize;
 use trainee_tracker::{
     Error,
-    config::{CourseSchedule, CourseScheduleWithRegisterSheetId},
-    course::{get_descriptor_id_for_pr, match_prs_to_assignments},
-    newtypes::Region,
+    commit_authorship::{CommitAuthor, get_pr_commit_authors, mismatched_commits},
+    copied_bodies::{CopiedBody, DEFAULT_COPIED_BODY_THRESHOLD, find_copied_body},
+    error_reports::{ErrorContext, ErrorReport, init_error_reports, send_error_report},
+    module_discovery::get_module_metadata,
     octocrab::{all_pages, octocrab_for_token},
-    pr_comments::{PullRequest, close_existing_comments, leave_tagged_comment},
+    on_call::OnCallRota,
+    pr_comments::{PullRequest, close_existing_comments, has_tagged_comment, leave_tagged_comment},
+    pr_validator::{
+        KnownRegions, MatchedAssignment, PR_METADATA_VALIDATOR_LABEL, ValidationResult,
+        ValidatorExitCode, module_course, validate_pr,
+    },
     prs::get_prs,
+    single_flight::InFlight,
 };
 
-const ARBITRARY_REGION: Region = Region(String::new());
-
 #[derive(Parser)]
 struct Args {
     pr_url: String,
 
     #[arg(long)]
     give_more_specific_comment_for_earlier_learners: bool,
+
+    /// Label the PR for a mentor to look at if its description looks copied from another trainee's PR or from the assignment.
+    /// This never comments or fails validation, as it's only a heuristic.
+    #[arg(long)]
+    flag_copied_bodies: bool,
+
+    /// Warn, and label the PR, if any of its commits don't look like they were authored by the PR's author.
+    /// Like --flag-copied-bodies, this never comments or fails validation.
+    #[arg(long)]
+    check_commit_authors: bool,
+
+    /// When the PR passes validation, leave a (minimised) comment linking the assignment issue it was matched to,
+    /// and label it with its sprint (e.g. `sprint-2`), so the match doesn't need to be worked out again.
+    #[arg(long)]
+    link_assignments: bool,
+
+    /// Fail validation if links the trainee added to the PR description don't load.
+    /// Only links to public addresses are requested.
+    #[arg(long)]
+    check_links: bool,
+
+    /// Path to a JSON file in the same shape as the tracker config's `on_call_rota`.
+    /// If a PR fails validation for the same reason it already has a comment about, the trainee is probably stuck,
+    /// so whoever is on call is messaged about it.
+    #[arg(long)]
+    on_call_rota: Option<String>,
+
+    /// Print the result as JSON on stdout (and only that - everything else goes to stderr), e.g. for a workflow to act on.
+    /// If the PR couldn't be validated, this is `{"error": "..."}`.
+    #[arg(long)]
+    json: bool,
 }
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
-    let pr = PullRequest::from_html_url(&args.pr_url).expect("Failed to parse PR URL");
+    init_error_reports(
+        env!("CARGO_BIN_NAME"),
+        std::env::var("ERROR_REPORT_DSN").ok().as_ref(),
+    );
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        Err(err) => {
+            let _ = err.print();
+            // --help and --version print to stdout, and aren't failures.
+            if err.use_stderr() {
+                ValidatorExitCode::Error.exit();
+            }
+            ValidatorExitCode::Passed.exit();
+        }
+    };
+    let json = args.json;
+    match run(args).await {
+        Ok(outcome) => {
+            if json {
+                println!("{}", serde_json::to_string(&outcome).unwrap());
+            }
+            if matches!(outcome.result, ValidationResult::Ok) {
+                ValidatorExitCode::Passed.exit();
+            }
+            ValidatorExitCode::FailedValidation.exit();
+        }
+        Err(err) => {
+            eprintln!("Failed to validate PR: {:?}", err);
+            if let Error::Fatal(err) = &err {
+                send_error_report(ErrorReport::fatal(err, ErrorContext::from_args())).await;
+            }
+            if json {
+                println!("{}", serde_json::json!({ "error": err.to_string() }));
+            }
+            ValidatorExitCode::Error.exit();
+        }
+    }
+}
 
-    // TODO: Fetch this from classplanner or somewhere when we have access to a useful API.
-    let known_region_aliases = KnownRegions(btreemap! {
-        "Cape Town" => vec!["South Africa", "SouthAfrica", "ZA", "ZA Cape Town"],
-        "Glasgow" => vec!["Scotland"],
-        "London" => vec![],
-        "North West" => vec!["NW", "Manchester"],
-        "Sheffield" => vec![],
-        "West Midlands" => vec!["WM", "WestMidlands", "West-Midlands", "Birmingham"],
-    });
+/// What the validator found, as printed by `--json`.
+#[derive(Serialize)]
+struct Outcome {
+    #[serde(flatten)]
+    result: ValidationResult,
+    matched_assignment: Option<MatchedAssignment>,
+    /// The comment left on the PR, if it failed validation.
+    comment: Option<String>,
+}
 
-    let github_token =
-        std::env::var("GH_TOKEN").expect("GH_TOKEN wasn't set - must be set to a GitHub API token");
-    let octocrab = octocrab_for_token(github_token).expect("Failed to get octocrab");
+async fn run(args: Args) -> Result<Outcome, Error> {
+    let pr = PullRequest::from_html_url(&args.pr_url)?;
 
-    let course_schedule = make_fake_course_schedule(pr.repo.clone());
+    let known_region_aliases = KnownRegions::default();
 
-    let course = CourseScheduleWithRegisterSheetId {
-        name: "itp".to_owned(),
-        register_sheet_id: "".to_owned(),
-        course_schedule,
-    };
-    let result = validate_pr(
+    let github_token = std::env::var("GH_TOKEN")
+        .context("GH_TOKEN wasn't set - must be set to a GitHub API token")?;
+    let octocrab = octocrab_for_token(github_token)?;
+
+    let (result, matched_assignment) = validate_pr(
         &octocrab,
-        course,
+        &InFlight::default(),
+        module_course(pr.repo.clone()),
         &pr.repo,
         &pr.org,
         pr.number,
         &known_region_aliases,
+        args.check_links,
     )
-    .await
-    .expect("Failed to validate PR");
-
-    const PR_METADATA_VALIDATOR_LABEL: &str = "pr-metadata-validator";
+    .await?;
 
-    let message = match &result {
-        ValidationResult::Ok => {
-            if let Err(err) =
-                close_existing_comments(&octocrab, &pr, PR_METADATA_VALIDATOR_LABEL).await
-            {
-                eprintln!("Failed to close existing comments: {:?}", err);
+    if args.flag_copied_bodies {
+        match check_for_copied_body(&octocrab, &pr).await {
+            Ok(Some(copied)) => {
+                eprintln!("Flagging PR #{} for a mentor: {}", pr.number, copied);
+                if let Err(err) = octocrab
+                    .issues(&pr.org, &pr.repo)
+                    .add_labels(pr.number, &[COPIED_BODY_LABEL.to_owned()])
+                    .await
+                {
+                    eprintln!("Failed to add {} label: {:?}", COPIED_BODY_LABEL, err);
+                }
             }
-            exit(0);
+            Ok(None) => {}
+            Err(err) => eprintln!("Failed to check for a copied description: {:?}", err),
         }
-        ValidationResult::CouldNotMatch => COULD_NOT_MATCH_COMMENT,
-        ValidationResult::BodyTemplateNotFilledOut => {
-            if args.give_more_specific_comment_for_earlier_learners {
-                BODY_TEMPLATE_NOT_FILLED_IN_SPECIFIC_COMMENT
-            } else {
-                BODY_TEMPLATE_NOT_FILLED_IN_VAGUE_COMMENT
+    }
+
+    if args.check_commit_authors {
+        match check_commit_authors(&octocrab, &pr).await {
+            Ok(mismatched) if !mismatched.is_empty() => {
+                for commit in &mismatched {
+                    eprintln!(
+                        "Warning: commit {} on PR #{} was authored by {}, not the PR's author",
+                        commit.short_sha(),
+                        pr.number,
+                        commit
+                            .login
+                            .as_ref()
+                            .map_or_else(|| commit.email.clone(), |login| format!("@{login}"))
+                    );
+                }
+                if let Err(err) = octocrab
+                    .issues(&pr.org, &pr.repo)
+                    .add_labels(pr.number, &[COMMIT_AUTHOR_MISMATCH_LABEL.to_owned()])
+                    .await
+                {
+                    eprintln!(
+                        "Failed to add {} label: {:?}",
+                        COMMIT_AUTHOR_MISMATCH_LABEL, err
+                    );
+                }
             }
+            Ok(_) => {}
+            Err(err) => eprintln!("Failed to check commit authors: {:?}", err),
+        }
+    }
+
+    let validator_comments = get_module_metadata(&octocrab, &pr.org, &pr.repo)
+        .await
+        .map_err(|err| err.context("Failed to get module metadata"))?
+        .map(|metadata| metadata.validator_comments)
+        .unwrap_or_default();
+    let Some(full_message) = validator_comments.comment(
+        &result,
+        args.give_more_specific_comment_for_earlier_learners,
+    ) else {
+        if args.link_assignments
+            && let Some(matched_assignment) = &matched_assignment
+            && let Err(err) = link_to_assignment(&octocrab, &pr, matched_assignment).await
+        {
+            eprintln!("Failed to link PR to its assignment: {:?}", err);
         }
-        ValidationResult::BadTitleFormat { reason } => {
-            &format!("{}{}", BAD_TITLE_COMMENT_PREFIX, reason)
+        if let Err(err) = close_existing_comments(&octocrab, &pr, PR_METADATA_VALIDATOR_LABEL).await
+        {
+            eprintln!("Failed to close existing comments: {:?}", err);
         }
-        ValidationResult::UnknownRegion => UNKNOWN_REGION_COMMENT,
-        ValidationResult::WrongFiles {
-            expected_files_pattern,
-        } => &format!("{}`{}`", WRONG_FILES, expected_files_pattern),
-        ValidationResult::NoFiles => NO_FILES,
-        ValidationResult::TooManyFiles => TOO_MANY_FILES,
+        return Ok(Outcome {
+            result,
+            matched_assignment,
+            comment: None,
+        });
     };
 
-    let full_message = format!(
-        "{message}\n\nIf this PR is not coursework, please add the NotCoursework label (and message on Slack in #cyf-curriculum or it will probably not be noticed).\n\nIf this PR needs reviewed, please add the 'Needs Review' label to this PR after you have resolved the issues listed above."
-    );
     eprintln!("{}", full_message);
+    if let Some(on_call_rota_path) = &args.on_call_rota
+        && let Err(err) = escalate_if_stuck(&octocrab, &pr, &result, on_call_rota_path).await
+    {
+        eprintln!("Failed to escalate to whoever is on call: {:?}", err);
+    }
     leave_tagged_comment(
         &octocrab,
         &pr,
         &[PR_METADATA_VALIDATOR_LABEL, &result.to_string()],
-        full_message,
+        full_message.clone(),
     )
     .await
-    .expect("Failed to create comment with validation error");
+    .map_err(|err| err.context("Failed to create comment with validation error"))?;
     let remove_label_response = octocrab
         .issues(&pr.org, &pr.repo)
         .remove_label(pr.number, "Needs Review")
         .await;
     match remove_label_response {
         Ok(_) => {
-            println!(
+            eprintln!(
                 "Found issues for PR #{}, notified and removed label",
                 pr.number
             );
         }
         Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 404 => {
-            println!(
+            eprintln!(
                 "Found issues for PR #{}, notified and label already removed",
                 pr.number
             );
@@ -127,266 +239,125 @@ async fn main() {
             eprintln!("Error removing label: {:?}", err);
         }
     };
-    exit(2);
-}
-
-const COULD_NOT_MATCH_COMMENT: &str = r#"Your PR couldn't be matched to an assignment in this module.
-
-Please check its title is in the correct format, and that you only have one PR per assignment."#;
-
-const BODY_TEMPLATE_NOT_FILLED_IN_VAGUE_COMMENT: &str = r#"Your PR description contained template fields which weren't filled in.
-
-Check you've ticked everything in the self checklist, and that any sections which prompt you to fill in an answer are either filled in or removed."#;
-
-const BODY_TEMPLATE_NOT_FILLED_IN_SPECIFIC_COMMENT: &str = r#"Your PR description is incomplete.
-
-You filled out a template (that starts "Learners, PR Template") when you created this PR - you can see it at the top of this page.
-
-Make sure to fill in all fields in the template.
-
-Please ensure:
-- [ ] All self checklist items are ticked (with a `[x]`)
-- [ ] The "Changelist" section is filled with details of what your PR does.
-- [ ] The "Questions" section is either filled in (if you have questions) or is removed (if you don't)."#;
-
-const BAD_TITLE_COMMENT_PREFIX: &str = r#"Your PR's title isn't in the expected format.
-
-Please check the expected title format, and update yours to match.
-
-Reason: "#;
-
-const UNKNOWN_REGION_COMMENT: &str = r#"Your PR's title didn't contain a known region.
-
-Please check the expected title format, and make sure your region is in the correct place and spelled correctly."#;
-
-const WRONG_FILES: &str = r#"The changed files in this PR don't match what is expected for this task.
-
-Please check that you committed the right files for the task, and that there are no accidentally committed files from other sprints.
-
-Please review the changed files tab at the top of the page, we are only expecting changes in this directory: "#;
-
-const NO_FILES: &str = r#"This PR is missing any submitted files.
-
-Please check that you committed the right files and pushed to the repository"#;
-
-const TOO_MANY_FILES: &str = r#"There are too many files committed in this pull request.
-
-Please check and make sure you have not accidentally committed a cache, virtual environment, or npm package directory."#;
-
-#[derive(strum_macros::Display)]
-enum ValidationResult {
-    Ok,
-    BodyTemplateNotFilledOut,
-    CouldNotMatch,
-    BadTitleFormat { reason: String },
-    UnknownRegion,
-    WrongFiles { expected_files_pattern: String },
-    NoFiles,
-    TooManyFiles,
+    Ok(Outcome {
+        result,
+        matched_assignment,
+        comment: Some(full_message),
+    })
 }
 
-async fn validate_pr(
+/// Messages whoever is on call if the PR already has a comment about this same problem, i.e. the trainee has tried again and is still stuck.
+async fn escalate_if_stuck(
     octocrab: &Octocrab,
-    course_schedule: CourseScheduleWithRegisterSheetId,
-    module_name: &str,
-    github_org_name: &str,
-    pr_number: u64,
-    known_region_aliases: &KnownRegions,
-) -> Result<ValidationResult, Error> {
-    let course = course_schedule
-        .with_assignments(octocrab, github_org_name)
-        .await
-        .map_err(|err| err.context("Failed to get assignments"))?;
-
-    let module_prs = get_prs(octocrab, github_org_name, module_name, false)
-        .await
-        .map_err(|err| err.context("Failed to get PRs"))?;
-    let pr_in_question = module_prs
-        .iter()
-        .find(|pr| pr.number == pr_number)
-        .ok_or_else(|| {
-            anyhow::anyhow!(
-                "Failed to find PR {} in list of PRs for module {}",
-                pr_number,
-                module_name
-            )
-        })?
-        .clone();
-
-    if pr_in_question.labels.contains("NotCoursework") {
-        return Ok(ValidationResult::Ok);
-    }
-
-    let user_prs: Vec<_> = module_prs
-        .into_iter()
-        .filter(|pr| pr.author == pr_in_question.author)
-        .collect();
-    let matched = match_prs_to_assignments(
-        &course.modules[module_name],
-        user_prs,
-        Vec::new(),
-        &ARBITRARY_REGION,
-    )
-    .map_err(|err| err.context("Failed to match PRs to assignments"))?;
-
-    for pr in matched.unknown_prs {
-        if pr.number == pr_number {
-            return Ok(ValidationResult::CouldNotMatch);
-        }
-    }
-
-    let title_sections: Vec<&str> = pr_in_question.title.split("|").collect();
-    if title_sections.len() != 5 {
-        return Ok(ValidationResult::BadTitleFormat {
-            reason: "Wrong number of parts separated by |s".to_owned(),
-        });
-    }
-
-    if !known_region_aliases.is_known_ignoring_case(title_sections[0].trim()) {
-        return Ok(ValidationResult::UnknownRegion);
-    }
-
-    // TODO: Validate cohorts when they're known (1)
-    let sprint_regex = Regex::new(r"^(S|s)print \d+$").unwrap();
-    let sprint_section = title_sections[3].trim();
-    if !sprint_regex.is_match(sprint_section) {
-        return Ok(ValidationResult::BadTitleFormat {
-            reason: format!(
-                "Sprint part ({}) doesn't match expected format (example: 'Sprint 2', without quotes)",
-                sprint_section
-            ),
-        });
+    pr: &PullRequest,
+    result: &ValidationResult,
+    on_call_rota_path: &str,
+) -> Result<(), Error> {
+    if !has_tagged_comment(octocrab, pr, &result.to_string()).await? {
+        return Ok(());
     }
-
-    if pr_in_question.title.to_ascii_uppercase() == pr_in_question.title {
-        return Ok(ValidationResult::BadTitleFormat {
-            reason: "PR title should not all be in uppercase".to_owned(),
-        });
-    }
-
-    if pr_in_question.body.contains("Briefly explain your PR.")
-        || pr_in_question
-            .body
-            .contains("Ask any questions you have for your reviewer.")
-        || pr_in_question.body.contains("- [ ]")
+    let rota_bytes = std::fs::read(on_call_rota_path).context("Failed to read on-call rota")?;
+    let rota: OnCallRota =
+        serde_json::from_slice(&rota_bytes).context("Failed to parse on-call rota")?;
+    let text = format!(
+        "PR {}/{}#{} has failed validation again ({}), so the trainee may need help: https://github.com/{}/{}/pull/{}",
+        pr.org, pr.repo, pr.number, result, pr.org, pr.repo, pr.number
+    );
+    match rota
+        .notify(
+            &reqwest::Client::new(),
+            None,
+            chrono::Utc::now().date_naive(),
+            &text,
+        )
+        .await?
     {
-        return Ok(ValidationResult::BodyTemplateNotFilledOut);
+        Some(on_call) => eprintln!("Escalated PR #{} to {}", pr.number, on_call),
+        None => eprintln!("Nobody is on call to escalate PR #{} to", pr.number),
     }
-
-    let pr_assignment_descriptor_id =
-        get_descriptor_id_for_pr(&matched.sprints, pr_number).expect("This PR does not exist");
-    // This should never error, as a PR by this point in code must have been matched
-    // with an assignment, and PR assignments must have an associated issue descriptor
-
-    check_pr_file_changes(
-        octocrab,
-        github_org_name,
-        module_name,
-        pr_number,
-        pr_assignment_descriptor_id,
-    )
-    .await
+    Ok(())
 }
 
-// Check the changed files in a pull request match what is expected for that sprint task
-async fn check_pr_file_changes(
+const COPIED_BODY_LABEL: &str = "Possibly Copied Description";
+
+async fn check_for_copied_body(
     octocrab: &Octocrab,
-    org_name: &str,
-    module_name: &str,
-    pr_number: u64,
-    task_issue_number: u64,
-) -> Result<ValidationResult, Error> {
-    // Get the Sprint Task's description of expected changes
-    let Ok(task_issue) = octocrab
-        .issues(org_name, module_name)
-        .get(task_issue_number)
+    pr: &PullRequest,
+) -> Result<Option<CopiedBody>, Error> {
+    // Include closed PRs, so that copies of earlier cohorts' work are caught too.
+    let module_prs = get_prs(octocrab, &InFlight::default(), &pr.org, &pr.repo, true)
         .await
-    else {
-        return Ok(ValidationResult::CouldNotMatch); // Failed to find the right task
-    };
-
-    let task_issue_body = task_issue.body.unwrap_or_default();
-
-    let directory_description = Regex::new("CHANGE_DIR=(.+)\\n")
-        .map_err(|err| Error::UserFacing(format!("Known good regex failed to compile: {}", err)))?;
-    let Some(directory_regex_captures) = directory_description.captures(&task_issue_body) else {
-        return Ok(ValidationResult::Ok); // There is no match defined for this task, don't do any more checks
+        .map_err(|err| err.context("Failed to get PRs"))?;
+    let Some(pr_in_question) = module_prs.iter().find(|other| other.number == pr.number) else {
+        return Ok(None);
     };
-    let directory_description_regex = directory_regex_captures
-        .get(1)
-        .expect("Regex capture failed to return string match")
-        .as_str(); // Only allows a single directory for now
-
-    let directory_matcher = Regex::new(directory_description_regex).map_err(|err| {
-        Error::UserFacing(format!(
-            "Failed to compile regex from {}, check the CHANGE_DIR declaration: {}",
-            task_issue.html_url, err
-        ))
-    })?;
-
-    // Get all of the changed files
-    let pr_files = all_pages("changed files in pull request", octocrab, async || {
-        octocrab
-            .pulls(org_name, module_name)
-            .list_files(pr_number)
-            .await
+    let assignment_issues = all_pages("issues", octocrab, async || {
+        octocrab.issues(&pr.org, &pr.repo).list().send().await
     })
-    .await?;
-    if pr_files.is_empty() {
-        return Ok(ValidationResult::NoFiles); // no files committed
-    }
-
-    if pr_files.len() > 100 {
-        return Ok(ValidationResult::TooManyFiles); // too many files probably a venv or npm cache
-    }
-
-    // check each file and error if one is in unexpected place
-    for pr_file in pr_files {
-        if pr_file.filename == ".gitignore" {
-            continue; // always allow top-level gitignore changes
-        }
-        if !directory_matcher.is_match(&pr_file.filename) {
-            return Ok(ValidationResult::WrongFiles {
-                expected_files_pattern: directory_description_regex.to_string(),
-            });
-        }
-    }
-
-    Ok(ValidationResult::Ok)
+    .await
+    .map_err(|err| err.context("Failed to get assignment issues"))?
+    .into_iter()
+    .filter(|issue| issue.pull_request.is_none())
+    .map(|issue| (issue.html_url.to_string(), issue.body.unwrap_or_default()))
+    .collect::<Vec<_>>();
+    Ok(find_copied_body(
+        pr_in_question,
+        &module_prs,
+        &assignment_issues,
+        DEFAULT_COPIED_BODY_THRESHOLD,
+    ))
 }
 
-struct KnownRegions(BTreeMap<&'static str, Vec<&'static str>>);
+const COMMIT_AUTHOR_MISMATCH_LABEL: &str = "Commit Author Mismatch";
 
-impl KnownRegions {
-    fn is_known_ignoring_case(&self, possible_region: &str) -> bool {
-        let possible_region_lower = possible_region.to_ascii_lowercase();
-        for (known_region, known_region_aliases) in &self.0 {
-            if known_region.to_ascii_lowercase() == possible_region_lower {
-                return true;
-            }
-            for known_region_alias in known_region_aliases {
-                if known_region_alias.to_ascii_lowercase() == possible_region_lower {
-                    return true;
-                }
-            }
-        }
-        false
-    }
+async fn check_commit_authors(
+    octocrab: &Octocrab,
+    pr: &PullRequest,
+) -> Result<Vec<CommitAuthor>, Error> {
+    let pr_author = octocrab
+        .pulls(&pr.org, &pr.repo)
+        .get(pr.number)
+        .await
+        .context("Failed to get PR")?
+        .user
+        .ok_or_else(|| anyhow::anyhow!("PR has no author"))?
+        .login;
+    let commits = get_pr_commit_authors(octocrab, &pr.org, &pr.repo, pr.number).await?;
+    // We don't know trainees' emails here, so only linked accounts and noreply addresses count as matching.
+    Ok(mismatched_commits(&commits, &pr_author.into(), &[]))
 }
 
-fn make_fake_course_schedule(module_name: String) -> CourseSchedule {
-    let fixed_date = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
-    let mut sprints = IndexMap::new();
-    sprints.insert(
-        module_name,
-        std::iter::repeat_with(|| btreemap![ARBITRARY_REGION => fixed_date])
-            // 5 is the max number of sprints a module (currently) contains.
-            .take(5)
-            .collect(),
-    );
-    CourseSchedule {
-        start: fixed_date,
-        end: fixed_date,
-        sprints,
+const ASSIGNMENT_LINK_TAG: &str = "assignment-link";
+
+/// Links the PR to the assignment it was matched to, unless it's already linked to that assignment.
+/// The comment is minimised straight away, as it's for tools and mentors rather than the trainee.
+async fn link_to_assignment(
+    octocrab: &Octocrab,
+    pr: &PullRequest,
+    matched_assignment: &MatchedAssignment,
+) -> Result<(), Error> {
+    let assignment_tag = format!("assignment-{}", matched_assignment.assignment_issue_id);
+    if !has_tagged_comment(octocrab, pr, &assignment_tag).await? {
+        // Minimise any links to assignments the PR used to be matched to, along with the new one.
+        leave_tagged_comment(
+            octocrab,
+            pr,
+            &[ASSIGNMENT_LINK_TAG, &assignment_tag],
+            format!(
+                "This PR is for the sprint {} assignment #{}.",
+                matched_assignment.sprint_number, matched_assignment.assignment_issue_id
+            ),
+        )
+        .await?;
+        close_existing_comments(octocrab, pr, ASSIGNMENT_LINK_TAG).await?;
     }
+    octocrab
+        .issues(&pr.org, &pr.repo)
+        .add_labels(
+            pr.number,
+            &[format!("sprint-{}", matched_assignment.sprint_number)],
+        )
+        .await
+        .context("Failed to add sprint label")?;
+    Ok(())
 }
@@ -0,0 +1,206 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    course::get_batch_with_submissions,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    event_webhooks::{pending_events, publish_event, published_events, record_published_event},
+    newtypes::CourseName,
+    octocrab::octocrab_for_token,
+    on_call::escalation_for_event,
+    persistence::RecordStore,
+    setup_logging,
+    sheets::SheetsClient,
+};
+
+/// Sends events (e.g. a trainee's status changing) about each course's batches to the course's `event_webhooks`,
+/// so other systems can react to them without polling our API.
+/// Events which need someone to act on them (e.g. a trainee becoming at risk) are also sent to whoever is on call in the config's `on_call_rota`.
+/// Intended to be run regularly (e.g. hourly from cron). Each change is only sent once, as what was sent is recorded in the persistence sheet.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// Only publish events for batches of this course.
+    #[arg(long)]
+    course: Option<CourseName>,
+
+    /// Print events instead of sending or recording them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to publish events");
+        exit(1);
+    };
+    if let Some(course) = &args.course {
+        if !config.courses.contains_key(course) {
+            eprintln!("Unknown course {}", course);
+            exit(1);
+        }
+    }
+
+    let server_state = ServerState::new(config);
+    let config = &server_state.config;
+    let Some(store) = RecordStore::from_config(&server_state)
+        .await
+        .expect("Failed to create record store")
+    else {
+        eprintln!("Config must contain persistence_sheet_id to record which events were published");
+        exit(1);
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+    let http_client = reqwest::Client::new();
+    let now = chrono::Utc::now();
+
+    let mut failure_count = 0;
+    for (course_name, course_info) in &config.courses {
+        if (course_info.event_webhooks.is_empty() && config.on_call_rota.weeks.is_empty())
+            || args
+                .course
+                .as_ref()
+                .is_some_and(|course| course != course_name)
+        {
+            continue;
+        }
+        for batch_github_slug in course_info.batches.keys() {
+            // UNWRAP: We're iterating over the config's own courses and batches.
+            let course_schedule = config
+                .get_course_schedule_with_register_sheet_id(
+                    course_name.to_string(),
+                    batch_github_slug,
+                )
+                .unwrap();
+            let result = async {
+                let course = course_schedule
+                    .with_assignments(&octocrab, &server_state.in_flight, &config.github_org)
+                    .await?;
+                let batch = get_batch_with_submissions(
+                    &octocrab,
+                    sheets_client.clone(),
+                    &config.github_email_mapping_sheet_id,
+                    &config.mentoring_records_sheet_id,
+                    &config.github_org,
+                    batch_github_slug,
+                    &course,
+                )
+                .await?;
+                let published = published_events(&store, course_name, batch_github_slug).await?;
+                let mut sent = 0;
+                for pending in pending_events(
+                    &course,
+                    &batch,
+                    &published,
+                    config.absence_streak.as_ref(),
+                    now.date_naive(),
+                ) {
+                    if let Some(event) = &pending.event {
+                        if args.dry_run {
+                            println!(
+                                "Would send {}",
+                                serde_json::to_string(event).expect("Failed to serialize event")
+                            );
+                            if let Some((region, text)) = escalation_for_event(
+                                event,
+                                batch.trainees.iter().map(|trainee| &trainee.trainee),
+                                &config.public_base_url,
+                            ) {
+                                let on_call = config
+                                    .on_call_rota
+                                    .on_call(region.as_ref(), now.date_naive())?
+                                    .map_or("nobody", |(name, _)| name);
+                                println!("Would escalate to {}: {}", on_call, text);
+                            }
+                            continue;
+                        }
+                        for webhook in &course_info.event_webhooks {
+                            publish_event(&http_client, webhook, event).await?;
+                        }
+                        if let Some((region, text)) = escalation_for_event(
+                            event,
+                            batch.trainees.iter().map(|trainee| &trainee.trainee),
+                            &config.public_base_url,
+                        ) {
+                            let on_call = config
+                                .on_call_rota
+                                .notify(
+                                    &server_state.http_clients.slack,
+                                    region.as_ref(),
+                                    now.date_naive(),
+                                    &text,
+                                )
+                                .await?;
+                            if on_call.is_none() {
+                                eprintln!("Nobody is on call to escalate to: {}", text);
+                            }
+                        }
+                        sent += 1;
+                    }
+                    // Only recorded once every webhook has accepted it, so failures are retried next time.
+                    // This means a webhook may see the same event twice if another webhook failed.
+                    if !args.dry_run {
+                        record_published_event(
+                            &store,
+                            course_name,
+                            batch_github_slug,
+                            &pending,
+                            now,
+                        )
+                        .await?;
+                    }
+                }
+                Ok::<_, trainee_tracker::Error>(sent)
+            }
+            .await;
+            match result {
+                Ok(sent) => println!(
+                    "Published {} events for {} {}",
+                    sent, course_name, batch_github_slug
+                ),
+                Err(err) => {
+                    eprintln!(
+                        "Failed to publish events for {} {}: {:?}",
+                        course_name, batch_github_slug, err
+                    );
+                    failure_count += 1;
+                }
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        exit(1);
+    }
+}
@@ -0,0 +1,91 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    register::{check_register_structure, fix_register_structure_problem},
+    setup_logging,
+    sheets::SheetsClient,
+};
+
+/// Checks that a course's register spreadsheet has one correctly set up sheet per module,
+/// and optionally creates any missing sheets and headings.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    course: String,
+
+    /// Create missing sheets and headings. Rows which have already been filled in are never changed.
+    #[arg(long)]
+    fix: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(course_info) = config.courses.get(args.course.as_str()) else {
+        eprintln!("Unknown course {}", args.course);
+        exit(1);
+    };
+    let register_sheet_id = course_info.register_sheet_id.clone();
+
+    let modules = course_info.module_sprint_counts();
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to access the register");
+        exit(1);
+    };
+    let client = SheetsClient::for_service_account(
+        ServerState::new(config),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+
+    let problems = check_register_structure(client.clone(), &register_sheet_id, &modules)
+        .await
+        .expect("Failed to check register structure");
+
+    let mut unfixed_problem_count = 0;
+    for problem in &problems {
+        if args.fix && problem.is_fixable() {
+            fix_register_structure_problem(&client, &register_sheet_id, problem)
+                .await
+                .expect("Failed to fix register structure");
+            println!("Fixed: {}", problem);
+        } else {
+            println!("{}", problem);
+            unfixed_problem_count += 1;
+        }
+    }
+
+    if unfixed_problem_count > 0 {
+        if !args.fix && problems.iter().any(|problem| problem.is_fixable()) {
+            eprintln!("Some problems can be fixed automatically by re-running with --fix");
+        }
+        exit(1);
+    }
+    println!("Register {} is set up correctly", register_sheet_id);
+}
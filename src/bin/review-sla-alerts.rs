@@ -0,0 +1,117 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    octocrab::octocrab_for_token,
+    review_sla::{review_sla_alert_text, review_sla_report},
+    setup_logging,
+    single_flight::InFlight,
+    slack::post_to_slack_webhook,
+};
+
+/// Alerts each course's Slack webhook when fewer PRs than its review SLA's target got a first review in time last week.
+/// Intended to be run weekly, early in the week (e.g. from cron on Mondays).
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// Print alerts instead of posting them to Slack.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to check review SLAs");
+        exit(1);
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let http_client = reqwest::Client::new();
+    let now = chrono::Utc::now();
+
+    let mut failure_count = 0;
+    for (course_name, course_info) in &config.courses {
+        let Some(sla) = &course_info.review_sla else {
+            continue;
+        };
+        let webhook_url = sla
+            .slack_webhook_url
+            .as_ref()
+            .map(|webhook_url| webhook_url.to_string());
+        if webhook_url.is_none() && !args.dry_run {
+            eprintln!(
+                "{}'s review_sla must contain slack_webhook_url to post alerts",
+                course_name
+            );
+            failure_count += 1;
+            continue;
+        }
+        let result = async {
+            let report = review_sla_report(
+                &octocrab,
+                &InFlight::default(),
+                &config,
+                course_name,
+                sla,
+                now,
+            )
+            .await?;
+            let (week, compliance) = report.previous_week(now);
+            let is_below = compliance.is_below(sla.alert_below_percent());
+            if is_below {
+                let text = review_sla_alert_text(course_name, sla, week, &compliance, &report);
+                match &webhook_url {
+                    Some(webhook_url) if !args.dry_run => {
+                        post_to_slack_webhook(&http_client, webhook_url, &text).await?
+                    }
+                    _ => println!("{}\n", text),
+                }
+            }
+            Ok::<_, trainee_tracker::Error>((week, compliance, is_below))
+        }
+        .await;
+        match result {
+            Ok((week, compliance, is_below)) => println!(
+                "{}: week of {}: {} met, {} missed{}",
+                course_name,
+                week,
+                compliance.met,
+                compliance.missed,
+                if is_below { " - alerted" } else { "" }
+            ),
+            Err(err) => {
+                eprintln!("Failed to check review SLA for {}: {:?}", course_name, err);
+                failure_count += 1;
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        exit(1);
+    }
+}
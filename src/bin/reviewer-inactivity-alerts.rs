@@ -0,0 +1,142 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    octocrab::octocrab_for_token,
+    reviewer_alerts::{get_reviewer_activity, inactivity_alert_text, newly_inactive_reviewers},
+    setup_logging,
+    sheets::SheetsClient,
+    slack::post_to_slack_webhook,
+    snapshots::{latest_reviewer_snapshot, write_reviewer_snapshot},
+};
+
+/// Records each course's reviewer activity in the snapshot sheet, and alerts Slack about reviewers who have stopped reviewing since the last snapshot.
+/// Intended to be run daily (e.g. from cron).
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// Print alerts instead of posting them to Slack. Snapshots are still written.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(snapshot_sheet_id) = config.snapshot_sheet_id.clone() else {
+        eprintln!("Config must contain snapshot_sheet_id to track reviewer activity");
+        exit(1);
+    };
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to track reviewer activity");
+        exit(1);
+    };
+    let webhook_url = config
+        .reviewer_alerts_slack_webhook_url
+        .as_ref()
+        .map(|webhook_url| webhook_url.to_string());
+    if webhook_url.is_none() && !args.dry_run {
+        eprintln!("Config must contain reviewer_alerts_slack_webhook_url to post alerts");
+        exit(1);
+    }
+
+    let server_state = ServerState::new(config);
+    let config = &server_state.config;
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+    let now = chrono::Utc::now();
+    let today = now.date_naive();
+
+    let mut failure_count = 0;
+    for course_name in config.courses.keys() {
+        let result = async {
+            // UNWRAP: We're iterating over the config's own courses.
+            let module_names = config.get_course_module_names(course_name).unwrap();
+            let batch_github_slugs = config.courses[course_name]
+                .batches
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            let reviewers = get_reviewer_activity(
+                &octocrab,
+                &server_state.in_flight,
+                &config.github_org,
+                &module_names,
+                &batch_github_slugs,
+            )
+            .await?;
+            let previous =
+                latest_reviewer_snapshot(&sheets_client, &snapshot_sheet_id, course_name, today)
+                    .await?;
+            let newly_inactive = match &previous {
+                Some((previous_date, previous_reviewers)) => {
+                    newly_inactive_reviewers(*previous_date, previous_reviewers, &reviewers, now)
+                }
+                None => Vec::new(),
+            };
+            if !newly_inactive.is_empty() {
+                let text = inactivity_alert_text(course_name, &newly_inactive);
+                match &webhook_url {
+                    Some(webhook_url) if !args.dry_run => {
+                        post_to_slack_webhook(&server_state.http_clients.slack, webhook_url, &text)
+                            .await?
+                    }
+                    _ => println!("{}\n", text),
+                }
+            }
+            write_reviewer_snapshot(
+                &sheets_client,
+                &snapshot_sheet_id,
+                course_name,
+                &reviewers,
+                today,
+            )
+            .await?;
+            Ok::<_, trainee_tracker::Error>(newly_inactive.len())
+        }
+        .await;
+        match result {
+            Ok(count) => println!("{}: {} newly inactive reviewers", course_name, count),
+            Err(err) => {
+                eprintln!(
+                    "Failed to check reviewer activity for {}: {:?}",
+                    course_name, err
+                );
+                failure_count += 1;
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        exit(1);
+    }
+}
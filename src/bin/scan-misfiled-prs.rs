@@ -0,0 +1,94 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, course_definitions::load_course_definitions, error_reports::init_error_reports,
+    github_accounts::get_trainee_logins, misfiled_prs::find_misfiled_prs,
+    octocrab::octocrab_for_token, setup_logging, single_flight::InFlight,
+};
+
+/// Scans every repo in the org for open PRs by a course's trainees which look like coursework, but weren't opened against one of the course's module repos.
+/// The tracker doesn't see these, so trainees who filed them show up as not having submitted.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    course: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(course_info) = config.courses.get(args.course.as_str()) else {
+        eprintln!("Unknown course {}", args.course);
+        exit(1);
+    };
+    let Some(batch_github_slug) = course_info.batches.keys().next() else {
+        eprintln!("Course {} has no batches", args.course);
+        exit(1);
+    };
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to scan PRs");
+        exit(1);
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+
+    // All batches of a course share the same modules, so any batch's schedule will do for working out which repos are expected.
+    let course = config
+        .get_course_schedule_with_register_sheet_id(args.course.clone(), batch_github_slug)
+        // UNWRAP: We just found this batch in the course.
+        .unwrap()
+        .with_assignments(&octocrab, &InFlight::default(), &config.github_org)
+        .await
+        .expect("Failed to get course assignments");
+
+    let batch_github_slugs = course_info.batches.keys().cloned().collect::<Vec<_>>();
+    let trainee_logins = get_trainee_logins(&octocrab, &config.github_org, &batch_github_slugs)
+        .await
+        .expect("Failed to get batch members");
+
+    let misfiled_prs = find_misfiled_prs(
+        &octocrab,
+        &InFlight::default(),
+        &config.github_org,
+        &course,
+        &trainee_logins,
+    )
+    .await
+    .expect("Failed to scan for misfiled PRs");
+
+    for misfiled_pr in &misfiled_prs {
+        println!(
+            "{} by @{} in {}: {}",
+            misfiled_pr.pr.url,
+            misfiled_pr.pr.author,
+            misfiled_pr.pr.repo_name,
+            misfiled_pr.pr.title
+        );
+        match &misfiled_pr.suggested_module {
+            Some(module_name) => println!("  Probably belongs in {}", module_name),
+            None => println!("  Doesn't match any assignment in {}", args.course),
+        }
+    }
+    println!("Found {} misfiled PRs", misfiled_prs.len());
+}
@@ -0,0 +1,134 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config, ServerState,
+    course::get_batch_with_submissions,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    octocrab::octocrab_for_token,
+    setup_logging,
+    sheets::SheetsClient,
+    sprint_summaries::{post_sprint_summary, sprints_ending_on, summarise_sprint},
+};
+
+/// Posts a summary issue in each module repo for each batch whose sprint ends today,
+/// tagging the course's mentors team.
+/// Intended to be run daily (e.g. from cron) - summaries which have already been posted are skipped.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    /// Post summaries for sprints ending on this date (YYYY-MM-DD) instead of today.
+    #[arg(long)]
+    date: Option<chrono::NaiveDate>,
+
+    /// Print summaries instead of posting them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to post sprint summaries");
+        exit(1);
+    };
+
+    let server_state = ServerState::new(config);
+    let config = &server_state.config;
+    let github_org = &config.github_org;
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await
+    .expect("Failed to create Sheets client");
+    let date = args.date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+
+    let mut failure_count = 0;
+    for (course_name, course_info) in &config.courses {
+        for batch_github_slug in course_info.batches.keys() {
+            // UNWRAP: We're iterating over the config's own courses and batches.
+            let course_schedule = config
+                .get_course_schedule_with_register_sheet_id(
+                    course_name.to_string(),
+                    batch_github_slug,
+                )
+                .unwrap();
+            let result = async {
+                let course = course_schedule
+                    .with_assignments(&octocrab, &server_state.in_flight, github_org)
+                    .await?;
+                let ending_sprints = sprints_ending_on(&course, date);
+                if ending_sprints.is_empty() {
+                    return Ok(());
+                }
+                let batch = get_batch_with_submissions(
+                    &octocrab,
+                    sheets_client.clone(),
+                    &config.github_email_mapping_sheet_id,
+                    &config.mentoring_records_sheet_id,
+                    github_org,
+                    batch_github_slug,
+                    &course,
+                )
+                .await?;
+                for (module_name, sprint_index) in ending_sprints {
+                    let summary = summarise_sprint(
+                        &octocrab,
+                        github_org,
+                        &course,
+                        &batch,
+                        &module_name,
+                        sprint_index,
+                    )
+                    .await?;
+                    if args.dry_run {
+                        println!("# {}\n\n{}\n", summary.title(), summary.body(github_org));
+                    } else {
+                        match post_sprint_summary(&octocrab, github_org, &summary).await? {
+                            Some(url) => println!("Posted {}", url),
+                            None => println!("Already posted: {}", summary.title()),
+                        }
+                    }
+                }
+                Ok::<_, trainee_tracker::Error>(())
+            }
+            .await;
+            if let Err(err) = result {
+                eprintln!(
+                    "Failed to summarise sprints for {} {}: {:?}",
+                    course_name, batch_github_slug, err
+                );
+                failure_count += 1;
+            }
+        }
+    }
+
+    if failure_count > 0 {
+        exit(1);
+    }
+}
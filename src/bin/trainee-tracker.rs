@@ -2,7 +2,12 @@ use axum::routing::{get, post};
 use dotenv::dotenv;
 use tower_sessions::{Expiry, MemoryStore, SessionManagerLayer};
 use tracing::info;
-use trainee_tracker::{Config, ServerState, setup_logging};
+use trainee_tracker::{
+    Config, ServerState,
+    course_definitions::load_course_definitions,
+    error_reports::{init_error_reports, report_fatal_errors},
+    setup_logging,
+};
 
 use std::net::SocketAddr;
 
@@ -25,8 +30,12 @@ async fn main() {
     }
 
     let config_bytes = std::fs::read(&args[0]).expect("Failed to read config file");
-    let config: Config =
+    let mut config: Config =
         serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
 
     let addr = config.addr.unwrap_or_else(|| "127.0.0.1".parse().unwrap());
     let sock_addr = SocketAddr::from((addr, config.port));
@@ -51,6 +60,26 @@ async fn main() {
             "/api/courses/{course}/prs",
             get(trainee_tracker::endpoints::course_prs),
         )
+        .route(
+            "/api/courses/{course}/modules/{module}/assignments",
+            get(trainee_tracker::endpoints::module_assignments),
+        )
+        .route(
+            "/api/courses/{course}/modules/{module}/assignments/diff",
+            get(trainee_tracker::assignment_diff::get_assignment_diff),
+        )
+        .route(
+            "/api/courses/{course}/mentors",
+            get(trainee_tracker::endpoints::mentors),
+        )
+        .route(
+            "/api/courses/{course}/reviewers",
+            get(trainee_tracker::endpoints::reviewers),
+        )
+        .route(
+            "/api/courses/{course}/help-requests",
+            get(trainee_tracker::endpoints::help_requests),
+        )
         .route(
             "/api/courses/{course}/trainee-batches",
             get(trainee_tracker::endpoints::trainee_batches),
@@ -59,11 +88,19 @@ async fn main() {
             "/api/courses/{course}/trainee-batches/{batch}",
             get(trainee_tracker::endpoints::trainee_batch),
         )
+        .route(
+            "/api/reports/flat/submissions",
+            get(trainee_tracker::endpoints::flat_submissions),
+        )
         .route("/api/teams", get(trainee_tracker::endpoints::teams))
         .route(
             "/api/trainees/{trainee}/region",
             get(trainee_tracker::endpoints::get_region),
         )
+        .route(
+            "/api/v1/trainees/{trainee}/progress",
+            get(trainee_tracker::api_v1::get_trainee_progress),
+        )
         .route(
             "/api/oauth-callbacks/github",
             get(trainee_tracker::auth::handle_github_oauth_callback),
@@ -76,20 +113,166 @@ async fn main() {
             "/api/oauth-callbacks/slack",
             get(trainee_tracker::auth::handle_slack_oauth_callback),
         )
+        .route(
+            "/auth/reconnect/{provider}",
+            get(trainee_tracker::auth::reconnect),
+        )
         .route("/", get(trainee_tracker::frontend::index))
         .route("/courses", get(trainee_tracker::frontend::list_courses))
+        .route("/lookup", get(trainee_tracker::lookup::lookup))
+        .route(
+            "/check-in",
+            get(trainee_tracker::check_in::get_check_in)
+                .post(trainee_tracker::check_in::post_check_in),
+        )
+        .route("/me", get(trainee_tracker::frontend::get_me))
+        .route("/me/goals", post(trainee_tracker::frontend::post_me_goals))
+        .route(
+            "/me/leaderboard",
+            get(trainee_tracker::leaderboard::get_me_leaderboard),
+        )
+        .route(
+            "/me/progress",
+            get(trainee_tracker::frontend::get_me_progress),
+        )
+        .route("/status", get(trainee_tracker::frontend::get_status))
+        .route(
+            "/trainee-directory/reload",
+            post(trainee_tracker::trainee_directory::post_reload_trainee_directory),
+        )
+        .route(
+            "/admin/view-as",
+            get(trainee_tracker::impersonation::get_view_as)
+                .post(trainee_tracker::impersonation::post_view_as),
+        )
+        .route(
+            "/admin/courses/{course}/batches/{batch_github_slug}/slack-usergroup",
+            get(trainee_tracker::slack_usergroups::get_slack_usergroup)
+                .post(trainee_tracker::slack_usergroups::post_slack_usergroup),
+        )
+        .route(
+            "/admin/courses/{course}/batches/{batch_github_slug}/clone",
+            get(trainee_tracker::course_definitions::get_clone_batch)
+                .post(trainee_tracker::course_definitions::post_clone_batch),
+        )
+        .route(
+            "/admin/view-as/stop",
+            post(trainee_tracker::impersonation::post_stop_viewing_as),
+        )
         .route(
             "/courses/{course}/batches/{batch_github_slug}",
             get(trainee_tracker::frontend::get_trainee_batch),
         )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/report.pdf",
+            get(trainee_tracker::frontend::get_trainee_batch_pdf),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/missing-work.csv",
+            get(trainee_tracker::frontend::get_missing_work_mail_merge_csv),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/commit-authors",
+            get(trainee_tracker::commit_authorship::get_commit_authorship),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/assessments",
+            get(trainee_tracker::assessments::get_assessments),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/assessments/reinvite",
+            post(trainee_tracker::assessments::post_reinvite),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/check-in",
+            get(trainee_tracker::check_in::get_check_in_code),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/data-quality",
+            get(trainee_tracker::data_quality::get_data_quality),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/early-engagement",
+            get(trainee_tracker::early_engagement::get_early_engagement),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/heat-map",
+            get(trainee_tracker::heat_map::get_heat_map),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/leaderboard",
+            get(trainee_tracker::leaderboard::get_batch_leaderboard),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/similar-prs",
+            get(trainee_tracker::diff_similarity::get_diff_similarity),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/slack-channels",
+            get(trainee_tracker::slack_channel_audit::get_slack_channel_audit),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/timeline",
+            get(trainee_tracker::timeline::get_batch_timeline),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/trainees/{github_login}",
+            get(trainee_tracker::frontend::get_trainee),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/trainees/{github_login}/notes",
+            post(trainee_tracker::frontend::post_trainee_note),
+        )
+        .route(
+            "/courses/{course}/batches/{batch_github_slug}/trainees/{github_login}/report.pdf",
+            get(trainee_tracker::frontend::get_trainee_pdf),
+        )
+        .route(
+            "/courses/{course}/this-week",
+            get(trainee_tracker::mentor_home::get_mentor_home)
+                .post(trainee_tracker::mentor_home::post_mentor_home_preferences),
+        )
         .route(
             "/courses/{course}/reviewers",
             get(trainee_tracker::frontend::get_reviewers),
         )
+        .route(
+            "/courses/{course}/mentors",
+            get(trainee_tracker::frontend::get_mentors),
+        )
+        .route(
+            "/courses/{course}/workload",
+            get(trainee_tracker::staff_workload::get_staff_workload),
+        )
+        .route(
+            "/courses/{course}/force-pushes",
+            get(trainee_tracker::force_pushes::get_course_force_pushes),
+        )
+        .route(
+            "/courses/{course}/funnel",
+            get(trainee_tracker::funnel::get_funnel),
+        )
+        .route(
+            "/courses/{course}/funnel.csv",
+            get(trainee_tracker::funnel::get_funnel_csv),
+        )
+        .route(
+            "/courses/{course}/needs-attention",
+            get(trainee_tracker::frontend::get_needs_attention),
+        )
         .route(
             "/courses/{course}/review-metrics",
             get(trainee_tracker::frontend::get_review_metrics),
         )
+        .route(
+            "/courses/{course}/review-sla",
+            get(trainee_tracker::review_sla::get_review_sla),
+        )
+        .route(
+            "/courses/{course}/complete-suggestions",
+            get(trainee_tracker::complete_suggestions::get_complete_suggestions)
+                .post(trainee_tracker::complete_suggestions::post_mark_complete),
+        )
         .route(
             "/groups/google",
             get(trainee_tracker::frontend::list_google_groups),
@@ -114,11 +297,20 @@ async fn main() {
             "/api/started-itp",
             get(trainee_tracker::endpoints::started_itp),
         )
+        .route(
+            "/api/slack/commands",
+            post(trainee_tracker::slack_commands::handle_slash_command),
+        )
+        .route(
+            "/api/github/webhook",
+            post(trainee_tracker::github_webhooks::handle_github_webhook),
+        )
         .route(
             "/codility/verify-webhook",
             post(trainee_tracker::codility::verify_webhook),
         )
         .layer(session_layer)
+        .layer(axum::middleware::from_fn(report_fatal_errors))
         .with_state(server_state);
 
     let listener = tokio::net::TcpListener::bind(sock_addr)
@@ -0,0 +1,97 @@
+use std::process::exit;
+
+use clap::Parser;
+use dotenv::dotenv;
+use trainee_tracker::{
+    Config,
+    course_definitions::load_course_definitions,
+    error_reports::init_error_reports,
+    github_accounts::get_trainee_logins,
+    issue_triage::{find_misfiled_issues, redirect_misfiled_issue},
+    octocrab::octocrab_for_token,
+    setup_logging,
+};
+
+/// Finds issues which trainees opened in a course's module repos instead of their forks,
+/// and comments on them explaining where they should go.
+#[derive(Parser)]
+struct Args {
+    /// Path to the trainee-tracker config file.
+    config_path: String,
+
+    course: String,
+
+    /// Close misfiled issues as well as commenting on them.
+    #[arg(long)]
+    close: bool,
+
+    /// List misfiled issues without commenting on or closing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    setup_logging();
+
+    if let Err(err) = dotenv() {
+        if !err.not_found() {
+            panic!("Error loading .env file: {}", err);
+        }
+    }
+
+    let config_bytes = std::fs::read(&args.config_path).expect("Failed to read config file");
+    let mut config: Config =
+        serde_json::from_slice(&config_bytes).expect("Failed to parse config file");
+    init_error_reports(env!("CARGO_BIN_NAME"), config.error_report_dsn.as_deref());
+    load_course_definitions(&mut config)
+        .await
+        .expect("Failed to load course definitions");
+
+    let Some(course_info) = config.courses.get(args.course.as_str()) else {
+        eprintln!("Unknown course {}", args.course);
+        exit(1);
+    };
+    let Some(module_names) = config.get_course_module_names(&args.course) else {
+        eprintln!("Course {} has no batches", args.course);
+        exit(1);
+    };
+    let Some(service_credentials) = config.service_credentials.clone() else {
+        eprintln!("Config must contain service_credentials to triage issues");
+        exit(1);
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())
+        .expect("Failed to create GitHub client");
+
+    let batch_github_slugs = course_info.batches.keys().cloned().collect::<Vec<_>>();
+    let trainee_logins = get_trainee_logins(&octocrab, &config.github_org, &batch_github_slugs)
+        .await
+        .expect("Failed to get batch members");
+    let misfiled_issues = find_misfiled_issues(
+        &octocrab,
+        &config.github_org,
+        &module_names,
+        &trainee_logins,
+    )
+    .await
+    .expect("Failed to find misfiled issues");
+
+    for issue in &misfiled_issues {
+        println!("{} by @{}: {}", issue.html_url, issue.author, issue.title);
+        if args.dry_run {
+            continue;
+        }
+        let commented = redirect_misfiled_issue(&octocrab, &config.github_org, issue, args.close)
+            .await
+            .expect("Failed to redirect misfiled issue");
+        if !commented {
+            println!("  Already commented");
+        }
+        if args.close {
+            println!("  Closed");
+        }
+    }
+    println!("Found {} misfiled issues", misfiled_issues.len());
+}
@@ -0,0 +1,172 @@
+use chrono::NaiveDate;
+
+use crate::{
+    course::{
+        Assignment, AssignmentOptionality, Batch, Course, ONBOARDING_MODULE_NAME, Submission,
+        SubmissionState,
+    },
+    prs::PrState,
+};
+
+const CHART_WIDTH: f64 = 400.0;
+const CHART_HEIGHT: f64 = 150.0;
+
+/// Counts of a sprint's mandatory PR assignments across a batch, as of one day.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BurndownPoint {
+    pub date: NaiveDate,
+    pub module_name: String,
+    /// 1-based, as in sprint labels.
+    pub sprint_number: usize,
+    pub expected: usize,
+    pub received: usize,
+    pub reviewed: usize,
+    pub complete: usize,
+}
+
+/// The most recent sprint which has started in any region, and the date it started.
+/// The induction pseudo-module is skipped, as it has no PRs to burn down.
+pub fn current_sprint(course: &Course, today: NaiveDate) -> Option<(String, usize, NaiveDate)> {
+    if today > course.end_date {
+        return None;
+    }
+    let mut current = None;
+    for (module_name, module) in &course.modules {
+        if module_name == ONBOARDING_MODULE_NAME {
+            continue;
+        }
+        for (sprint_index, sprint) in module.sprints.iter().enumerate() {
+            if let Some(start) = sprint.dates.values().min()
+                && *start <= today
+                && current
+                    .as_ref()
+                    .is_none_or(|(_, _, current_start)| start >= current_start)
+            {
+                current = Some((module_name.clone(), sprint_index + 1, *start));
+            }
+        }
+    }
+    current
+}
+
+/// Tallies where the batch's mandatory PRs for a sprint have got to.
+pub fn burndown_point(
+    course: &Course,
+    batch: &Batch,
+    module_name: &str,
+    sprint_number: usize,
+    date: NaiveDate,
+) -> BurndownPoint {
+    let mut point = BurndownPoint {
+        date,
+        module_name: module_name.to_owned(),
+        sprint_number,
+        expected: 0,
+        received: 0,
+        reviewed: 0,
+        complete: 0,
+    };
+    let Some(sprint) = course
+        .modules
+        .get(module_name)
+        .and_then(|module| module.sprints.get(sprint_number - 1))
+    else {
+        return point;
+    };
+    for trainee in &batch.trainees {
+        let Some(trainee_sprint) = trainee
+            .modules
+            .get(module_name)
+            .and_then(|module| module.sprints.get(sprint_number - 1))
+        else {
+            continue;
+        };
+        for (assignment, submission) in sprint.assignments.iter().zip(&trainee_sprint.submissions) {
+            if !matches!(assignment, Assignment::ExpectedPullRequest { .. })
+                || assignment.optionality() != AssignmentOptionality::Mandatory
+            {
+                continue;
+            }
+            point.expected += 1;
+            if let SubmissionState::Some(Submission::PullRequest { pull_request, .. }) = submission
+            {
+                point.received += 1;
+                match pull_request.state {
+                    PrState::Complete => {
+                        point.reviewed += 1;
+                        point.complete += 1;
+                    }
+                    PrState::Reviewed => point.reviewed += 1,
+                    PrState::NeedsReview | PrState::Unknown => {}
+                }
+            }
+        }
+    }
+    point
+}
+
+/// A sprint's burndown, drawn as an SVG line chart on the batch page.
+pub struct SprintBurndown {
+    pub module_name: String,
+    pub sprint_number: usize,
+    pub start: NaiveDate,
+    /// One point per day we have a snapshot for, oldest first.
+    pub points: Vec<BurndownPoint>,
+}
+
+impl SprintBurndown {
+    pub fn width(&self) -> f64 {
+        CHART_WIDTH
+    }
+
+    pub fn height(&self) -> f64 {
+        CHART_HEIGHT
+    }
+
+    pub fn latest(&self) -> Option<&BurndownPoint> {
+        self.points.last()
+    }
+
+    pub fn expected_line(&self) -> String {
+        self.line(|point| point.expected)
+    }
+
+    pub fn received_line(&self) -> String {
+        self.line(|point| point.received)
+    }
+
+    pub fn reviewed_line(&self) -> String {
+        self.line(|point| point.reviewed)
+    }
+
+    pub fn complete_line(&self) -> String {
+        self.line(|point| point.complete)
+    }
+
+    /// Points for an SVG polyline, with the sprint's first week across the x axis (or longer, if there are later snapshots), and 0 to the most expected submissions up the y axis.
+    fn line(&self, count: impl Fn(&BurndownPoint) -> usize) -> String {
+        let days = self
+            .points
+            .iter()
+            .map(|point| (point.date - self.start).num_days())
+            .max()
+            .unwrap_or_default()
+            .max(7) as f64;
+        let max_count = self
+            .points
+            .iter()
+            .map(|point| point.expected)
+            .max()
+            .unwrap_or_default()
+            .max(1) as f64;
+        self.points
+            .iter()
+            .map(|point| {
+                let x = (point.date - self.start).num_days() as f64 / days * CHART_WIDTH;
+                let y = CHART_HEIGHT - count(point) as f64 / max_count * CHART_HEIGHT;
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,392 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use askama::Template;
+use axum::{
+    Form,
+    extract::{OriginalUri, Path, Query, State},
+    response::Html,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use email_address::EmailAddress;
+use http::HeaderMap;
+use serde::Deserialize;
+use tower_sessions::Session;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    Error, ServerState,
+    config::CourseScheduleWithRegisterSheetId,
+    course::ONBOARDING_MODULE_NAME,
+    frontend::{current_github_login, record_store},
+    github_accounts::get_trainees,
+    i18n::{Language, language},
+    impersonation::forbid_while_viewing_as,
+    newtypes::{GithubLogin, new_case_insensitive_email_address},
+    octocrab::{all_pages, octocrab, octocrab_for_token},
+    persistence::RecordStore,
+    register::{Attendance, RegisterEntry},
+    sheets::{SheetsClient, sheets_client},
+};
+
+/// How long a check-in code works for. The staff page shows a new code more often than this, so a trainee who reads one just before it changes still has time to type it in.
+pub const CHECK_IN_CODE_LIFETIME: Duration = Duration::from_secs(120);
+const CHECK_IN_PAGE_REFRESH_SECONDS: u64 = 60;
+/// Codes are short enough to guess given enough tries, so after this many wrong codes a trainee must wait before trying again.
+const MAX_WRONG_CHECK_IN_CODES: u32 = 5;
+/// How long a trainee who entered too many wrong codes must wait, counted from their last wrong code.
+pub const WRONG_CHECK_IN_CODES_LIFETIME: Duration = Duration::from_secs(10 * 60);
+
+const CHECK_INS_TAB: &str = "Check-ins";
+const CHECK_INS_HEADINGS: [&str; 8] = [
+    "Recorded", "Course", "Batch", "Module", "Sprint", "Name", "Email", "Location",
+];
+
+/// A class trainees can check in to.
+#[derive(Clone, Debug)]
+pub struct CheckInClass {
+    pub course_name: String,
+    pub batch_github_slug: String,
+    pub module_name: String,
+    /// 1-based, as in the register's `sprint-N` days.
+    pub sprint_number: usize,
+}
+
+impl CheckInClass {
+    /// The class happening today in any of the batch's regions, if there is one.
+    fn today(
+        course_schedule: &CourseScheduleWithRegisterSheetId,
+        batch_github_slug: &str,
+        today: NaiveDate,
+    ) -> Option<Self> {
+        let schedule = &course_schedule.course_schedule;
+        let class = |module_name: &str, sprint_index: usize| CheckInClass {
+            course_name: course_schedule.name.clone(),
+            batch_github_slug: batch_github_slug.to_owned(),
+            module_name: module_name.to_owned(),
+            sprint_number: sprint_index + 1,
+        };
+        if let Some(session_index) = schedule
+            .onboarding_sessions
+            .iter()
+            .position(|dates| dates.values().any(|date| *date == today))
+        {
+            return Some(class(ONBOARDING_MODULE_NAME, session_index));
+        }
+        schedule.sprints.iter().find_map(|(module_name, sprints)| {
+            (0..sprints.len())
+                .find(|sprint_index| {
+                    schedule
+                        .class_dates(module_name, *sprint_index)
+                        .values()
+                        .any(|date| *date == today)
+                })
+                .map(|sprint_index| class(module_name, sprint_index))
+        })
+    }
+}
+
+/// Attendance trainees recorded by checking in, as register entries, so it counts alongside the register sheet.
+///
+/// Rows which can't be read (e.g. because someone edited the sheet by hand) are skipped, rather than breaking every batch page for the course.
+pub(crate) async fn get_check_ins(
+    store: &RecordStore,
+    course_name: &str,
+) -> Result<Vec<RegisterEntry>, Error> {
+    Ok(store
+        .read(CHECK_INS_TAB)
+        .await?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, row)| row.get(1).is_some_and(|course| course == course_name))
+        .filter_map(|(index, row)| {
+            parse_check_in(&row)
+                .inspect_err(|err| {
+                    warn!(
+                        "Skipping unreadable check-in in row {} of {}: {:?}",
+                        index + 2,
+                        CHECK_INS_TAB,
+                        err
+                    )
+                })
+                .ok()
+        })
+        .collect())
+}
+
+fn parse_check_in(row: &[String]) -> Result<RegisterEntry, anyhow::Error> {
+    let wrong_length = || anyhow::anyhow!("Expected 8 columns, got {}", row.len());
+    // Sheets drops trailing empty cells, so a check-in with no region has one fewer.
+    let region = match row {
+        [.., region] if row.len() == CHECK_INS_HEADINGS.len() => region.as_str(),
+        _ if row.len() == CHECK_INS_HEADINGS.len() - 1 => "",
+        _ => return Err(wrong_length()),
+    };
+    let [
+        recorded_at,
+        course,
+        batch,
+        module_name,
+        sprint_number,
+        name,
+        email,
+        ..,
+    ] = row
+    else {
+        return Err(wrong_length());
+    };
+    Ok(RegisterEntry {
+        module_name: module_name.clone(),
+        sprint_number: sprint_number
+            .parse()
+            .context("Failed to parse sprint number")?,
+        attendance: Attendance {
+            name: name.clone(),
+            email: new_case_insensitive_email_address(email).context("Failed to parse email")?,
+            timestamp: DateTime::parse_from_rfc3339(recorded_at)
+                .context("Failed to parse recorded time")?
+                .to_utc(),
+            region: region.to_owned(),
+            register_url: format!("/courses/{}/batches/{}/check-in", course, batch),
+        },
+    })
+}
+
+async fn record_check_in(
+    store: &RecordStore,
+    class: &CheckInClass,
+    name: &str,
+    email: &EmailAddress,
+    region: &str,
+    recorded_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    store
+        .append(
+            CHECK_INS_TAB,
+            &CHECK_INS_HEADINGS,
+            vec![
+                recorded_at.to_rfc3339(),
+                class.course_name.clone(),
+                class.batch_github_slug.clone(),
+                class.module_name.clone(),
+                class.sprint_number.to_string(),
+                name.to_owned(),
+                email.to_string(),
+                region.to_owned(),
+            ],
+        )
+        .await
+}
+
+/// The page staff put up on the screen in class, showing a code trainees can check in with.
+/// It refreshes itself with a new code every minute.
+pub async fn get_check_in_code(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    // Only staff can read the GitHub email mapping sheet, so this stops trainees making codes for themselves.
+    let sheets_client =
+        sheets_client(&session, server_state.clone(), headers, original_uri).await?;
+    get_trainees(
+        sheets_client,
+        &server_state.config.github_email_mapping_sheet_id,
+    )
+    .await?;
+    // Check-ins are stored as records, so there's nowhere to put them without a persistence sheet.
+    record_store(&server_state).await?;
+    let course_schedule = server_state
+        .config
+        .get_course_schedule_with_register_sheet_id(course.clone(), &batch_github_slug)
+        .ok_or_else(|| Error::UserFacing(format!("Course not found: {course}")))?;
+    let class = CheckInClass::today(
+        &course_schedule,
+        &batch_github_slug,
+        Utc::now().date_naive(),
+    );
+    let code = match &class {
+        Some(class) => {
+            let code = Uuid::new_v4().simple().to_string()[..6].to_uppercase();
+            server_state
+                .check_in_codes
+                .insert(code.clone(), class.clone())
+                .await;
+            Some(code)
+        }
+        None => None,
+    };
+    Ok(Html(
+        CheckInCodeTemplate {
+            course_name: course,
+            class,
+            code,
+            public_base_url: server_state.config.public_base_url.to_string(),
+            refresh_seconds: CHECK_IN_PAGE_REFRESH_SECONDS,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "check-in-code.html")]
+struct CheckInCodeTemplate {
+    course_name: String,
+    class: Option<CheckInClass>,
+    code: Option<String>,
+    public_base_url: String,
+    refresh_seconds: u64,
+}
+
+#[derive(Deserialize)]
+pub struct CheckInParams {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    lang: Option<Language>,
+}
+
+/// Where trainees enter the code shown in class.
+pub async fn get_check_in(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Query(params): Query<CheckInParams>,
+) -> Result<Html<String>, Error> {
+    let lang = language(&session, &headers, params.lang).await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let github_login = current_github_login(&octocrab).await?;
+    Ok(Html(
+        CheckInTemplate {
+            github_login: github_login.to_string(),
+            code: params.code.unwrap_or_default(),
+            checked_in: None,
+            error: None,
+            lang,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CheckInForm {
+    code: String,
+}
+
+pub async fn post_check_in(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Form(form): Form<CheckInForm>,
+) -> Result<Html<String>, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let lang = language(&session, &headers, None).await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let github_login = current_github_login(&octocrab).await?;
+    let code = form.code.trim().to_uppercase();
+    let render = |checked_in: Option<CheckInClass>, error: Option<String>| {
+        CheckInTemplate {
+            github_login: github_login.to_string(),
+            code: code.clone(),
+            checked_in,
+            error,
+            lang,
+        }
+        .render()
+        .unwrap()
+    };
+    let wrong_codes = server_state.wrong_check_in_codes.get(&github_login).await;
+    if wrong_codes.is_some_and(|wrong_codes| wrong_codes >= MAX_WRONG_CHECK_IN_CODES) {
+        return Ok(Html(render(
+            None,
+            Some(lang.t("check-in-too-many-attempts")),
+        )));
+    }
+    let Some(class) = server_state.check_in_codes.get(&code).await else {
+        server_state
+            .wrong_check_in_codes
+            .entry(github_login.clone())
+            .and_upsert_with(|wrong_codes| {
+                std::future::ready(
+                    wrong_codes.map_or(1, |wrong_codes| wrong_codes.into_value() + 1),
+                )
+            })
+            .await;
+        return Ok(Html(render(None, Some(lang.t("check-in-wrong-code")))));
+    };
+
+    // Trainees can't read the GitHub email mapping sheet, so look them up as the tracker itself.
+    let Some(service_credentials) = &server_state.config.service_credentials else {
+        return Err(Error::UserFacing(
+            "The trainee tracker isn't configured with service credentials, so can't check you in."
+                .to_owned(),
+        ));
+    };
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await?;
+    let trainees = get_trainees(
+        sheets_client,
+        &server_state.config.github_email_mapping_sheet_id,
+    )
+    .await?;
+    let Some(trainee) = trainees.get(&github_login) else {
+        return Ok(Html(render(
+            None,
+            Some(lang.t_with(
+                "check-in-unknown-trainee",
+                [("login", github_login.to_string())],
+            )),
+        )));
+    };
+    // Codes are only shown in the batch's class, but could be passed on to anyone.
+    let service_octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let batch_members = all_pages("members", &service_octocrab, async || {
+        service_octocrab
+            .teams(&server_state.config.github_org)
+            .members(&class.batch_github_slug)
+            .send()
+            .await
+    })
+    .await?;
+    if !batch_members
+        .iter()
+        .any(|member| GithubLogin::from(member.login.clone()) == github_login)
+    {
+        return Ok(Html(render(
+            None,
+            Some(lang.t_with(
+                "check-in-not-in-batch",
+                [("login", github_login.to_string())],
+            )),
+        )));
+    }
+    record_check_in(
+        &record_store(&server_state).await?,
+        &class,
+        &trainee.name,
+        &trainee.email,
+        trainee.region.as_str(),
+        Utc::now(),
+    )
+    .await?;
+    Ok(Html(render(Some(class), None)))
+}
+
+#[derive(Template)]
+#[template(path = "check-in.html")]
+struct CheckInTemplate {
+    github_login: String,
+    code: String,
+    checked_in: Option<CheckInClass>,
+    error: Option<String>,
+    lang: Language,
+}
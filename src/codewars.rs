@@ -0,0 +1,283 @@
+//! Trainees' progress on Codewars, where they practise solving small problems (kata) alongside their coursework.
+//!
+//! Trainees' Codewars usernames come from a sheet mapping GitHub logins to them, and/or from the issues trainees opened
+//! when they applied (Step 1), whose forms asked for their Codewars username. The sheet wins where both have one.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use futures::future::join_all;
+use octocrab::{Octocrab, params::State};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    Error, ServerState,
+    newtypes::GithubLogin,
+    octocrab::{all_pages, check_repo_access},
+    sheets::{SheetsClient, cell_string},
+};
+
+pub const CODEWARS_PROGRESS_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+/// New trainees' usernames show up this long after they open their issue, at worst.
+pub const CODEWARS_USERNAMES_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+
+const USERNAMES_SHEET_NAME: &str = "Codewars";
+
+/// Where to find trainees' Codewars usernames. Either or both can be set.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CodewarsConfig {
+    /// A sheet with a "Codewars" tab: a heading row, then a GitHub login and a Codewars username on each row.
+    #[serde(default)]
+    pub usernames_sheet_id: Option<String>,
+    /// A repo in the org whose issues were opened by trainees with a form with a "Codewars username" field, e.g. the Step 1 repo.
+    #[serde(default)]
+    pub usernames_issue_repo: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CodewarsProgress {
+    pub username: String,
+    pub completed_kata: u64,
+    /// E.g. "6 kyu".
+    pub rank: String,
+    pub honor: u64,
+}
+
+impl CodewarsProgress {
+    pub fn profile_url(&self) -> String {
+        format!("https://www.codewars.com/users/{}", self.username)
+    }
+}
+
+/// GitHub login -> Codewars username, for everyone we know one for.
+pub async fn get_codewars_usernames(
+    octocrab: &Octocrab,
+    sheets_client: SheetsClient,
+    github_org: &str,
+    config: &CodewarsConfig,
+) -> Result<BTreeMap<GithubLogin, String>, Error> {
+    let mut usernames = BTreeMap::new();
+    if let Some(repo) = &config.usernames_issue_repo {
+        let issue_usernames =
+            get_issue_usernames(sheets_client.server_state(), octocrab, github_org, repo).await?;
+        usernames.extend(
+            issue_usernames
+                .iter()
+                .map(|(github_login, username)| (github_login.clone(), username.clone())),
+        );
+    }
+    if let Some(sheet_id) = &config.usernames_sheet_id {
+        match sheets_client.get(sheet_id).await {
+            Ok(sheets) => {
+                let Some(sheet) = sheets.get(USERNAMES_SHEET_NAME) else {
+                    return Err(Error::Fatal(anyhow::anyhow!(
+                        "Didn't find sheet '{}' in Codewars usernames sheet with id {}",
+                        USERNAMES_SHEET_NAME,
+                        sheet_id
+                    )));
+                };
+                for cells in sheet.rows.iter().skip(1) {
+                    let [github_login, username, ..] = cells.as_slice() else {
+                        continue;
+                    };
+                    let github_login = cell_string(github_login).trim().to_owned();
+                    let username = cell_string(username).trim().to_owned();
+                    if !github_login.is_empty() && !username.is_empty() {
+                        usernames.insert(GithubLogin::from(github_login), username);
+                    }
+                }
+            }
+            // Codewars progress is a nice-to-have, so people who can't read the sheet just see less of it.
+            Err(Error::PermissionDenied(_)) => {
+                warn!("Can't read Codewars usernames sheet {}", sheet_id);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(usernames)
+}
+
+/// The usernames in the issue repo, which are cached as listing every issue in it takes many requests.
+async fn get_issue_usernames(
+    server_state: &ServerState,
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo: &str,
+) -> Result<Arc<BTreeMap<GithubLogin, String>>, Error> {
+    let key = format!("{}/{}", github_org, repo);
+    if let Some(usernames) = server_state.codewars_issue_usernames.get(&key).await {
+        // Whoever filled the cache could see the repo, but that says nothing about this client.
+        check_repo_access(octocrab, github_org, repo).await?;
+        return Ok(usernames);
+    }
+    let flight = server_state
+        .in_flight
+        .codewars_issue_usernames
+        .run(&key, async || {
+            fetch_issue_usernames(octocrab, github_org, repo)
+                .await
+                .map(Arc::new)
+        })
+        .await?;
+    if flight.shared {
+        check_repo_access(octocrab, github_org, repo).await?;
+    } else {
+        server_state
+            .codewars_issue_usernames
+            .insert(key, flight.value.clone())
+            .await;
+    }
+    Ok(flight.value)
+}
+
+async fn fetch_issue_usernames(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo: &str,
+) -> Result<BTreeMap<GithubLogin, String>, Error> {
+    let issues = all_pages("issues", octocrab, async || {
+        octocrab
+            .issues(github_org, repo)
+            .list()
+            .state(State::All)
+            .send()
+            .await
+    })
+    .await?;
+    let mut usernames = BTreeMap::new();
+    // Oldest first, so that if someone applied more than once, their latest username wins.
+    for issue in issues.into_iter().rev() {
+        if issue.pull_request.is_some() {
+            continue;
+        }
+        if let Some(username) = username_from_issue_body(&issue.body.unwrap_or_default()) {
+            usernames.insert(GithubLogin::from(issue.user.login), username);
+        }
+    }
+    Ok(usernames)
+}
+
+/// Each trainee's progress, for those whose Codewars profiles could be loaded. Profiles which couldn't be are logged and left out.
+pub async fn get_codewars_progress(
+    server_state: &ServerState,
+    usernames: &BTreeMap<GithubLogin, String>,
+) -> BTreeMap<GithubLogin, CodewarsProgress> {
+    let futures = usernames.iter().map(async |(github_login, username)| {
+        if let Some(progress) = server_state.codewars_progress.get(username).await {
+            return Some((github_login.clone(), progress));
+        }
+        match fetch_codewars_progress(server_state, username).await {
+            Ok(progress) => {
+                server_state
+                    .codewars_progress
+                    .insert(username.clone(), progress.clone())
+                    .await;
+                Some((github_login.clone(), progress))
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to get Codewars progress for {} ({}): {:?}",
+                    github_login, username, err
+                );
+                None
+            }
+        }
+    });
+    join_all(futures).await.into_iter().flatten().collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CodewarsUser {
+    username: String,
+    honor: u64,
+    ranks: CodewarsRanks,
+    code_challenges: CodewarsCodeChallenges,
+}
+
+#[derive(Deserialize)]
+struct CodewarsRanks {
+    overall: CodewarsRank,
+}
+
+#[derive(Deserialize)]
+struct CodewarsRank {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CodewarsCodeChallenges {
+    total_completed: u64,
+}
+
+async fn fetch_codewars_progress(
+    server_state: &ServerState,
+    username: &str,
+) -> Result<CodewarsProgress, Error> {
+    let mut url = url::Url::parse("https://www.codewars.com/api/v1/users/").unwrap();
+    url.path_segments_mut().unwrap().push(username);
+    let user: CodewarsUser = server_state
+        .http_clients
+        .codewars
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(anyhow::Error::from)?
+        .json()
+        .await
+        .map_err(anyhow::Error::from)?;
+    Ok(CodewarsProgress {
+        username: user.username,
+        completed_kata: user.code_challenges.total_completed,
+        rank: user.ranks.overall.name,
+        honor: user.honor,
+    })
+}
+
+/// Issue forms render each field as a `### Heading`, followed by the answer, or `_No response_`.
+fn username_from_issue_body(body: &str) -> Option<String> {
+    let mut lines = body.lines().map(str::trim);
+    lines.find(|line| {
+        line.starts_with('#') && line.to_ascii_lowercase().contains("codewars username")
+    })?;
+    let answer = lines.find(|line| !line.is_empty())?;
+    if answer.starts_with('#') || answer == "_No response_" {
+        return None;
+    }
+    let username = answer
+        .trim_start_matches("https://www.codewars.com/users/")
+        .trim_start_matches('@')
+        .trim_end_matches('/');
+    (!username.is_empty()).then(|| username.to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::username_from_issue_body;
+
+    #[test]
+    fn finds_username_in_issue_form() {
+        let body = "### Your name\n\nSam Smith\n\n### Codewars username\n\nsamsmith99\n\n### Anything else?\n\n_No response_";
+        assert_eq!(
+            username_from_issue_body(body),
+            Some("samsmith99".to_owned())
+        );
+        assert_eq!(
+            username_from_issue_body(
+                "### Codewars Username\n\nhttps://www.codewars.com/users/samsmith99/"
+            ),
+            Some("samsmith99".to_owned())
+        );
+        assert_eq!(
+            username_from_issue_body("### Codewars username\n\n_No response_"),
+            None
+        );
+        assert_eq!(
+            username_from_issue_body("### Codewars username\n\n### Next question\n\nAnswer"),
+            None
+        );
+        assert_eq!(username_from_issue_body("Just some text"), None);
+    }
+}
@@ -1,11 +1,44 @@
-use axum::{Json, body::Body, extract::Request};
+use axum::{Json, body::Body, extract::Request, extract::State};
 use futures::StreamExt;
 use http::HeaderMap;
+use serde::Deserialize;
+use tracing::warn;
 
-use crate::Error;
+use crate::{
+    Error, ServerState,
+    assessments::{AssessmentEvent, AssessmentEventKind, record_assessment_event},
+    persistence::RecordStore,
+};
+
+/// The parts of the candidate session Codility calls back with that we record.
+#[derive(Deserialize)]
+struct CodilitySession {
+    candidate: CodilityCandidate,
+    test: CodilityTest,
+    /// Only set once the session has been scored.
+    #[serde(default)]
+    evaluation: Option<CodilityEvaluation>,
+}
+
+#[derive(Deserialize)]
+struct CodilityCandidate {
+    email: String,
+}
+
+#[derive(Deserialize)]
+struct CodilityTest {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct CodilityEvaluation {
+    result: u32,
+    max_result: u32,
+}
 
 // See Callback Authentication section of https://codility.com/api-documentation/#/operations/tests_invite_create
 pub async fn verify_webhook(
+    State(server_state): State<ServerState>,
     header_map: HeaderMap,
     body: Request<Body>,
 ) -> Result<Json<bool>, Error> {
@@ -20,11 +53,13 @@ pub async fn verify_webhook(
     };
 
     let mut hasher = md5::Context::new();
+    let mut bytes = Vec::new();
 
     let mut data_stream = body.into_body().into_data_stream();
     while let Some(chunk) = data_stream.next().await {
         if let Ok(chunk) = chunk {
-            hasher.consume(chunk);
+            hasher.consume(&chunk);
+            bytes.extend_from_slice(&chunk);
         } else {
             return Err(Error::UserFacing("Failed to read request body".to_owned()));
         }
@@ -32,7 +67,52 @@ pub async fn verify_webhook(
     hasher.consume(token);
     let digest = hasher.finalize();
     let formatted_digest = format!("{:x}", digest);
-    Ok(Json(
-        formatted_digest.as_bytes() == posted_checksum.as_bytes(),
-    ))
+    let verified = formatted_digest.as_bytes() == posted_checksum.as_bytes();
+    if verified {
+        record_session(&server_state, &bytes).await?;
+    }
+    Ok(Json(verified))
+}
+
+/// Records the callback in the timed assessment store, if the test is one of the configured timed assessments.
+async fn record_session(server_state: &ServerState, body: &[u8]) -> Result<(), Error> {
+    let Some(config) = &server_state.config.timed_assessments else {
+        return Ok(());
+    };
+    let session: CodilitySession = match serde_json::from_slice(body) {
+        Ok(session) => session,
+        Err(err) => {
+            warn!("Ignoring Codility callback we couldn't parse: {}", err);
+            return Ok(());
+        }
+    };
+    let Some(assessment) = config
+        .tests
+        .iter()
+        .find(|(_, test_id)| **test_id == session.test.id)
+        .map(|(assessment, _)| assessment.clone())
+    else {
+        return Ok(());
+    };
+    let Some(store) = RecordStore::from_config(server_state).await? else {
+        return Ok(());
+    };
+    let (kind, score) = match session.evaluation {
+        Some(evaluation) if evaluation.max_result > 0 => (
+            AssessmentEventKind::Completed,
+            Some(evaluation.result * 100 / evaluation.max_result),
+        ),
+        _ => (AssessmentEventKind::Started, None),
+    };
+    record_assessment_event(
+        &store,
+        &AssessmentEvent {
+            email: session.candidate.email,
+            assessment,
+            kind,
+            recorded_at: chrono::Utc::now(),
+            score,
+        },
+    )
+    .await
 }
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use futures::future::join_all;
+use http::HeaderMap;
+use octocrab::Octocrab;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::{Batch, Submission, SubmissionState},
+    frontend::get_course_and_batch,
+    newtypes::GithubLogin,
+    octocrab::{all_pages, octocrab},
+    prs::Pr,
+};
+
+/// Who git says wrote one of a PR's commits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitAuthor {
+    pub sha: String,
+    /// The GitHub account the commit's email is linked to, if any.
+    pub login: Option<GithubLogin>,
+    pub email: String,
+}
+
+impl CommitAuthor {
+    pub fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+}
+
+pub async fn get_pr_commit_authors(
+    octocrab: &Octocrab,
+    org_name: &str,
+    repo_name: &str,
+    number: u64,
+) -> Result<Vec<CommitAuthor>, Error> {
+    let commits = all_pages("commits in pull request", octocrab, async || {
+        octocrab
+            .pulls(org_name, repo_name)
+            .pr_commits(number)
+            .send()
+            .await
+    })
+    .await
+    .map_err(|err| err.with_context(|| format!("Failed to get commits for PR {}", number)))?;
+    Ok(commits
+        .into_iter()
+        .map(|commit| CommitAuthor {
+            sha: commit.sha,
+            login: commit.author.map(|author| GithubLogin::from(author.login)),
+            email: commit
+                .commit
+                .author
+                .map(|author| author.email)
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Whether `email` is a GitHub noreply address for `login`, i.e. `login@users.noreply.github.com` or `123+login@users.noreply.github.com`.
+fn is_noreply_email_for(email: &str, login: &GithubLogin) -> bool {
+    let email = email.to_ascii_lowercase();
+    let Some(local_part) = email.strip_suffix("@users.noreply.github.com") else {
+        return false;
+    };
+    let username = local_part
+        .split_once('+')
+        .map_or(local_part, |(_, username)| username);
+    GithubLogin::from(username.to_owned()) == *login
+}
+
+/// The commits which don't look like they were written by `pr_author`.
+///
+/// A commit is fine if GitHub links it to the PR author's account, or its email is one we know belongs to them.
+/// Anything else suggests the work was pushed from someone else's machine, or that the trainee's git is misconfigured.
+pub fn mismatched_commits(
+    commits: &[CommitAuthor],
+    pr_author: &GithubLogin,
+    known_emails: &[String],
+) -> Vec<CommitAuthor> {
+    commits
+        .iter()
+        .filter(|commit| {
+            let linked_to_author = commit.login.as_ref() == Some(pr_author);
+            let known_email = known_emails
+                .iter()
+                .any(|known_email| known_email.eq_ignore_ascii_case(&commit.email));
+            !linked_to_author && !known_email && !is_noreply_email_for(&commit.email, pr_author)
+        })
+        .cloned()
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct PrAuthorship {
+    pub trainee_name: String,
+    pub pr: Pr,
+    pub mismatched: Vec<CommitAuthor>,
+}
+
+/// The batch's coursework PRs, by module, each with any commits whose authors don't match the trainee.
+pub async fn batch_commit_authorship(
+    octocrab: &Octocrab,
+    github_org: &str,
+    batch: &Batch,
+) -> Result<BTreeMap<String, Vec<PrAuthorship>>, Error> {
+    let mut prs = Vec::new();
+    for trainee in &batch.trainees {
+        for (module_name, module) in &trainee.modules {
+            for sprint in &module.sprints {
+                for submission in &sprint.submissions {
+                    if let SubmissionState::Some(Submission::PullRequest { pull_request, .. }) =
+                        submission
+                    {
+                        prs.push((module_name.clone(), trainee, pull_request.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    let commit_authors =
+        join_all(prs.iter().map(|(_, _, pr)| {
+            get_pr_commit_authors(octocrab, github_org, &pr.repo_name, pr.number)
+        }))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut by_module: BTreeMap<String, Vec<PrAuthorship>> = BTreeMap::new();
+    for ((module_name, trainee, pr), commits) in prs.into_iter().zip(commit_authors) {
+        let known_emails = [trainee.trainee.email.to_string()];
+        by_module
+            .entry(module_name)
+            .or_default()
+            .push(PrAuthorship {
+                trainee_name: trainee.trainee.display_name().to_owned(),
+                mismatched: mismatched_commits(&commits, &pr.author, &known_emails),
+                pr,
+            });
+    }
+    Ok(by_module)
+}
+
+pub async fn get_commit_authorship(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    // Loading the batch needs the staff-only GitHub email mapping sheet, so this is staff-only too.
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri.clone(),
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let modules =
+        batch_commit_authorship(&octocrab, &server_state.config.github_org, &batch).await?;
+    Ok(Html(
+        CommitAuthorshipTemplate {
+            course_name: course.name,
+            batch_name: batch.name,
+            modules,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "commit-authorship.html")]
+struct CommitAuthorshipTemplate {
+    course_name: String,
+    batch_name: String,
+    modules: BTreeMap<String, Vec<PrAuthorship>>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommitAuthor, mismatched_commits};
+    use crate::newtypes::GithubLogin;
+
+    fn commit(login: Option<&str>, email: &str) -> CommitAuthor {
+        CommitAuthor {
+            sha: "0123456789abcdef".to_owned(),
+            login: login.map(|login| GithubLogin::from(login.to_owned())),
+            email: email.to_owned(),
+        }
+    }
+
+    #[test]
+    fn accepts_commits_linked_to_the_author() {
+        let author = GithubLogin::from("trainee".to_owned());
+        let commits = [commit(Some("trainee"), "somewhere@example.com")];
+        assert_eq!(mismatched_commits(&commits, &author, &[]), vec![]);
+    }
+
+    #[test]
+    fn accepts_known_and_noreply_emails() {
+        let author = GithubLogin::from("Trainee".to_owned());
+        let commits = [
+            commit(None, "Trainee@Example.com"),
+            commit(None, "12345+trainee@users.noreply.github.com"),
+            commit(None, "trainee@users.noreply.github.com"),
+        ];
+        assert_eq!(
+            mismatched_commits(&commits, &author, &["trainee@example.com".to_owned()]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn flags_other_peoples_commits() {
+        let author = GithubLogin::from("trainee".to_owned());
+        let commits = [
+            commit(Some("someone-else"), "someone@example.com"),
+            commit(None, "root@localhost"),
+            commit(None, "12345+someone-else@users.noreply.github.com"),
+        ];
+        assert_eq!(
+            mismatched_commits(&commits, &author, &["trainee@example.com".to_owned()]),
+            commits.to_vec()
+        );
+    }
+}
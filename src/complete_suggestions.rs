@@ -0,0 +1,324 @@
+use anyhow::Context;
+use askama::Template;
+use axum::{
+    Form,
+    extract::{OriginalUri, Path, State},
+    response::{Html, Redirect},
+};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use octocrab::{
+    Octocrab,
+    models::{Event, pulls::ReviewState},
+};
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    impersonation::forbid_while_viewing_as,
+    newtypes::GithubLogin,
+    octocrab::{all_pages, octocrab},
+    pr_comments::PullRequest,
+    prs::{PrState, get_last_push, get_prs, get_review_states},
+};
+
+/// A PR labelled Reviewed which has since been approved, so probably just needs its label flipping to Complete.
+#[derive(Clone, Debug)]
+pub struct CompleteSuggestion {
+    pub module: String,
+    pub number: u64,
+    pub url: String,
+    pub title: String,
+    pub author: GithubLogin,
+    pub approved_by: GithubLogin,
+    pub approved_at: DateTime<Utc>,
+}
+
+/// Who approved the PR, if the trainee pushed changes after it was labelled Reviewed, and a reviewer approved it after their last push
+/// without anyone requesting changes since.
+fn approval_after_changes(
+    reviewed_at: DateTime<Utc>,
+    last_push: Option<DateTime<Utc>>,
+    reviews: &[(GithubLogin, ReviewState, DateTime<Utc>)],
+    author: &GithubLogin,
+) -> Option<(GithubLogin, DateTime<Utc>)> {
+    let last_push = last_push.filter(|last_push| *last_push > reviewed_at)?;
+    // Comments on their own don't say whether the PR is done.
+    let (approved_by, state, approved_at) = reviews
+        .iter()
+        .filter(|(reviewer, state, _)| {
+            reviewer != author
+                && matches!(state, ReviewState::Approved | ReviewState::ChangesRequested)
+        })
+        .max_by_key(|(_, _, submitted_at)| *submitted_at)?;
+    if *state != ReviewState::Approved || *approved_at < last_push {
+        return None;
+    }
+    Some((approved_by.clone(), *approved_at))
+}
+
+/// Works out whether a PR currently labelled Reviewed should be suggested for Complete.
+pub(crate) async fn complete_suggestion(
+    octocrab: &Octocrab,
+    pull_request: &PullRequest,
+    author: &GithubLogin,
+) -> Result<Option<(GithubLogin, DateTime<Utc>)>, Error> {
+    let events = all_pages("timeline events", octocrab, async || {
+        octocrab
+            .issues(&pull_request.org, &pull_request.repo)
+            .list_timeline_events(pull_request.number)
+            .send()
+            .await
+    })
+    .await?;
+    let Some(reviewed_at) = events
+        .iter()
+        .filter(|event| {
+            event.event == Event::Labeled
+                && event
+                    .label
+                    .as_ref()
+                    .is_some_and(|label| label.name == "Reviewed")
+        })
+        .filter_map(|event| event.created_at)
+        .max()
+    else {
+        return Ok(None);
+    };
+
+    let last_push = get_last_push(
+        octocrab,
+        &pull_request.org,
+        &pull_request.repo,
+        pull_request.number,
+    )
+    .await?;
+    let reviews = get_review_states(
+        octocrab,
+        &pull_request.org,
+        &pull_request.repo,
+        pull_request.number,
+    )
+    .await?;
+
+    Ok(approval_after_changes(
+        reviewed_at,
+        last_push,
+        &reviews,
+        author,
+    ))
+}
+
+/// Swaps a PR's Reviewed label for Complete.
+pub(crate) async fn mark_complete(
+    octocrab: &Octocrab,
+    pull_request: &PullRequest,
+) -> Result<(), Error> {
+    octocrab
+        .issues(&pull_request.org, &pull_request.repo)
+        .add_labels(pull_request.number, &["Complete".to_owned()])
+        .await
+        .with_context(|| format!("Failed to label {} Complete", pull_request.html_url()))?;
+    match octocrab
+        .issues(&pull_request.org, &pull_request.repo)
+        .remove_label(pull_request.number, "Reviewed")
+        .await
+    {
+        Ok(_) => Ok(()),
+        // The only time this API 404s is if the label is already removed.
+        Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 404 => Ok(()),
+        Err(err) => Err(err)
+            .with_context(|| {
+                format!(
+                    "Failed to remove Reviewed label from {}",
+                    pull_request.html_url()
+                )
+            })
+            .map_err(Error::from),
+    }
+}
+
+/// Open PRs in the course's modules which are stuck on Reviewed even though they've been approved since the trainee's changes.
+pub async fn get_complete_suggestions(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Html<String>, Error> {
+    let module_names = server_state
+        .config
+        .get_course_module_names(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let github_org = &server_state.config.github_org;
+
+    let module_futures = module_names.iter().map(async |module_name| {
+        let prs = get_prs(
+            &octocrab,
+            &server_state.in_flight,
+            github_org,
+            module_name,
+            false,
+        )
+        .await?;
+        let suggestion_futures = prs
+            .into_iter()
+            .filter(|pr| pr.state == PrState::Reviewed)
+            .map(async |pr| {
+                let pull_request = PullRequest {
+                    org: github_org.clone(),
+                    repo: pr.repo_name.clone(),
+                    number: pr.number,
+                };
+                let approval = complete_suggestion(&octocrab, &pull_request, &pr.author).await?;
+                Ok::<_, Error>(
+                    approval.map(|(approved_by, approved_at)| CompleteSuggestion {
+                        module: pr.repo_name,
+                        number: pr.number,
+                        url: pr.url,
+                        title: pr.title,
+                        author: pr.author,
+                        approved_by,
+                        approved_at,
+                    }),
+                )
+            });
+        join_all(suggestion_futures)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+    });
+    let mut suggestions = Vec::new();
+    for result in join_all(module_futures).await {
+        suggestions.extend(result?.into_iter().flatten());
+    }
+    suggestions.sort_by_key(|suggestion| suggestion.approved_at);
+
+    Ok(Html(
+        CompleteSuggestionsTemplate {
+            course,
+            suggestions,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct MarkCompleteForm {
+    repo: String,
+    number: u64,
+}
+
+pub async fn post_mark_complete(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+    Form(form): Form<MarkCompleteForm>,
+) -> Result<Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let module_names = server_state
+        .config
+        .get_course_module_names(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    if !module_names.contains(&form.repo) {
+        return Err(Error::UserFacing(format!(
+            "{} isn't one of {}'s modules",
+            form.repo, course
+        )));
+    }
+    // Labelled with the user's own token, so only people who can label PRs in the module can do this.
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    mark_complete(
+        &octocrab,
+        &PullRequest {
+            org: server_state.config.github_org.clone(),
+            repo: form.repo,
+            number: form.number,
+        },
+    )
+    .await?;
+    Ok(Redirect::to(&format!(
+        "/courses/{}/complete-suggestions",
+        course
+    )))
+}
+
+#[derive(Template)]
+#[template(path = "complete-suggestions.html")]
+struct CompleteSuggestionsTemplate {
+    course: String,
+    suggestions: Vec<CompleteSuggestion>,
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Utc};
+    use octocrab::models::pulls::ReviewState;
+
+    use super::approval_after_changes;
+    use crate::newtypes::GithubLogin;
+
+    #[test]
+    fn approval_after_changes_needs_push_then_approval() {
+        let time = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let trainee = GithubLogin::from("trainee".to_owned());
+        let mentor = GithubLogin::from("mentor".to_owned());
+        let reviewed_at = time("2026-09-01T10:00:00Z");
+        let pushed_at = time("2026-09-02T10:00:00Z");
+        let approval = (
+            mentor.clone(),
+            ReviewState::Approved,
+            time("2026-09-03T10:00:00Z"),
+        );
+
+        assert_eq!(
+            approval_after_changes(reviewed_at, Some(pushed_at), &[approval.clone()], &trainee),
+            Some((mentor.clone(), time("2026-09-03T10:00:00Z")))
+        );
+        // No changes since the Reviewed label.
+        assert_eq!(
+            approval_after_changes(
+                reviewed_at,
+                Some(time("2026-08-31T10:00:00Z")),
+                &[approval.clone()],
+                &trainee
+            ),
+            None
+        );
+        // Pushed again after the approval.
+        assert_eq!(
+            approval_after_changes(
+                reviewed_at,
+                Some(time("2026-09-04T10:00:00Z")),
+                &[approval.clone()],
+                &trainee
+            ),
+            None
+        );
+        // Someone requested changes after approving.
+        assert_eq!(
+            approval_after_changes(
+                reviewed_at,
+                Some(pushed_at),
+                &[
+                    approval.clone(),
+                    (
+                        mentor.clone(),
+                        ReviewState::ChangesRequested,
+                        time("2026-09-03T12:00:00Z")
+                    ),
+                    (
+                        trainee.clone(),
+                        ReviewState::Commented,
+                        time("2026-09-03T13:00:00Z")
+                    ),
+                ],
+                &trainee
+            ),
+            None
+        );
+    }
+}
@@ -1,11 +1,26 @@
-use std::{collections::BTreeMap, net::IpAddr};
+use std::{collections::BTreeMap, net::IpAddr, time::Duration};
 
-use chrono::NaiveDate;
+use chrono::{Days, NaiveDate, TimeDelta};
 use indexmap::IndexMap;
 use serde::Deserialize;
 use serde_env_field::EnvField;
 
-use crate::newtypes::Region;
+use crate::{
+    absence_streaks::AbsenceStreakPolicy,
+    assessments::TimedAssessmentConfig,
+    attendance_policy::AttendancePolicy,
+    codewars::CodewarsConfig,
+    discussions::DEFAULT_HELP_REQUEST_AGE_HOURS,
+    groups::GroupAssignment,
+    holidays::HolidayCalendar,
+    newtypes::{CourseName, GithubLogin, Region, SheetId, TeamSlug},
+    on_call::OnCallRota,
+    project_repos::ProjectRepoAssignment,
+    review_sla::ReviewSla,
+    risk_rules::AtRiskRule,
+    timeline::AssessmentWindow,
+    trainee_directory::DEFAULT_TRAINEE_DIRECTORY_CACHE_MINUTES,
+};
 
 #[derive(Clone, Deserialize)]
 pub struct Config {
@@ -26,7 +41,7 @@ pub struct Config {
     ///  ${course}-trainees contains groups of batches of trainees.
     ///  ${course}-mentors is a group of reviewers.
     /// e.g. for itp, we'd expect itp-trainees/2025-05 and itp-mentors to exist.
-    pub courses: IndexMap<String, CourseInfo>,
+    pub courses: IndexMap<CourseName, CourseInfo>,
 
     pub google_apis_client_id: EnvField<String>,
     pub google_apis_client_secret: EnvField<String>,
@@ -34,31 +49,227 @@ pub struct Config {
     pub slack_client_id: String,
     pub slack_client_secret: EnvField<String>,
 
-    pub github_email_mapping_sheet_id: String,
+    pub github_email_mapping_sheet_id: SheetId,
 
-    pub mentoring_records_sheet_id: String,
+    pub mentoring_records_sheet_id: SheetId,
 
-    pub reviewer_staff_info_sheet_id: String,
+    pub reviewer_staff_info_sheet_id: SheetId,
+
+    /// Sheet containing trainees' application data (e.g. entry test scores).
+    /// Only shown to users whose Google account can read the sheet.
+    #[serde(default)]
+    pub admissions_sheet_id: Option<SheetId>,
+
+    /// Sheet which the batch-snapshots job adds a dated tab to for each batch, for reports which read from Sheets.
+    /// The service account needs edit access to it.
+    #[serde(default)]
+    pub snapshot_sheet_id: Option<SheetId>,
+
+    /// Sheet where the tracker stores records it's the source of truth for, e.g. trainees' sprint goals.
+    /// The service account needs edit access to it. If unset, features which store records are disabled.
+    #[serde(default)]
+    pub persistence_sheet_id: Option<SheetId>,
+
+    /// Used to verify that requests to our Slack command endpoints really came from Slack.
+    /// If unset, Slack commands are rejected.
+    #[serde(default)]
+    pub slack_signing_secret: Option<EnvField<String>>,
+
+    /// Slack user IDs (e.g. `U2CERLKJA`) of staff who may use our Slack commands.
+    /// Anyone in the workspace can run a slash command, so commands from anyone else are refused.
+    #[serde(default)]
+    pub slack_command_users: Vec<String>,
+
+    /// Used to verify that requests to our GitHub webhook endpoint really came from GitHub.
+    /// If unset, GitHub webhooks are rejected.
+    #[serde(default)]
+    pub github_webhook_secret: Option<EnvField<String>>,
+
+    /// Review rubric for each module (keyed by module repo name).
+    /// When a mentor claims a PR in the module by assigning themselves, the rubric is posted on the PR as a checklist.
+    #[serde(default)]
+    pub review_checklists: BTreeMap<String, Vec<String>>,
+
+    /// When a PR labelled Reviewed is approved after the trainee has pushed changes, swap its label for Complete automatically
+    /// (needs the GitHub webhook to send "Pull request reviews" events), rather than just suggesting it on the course's complete suggestions page.
+    #[serde(default)]
+    pub auto_complete_approved_prs: bool,
+
+    /// When a trainee edits the title or description of a PR which failed the PR validator, validate it again straight away
+    /// (needs the GitHub webhook to send "Pull requests" events), hiding the validator's comments if it now passes.
+    #[serde(default)]
+    pub rerun_pr_validator_on_edit: bool,
+
+    /// Sentry-compatible DSN (e.g. `https://key@o123.ingest.sentry.io/456`) which the server and jobs send fatal errors and panics to.
+    /// If unset, errors are only logged.
+    #[serde(default)]
+    pub error_report_dsn: Option<EnvField<String>>,
+
+    /// Slack incoming webhook which the reviewer-inactivity-alerts job posts to, e.g. for the volunteer coordinators' channel.
+    #[serde(default)]
+    pub reviewer_alerts_slack_webhook_url: Option<EnvField<String>>,
+
+    /// Slack incoming webhook which the data-health job posts its daily report to, e.g. for the ops team's channel.
+    #[serde(default)]
+    pub data_health_slack_webhook_url: Option<EnvField<String>>,
+
+    /// GitHub logins of people who can use admin tools, e.g. viewing the tracker as someone else.
+    #[serde(default)]
+    pub admins: Vec<GithubLogin>,
+
+    /// Read-only GitHub tokens which back the few pages that only show public data (e.g. the courses list),
+    /// so that they work for people who haven't logged in with GitHub yet.
+    /// Requests are spread across the tokens, and each token's use is counted separately on the status page.
+    #[serde(default)]
+    pub shared_github_tokens: Vec<SharedGithubToken>,
+
+    /// Tokens which other services (e.g. a trainee portal) can send as `Authorization: Bearer <token>` to use the versioned `/api/v1` endpoints on behalf of any trainee.
+    #[serde(default)]
+    pub api_tokens: Vec<ApiToken>,
+
+    /// Credentials used when we're acting on behalf of the tracker itself, rather than a signed-in user
+    /// (e.g. when responding to a Slack command, where there is no browser session to take tokens from).
+    #[serde(default)]
+    pub service_credentials: Option<ServiceCredentials>,
+
+    /// How long a question in a module repo's Discussions can go unanswered before it's listed as needing attention.
+    /// Defaults to discussions::DEFAULT_HELP_REQUEST_AGE_HOURS.
+    #[serde(default)]
+    pub help_request_age_hours: Option<i64>,
+
+    /// How long the GitHub email mapping sheet is cached for before it's read again. Staff can reload it sooner from /status.
+    /// Defaults to trainee_directory::DEFAULT_TRAINEE_DIRECTORY_CACHE_MINUTES.
+    #[serde(default)]
+    pub trainee_directory_cache_minutes: Option<u64>,
+
+    /// Where to find trainees' Codewars usernames, to show their Codewars progress on batch pages.
+    #[serde(default)]
+    pub codewars: Option<CodewarsConfig>,
+
+    /// How to handle each `Submit:` label on assignment issues, in addition to the built-in ones.
+    #[serde(default)]
+    pub submit_labels: SubmitLabelTaxonomy,
+
+    /// Settings for modules which don't follow the usual conventions, keyed by module repo name.
+    #[serde(default)]
+    pub module_settings: BTreeMap<String, ModuleSettings>,
+
+    /// Path to the weasyprint binary used to render PDF reports.
+    /// Defaults to pdf::DEFAULT_WEASYPRINT_PATH.
+    #[serde(default)]
+    pub weasyprint_path: Option<String>,
+
+    /// Warning signs to flag on trainees, in addition to their overall status.
+    #[serde(default)]
+    pub at_risk_rules: Vec<AtRiskRule>,
+
+    /// How many consecutive absences from their region's classes get a trainee a badge, and an event from the publish-events job.
+    /// If unset, absence streaks aren't flagged (though an at-risk rule can still badge consecutive absences).
+    #[serde(default)]
+    pub absence_streak: Option<AbsenceStreakPolicy>,
+
+    /// Who escalations (e.g. trainees becoming at risk, found by the publish-events job) go to each week.
+    #[serde(default)]
+    pub on_call_rota: OnCallRota,
+
+    /// Timed assessments (e.g. Codility tests) to track on each batch's assessments page.
+    /// If unset, the page is disabled and Codility callbacks aren't recorded.
+    #[serde(default)]
+    pub timed_assessments: Option<TimedAssessmentConfig>,
+
+    /// Each region's holidays (e.g. bank holidays), which class dates are moved off when cloning batches or discovering modules.
+    #[serde(default)]
+    pub holidays: HolidayCalendar,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ServiceCredentials {
+    /// A GitHub token with read access to the org's teams and module repos.
+    pub github_token: EnvField<String>,
+    /// Path to a Google service account key file.
+    /// The service account needs to have been given read access to the sheets referenced in this config.
+    pub google_service_account_key_path: EnvField<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SharedGithubToken {
+    /// Shown on the status page and in logs, so that rate limit use can be attributed to whoever the token belongs to.
+    pub name: String,
+    /// Should only have read access to public data.
+    pub token: EnvField<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ApiToken {
+    /// Logged when the token is used, so that requests can be attributed to whichever service made them.
+    pub name: String,
+    pub token: EnvField<String>,
 }
 
 #[derive(Clone, Deserialize)]
 pub struct CourseInfo {
-    pub register_sheet_id: String,
-    pub batches: IndexMap<String, CourseSchedule>,
+    pub register_sheet_id: SheetId,
+    /// Keyed by the GitHub team slug of the batch's trainees team.
+    pub batches: IndexMap<TeamSlug, CourseSchedule>,
+    /// Where the publish-events job sends events about the course's batches (e.g. a trainee's status changing).
+    #[serde(default)]
+    pub event_webhooks: Vec<EventWebhook>,
+    /// Which entry counts when a trainee registers more than once for the same class.
+    #[serde(default)]
+    pub duplicate_register_entries: DuplicateRegisterEntries,
+    /// When trainees count as late to the course's classes.
+    #[serde(default)]
+    pub lateness: LatenessPolicy,
+    /// The course's formal attendance requirements, checked separately from the progress score.
+    #[serde(default)]
+    pub attendance_policy: Option<AttendancePolicy>,
+    /// GitHub topic (e.g. cyf-itp-module) marking the org's repos which are modules of this course.
+    /// Tagged repos with a tracker.toml are added to every batch's sprints, see module_discovery.
+    #[serde(default)]
+    pub module_topic: Option<String>,
+    /// How quickly the course's PRs should get a first review, tracked on the review SLA page and by the review-sla-alerts job.
+    #[serde(default)]
+    pub review_sla: Option<ReviewSla>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct EventWebhook {
+    pub url: EnvField<String>,
+    /// Used to sign each event, so the receiver can check it came from us.
+    pub secret: EnvField<String>,
 }
 
 impl Config {
+    pub fn help_request_age(&self) -> TimeDelta {
+        TimeDelta::hours(
+            self.help_request_age_hours
+                .unwrap_or(DEFAULT_HELP_REQUEST_AGE_HOURS),
+        )
+    }
+
+    pub fn trainee_directory_cache_ttl(&self) -> Duration {
+        Duration::from_secs(
+            60 * self
+                .trainee_directory_cache_minutes
+                .unwrap_or(DEFAULT_TRAINEE_DIRECTORY_CACHE_MINUTES),
+        )
+    }
+
     pub fn get_course_schedule_with_register_sheet_id(
         &self,
         course_name: String,
         batch: &str,
     ) -> Option<CourseScheduleWithRegisterSheetId> {
-        if let Some(course_info) = self.courses.get(&course_name) {
+        if let Some(course_info) = self.courses.get(course_name.as_str()) {
             course_info.batches.get(batch).map(|course_schedule| {
                 CourseScheduleWithRegisterSheetId {
                     name: course_name,
                     course_schedule: course_schedule.clone(),
-                    register_sheet_id: course_info.register_sheet_id.clone(),
+                    register_sheet_id: Some(course_info.register_sheet_id.clone()),
+                    submit_labels: self.submit_labels.clone(),
+                    module_settings: self.module_settings.clone(),
+                    duplicate_register_entries: course_info.duplicate_register_entries,
+                    lateness: course_info.lateness,
                 }
             })
         } else {
@@ -66,6 +277,12 @@ impl Config {
         }
     }
 
+    pub fn attendance_policy(&self, course_name: &str) -> Option<AttendancePolicy> {
+        self.courses
+            .get(course_name)
+            .and_then(|course_info| course_info.attendance_policy.clone())
+    }
+
     pub fn get_course_module_names(&self, course_name: &str) -> Option<Vec<String>> {
         if let Some(course_info) = self.courses.get(course_name) {
             if let Some((_batch_name, course_schedule)) = course_info.batches.get_index(0) {
@@ -85,10 +302,239 @@ pub struct CourseSchedule {
     pub end: NaiveDate,
     // Module -> [{region: Date}]
     pub sprints: IndexMap<String, Vec<BTreeMap<Region, NaiveDate>>>,
+    /// Trainees working together in a shared repo, e.g. for a final project.
+    #[serde(default)]
+    pub groups: Vec<GroupAssignment>,
+    /// Assignments submitted as a trainee-created repo rather than a PR.
+    #[serde(default)]
+    pub project_repos: Vec<ProjectRepoAssignment>,
+    /// Sprints whose deadline moved in a particular region, e.g. because of a bank holiday.
+    #[serde(default)]
+    pub deadline_extensions: Vec<DeadlineExtension>,
+    /// Induction sessions before the first module (e.g. "Welcome to Code Your Future"), as [{region: Date}].
+    /// These are tracked as their own attendance-only pseudo-module, so they don't collide with any module's sprints.
+    #[serde(default)]
+    pub onboarding_sessions: Vec<BTreeMap<Region, NaiveDate>>,
+    /// Slack incoming webhook for the batch's channel, which the peer-review-pairs job posts pairings to.
+    #[serde(default)]
+    pub peer_review_slack_webhook_url: Option<EnvField<String>>,
+    /// IDs (e.g. C0123ABCDEF) of the Slack channels every trainee in the batch should be in, for the Slack channel audit.
+    #[serde(default)]
+    pub slack_channels: Vec<String>,
+    /// Handle (without the @) of the Slack usergroup for the batch's trainees, e.g. itp-2025-05.
+    /// Defaults to the batch's GitHub team slug.
+    #[serde(default)]
+    pub slack_usergroup_handle: Option<String>,
+    /// Whether the batch has opted in to a leaderboard, which trainees in it can see at /me/leaderboard.
+    #[serde(default)]
+    pub leaderboard: bool,
+    /// Each region's first class for modules discovered by topic, as {region: Date}.
+    /// Discovered modules get one sprint a week from here, in course order.
+    #[serde(default)]
+    pub weekly_classes_from: BTreeMap<Region, NaiveDate>,
+    /// When trainees sit timed assessments, for the batch timeline.
+    #[serde(default)]
+    pub assessment_windows: Vec<AssessmentWindow>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct DeadlineExtension {
+    pub region: Region,
+    pub module: String,
+    /// 1-based, as in sprint labels.
+    pub sprint: usize,
+    /// How many days later work is due (and, unless there was no class, the class was).
+    pub days: u64,
+    /// If true, the region had no class that sprint, so attendance isn't expected.
+    #[serde(default)]
+    pub no_class: bool,
+    /// Why the deadline moved, e.g. "Eid" - just for reference.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl CourseInfo {
+    /// Each module in any of the course's batches, with its number of sprints.
+    /// Batches may differ in length, so this allows for the longest.
+    pub fn module_sprint_counts(&self) -> IndexMap<String, usize> {
+        let mut modules: IndexMap<String, usize> = IndexMap::new();
+        for course_schedule in self.batches.values() {
+            for (module_name, sprints) in &course_schedule.sprints {
+                let sprint_count = modules.entry(module_name.clone()).or_default();
+                *sprint_count = (*sprint_count).max(sprints.len());
+            }
+        }
+        modules
+    }
+}
+
+impl CourseSchedule {
+    /// The module and (1-based) sprint which most recently started in any region, if the course has started.
+    pub fn current_sprint(&self, today: NaiveDate) -> Option<(String, usize)> {
+        let mut current: Option<(String, usize, NaiveDate)> = None;
+        for (module, sprints) in &self.sprints {
+            for (sprint_index, dates) in sprints.iter().enumerate() {
+                if let Some(start) = dates.values().min()
+                    && *start <= today
+                    && current
+                        .as_ref()
+                        .is_none_or(|(_, _, current_start)| start >= current_start)
+                {
+                    current = Some((module.clone(), sprint_index + 1, *start));
+                }
+            }
+        }
+        current.map(|(module, sprint_number, _start)| (module, sprint_number))
+    }
+
+    /// The date each region's work for a sprint is due, after any extensions.
+    pub fn sprint_dates(&self, module: &str, sprint_index: usize) -> BTreeMap<Region, NaiveDate> {
+        let Some(dates) = self
+            .sprints
+            .get(module)
+            .and_then(|sprints| sprints.get(sprint_index))
+        else {
+            return BTreeMap::new();
+        };
+        dates
+            .iter()
+            .map(|(region, date)| {
+                let date = match self.deadline_extension(module, sprint_index, region) {
+                    Some(extension) => date
+                        .checked_add_days(Days::new(extension.days))
+                        .unwrap_or(*date),
+                    None => *date,
+                };
+                (region.clone(), date)
+            })
+            .collect()
+    }
+
+    /// The date of each region's class for a sprint, after any extensions.
+    /// Regions which had no class that sprint are left out.
+    pub fn class_dates(&self, module: &str, sprint_index: usize) -> BTreeMap<Region, NaiveDate> {
+        let mut dates = self.sprint_dates(module, sprint_index);
+        dates.retain(|region, _date| {
+            !self
+                .deadline_extension(module, sprint_index, region)
+                .is_some_and(|extension| extension.no_class)
+        });
+        dates
+    }
+
+    fn deadline_extension(
+        &self,
+        module: &str,
+        sprint_index: usize,
+        region: &Region,
+    ) -> Option<&DeadlineExtension> {
+        self.deadline_extensions.iter().find(|extension| {
+            extension.module == module
+                && extension.sprint == sprint_index + 1
+                && &extension.region == region
+        })
+    }
 }
 
 pub struct CourseScheduleWithRegisterSheetId {
     pub name: String,
     pub course_schedule: CourseSchedule,
-    pub register_sheet_id: String,
+    /// None for stand-in courses which are only used to validate PRs, and so have no register.
+    pub register_sheet_id: Option<SheetId>,
+    pub submit_labels: SubmitLabelTaxonomy,
+    pub module_settings: BTreeMap<String, ModuleSettings>,
+    pub duplicate_register_entries: DuplicateRegisterEntries,
+    pub lateness: LatenessPolicy,
+}
+
+/// How long after a class starts a trainee can register before they count as late.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct LatenessPolicy {
+    /// Defaults to DEFAULT_LATE_AFTER_MINUTES.
+    #[serde(default)]
+    pub late_after_minutes: Option<i64>,
+    /// Registering this long after the class starts counts as very late, e.g. because the trainee missed the morning's session.
+    /// If unset, everyone after the late threshold is just late.
+    #[serde(default)]
+    pub very_late_after_minutes: Option<i64>,
+}
+
+pub const DEFAULT_LATE_AFTER_MINUTES: i64 = 10;
+
+impl LatenessPolicy {
+    pub fn late_after(&self) -> TimeDelta {
+        TimeDelta::minutes(
+            self.late_after_minutes
+                .unwrap_or(DEFAULT_LATE_AFTER_MINUTES),
+        )
+    }
+
+    pub fn very_late_after(&self) -> Option<TimeDelta> {
+        self.very_late_after_minutes.map(TimeDelta::minutes)
+    }
+}
+
+/// How to pick between a trainee's register entries for the same class, e.g. if they submitted the form twice.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateRegisterEntries {
+    #[default]
+    KeepEarliest,
+    KeepLatest,
+    /// Whichever gives the best attendance, e.g. on time rather than late.
+    KeepMostOnTime,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ModuleSettings {
+    #[serde(default)]
+    pub sprint_source: SprintSource,
+    /// Other repos whose assignment issues and PRs belong to this module, as well as the module's own repo.
+    /// e.g. a module whose project work lives in a separate repo from its exercises.
+    #[serde(default)]
+    pub extra_repos: Vec<String>,
+}
+
+/// Where an assignment issue's sprint comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SprintSource {
+    /// A `📅 Sprint N` label.
+    #[default]
+    Labels,
+    /// The issue's milestone, named like "Sprint N".
+    /// Some module repos are organised this way, and relabelling all of their issues would be a lot of work.
+    Milestones,
+}
+
+/// What to do with assignment issues with a particular `Submit:` label (e.g. "Submit:PR").
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmitLabelHandler {
+    /// Trainees submit a PR, which we match to the issue.
+    PullRequest,
+    /// We don't track submissions of this type.
+    Ignore,
+}
+
+/// Submit label (without the `Submit:` prefix) -> how to handle it.
+///
+/// The curriculum team adds new submission types from time to time.
+/// Labels which aren't configured here or built in are reported on the batch page rather than breaking it.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct SubmitLabelTaxonomy(pub BTreeMap<String, SubmitLabelHandler>);
+
+impl SubmitLabelTaxonomy {
+    pub fn handler(&self, label: &str) -> Option<SubmitLabelHandler> {
+        if let Some(handler) = self.0.get(label) {
+            return Some(*handler);
+        }
+        match label {
+            "PR" => Some(SubmitLabelHandler::PullRequest),
+            // TODO: Handle Codility, Issue and Slack submissions.
+            "None" | "Codility" | "Issue" | "Slack" => Some(SubmitLabelHandler::Ignore),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    prs::Pr,
+    similarity::{jaccard, shingles},
+};
+
+/// Bodies with fewer distinctive shingles than this are too short to say anything about.
+const MIN_SHINGLES: usize = 10;
+
+/// Above this Jaccard similarity, a body looks copied.
+pub const DEFAULT_COPIED_BODY_THRESHOLD: f64 = 0.8;
+
+/// A PR body which looks like it was copied from somewhere, for a mentor to look at.
+#[derive(Clone, Debug)]
+pub struct CopiedBody {
+    pub copied_from: CopiedFrom,
+    /// From 0 to 1.
+    pub similarity: f64,
+}
+
+#[derive(Clone, Debug)]
+pub enum CopiedFrom {
+    /// Another trainee's PR.
+    Pr { url: String, author: String },
+    /// The text of an assignment issue.
+    Assignment { url: String },
+}
+
+impl std::fmt::Display for CopiedBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = (self.similarity * 100.0).round();
+        match &self.copied_from {
+            CopiedFrom::Pr { url, author } => write!(
+                f,
+                "Description is {}% similar to @{}'s PR {}",
+                percent, author, url
+            ),
+            CopiedFrom::Assignment { url } => write!(
+                f,
+                "Description is {}% similar to the assignment {}",
+                percent, url
+            ),
+        }
+    }
+}
+
+/// Looks for another trainee's PR, or an assignment, whose text `pr`'s body is (nearly) a copy of.
+///
+/// Text which lots of the module's PRs share (i.e. the PR template) is ignored, as everyone is expected to have it.
+pub fn find_copied_body(
+    pr: &Pr,
+    module_prs: &[Pr],
+    assignment_issues: &[(String, String)],
+    threshold: f64,
+) -> Option<CopiedBody> {
+    let mut shingle_counts: BTreeMap<u64, usize> = BTreeMap::new();
+    let pr_shingles = module_prs
+        .iter()
+        .map(|other| {
+            let other_shingles = shingles(&other.body);
+            for shingle in &other_shingles {
+                *shingle_counts.entry(*shingle).or_default() += 1;
+            }
+            (other, other_shingles)
+        })
+        .collect::<Vec<_>>();
+    let template_count = (module_prs.len() / 5).max(3);
+    let distinctive = |shingles: BTreeSet<u64>| {
+        shingles
+            .into_iter()
+            .filter(|shingle| shingle_counts.get(shingle).copied().unwrap_or(0) < template_count)
+            .collect::<BTreeSet<_>>()
+    };
+
+    let body = distinctive(shingles(&pr.body));
+    if body.len() < MIN_SHINGLES {
+        return None;
+    }
+
+    let from_prs = pr_shingles
+        .into_iter()
+        .filter(|(other, _)| other.author != pr.author)
+        .map(|(other, other_shingles)| CopiedBody {
+            copied_from: CopiedFrom::Pr {
+                url: other.url.clone(),
+                author: other.author.to_string(),
+            },
+            similarity: jaccard(&body, &distinctive(other_shingles)),
+        });
+    let from_assignments = assignment_issues.iter().map(|(url, text)| CopiedBody {
+        copied_from: CopiedFrom::Assignment { url: url.clone() },
+        similarity: jaccard(&body, &shingles(text)),
+    });
+    from_prs
+        .chain(from_assignments)
+        .filter(|copied| copied.similarity >= threshold)
+        .max_by(|left, right| left.similarity.total_cmp(&right.similarity))
+}
@@ -2,21 +2,35 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     num::NonZeroUsize,
     str::FromStr,
+    sync::Arc,
 };
 
 use crate::{
-    Error,
-    config::CourseScheduleWithRegisterSheetId,
+    Config, Error,
+    check_in::get_check_ins,
+    codewars::{CodewarsProgress, get_codewars_progress, get_codewars_usernames},
+    config::{
+        CourseSchedule, CourseScheduleWithRegisterSheetId, DuplicateRegisterEntries,
+        LatenessPolicy, SprintSource, SubmitLabelHandler, SubmitLabelTaxonomy,
+    },
     github_accounts::{Trainee, get_trainees},
+    groups::{GroupAssignment, GroupProgress, get_group_progress},
     mentoring::{MentoringRecord, get_mentoring_records},
-    newtypes::{GithubLogin, Region},
-    octocrab::all_pages,
+    module_discovery::get_module_metadata,
+    newtypes::{GithubLogin, Region, SheetId},
+    octocrab::{all_pages, check_repo_access},
+    persistence::RecordStore,
+    project_repos::{ProjectRepoSubmission, fill_in_project_repos},
     prs::{Pr, PrState, get_prs},
-    register::{Register, get_register},
+    register::{
+        Register, UnreadableRegisterRow, UnreadableRegisterSheet, choose_register_entry,
+        get_register,
+    },
     sheets::SheetsClient,
+    single_flight::InFlight,
 };
 use anyhow::Context;
-use chrono::{NaiveDate, Utc};
+use chrono::{Days, NaiveDate, Utc};
 use email_address::EmailAddress;
 use futures::future::join_all;
 use indexmap::{IndexMap, IndexSet};
@@ -26,111 +40,292 @@ use octocrab::{
     models::{Author, issues::Issue, teams::RequestedTeam},
 };
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 use url::Url;
 
+/// The pseudo-module induction sessions are tracked under.
+/// It has no repo, and each "sprint" is one session with only an attendance assignment.
+pub const ONBOARDING_MODULE_NAME: &str = "Induction";
+
 impl CourseScheduleWithRegisterSheetId {
     pub fn module_names(&self) -> Vec<String> {
         self.course_schedule.sprints.keys().cloned().collect()
     }
 
+    /// The repos a module's assignment issues and PRs live in - the module's own repo first, then any extra repos configured for it.
+    pub fn module_repos(&self, module_name: &str) -> Vec<String> {
+        std::iter::once(module_name.to_owned())
+            .chain(
+                self.module_settings
+                    .get(module_name)
+                    .into_iter()
+                    .flat_map(|settings| settings.extra_repos.iter().cloned()),
+            )
+            .collect()
+    }
+
     pub async fn with_assignments(
         &self,
         octocrab: &Octocrab,
+        in_flight: &InFlight,
         github_org: &str,
     ) -> Result<Course, Error> {
         let mut modules = IndexMap::new();
         let mut module_futures = Vec::new();
 
+        // Induction happens before any module, so list it first.
+        if !self.course_schedule.onboarding_sessions.is_empty() {
+            modules.insert(
+                ONBOARDING_MODULE_NAME.to_owned(),
+                Module {
+                    sprints: self
+                        .course_schedule
+                        .onboarding_sessions
+                        .iter()
+                        .map(|session_dates| Sprint {
+                            assignments: vec![Assignment::Attendance {
+                                class_dates: session_dates.clone(),
+                            }],
+                            dates: session_dates.clone(),
+                        })
+                        .collect(),
+                    repos: Vec::new(),
+                },
+            );
+        }
+
         for (module_name, module_sprint_dates) in &self.course_schedule.sprints {
+            let repos = self.module_repos(module_name);
             modules.insert(
                 module_name.clone(),
                 Module {
-                    sprints: module_sprint_dates
-                        .iter()
-                        .map(|class_dates| Sprint {
+                    sprints: (0..module_sprint_dates.len())
+                        .map(|sprint_index| Sprint {
                             assignments: vec![Assignment::Attendance {
-                                class_dates: class_dates.clone(),
+                                class_dates: self
+                                    .course_schedule
+                                    .class_dates(module_name, sprint_index),
                             }],
-                            dates: class_dates.clone(),
+                            dates: self.course_schedule.sprint_dates(module_name, sprint_index),
                         })
                         .collect(),
+                    repos: repos.clone(),
                 },
             );
             module_futures.push(Self::fetch_module_assignments(
                 octocrab,
+                in_flight,
                 github_org,
                 module_name,
+                repos,
                 module_sprint_dates.len(),
+                &self.submit_labels,
+                self.module_settings
+                    .get(module_name)
+                    .map(|settings| settings.sprint_source)
+                    .unwrap_or_default(),
             ));
         }
 
+        let mut unhandled_submit_labels = Vec::new();
+
         for (module_name, sprints_module_assignments) in self
             .module_names()
             .into_iter()
             .zip(join_all(module_futures).await.into_iter())
         {
+            let (sprints_module_assignments, module_unhandled_submit_labels) =
+                sprints_module_assignments.map_err(|err| {
+                    err.with_context(|| {
+                        format!("Failed to fetch issues for module {}", module_name)
+                    })
+                })?;
+            let module_sprints = &mut modules[&module_name].sprints;
+            // The module's issues may have more sprints than the schedule knows about.
+            // We don't know when (or whether) there are classes for those, so assume they follow on weekly.
+            while module_sprints.len() < sprints_module_assignments.len() {
+                let dates = module_sprints
+                    .last()
+                    .map(|sprint| {
+                        sprint
+                            .dates
+                            .iter()
+                            .filter_map(|(region, date)| {
+                                Some((region.clone(), date.checked_add_days(Days::new(7))?))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                module_sprints.push(Sprint {
+                    assignments: Vec::new(),
+                    dates,
+                });
+            }
             for (module_sprint, module_assignments) in
-                modules[&module_name]
-                    .sprints
-                    .iter_mut()
-                    .zip(sprints_module_assignments.map_err(|err| {
-                        err.with_context(|| {
-                            format!("Failed to fetch issues for module {}", module_name)
-                        })
-                    })?)
+                module_sprints.iter_mut().zip(sprints_module_assignments)
             {
                 module_sprint.assignments.extend(module_assignments);
             }
+            unhandled_submit_labels.extend(module_unhandled_submit_labels);
+        }
+
+        for project_repo in &self.course_schedule.project_repos {
+            let sprint = modules
+                .get_mut(&project_repo.module)
+                .and_then(|module| module.sprints.get_mut(project_repo.sprint.get() - 1))
+                .ok_or_else(|| {
+                    Error::Fatal(anyhow::anyhow!(
+                        "Project repo assignment {} is for module {} sprint {}, which isn't in the course",
+                        project_repo.title,
+                        project_repo.module,
+                        project_repo.sprint
+                    ))
+                })?;
+            sprint.assignments.push(project_repo.assignment());
         }
 
         Ok(Course {
             name: self.name.clone(),
             modules,
             register_sheet_id: self.register_sheet_id.clone(),
+            duplicate_register_entries: self.duplicate_register_entries,
+            lateness: self.lateness,
             start_date: self.course_schedule.start,
             end_date: self.course_schedule.end,
+            groups: self.course_schedule.groups.clone(),
+            unhandled_submit_labels,
         })
     }
 
     pub async fn fetch_module_assignments(
         octocrab: &Octocrab,
+        in_flight: &InFlight,
         github_org: &str,
         module_name: &str,
+        repos: Vec<String>,
         sprint_count: usize,
-    ) -> Result<Vec<Vec<Assignment>>, Error> {
+        submit_labels: &SubmitLabelTaxonomy,
+        sprint_source: SprintSource,
+    ) -> Result<(Vec<Vec<Assignment>>, Vec<UnhandledSubmitLabel>), Error> {
+        // The module's tracker.toml, if it has one, can add sprints and submit types, but the config has the final say.
+        let metadata = get_module_metadata(octocrab, github_org, module_name).await?;
+        let (sprint_count, submit_labels, assignment_order) = match metadata {
+            Some(metadata) => (
+                sprint_count.max(metadata.sprints),
+                SubmitLabelTaxonomy(
+                    metadata
+                        .submit_types
+                        .into_iter()
+                        .chain(submit_labels.0.clone())
+                        .collect(),
+                ),
+                metadata.assignment_order,
+            ),
+            None => (sprint_count, submit_labels.clone(), Vec::new()),
+        };
+
         let mut sprints = std::iter::repeat_with(Vec::new)
             .take(sprint_count)
             .collect::<Vec<_>>();
 
-        let mut issues = all_pages("issues", octocrab, async || {
-            octocrab.issues(github_org, module_name).list().send().await
-        })
+        let mut issues = Vec::new();
+        for (repo_index, repo_issues) in join_all(repos.iter().map(async |repo| {
+            get_repo_issues(octocrab, in_flight, github_org, repo)
+                .await
+                .map_err(|err| err.context(format!("Failed to fetch module issues from {}", repo)))
+        }))
         .await
-        .map_err(|err| err.context("Failed to fetch module issues"))?;
+        .into_iter()
+        .enumerate()
+        {
+            issues.extend(repo_issues?.into_iter().map(|issue| (repo_index, issue)));
+        }
 
-        issues.sort_by_cached_key(|Issue { title, .. }| title.clone());
+        // Titles get edited, so sorting by them would reorder the batch page's columns between visits.
+        // Issue numbers never change, but the module's own repo can list its issues in a different order.
+        issues.sort_by_key(|(repo_index, issue)| {
+            let listed_position = assignment_order
+                .iter()
+                .position(|number| *number == issue.number)
+                .filter(|_| *repo_index == 0);
+            (
+                listed_position.is_none(),
+                listed_position,
+                *repo_index,
+                issue.number,
+            )
+        });
 
-        for issue in issues {
-            if let Some((sprint_number, assignment)) = parse_issue(&issue)? {
+        let mut unhandled_submit_labels = Vec::new();
+        for (_, issue) in issues {
+            if let Some((sprint_number, assignment)) = parse_issue(
+                &issue,
+                module_name,
+                &submit_labels,
+                sprint_source,
+                &mut unhandled_submit_labels,
+            )? {
                 let sprint_index = usize::from(sprint_number) - 1;
-                if sprints.len() <= sprint_index {
+                if sprint_index >= MAX_SPRINT_COUNT {
                     return Err(Error::Fatal(anyhow::anyhow!(
-                        "Found issue {} in sprint {} but module only has {} sprints",
+                        "Found issue {} in sprint {} but modules can have at most {} sprints",
                         issue.html_url,
                         sprint_number,
-                        sprints.len()
+                        MAX_SPRINT_COUNT
                     )));
                 }
+                // The sprint count comes from the schedule, but the module's sprint labels are the source of truth.
+                if sprints.len() <= sprint_index {
+                    sprints.resize_with(sprint_index + 1, Vec::new);
+                }
                 sprints[sprint_index].push(assignment);
             }
         }
-        Ok(sprints)
+        Ok((sprints, unhandled_submit_labels))
     }
 }
 
-fn parse_issue(issue: &Issue) -> Result<Option<(NonZeroUsize, Assignment)>, Error> {
+/// Sanity bound on sprint numbers, from sprint labels or PR titles.
+pub const MAX_SPRINT_COUNT: usize = 20;
+
+/// An assignment issue whose `Submit:` label we don't know how to handle, so which isn't being tracked.
+#[derive(Clone, Debug, Serialize)]
+pub struct UnhandledSubmitLabel {
+    pub module_name: String,
+    pub issue_url: String,
+    pub label: String,
+}
+
+async fn get_repo_issues(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    github_org: &str,
+    repo: &str,
+) -> Result<Vec<Issue>, Error> {
+    let flight = in_flight
+        .issues
+        .run(&format!("{}/{}", github_org, repo), async || {
+            all_pages("issues", octocrab, async || {
+                octocrab.issues(github_org, repo).list().send().await
+            })
+            .await
+            .map(Arc::new)
+        })
+        .await?;
+    if flight.shared {
+        check_repo_access(octocrab, github_org, repo).await?;
+    }
+    Ok(Arc::unwrap_or_clone(flight.value))
+}
+
+pub(crate) fn parse_issue(
+    issue: &Issue,
+    module_name: &str,
+    submit_labels: &SubmitLabelTaxonomy,
+    sprint_source: SprintSource,
+    unhandled_submit_labels: &mut Vec<UnhandledSubmitLabel>,
+) -> Result<Option<(NonZeroUsize, Assignment)>, Error> {
     if issue.pull_request.is_some() {
         return Ok(None);
     }
@@ -140,6 +335,7 @@ fn parse_issue(issue: &Issue) -> Result<Option<(NonZeroUsize, Assignment)>, Erro
         title,
         html_url,
         number,
+        milestone,
         ..
     } = issue;
 
@@ -208,68 +404,96 @@ fn parse_issue(issue: &Issue) -> Result<Option<(NonZeroUsize, Assignment)>, Erro
         ))
     })?;
 
-    let assignment = match submit_label {
-        "None" => None,
-        "PR" => Some(Assignment::ExpectedPullRequest {
+    let assignment = match submit_labels.handler(submit_label) {
+        Some(SubmitLabelHandler::PullRequest) => Some(Assignment::ExpectedPullRequest {
             title: title.clone(),
             html_url: html_url.clone(),
             optionality,
             assignment_issue_id: *number,
+            submit_type: submit_label.trim().to_owned(),
         }),
-        "Codility" => {
-            // TODO: Handle these.
-            None
-        }
-        "Issue" => {
-            // TODO: Handle these.
-            None
-        }
-        "Slack" => {
-            // TODO: Handle these.
+        Some(SubmitLabelHandler::Ignore) => None,
+        None => {
+            unhandled_submit_labels.push(UnhandledSubmitLabel {
+                module_name: module_name.to_owned(),
+                issue_url: html_url.to_string(),
+                label: submit_label.to_owned(),
+            });
             None
         }
-        other => {
-            return Err(Error::UserFacing(format!(
-                "Failed to parse issue {} - submit label wasn't recognised: {}",
-                html_url, other
-            )));
-        }
     };
 
     let Some(assignment) = assignment else {
         return Ok(None);
     };
 
-    let sprint = match sprints.as_slice() {
-        [sprint] => *sprint,
-        // If empty, or more than one value:
-        empty_or_more_than_one => {
-            return Err(Error::UserFacing(format!(
-                "Failed to parse issue {} - expected exactly one sprint label but got {}",
-                html_url,
-                empty_or_more_than_one.len()
-            )));
-        }
+    let sprint = match sprint_source {
+        SprintSource::Labels => match sprints.as_slice() {
+            [sprint] => *sprint,
+            // If empty, or more than one value:
+            empty_or_more_than_one => {
+                return Err(Error::UserFacing(format!(
+                    "Failed to parse issue {} - expected exactly one sprint label but got {}",
+                    html_url,
+                    empty_or_more_than_one.len()
+                )));
+            }
+        },
+        SprintSource::Milestones => milestone
+            .as_ref()
+            .and_then(|milestone| sprint_number_in(&milestone.title))
+            .ok_or_else(|| {
+                Error::UserFacing(format!(
+                    "Failed to parse issue {} - expected a milestone named like \"Sprint 1\", as this module's sprints come from milestones",
+                    html_url
+                ))
+            })?,
     };
 
     Ok(Some((sprint, assignment)))
 }
 
+/// Finds a sprint number written like "Sprint 2", "sprint-2", "[SPRINT #2]" or "Week 2" (as some older modules called sprints).
+pub(crate) fn sprint_number_in(text: &str) -> Option<NonZeroUsize> {
+    let lower = text.to_lowercase();
+    ["sprint", "week"].into_iter().find_map(|word| {
+        lower.match_indices(word).find_map(|(index, _)| {
+            let digits = lower[index + word.len()..]
+                .trim_start_matches([' ', '-', '_', '#', ':'])
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect::<String>();
+            NonZeroUsize::from_str(&digits)
+                .ok()
+                .filter(|sprint| sprint.get() <= MAX_SPRINT_COUNT)
+        })
+    })
+}
+
 const BAD_LABEL_SUFFIX: &str = "\n\nIf this issue was made my a curriculum team member it should be given a sprint label.\nIf this issue was created by a trainee for step submission, it should probably be closed (and they should create the issue in their fork).";
 
 #[derive(Serialize)]
 pub struct Course {
     pub name: String,
     pub modules: IndexMap<String, Module>,
-    pub register_sheet_id: String,
+    /// None for stand-in courses which are only used to validate PRs (see CourseScheduleWithRegisterSheetId).
+    pub register_sheet_id: Option<SheetId>,
+    pub duplicate_register_entries: DuplicateRegisterEntries,
+    pub lateness: LatenessPolicy,
 
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+
+    pub groups: Vec<GroupAssignment>,
+    pub unhandled_submit_labels: Vec<UnhandledSubmitLabel>,
 }
 
 #[derive(Serialize)]
 pub struct Module {
     pub sprints: Vec<Sprint>,
+    /// Where the module's assignment issues and PRs live.
+    /// Empty for the induction pseudo-module, which has no repo.
+    pub repos: Vec<String>,
 }
 
 impl Module {
@@ -318,6 +542,15 @@ pub enum Assignment {
         html_url: Url,
         assignment_issue_id: u64,
         optionality: AssignmentOptionality,
+        /// What the issue's `Submit:` label says to submit, e.g. "PR".
+        submit_type: String,
+    },
+    ProjectRepo {
+        title: String,
+        repo_name: String,
+        submitted_repos: BTreeMap<GithubLogin, String>,
+        min_commits: usize,
+        optionality: AssignmentOptionality,
     },
 }
 
@@ -326,6 +559,7 @@ impl Assignment {
         match self {
             Assignment::Attendance { .. } => AssignmentOptionality::Mandatory,
             Assignment::ExpectedPullRequest { optionality, .. } => optionality.clone(),
+            Assignment::ProjectRepo { optionality, .. } => *optionality,
         }
     }
 
@@ -337,11 +571,12 @@ impl Assignment {
             Assignment::ExpectedPullRequest {
                 title, html_url, ..
             } => format!("<a href=\"{html_url}\">PR: {title}</a>"),
+            Assignment::ProjectRepo { title, .. } => format!("Repo: {title}"),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum AssignmentOptionality {
     Mandatory,
     Stretch,
@@ -355,7 +590,31 @@ pub struct BatchMembers {
 #[derive(Debug)]
 pub struct Batch {
     pub name: String,
+    pub github_team_slug: String,
     pub trainees: Vec<TraineeWithSubmissions>,
+    /// Register sheets whose attendance is missing from this batch because we couldn't read them.
+    pub unreadable_register_sheets: Vec<UnreadableRegisterSheet>,
+    /// Register rows which were skipped because we couldn't read them.
+    pub unreadable_register_rows: Vec<UnreadableRegisterRow>,
+    /// Why there are no mentoring check-ins, if the current user can't read the mentoring records sheet.
+    pub mentoring_records_unavailable: Option<String>,
+    pub name_matched_attendance: Vec<NameMatchedAttendance>,
+    pub groups: Vec<GroupProgress>,
+}
+
+/// Attendance we attributed to a trainee by name, because it was registered with an email we don't know.
+/// Staff should check these, and fix the email in the register or the GitHub email mapping sheet.
+#[derive(Clone, Debug, Serialize)]
+pub struct NameMatchedAttendance {
+    pub github_login: GithubLogin,
+    pub trainee_name: String,
+    pub registered_name: String,
+    pub registered_email: EmailAddress,
+    pub module_name: String,
+    pub sprint_number: usize,
+    /// From 0 to 1.
+    pub confidence: f64,
+    pub register_url: String,
 }
 
 impl Batch {
@@ -388,22 +647,40 @@ impl Batch {
             .iter()
             .any(|trainee| trainee.mentoring_record.is_some())
     }
+
+    pub fn has_codewars(&self) -> bool {
+        self.trainees
+            .iter()
+            .any(|trainee| trainee.codewars.is_some())
+    }
 }
 
 #[derive(Debug)]
 pub struct TraineeWithSubmissions {
     pub trainee: Trainee,
     pub mentoring_record: Option<MentoringRecord>,
+    /// None if Codewars isn't configured, we don't know the trainee's username, or their profile couldn't be loaded.
+    pub codewars: Option<CodewarsProgress>,
     pub modules: IndexMap<String, ModuleWithSubmissions>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum TraineeStatus {
     OnTrack,
     Behind,
     AtRisk,
 }
 
+impl TraineeStatus {
+    pub fn display_text(&self) -> &'static str {
+        match self {
+            TraineeStatus::OnTrack => "On track",
+            TraineeStatus::Behind => "Behind",
+            TraineeStatus::AtRisk => "At risk",
+        }
+    }
+}
+
 impl TraineeWithSubmissions {
     pub fn status(&self) -> TraineeStatus {
         let progress_score = self.progress_score();
@@ -434,6 +711,9 @@ impl TraineeWithSubmissions {
                                 Attendance::Late { .. } => {
                                     numerator += 8;
                                 }
+                                Attendance::VeryLate { .. } => {
+                                    numerator += 5;
+                                }
                                 Attendance::WrongDay { .. } => {
                                     numerator += 3;
                                 }
@@ -462,9 +742,20 @@ impl TraineeWithSubmissions {
                                 }
                             }
                         }
+                        SubmissionState::Some(Submission::ProjectRepo(project_repo)) => {
+                            let max = match project_repo.optionality {
+                                AssignmentOptionality::Mandatory => 10,
+                                AssignmentOptionality::Stretch => 12,
+                            };
+                            denominator += max;
+                            // Partial credit for each check passed.
+                            numerator += max * project_repo.checks_passed() as u64
+                                / ProjectRepoSubmission::CHECK_COUNT as u64;
+                        }
                         SubmissionState::MissingButExpected(assignment) => match assignment {
                             Assignment::Attendance { .. } => denominator += 20,
-                            Assignment::ExpectedPullRequest { .. } => denominator += 10,
+                            Assignment::ExpectedPullRequest { .. }
+                            | Assignment::ProjectRepo { .. } => denominator += 10,
                         },
                         SubmissionState::MissingStretch(_) => {
                             denominator += 2;
@@ -483,6 +774,13 @@ pub struct Fraction {
     pub denominator: usize,
 }
 
+impl Fraction {
+    /// None if there's nothing to be a percentage of.
+    pub fn percent(&self) -> Option<usize> {
+        (self.denominator > 0).then(|| self.numerator * 100 / self.denominator)
+    }
+}
+
 impl TraineeWithSubmissions {
     pub fn attendance(&self) -> Fraction {
         let mut numerator = 0;
@@ -493,7 +791,9 @@ impl TraineeWithSubmissions {
                     if let SubmissionState::Some(Submission::Attendance(attendance)) = submission {
                         denominator += 1;
                         match attendance {
-                            Attendance::OnTime { .. } | Attendance::Late { .. } => {
+                            Attendance::OnTime { .. }
+                            | Attendance::Late { .. }
+                            | Attendance::VeryLate { .. } => {
                                 numerator += 1;
                             }
                             Attendance::Absent { .. } | Attendance::WrongDay { .. } => {}
@@ -507,6 +807,87 @@ impl TraineeWithSubmissions {
             denominator,
         }
     }
+
+    /// Mandatory pull request assignments which are due, but which haven't been submitted.
+    pub fn missing_mandatory_pull_requests(&self) -> Vec<&Assignment> {
+        self.modules
+            .values()
+            .flat_map(|module| module.sprints.iter())
+            .flat_map(|sprint| sprint.submissions.iter())
+            .filter_map(|submission| match submission {
+                SubmissionState::MissingButExpected(
+                    assignment @ Assignment::ExpectedPullRequest { .. },
+                ) => Some(assignment),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A piece of outstanding work, in the order a trainee who is behind should tackle it.
+#[derive(Clone, Debug, Serialize)]
+pub struct CatchUpStep {
+    pub module_name: String,
+    pub sprint_number: usize,
+    pub title: String,
+    pub url: String,
+    pub action: CatchUpAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum CatchUpAction {
+    Submit,
+    AddressReview,
+}
+
+impl CatchUpAction {
+    pub fn display_text(&self) -> &'static str {
+        match self {
+            CatchUpAction::Submit => "Submit",
+            CatchUpAction::AddressReview => "Address review feedback on",
+        }
+    }
+}
+
+impl TraineeWithSubmissions {
+    /// Unresolved mandatory pull request assignments, earliest first.
+    ///
+    /// Later modules build on earlier ones, so a trainee who is struggling now is often missing something from earlier on.
+    /// PRs which are waiting for a review aren't included, as there's nothing for the trainee to do.
+    pub fn catch_up_plan(&self) -> Vec<CatchUpStep> {
+        let mut steps = Vec::new();
+        for (module_name, module) in &self.modules {
+            for (sprint_index, sprint) in module.sprints.iter().enumerate() {
+                for submission in &sprint.submissions {
+                    let (title, url, action) = match submission {
+                        SubmissionState::MissingButExpected(Assignment::ExpectedPullRequest {
+                            title,
+                            html_url,
+                            ..
+                        }) => (title.clone(), html_url.to_string(), CatchUpAction::Submit),
+                        SubmissionState::Some(Submission::PullRequest {
+                            pull_request,
+                            optionality: AssignmentOptionality::Mandatory,
+                            ..
+                        }) if pull_request.state == PrState::Reviewed => (
+                            pull_request.title.clone(),
+                            pull_request.url.clone(),
+                            CatchUpAction::AddressReview,
+                        ),
+                        _ => continue,
+                    };
+                    steps.push(CatchUpStep {
+                        module_name: module_name.clone(),
+                        sprint_number: sprint_index + 1,
+                        title,
+                        url,
+                        action,
+                    });
+                }
+            }
+        }
+        steps
+    }
 }
 
 #[derive(Debug)]
@@ -515,6 +896,43 @@ pub struct ModuleWithSubmissions {
     pub unknown_prs: Vec<Pr>,
 }
 
+impl ModuleWithSubmissions {
+    /// How much of the module's mandatory coursework which is due has been submitted.
+    /// Attendance and stretch work aren't counted.
+    pub fn completion(&self) -> Fraction {
+        let mut numerator = 0;
+        let mut denominator = 0;
+        for submission in self.sprints.iter().flat_map(|sprint| &sprint.submissions) {
+            match submission {
+                SubmissionState::Some(Submission::PullRequest {
+                    optionality: AssignmentOptionality::Mandatory,
+                    ..
+                }) => {
+                    numerator += 1;
+                    denominator += 1;
+                }
+                SubmissionState::Some(Submission::ProjectRepo(project_repo))
+                    if project_repo.optionality == AssignmentOptionality::Mandatory =>
+                {
+                    numerator += 1;
+                    denominator += 1;
+                }
+                SubmissionState::MissingButExpected(assignment)
+                    if !matches!(assignment, Assignment::Attendance { .. })
+                        && assignment.optionality() == AssignmentOptionality::Mandatory =>
+                {
+                    denominator += 1;
+                }
+                _ => {}
+            }
+        }
+        Fraction {
+            numerator,
+            denominator,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SprintWithSubmissions {
     pub submissions: Vec<SubmissionState>,
@@ -547,6 +965,7 @@ pub enum Submission {
         optionality: AssignmentOptionality,
         assignment_issue_id: u64,
     },
+    ProjectRepo(ProjectRepoSubmission),
 }
 
 impl Submission {
@@ -555,8 +974,14 @@ impl Submission {
             Self::Attendance(Attendance::Absent { .. }) => String::from("Absent"),
             Self::Attendance(Attendance::OnTime { .. }) => String::from("On time"),
             Self::Attendance(Attendance::Late { .. }) => String::from("Late"),
+            Self::Attendance(Attendance::VeryLate { .. }) => String::from("Very late"),
             Self::Attendance(Attendance::WrongDay { .. }) => String::from("Wrong day"),
             Self::PullRequest { pull_request, .. } => format!("#{}", pull_request.number),
+            Self::ProjectRepo(project_repo) => format!(
+                "{}/{} checks",
+                project_repo.checks_passed(),
+                ProjectRepoSubmission::CHECK_COUNT
+            ),
         }
     }
 
@@ -564,16 +989,29 @@ impl Submission {
         match self {
             Self::Attendance(attendance) => attendance.register_url().to_owned(),
             Self::PullRequest { pull_request, .. } => pull_request.url.clone(),
+            Self::ProjectRepo(project_repo) => project_repo.url.clone(),
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Attendance {
-    Absent { register_url: String },
-    OnTime { register_url: String },
-    Late { register_url: String },
-    WrongDay { register_url: String },
+    Absent {
+        register_url: String,
+    },
+    OnTime {
+        register_url: String,
+    },
+    Late {
+        register_url: String,
+    },
+    /// Later than the course's `very_late_after_minutes`.
+    VeryLate {
+        register_url: String,
+    },
+    WrongDay {
+        register_url: String,
+    },
 }
 
 impl Attendance {
@@ -582,6 +1020,7 @@ impl Attendance {
             Attendance::Absent { register_url } => register_url,
             Attendance::OnTime { register_url } => register_url,
             Attendance::Late { register_url } => register_url,
+            Attendance::VeryLate { register_url } => register_url,
             Attendance::WrongDay { register_url } => register_url,
         }
     }
@@ -622,7 +1061,15 @@ pub async fn get_batch_members(
     batch_github_slug: &str,
 ) -> Result<BatchMembers, Error> {
     let trainee_info = get_trainees(sheets_client.clone(), github_email_mapping_sheet_id).await?;
+    get_batch_members_from_trainees(octocrab, &trainee_info, github_org, batch_github_slug).await
+}
 
+async fn get_batch_members_from_trainees(
+    octocrab: &Octocrab,
+    trainee_info: &BTreeMap<GithubLogin, Trainee>,
+    github_org: &str,
+    batch_github_slug: &str,
+) -> Result<BatchMembers, Error> {
     let members = all_pages("members", octocrab, async || {
         octocrab
             .teams(github_org)
@@ -656,6 +1103,131 @@ pub async fn get_batch_members(
     Ok(BatchMembers { name, trainees })
 }
 
+/// PRs from all of the module's repos.
+/// The induction pseudo-module has no repo to submit PRs to, so has no PRs.
+pub async fn get_module_prs(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    github_org: &str,
+    module: &Module,
+    include_complete_closed: bool,
+) -> Result<Vec<Pr>, Error> {
+    let mut prs = Vec::new();
+    for repo_prs in join_all(module.repos.iter().map(|repo| {
+        get_prs(
+            octocrab,
+            in_flight,
+            github_org,
+            repo,
+            include_complete_closed,
+        )
+    }))
+    .await
+    {
+        prs.extend(repo_prs?);
+    }
+    Ok(prs)
+}
+
+/// A trainee's submissions, along with which batch they're in.
+pub struct TraineeInBatch {
+    pub course_name: String,
+    pub batch_github_slug: String,
+    pub batch_name: String,
+    pub trainee: TraineeWithSubmissions,
+}
+
+/// Finds the first configured batch whose GitHub team the trainee is in, and loads their submissions in it.
+/// Returns None if they're not in any batch we know about.
+pub async fn find_trainee_in_batches(
+    octocrab: &Octocrab,
+    sheets_client: SheetsClient,
+    config: &Config,
+    github_login: &GithubLogin,
+) -> Result<Option<TraineeInBatch>, Error> {
+    let Some((course, batch)) =
+        find_batch_of_trainee(octocrab, sheets_client, config, github_login, |_| true).await?
+    else {
+        return Ok(None);
+    };
+    let batch_github_slug = batch.github_team_slug;
+    let trainee = batch
+        .trainees
+        .into_iter()
+        .find(|t| t.trainee.github_login == *github_login)
+        .ok_or_else(|| {
+            Error::Fatal(anyhow::anyhow!(
+                "Trainee {} was in batch {} team but not batch",
+                github_login,
+                batch_github_slug
+            ))
+        })?;
+    Ok(Some(TraineeInBatch {
+        course_name: course.name,
+        batch_github_slug,
+        batch_name: batch.name,
+        trainee,
+    }))
+}
+
+/// Finds the first configured batch matching `filter` whose GitHub team the trainee is in, and loads everyone's submissions in it.
+/// Returns None if they're not in any such batch.
+pub async fn find_batch_of_trainee(
+    octocrab: &Octocrab,
+    sheets_client: SheetsClient,
+    config: &Config,
+    github_login: &GithubLogin,
+    filter: impl Fn(&CourseSchedule) -> bool,
+) -> Result<Option<(Course, Batch)>, Error> {
+    for (course_name, course_info) in &config.courses {
+        for (batch_github_slug, schedule) in &course_info.batches {
+            if !filter(schedule) {
+                continue;
+            }
+            let members = all_pages("team members", octocrab, async || {
+                octocrab
+                    .teams(&config.github_org)
+                    .members(batch_github_slug.as_str())
+                    .send()
+                    .await
+            })
+            .await?;
+            if !members
+                .into_iter()
+                .any(|Author { login, .. }| GithubLogin::from(login) == *github_login)
+            {
+                continue;
+            }
+
+            let course_schedule = config
+                .get_course_schedule_with_register_sheet_id(
+                    course_name.to_string(),
+                    batch_github_slug,
+                )
+                .ok_or_else(|| Error::Fatal(anyhow::anyhow!("Course not found: {course_name}")))?;
+            let course = course_schedule
+                .with_assignments(
+                    octocrab,
+                    &sheets_client.server_state().in_flight,
+                    &config.github_org,
+                )
+                .await?;
+            let batch = get_batch_with_submissions(
+                octocrab,
+                sheets_client.clone(),
+                &config.github_email_mapping_sheet_id,
+                &config.mentoring_records_sheet_id,
+                &config.github_org,
+                batch_github_slug,
+                &course,
+            )
+            .await?;
+            return Ok(Some((course, batch)));
+        }
+    }
+    Ok(None)
+}
+
 pub async fn get_batch_with_submissions(
     octocrab: &Octocrab,
     sheets_client: SheetsClient,
@@ -665,30 +1237,66 @@ pub async fn get_batch_with_submissions(
     batch_github_slug: &str,
     course: &Course,
 ) -> Result<Batch, Error> {
-    let register_info = get_register(
+    let mut register_info = get_register(
         sheets_client.clone(),
-        course.register_sheet_id.clone(),
+        course
+            .register_sheet_id
+            .clone()
+            .context("Course has no register")?,
         course.start_date,
         course.end_date,
     )
     .await?;
+    if let Some(store) = RecordStore::from_config(sheets_client.server_state()).await? {
+        register_info.add_entries(get_check_ins(&store, &course.name).await?);
+    }
 
     let mentoring_records =
         get_mentoring_records(sheets_client.clone(), mentoring_records_sheet_id).await?;
 
-    let batch_members = get_batch_members(
-        octocrab,
-        sheets_client,
-        github_email_mapping_sheet_id,
-        github_org,
-        batch_github_slug,
-    )
-    .await?;
+    let trainee_info = get_trainees(sheets_client.clone(), github_email_mapping_sheet_id).await?;
+    // Register entries with any of these emails belong to a known trainee, so mustn't be matched to anyone else by name.
+    let known_emails = trainee_info
+        .values()
+        .map(|trainee| trainee.email.clone())
+        .collect::<BTreeSet<_>>();
+
+    let batch_members =
+        get_batch_members_from_trainees(octocrab, &trainee_info, github_org, batch_github_slug)
+            .await?;
+
+    let mut codewars_progress = match &sheets_client.server_state().config.codewars {
+        Some(codewars_config) => {
+            // The usernames cover everyone who ever applied, but we only need this batch's.
+            let usernames = get_codewars_usernames(
+                octocrab,
+                sheets_client.clone(),
+                github_org,
+                codewars_config,
+            )
+            .await?
+            .into_iter()
+            .filter(|(github_login, _)| batch_members.trainees.contains_key(github_login))
+            .collect();
+            get_codewars_progress(sheets_client.server_state(), &usernames).await
+        }
+        None => BTreeMap::new(),
+    };
+
+    let groups = get_group_progress(octocrab, github_org, &course.groups).await?;
 
     let pr_futures = course
         .modules
-        .keys()
-        .map(|module| get_prs(octocrab, github_org, module, true))
+        .values()
+        .map(|module| {
+            get_module_prs(
+                octocrab,
+                &sheets_client.server_state().in_flight,
+                github_org,
+                module,
+                true,
+            )
+        })
         .collect::<Vec<_>>();
     let prs_by_module = join_all(pr_futures)
         .await
@@ -718,11 +1326,14 @@ pub async fn get_batch_with_submissions(
         }
     }
     let mut trainees = Vec::with_capacity(batch_members.trainees.len());
+    let mut name_matched_attendance = Vec::new();
     for (github_login, module_to_prs) in member_to_module_to_prs {
         let trainee_specific_info = batch_members.trainees.get(&github_login);
         let trainee_name =
             trainee_specific_info.map_or_else(|| "unknown".to_owned(), |t| t.name.clone());
         let trainee_email = trainee_specific_info.map(|t| t.email.clone());
+        let preferred_name = trainee_specific_info.and_then(|t| t.preferred_name.clone());
+        let pronouns = trainee_specific_info.and_then(|t| t.pronouns.clone());
         let region = trainee_specific_info
             .map_or_else(|| Region("unknown".to_owned()), |t| t.region.clone());
 
@@ -731,22 +1342,32 @@ pub async fn get_batch_with_submissions(
             let module_attendance = get_trainee_module_attendance(
                 &register_info,
                 module_name,
-                trainee_email.clone(),
+                trainee_specific_info,
+                &known_emails,
                 course,
                 &region,
+                &mut name_matched_attendance,
             )?;
-            let module_with_submissions = match_prs_to_assignments(
+            let mut module_with_submissions = match_prs_to_assignments(
                 module,
                 module_to_prs[&module_name].clone(),
                 module_attendance,
                 &region,
             )
             .map_err(|err| err.context("Failed to match PRs to assignments"))?;
+            fill_in_project_repos(
+                octocrab,
+                &github_login,
+                module,
+                &mut module_with_submissions,
+            )
+            .await?;
 
             modules.insert(module_name.clone(), module_with_submissions);
         }
 
         let mentoring_record = mentoring_records.get(&trainee_name);
+        let codewars = codewars_progress.remove(&github_login);
 
         let trainee = TraineeWithSubmissions {
             trainee: Trainee {
@@ -757,8 +1378,11 @@ pub async fn get_batch_with_submissions(
                         .expect("Known good email didn't parse")
                 }),
                 region,
+                preferred_name,
+                pronouns,
             },
             mentoring_record,
+            codewars,
             modules,
         };
         trainees.push(trainee);
@@ -766,24 +1390,40 @@ pub async fn get_batch_with_submissions(
 
     Ok(Batch {
         name: batch_members.name,
+        github_team_slug: batch_github_slug.to_owned(),
         trainees,
+        unreadable_register_sheets: register_info.unreadable_sheets,
+        unreadable_register_rows: register_info.unreadable_rows,
+        mentoring_records_unavailable: mentoring_records.unavailable,
+        name_matched_attendance,
+        groups,
     })
 }
 
 fn get_trainee_module_attendance(
     register_info: &Register,
     module_name: &str,
-    trainee_email: Option<EmailAddress>,
+    trainee: Option<&Trainee>,
+    known_emails: &BTreeSet<EmailAddress>,
     course: &Course,
     region: &Region,
+    name_matched_attendance: &mut Vec<NameMatchedAttendance>,
 ) -> Result<Vec<SubmissionState>, Error> {
-    if let Some(ref trainee_email) = trainee_email {
-        let module_attendance = register_info.modules.get(module_name).with_context(|| {
-            format!(
+    if let Some(trainee) = trainee {
+        let Some(module_attendance) = register_info.modules.get(module_name) else {
+            // Already reported in Batch::unreadable_register_sheets.
+            if register_info.is_unreadable(module_name) {
+                return Ok(Vec::new());
+            }
+            // Nobody has registered for an induction session yet.
+            if module_name == ONBOARDING_MODULE_NAME {
+                return Ok(Vec::new());
+            }
+            return Err(Error::Fatal(anyhow::anyhow!(
                 "Register contained no attendance for module {}",
                 module_name
-            )
-        })?;
+            )));
+        };
         let sprints = course
             .modules
             .get(module_name)
@@ -811,8 +1451,40 @@ fn get_trainee_module_attendance(
                         let attendance = module_attendance
                             .attendance
                             .get(sprint_index)
-                            .and_then(|attendance| attendance.get(trainee_email))
-                            .map(|a| a.to_attendance_enum(start_time));
+                            .and_then(|attendance| attendance.get(&trainee.email))
+                            .and_then(|entries| {
+                                choose_register_entry(
+                                    course.duplicate_register_entries,
+                                    &course.lateness,
+                                    entries,
+                                    start_time,
+                                )
+                            })
+                            .or_else(|| {
+                                let (entries, confidence) = module_attendance.find_by_name(
+                                    sprint_index,
+                                    &trainee.name,
+                                    known_emails,
+                                )?;
+                                let attendance = choose_register_entry(
+                                    course.duplicate_register_entries,
+                                    &course.lateness,
+                                    entries,
+                                    start_time,
+                                )?;
+                                name_matched_attendance.push(NameMatchedAttendance {
+                                    github_login: trainee.github_login.clone(),
+                                    trainee_name: trainee.name.clone(),
+                                    registered_name: attendance.name.clone(),
+                                    registered_email: attendance.email.clone(),
+                                    module_name: module_name.to_owned(),
+                                    sprint_number: sprint_index + 1,
+                                    confidence,
+                                    register_url: attendance.register_url.clone(),
+                                });
+                                Some(attendance)
+                            })
+                            .map(|a| a.to_attendance_enum(start_time, &course.lateness));
                         match attendance {
                             Some(attendance) => {
                                 SubmissionState::Some(Submission::Attendance(attendance))
@@ -900,9 +1572,10 @@ pub fn match_prs_to_assignments(
                     let number_str = number_match.as_str();
                     let number = usize::from_str(number_str)
                         .with_context(|| format!("Failed to parse '{}' as number", number_str))?;
-                    if number == 0 || number > 20 {
+                    if number == 0 || number > MAX_SPRINT_COUNT {
                         return Err(Error::Fatal(anyhow::anyhow!(
-                            "Sprint number was impractical - expected something between 1 and 20 but was {}",
+                            "Sprint number was impractical - expected something between 1 and {} but was {}",
+                            MAX_SPRINT_COUNT,
                             number
                         )));
                     }
@@ -995,7 +1668,7 @@ fn match_pr_to_assignment(
                         });
                     }
                 }
-                Assignment::Attendance { .. } => {}
+                Assignment::Attendance { .. } | Assignment::ProjectRepo { .. } => {}
             }
         }
     }
@@ -1025,15 +1698,32 @@ pub fn get_descriptor_id_for_pr(
     sprints: &[SprintWithSubmissions],
     target_pr_number: u64,
 ) -> Option<u64> {
+    get_sprint_and_descriptor_id_for_pr(sprints, target_pr_number)
+        .map(|(_sprint_index, assignment_issue_id)| assignment_issue_id)
+}
+
+// As get_descriptor_id_for_pr, but also returns the index of the sprint the assignment is in
+pub fn get_sprint_and_descriptor_id_for_pr(
+    sprints: &[SprintWithSubmissions],
+    target_pr_number: u64,
+) -> Option<(usize, u64)> {
     sprints
         .iter()
-        .flat_map(|sprint_with_subs| sprint_with_subs.submissions.iter())
-        .filter_map(|submission_state| match submission_state {
+        .enumerate()
+        .flat_map(|(sprint_index, sprint_with_subs)| {
+            sprint_with_subs
+                .submissions
+                .iter()
+                .map(move |submission_state| (sprint_index, submission_state))
+        })
+        .filter_map(|(sprint_index, submission_state)| match submission_state {
             SubmissionState::Some(Submission::PullRequest {
                 pull_request,
                 assignment_issue_id,
                 ..
-            }) if pull_request.number == target_pr_number => Some(*assignment_issue_id),
+            }) if pull_request.number == target_pr_number => {
+                Some((sprint_index, *assignment_issue_id))
+            }
             _ => None,
         })
         .next()
@@ -1064,3 +1754,22 @@ fn title_word_set(title: &str) -> IndexSet<String> {
         .map(|s| s.to_owned())
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use super::sprint_number_in;
+
+    #[test]
+    fn finds_sprint_numbers() {
+        let sprint = |number| NonZeroUsize::new(number);
+        assert_eq!(sprint_number_in("Sprint 2 | Implement cowsay"), sprint(2));
+        assert_eq!(sprint_number_in("[SPRINT #3] Coursework"), sprint(3));
+        assert_eq!(sprint_number_in("sprint-1 prep"), sprint(1));
+        assert_eq!(sprint_number_in("Week 4: Debugging"), sprint(4));
+        assert_eq!(sprint_number_in("Sprint planning"), None);
+        assert_eq!(sprint_number_in("Sprint 0"), None);
+        assert_eq!(sprint_number_in("Feature: Search"), None);
+    }
+}
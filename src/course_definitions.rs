@@ -0,0 +1,439 @@
+//! Batches defined from the tracker, rather than by hand in the config's JSON.
+//!
+//! Setting up a new intake is mostly copying the previous one with every date moved, which is easy to get subtly wrong by hand.
+//! Admins can clone a batch instead, and the result is stored in the record store, which is read along with the config at startup.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use askama::Template;
+use axum::{
+    Form,
+    extract::{OriginalUri, Path, Query, State},
+    response::{Html, Redirect},
+};
+use chrono::{NaiveDate, TimeDelta};
+use http::HeaderMap;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+use tracing::warn;
+
+use crate::{
+    Config, Error, ServerState,
+    audit_log::{AuditEvent, record_audit_event},
+    config::CourseSchedule,
+    course::ONBOARDING_MODULE_NAME,
+    frontend::record_store,
+    holidays::{HolidayCalendar, HolidayConflict},
+    impersonation::{forbid_while_viewing_as, require_admin},
+    module_discovery::discover_course_modules,
+    newtypes::{GithubLogin, Region, SheetId, TeamSlug},
+    persistence::RecordStore,
+    project_repos::ProjectRepoAssignment,
+    register::{
+        RegisterStructureProblem, check_register_structure, register_sheet_title_for_module,
+    },
+    sheets::sheets_client,
+    timeline::AssessmentWindow,
+};
+
+const COURSE_DEFINITIONS_TAB: &str = "Course definitions";
+
+const COURSE_DEFINITION_HEADINGS: [&str; 6] = [
+    "Recorded at",
+    "Recorded by",
+    "Course",
+    "Batch",
+    "Cloned from",
+    "Definition",
+];
+
+/// The parts of a batch's schedule which carry over to a new intake, in the same shape as a batch in the config.
+///
+/// Anything about particular trainees or channels (groups, deadline extensions, Slack settings) is left for the new batch to set up.
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchDefinition {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub sprints: IndexMap<String, Vec<BTreeMap<Region, NaiveDate>>>,
+    pub project_repos: Vec<ProjectRepoAssignment>,
+    pub onboarding_sessions: Vec<BTreeMap<Region, NaiveDate>>,
+    pub weekly_classes_from: BTreeMap<Region, NaiveDate>,
+    pub assessment_windows: Vec<AssessmentWindow>,
+    pub leaderboard: bool,
+}
+
+impl BatchDefinition {
+    /// The batch's schedule, with every date moved so that it starts on new_start.
+    /// Classes which would land on one of their region's holidays are moved off it, and returned alongside so they can be checked.
+    pub fn cloned_from(
+        source: &CourseSchedule,
+        new_start: NaiveDate,
+        holidays: &HolidayCalendar,
+    ) -> (BatchDefinition, Vec<HolidayConflict>) {
+        let offset = new_start - source.start;
+        let shift = |dates: &BTreeMap<Region, NaiveDate>| {
+            dates
+                .iter()
+                .map(|(region, date)| (region.clone(), *date + offset))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let mut conflicts = Vec::new();
+        let mut shift_classes = |module_name: &str, sprints: &[BTreeMap<Region, NaiveDate>]| {
+            sprints
+                .iter()
+                .enumerate()
+                .map(|(sprint_index, dates)| {
+                    let mut dates = shift(dates);
+                    conflicts.extend(holidays.avoid_holidays(
+                        module_name,
+                        sprint_index + 1,
+                        &mut dates,
+                    ));
+                    dates
+                })
+                .collect::<Vec<_>>()
+        };
+        let sprints = source
+            .sprints
+            .iter()
+            .map(|(module_name, sprints)| {
+                (module_name.clone(), shift_classes(module_name, sprints))
+            })
+            .collect();
+        let onboarding_sessions =
+            shift_classes(ONBOARDING_MODULE_NAME, &source.onboarding_sessions);
+        let definition = BatchDefinition {
+            start: new_start,
+            end: source.end + offset,
+            sprints,
+            project_repos: source
+                .project_repos
+                .iter()
+                .map(|project_repo| ProjectRepoAssignment {
+                    submitted_repos: BTreeMap::new(),
+                    ..project_repo.clone()
+                })
+                .collect(),
+            onboarding_sessions,
+            weekly_classes_from: shift(&source.weekly_classes_from),
+            assessment_windows: source
+                .assessment_windows
+                .iter()
+                .map(|window| AssessmentWindow {
+                    name: window.name.clone(),
+                    start: window.start + offset,
+                    end: window.end + offset,
+                })
+                .collect(),
+            leaderboard: source.leaderboard,
+        };
+        (definition, conflicts)
+    }
+
+    pub fn offset(&self, source: &CourseSchedule) -> TimeDelta {
+        self.start - source.start
+    }
+
+    /// Module name -> sprint count, for checking the course's register has a tab for each of the batch's modules.
+    pub fn module_sprint_counts(&self) -> IndexMap<String, usize> {
+        self.sprints
+            .iter()
+            .map(|(module_name, sprints)| (module_name.clone(), sprints.len()))
+            .collect()
+    }
+}
+
+pub async fn store_batch_definition(
+    store: &RecordStore,
+    actor: &GithubLogin,
+    course: &str,
+    batch_github_slug: &TeamSlug,
+    cloned_from: &str,
+    definition: &BatchDefinition,
+) -> Result<(), Error> {
+    store
+        .append(
+            COURSE_DEFINITIONS_TAB,
+            &COURSE_DEFINITION_HEADINGS,
+            vec![
+                chrono::Utc::now().to_rfc3339(),
+                actor.to_string(),
+                course.to_owned(),
+                batch_github_slug.to_string(),
+                cloned_from.to_owned(),
+                serde_json::to_string(definition).map_err(anyhow::Error::from)?,
+            ],
+        )
+        .await
+}
+
+/// Adds batches stored in the record store to the config, then any modules discovered by topic.
+/// Batches in the config take precedence over stored ones with the same slug, and later stored definitions of a batch replace earlier ones.
+/// Should be called once, straight after loading the config, so everything else sees the same batches.
+pub async fn load_course_definitions(config: &mut Config) -> Result<(), Error> {
+    if let Some(store) = RecordStore::from_config(&ServerState::new(config.clone())).await? {
+        let configured_batches = config
+            .courses
+            .iter()
+            .flat_map(|(course, course_info)| {
+                course_info
+                    .batches
+                    .keys()
+                    .map(move |batch_github_slug| (course.clone(), batch_github_slug.clone()))
+            })
+            .collect::<BTreeSet<_>>();
+        for row in store.read(COURSE_DEFINITIONS_TAB).await? {
+            let [_, _, course, batch_github_slug, _, definition] = row.as_slice() else {
+                warn!("Ignoring malformed row in {} tab", COURSE_DEFINITIONS_TAB);
+                continue;
+            };
+            let Some(course_info) = config.courses.get_mut(course.as_str()) else {
+                warn!(
+                    "Ignoring stored batch {} of unknown course {}",
+                    batch_github_slug, course
+                );
+                continue;
+            };
+            let (Ok(batch_github_slug), Ok(schedule)) = (
+                TeamSlug::from_str(batch_github_slug),
+                serde_json::from_str::<CourseSchedule>(definition),
+            ) else {
+                warn!(
+                    "Ignoring stored batch {} of course {} which couldn't be parsed",
+                    batch_github_slug, course
+                );
+                continue;
+            };
+            if configured_batches
+                .iter()
+                .any(|(configured_course, configured_batch)| {
+                    configured_course == course && *configured_batch == batch_github_slug
+                })
+            {
+                continue;
+            }
+            course_info.batches.insert(batch_github_slug, schedule);
+        }
+    }
+    discover_course_modules(config).await
+}
+
+#[derive(Deserialize)]
+pub struct CloneBatchParams {
+    #[serde(default)]
+    new_batch_github_slug: Option<String>,
+    #[serde(default)]
+    new_start: Option<NaiveDate>,
+    #[serde(default)]
+    stored: bool,
+}
+
+/// A form for cloning a batch, which previews the new batch's dates and whether the register is ready for it once filled in.
+pub async fn get_clone_batch(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+    Query(params): Query<CloneBatchParams>,
+) -> Result<Html<String>, Error> {
+    require_admin(&session, &server_state, original_uri.clone()).await?;
+    let (source, register_sheet_id) = source_batch(&server_state, &course, &batch_github_slug)?;
+    let preview = match (&params.new_batch_github_slug, params.new_start) {
+        (Some(new_batch_github_slug), Some(new_start)) => {
+            let (definition, holiday_conflicts) =
+                BatchDefinition::cloned_from(&source, new_start, &server_state.config.holidays);
+            let client =
+                sheets_client(&session, server_state.clone(), headers, original_uri).await?;
+            // Only the register's layout matters for a batch which hasn't started, not rows earlier batches filled in.
+            let register_problems = check_register_structure(
+                client,
+                &register_sheet_id,
+                &definition.module_sprint_counts(),
+            )
+            .await?
+            .into_iter()
+            .filter(|problem| !matches!(problem, RegisterStructureProblem::BadRow { .. }))
+            .collect();
+            Some(CloneBatchPreview {
+                new_batch_github_slug: new_batch_github_slug.clone(),
+                offset_days: definition.offset(&source).num_days(),
+                definition,
+                holiday_conflicts,
+                register_problems,
+            })
+        }
+        _ => None,
+    };
+    Ok(Html(
+        CloneBatchTemplate {
+            course_name: course,
+            batch_github_slug,
+            source,
+            preview,
+            stored: params.stored,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CloneBatchForm {
+    new_batch_github_slug: String,
+    new_start: NaiveDate,
+}
+
+pub async fn post_clone_batch(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+    Form(form): Form<CloneBatchForm>,
+) -> Result<Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let admin = require_admin(&session, &server_state, original_uri).await?;
+    let (source, _) = source_batch(&server_state, &course, &batch_github_slug)?;
+    let new_batch_github_slug = TeamSlug::from_str(form.new_batch_github_slug.trim())
+        .map_err(|err| Error::UserFacing(format!("Invalid new batch GitHub team slug: {}", err)))?;
+    if server_state.config.courses[course.as_str()]
+        .batches
+        .contains_key(&new_batch_github_slug)
+    {
+        return Err(Error::UserFacing(format!(
+            "{} already has a batch {}",
+            course, new_batch_github_slug
+        )));
+    }
+    // Holiday conflicts were shown in the preview, and the classes already moved off them.
+    let (definition, _) =
+        BatchDefinition::cloned_from(&source, form.new_start, &server_state.config.holidays);
+    let store = record_store(&server_state).await?;
+    // Record before storing, so that there's never an unrecorded change to the course's batches.
+    record_audit_event(
+        &store,
+        &AuditEvent {
+            recorded_at: chrono::Utc::now(),
+            actor: admin.clone(),
+            action: "clone batch",
+            detail: format!(
+                "{} {} -> {} starting {}",
+                course, batch_github_slug, new_batch_github_slug, definition.start
+            ),
+        },
+    )
+    .await?;
+    store_batch_definition(
+        &store,
+        &admin,
+        &course,
+        &new_batch_github_slug,
+        &batch_github_slug,
+        &definition,
+    )
+    .await?;
+    Ok(Redirect::to(&format!(
+        "/admin/courses/{}/batches/{}/clone?stored=true",
+        course, batch_github_slug
+    )))
+}
+
+fn source_batch(
+    server_state: &ServerState,
+    course: &str,
+    batch_github_slug: &str,
+) -> Result<(CourseSchedule, SheetId), Error> {
+    let course_info = server_state
+        .config
+        .courses
+        .get(course)
+        .ok_or_else(|| Error::UserFacing(format!("Unknown course {}", course)))?;
+    let schedule = course_info.batches.get(batch_github_slug).ok_or_else(|| {
+        Error::UserFacing(format!(
+            "Unknown batch {} of course {}",
+            batch_github_slug, course
+        ))
+    })?;
+    Ok((schedule.clone(), course_info.register_sheet_id.clone()))
+}
+
+struct CloneBatchPreview {
+    new_batch_github_slug: String,
+    offset_days: i64,
+    definition: BatchDefinition,
+    holiday_conflicts: Vec<HolidayConflict>,
+    register_problems: Vec<RegisterStructureProblem>,
+}
+
+impl CloneBatchPreview {
+    fn register_tab(&self, module_name: &str) -> String {
+        register_sheet_title_for_module(module_name)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "clone-batch.html")]
+struct CloneBatchTemplate {
+    course_name: String,
+    batch_github_slug: String,
+    source: CourseSchedule,
+    preview: Option<CloneBatchPreview>,
+    stored: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use maplit::btreemap;
+
+    use super::BatchDefinition;
+    use crate::{
+        config::CourseSchedule,
+        holidays::{Holiday, HolidayCalendar},
+        newtypes::Region,
+    };
+
+    #[test]
+    fn clone_shifts_every_date() {
+        let source: CourseSchedule = serde_json::from_value(serde_json::json!({
+            "start": "2026-01-10",
+            "end": "2026-04-10",
+            "sprints": {
+                "Module-Onboarding": [{ "London": "2026-01-17", "Cape Town": "2026-01-18" }],
+            },
+            "onboarding_sessions": [{ "London": "2026-01-10" }],
+            "slack_channels": ["C0123ABCDEF"],
+        }))
+        .unwrap();
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let holidays = HolidayCalendar(btreemap! {
+            Region("Cape Town".to_owned()) => vec![Holiday {
+                date: date("2026-05-17"),
+                name: "Made-up holiday".to_owned(),
+            }],
+        });
+        let (definition, conflicts) =
+            BatchDefinition::cloned_from(&source, date("2026-05-09"), &holidays);
+        assert_eq!(definition.offset(&source).num_days(), 119);
+        assert_eq!(definition.end, date("2026-08-07"));
+        let sprint = &definition.sprints["Module-Onboarding"][0];
+        assert_eq!(sprint[&Region("London".to_owned())], date("2026-05-16"));
+        assert_eq!(sprint[&Region("Cape Town".to_owned())], date("2026-05-18"));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].module, "Module-Onboarding");
+        assert_eq!(
+            definition.onboarding_sessions[0][&Region("London".to_owned())],
+            date("2026-05-09")
+        );
+        // What's stored must be readable as a batch in the config.
+        let round_tripped: CourseSchedule =
+            serde_json::from_str(&serde_json::to_string(&definition).unwrap()).unwrap();
+        assert_eq!(round_tripped.start, date("2026-05-09"));
+        assert!(round_tripped.slack_channels.is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use chrono::NaiveDate;
+use octocrab::Octocrab;
+
+use crate::{
+    Config, Error,
+    config::CourseInfo,
+    data_quality::{RegisterAnomaly, find_register_anomalies},
+    github_accounts::{get_trainee_logins, get_trainees},
+    label_backfill::find_label_backfills,
+    register::{check_register_structure, get_register},
+    sheets::SheetsClient,
+};
+
+/// How many problems each check lists in the Slack message, so one noisy check doesn't drown out the rest.
+const MAX_PROBLEMS_PER_CHECK: usize = 10;
+
+/// The outcome of one of the data health job's checks.
+pub struct HealthCheck {
+    pub name: &'static str,
+    /// Problems found, or why the check couldn't be run.
+    pub outcome: Result<Vec<String>, Error>,
+}
+
+/// Problems with the config which won't stop it loading, but will make pages wrong.
+pub fn validate_config(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (course_name, course_info) in &config.courses {
+        problems.extend(validate_course(course_name, course_info));
+    }
+    for week in &config.on_call_rota.weeks {
+        if !config.on_call_rota.staff.contains_key(&week.staff) {
+            problems.push(format!(
+                "On-call rota week starting {} names {}, who isn't in the rota's staff",
+                week.starting, week.staff
+            ));
+        }
+    }
+    problems
+}
+
+fn validate_course(course_name: &str, course_info: &CourseInfo) -> Vec<String> {
+    let mut problems = Vec::new();
+    let Some((_, first_batch)) = course_info.batches.get_index(0) else {
+        problems.push(format!("{}: has no batches", course_name));
+        return problems;
+    };
+    let late_after = course_info.lateness.late_after();
+    if let Some(very_late_after) = course_info.lateness.very_late_after()
+        && very_late_after <= late_after
+    {
+        problems.push(format!(
+            "{}: very_late_after_minutes ({}) should be more than late_after_minutes ({})",
+            course_name,
+            very_late_after.num_minutes(),
+            late_after.num_minutes()
+        ));
+    }
+    for (batch_github_slug, schedule) in &course_info.batches {
+        let batch = format!("{} {}", course_name, batch_github_slug);
+        if schedule.start > schedule.end {
+            problems.push(format!(
+                "{}: starts ({}) after it ends ({})",
+                batch, schedule.start, schedule.end
+            ));
+        }
+        // Course-wide pages (e.g. reviewers) only look at the first batch's modules.
+        let missing_from_first_batch = schedule
+            .sprints
+            .keys()
+            .filter(|module_name| !first_batch.sprints.contains_key(*module_name))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing_from_first_batch.is_empty() {
+            problems.push(format!(
+                "{}: has modules the course's first batch doesn't, which course-wide pages will miss: {}",
+                batch,
+                missing_from_first_batch.join(", ")
+            ));
+        }
+        for (module_name, sprints) in &schedule.sprints {
+            for (sprint_index, dates) in sprints.iter().enumerate() {
+                if dates.is_empty() {
+                    problems.push(format!(
+                        "{}: {} sprint {} has no dates",
+                        batch,
+                        module_name,
+                        sprint_index + 1
+                    ));
+                }
+                for (region, date) in dates {
+                    if *date < schedule.start || *date > schedule.end {
+                        problems.push(format!(
+                            "{}: {} sprint {} in {} is on {}, outside the batch's dates",
+                            batch,
+                            module_name,
+                            sprint_index + 1,
+                            region,
+                            date
+                        ));
+                    }
+                }
+            }
+        }
+        for extension in &schedule.deadline_extensions {
+            if schedule
+                .sprints
+                .get(&extension.module)
+                .is_none_or(|sprints| extension.sprint == 0 || extension.sprint > sprints.len())
+            {
+                problems.push(format!(
+                    "{}: has a deadline extension for {} sprint {}, which doesn't exist",
+                    batch, extension.module, extension.sprint
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Trainees in the course's batch teams who aren't in the GitHub email mapping sheet, so whose attendance can't be matched up.
+pub async fn check_roster(
+    octocrab: &Octocrab,
+    sheets_client: SheetsClient,
+    config: &Config,
+    course_info: &CourseInfo,
+) -> Result<Vec<String>, Error> {
+    let batch_github_slugs = course_info.batches.keys().cloned().collect::<Vec<_>>();
+    let logins = get_trainee_logins(octocrab, &config.github_org, &batch_github_slugs).await?;
+    let trainees = get_trainees(sheets_client, &config.github_email_mapping_sheet_id).await?;
+    Ok(logins
+        .into_iter()
+        .filter(|login| !trainees.contains_key(login))
+        .map(|login| {
+            format!(
+                "{} is in a batch team but not in the GitHub email mapping sheet",
+                login
+            )
+        })
+        .collect())
+}
+
+/// Coursework issues missing the labels we need to parse them as assignments.
+pub async fn lint_modules(
+    octocrab: &Octocrab,
+    github_org: &str,
+    course_info: &CourseInfo,
+) -> Result<Vec<String>, Error> {
+    let mut problems = Vec::new();
+    for module_name in course_info.module_sprint_counts().keys() {
+        for backfill in find_label_backfills(octocrab, github_org, module_name).await? {
+            let missing = backfill
+                .labels_to_add
+                .iter()
+                .map(|(label, _reason)| label.as_str())
+                .chain(backfill.unresolved.iter().copied())
+                .collect::<Vec<_>>();
+            problems.push(format!(
+                "<{}|{}#{}> is missing {} (`backfill-labels` can add some)",
+                backfill.html_url,
+                module_name,
+                backfill.number,
+                missing.join(", ")
+            ));
+        }
+    }
+    Ok(problems)
+}
+
+/// Problems with how the course's register is set up, and suspicious entries in it.
+pub async fn check_register(
+    octocrab: &Octocrab,
+    sheets_client: SheetsClient,
+    config: &Config,
+    course_name: &str,
+    course_info: &CourseInfo,
+) -> Result<Vec<String>, Error> {
+    let mut problems = check_register_structure(
+        sheets_client.clone(),
+        &course_info.register_sheet_id,
+        &course_info.module_sprint_counts(),
+    )
+    .await?
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>();
+
+    // The register is shared by all of the course's batches, so check it against the latest one's class dates.
+    let Some((batch_github_slug, _)) = course_info
+        .batches
+        .iter()
+        .max_by_key(|(_, schedule)| schedule.start)
+    else {
+        return Ok(problems);
+    };
+    // UNWRAP: We just found the batch in the course.
+    let course = config
+        .get_course_schedule_with_register_sheet_id(course_name.to_owned(), batch_github_slug)
+        .unwrap()
+        .with_assignments(
+            octocrab,
+            &sheets_client.server_state().in_flight,
+            &config.github_org,
+        )
+        .await?;
+    let register = get_register(
+        sheets_client,
+        course_info.register_sheet_id.clone(),
+        course.start_date,
+        course.end_date,
+    )
+    .await?;
+    let anomalies = find_register_anomalies(&course, &register.entries)
+        .into_iter()
+        // Earlier batches' entries would all look like they were outside class time.
+        .filter(|anomaly| {
+            course_info.batches.len() == 1
+                || !matches!(anomaly, RegisterAnomaly::OutsideClassWindow { .. })
+        })
+        .count();
+    if anomalies > 0 {
+        problems.push(format!(
+            "{} suspicious register entries: {}/courses/{}/batches/{}/data-quality",
+            anomalies, config.public_base_url, course_name, batch_github_slug
+        ));
+    }
+    Ok(problems)
+}
+
+/// One consolidated message, with a section per course, for posting to Slack.
+pub fn slack_text(date: NaiveDate, sections: &[(String, Vec<HealthCheck>)]) -> String {
+    let problem_count = sections
+        .iter()
+        .flat_map(|(_, checks)| checks)
+        .map(|check| check.outcome.as_ref().map_or(1, Vec::len))
+        .sum::<usize>();
+    let mut text = format!("*Data health for {}*: ", date);
+    if problem_count == 0 {
+        text.push_str("all good :white_check_mark:");
+        return text;
+    }
+    text.push_str(&format!("{} problems", problem_count));
+    for (heading, checks) in sections {
+        if checks
+            .iter()
+            .all(|check| check.outcome.as_ref().is_ok_and(Vec::is_empty))
+        {
+            continue;
+        }
+        text.push_str(&format!("\n\n*{}*", heading));
+        for check in checks {
+            match &check.outcome {
+                Ok(problems) if problems.is_empty() => {}
+                Ok(problems) => {
+                    text.push_str(&format!("\n{} ({}):", check.name, problems.len()));
+                    for problem in problems.iter().take(MAX_PROBLEMS_PER_CHECK) {
+                        text.push_str(&format!("\n• {}", problem));
+                    }
+                    if problems.len() > MAX_PROBLEMS_PER_CHECK {
+                        text.push_str(&format!(
+                            "\n• …and {} more",
+                            problems.len() - MAX_PROBLEMS_PER_CHECK
+                        ));
+                    }
+                }
+                Err(err) => text.push_str(&format!("\n{}: couldn't check: {:#}", check.name, err)),
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::{HealthCheck, slack_text, validate_course};
+    use crate::config::CourseInfo;
+
+    #[test]
+    fn finds_problems_in_course_config() {
+        let course_info: CourseInfo = serde_json::from_value(serde_json::json!({
+            "register_sheet_id": "1aBcD_efGhIjKlMnOpQrStUvWxYz-0123456789abcd",
+            "batches": {
+                "itp-2026-09": {
+                    "start": "2026-09-01",
+                    "end": "2026-12-01",
+                    "sprints": {
+                        "Module-Onboarding": [
+                            { "London": "2026-09-05" },
+                            { "London": "2027-01-05" },
+                            {},
+                        ],
+                    },
+                    "deadline_extensions": [
+                        { "region": "London", "module": "Module-Onboarding", "sprint": 4, "days": 7 },
+                    ],
+                },
+            },
+            "lateness": { "late_after_minutes": 30, "very_late_after_minutes": 15 },
+        }))
+        .unwrap();
+        assert_eq!(
+            validate_course("itp", &course_info),
+            vec![
+                "itp: very_late_after_minutes (15) should be more than late_after_minutes (30)",
+                "itp itp-2026-09: Module-Onboarding sprint 2 in London is on 2027-01-05, outside the batch's dates",
+                "itp itp-2026-09: Module-Onboarding sprint 3 has no dates",
+                "itp itp-2026-09: has a deadline extension for Module-Onboarding sprint 4, which doesn't exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn summarises_clean_runs_in_one_line() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let sections = vec![(
+            "itp".to_owned(),
+            vec![HealthCheck {
+                name: "Roster",
+                outcome: Ok(Vec::new()),
+            }],
+        )];
+        assert_eq!(
+            slack_text(date, &sections),
+            "*Data health for 2026-10-15*: all good :white_check_mark:"
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use email_address::EmailAddress;
+use http::HeaderMap;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    config::DuplicateRegisterEntries,
+    course::Course,
+    octocrab::octocrab,
+    register::{RegisterEntry, get_register, normalise_name},
+    sheets::sheets_client,
+};
+
+/// Registrations for the same email closer together than this are probably not the same person registering twice.
+const RAPID_REGISTRATION_WINDOW: TimeDelta = TimeDelta::minutes(10);
+
+/// How long before a class starts trainees may register, e.g. while travelling in.
+const CLASS_WINDOW_BEFORE_START: TimeDelta = TimeDelta::hours(1);
+
+/// How long after a class starts trainees may register, i.e. roughly the length of a class day.
+const CLASS_WINDOW_AFTER_START: TimeDelta = TimeDelta::hours(8);
+
+/// A register entry (or entries) which looks wrong, for the ops team to check.
+#[derive(Clone, Debug)]
+pub enum RegisterAnomaly {
+    /// The same email registered from different regions within a few minutes, e.g. someone registering on behalf of a friend.
+    RapidRegistrations {
+        email: EmailAddress,
+        entries: Vec<RegisterEntry>,
+    },
+    /// Registered at a time when there was no class for the sprint in any region.
+    OutsideClassWindow { entry: RegisterEntry },
+    /// The same email registered more than once for the same class. Only one entry counts, chosen by the course's `duplicate_register_entries`.
+    Duplicates {
+        email: EmailAddress,
+        entries: Vec<RegisterEntry>,
+    },
+    /// The same email registered under different names.
+    SeveralNames {
+        email: EmailAddress,
+        names: Vec<String>,
+        entries: Vec<RegisterEntry>,
+    },
+}
+
+impl RegisterAnomaly {
+    pub fn description(&self) -> String {
+        match self {
+            RegisterAnomaly::RapidRegistrations { email, entries } => format!(
+                "{} registered {} times within {} minutes from different locations",
+                email,
+                entries.len(),
+                RAPID_REGISTRATION_WINDOW.num_minutes()
+            ),
+            RegisterAnomaly::OutsideClassWindow { entry } => format!(
+                "{} registered for {} sprint {} outside of class time",
+                entry.attendance.email, entry.module_name, entry.sprint_number
+            ),
+            RegisterAnomaly::Duplicates { email, entries } => format!(
+                "{} registered {} times for {} sprint {}",
+                email,
+                entries.len(),
+                entries[0].module_name,
+                entries[0].sprint_number
+            ),
+            RegisterAnomaly::SeveralNames { email, names, .. } => format!(
+                "{} registered under {} different names: {}",
+                email,
+                names.len(),
+                names.join(", ")
+            ),
+        }
+    }
+
+    pub fn entries(&self) -> &[RegisterEntry] {
+        match self {
+            RegisterAnomaly::RapidRegistrations { entries, .. }
+            | RegisterAnomaly::Duplicates { entries, .. }
+            | RegisterAnomaly::SeveralNames { entries, .. } => entries,
+            RegisterAnomaly::OutsideClassWindow { entry } => std::slice::from_ref(entry),
+        }
+    }
+}
+
+pub fn find_register_anomalies(course: &Course, entries: &[RegisterEntry]) -> Vec<RegisterAnomaly> {
+    let mut anomalies = rapid_registrations(entries);
+    anomalies.extend(
+        entries
+            .iter()
+            .filter(|entry| is_outside_class_window(course, entry))
+            .map(|entry| RegisterAnomaly::OutsideClassWindow {
+                entry: entry.clone(),
+            }),
+    );
+    anomalies.extend(duplicates(entries));
+    anomalies.extend(several_names(entries));
+    anomalies
+}
+
+fn entries_by_email(entries: &[RegisterEntry]) -> BTreeMap<&EmailAddress, Vec<&RegisterEntry>> {
+    let mut by_email: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for entry in entries {
+        by_email
+            .entry(&entry.attendance.email)
+            .or_default()
+            .push(entry);
+    }
+    by_email
+}
+
+fn rapid_registrations(entries: &[RegisterEntry]) -> Vec<RegisterAnomaly> {
+    let mut anomalies = Vec::new();
+    for (email, mut entries) in entries_by_email(entries) {
+        entries.sort_by_key(|entry| entry.attendance.timestamp);
+        // Runs of entries each within the window of the one before.
+        let mut runs: Vec<Vec<&RegisterEntry>> = Vec::new();
+        let mut last_timestamp: Option<DateTime<Utc>> = None;
+        for entry in entries {
+            let timestamp = entry.attendance.timestamp;
+            match (runs.last_mut(), last_timestamp) {
+                (Some(run), Some(last)) if timestamp - last <= RAPID_REGISTRATION_WINDOW => {
+                    run.push(entry)
+                }
+                _ => runs.push(vec![entry]),
+            }
+            last_timestamp = Some(timestamp);
+        }
+        for run in runs {
+            let first_region = &run[0].attendance.region;
+            if run
+                .iter()
+                .any(|entry| entry.attendance.region != *first_region)
+            {
+                anomalies.push(RegisterAnomaly::RapidRegistrations {
+                    email: email.clone(),
+                    entries: run.into_iter().cloned().collect(),
+                });
+            }
+        }
+    }
+    anomalies
+}
+
+/// Whether the entry was registered away from every region's class for its sprint.
+/// Entries for sprints we don't know the dates of aren't flagged, as there's nothing to check them against.
+fn is_outside_class_window(course: &Course, entry: &RegisterEntry) -> bool {
+    let Some(sprint) = course
+        .modules
+        .get(&entry.module_name)
+        .and_then(|module| module.sprints.get(entry.sprint_number - 1))
+    else {
+        return false;
+    };
+    if sprint.dates.is_empty() {
+        return false;
+    }
+    let timestamp = entry.attendance.timestamp;
+    !sprint.dates.iter().any(|(region, date)| {
+        let start = region.class_start_time(date);
+        timestamp >= start - CLASS_WINDOW_BEFORE_START
+            && timestamp <= start + CLASS_WINDOW_AFTER_START
+    })
+}
+
+fn duplicates(entries: &[RegisterEntry]) -> Vec<RegisterAnomaly> {
+    let mut by_class: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for entry in entries {
+        by_class
+            .entry((
+                &entry.attendance.email,
+                &entry.module_name,
+                entry.sprint_number,
+            ))
+            .or_default()
+            .push(entry.clone());
+    }
+    by_class
+        .into_iter()
+        .filter(|(_, entries)| entries.len() > 1)
+        .map(|((email, _, _), entries)| RegisterAnomaly::Duplicates {
+            email: email.clone(),
+            entries,
+        })
+        .collect()
+}
+
+fn several_names(entries: &[RegisterEntry]) -> Vec<RegisterAnomaly> {
+    let mut anomalies = Vec::new();
+    for (email, entries) in entries_by_email(entries) {
+        // Differences in case or spacing are just typing, not a different person.
+        let mut names: BTreeMap<String, String> = BTreeMap::new();
+        for entry in &entries {
+            names
+                .entry(normalise_name(&entry.attendance.name))
+                .or_insert_with(|| entry.attendance.name.clone());
+        }
+        if names.len() > 1 {
+            anomalies.push(RegisterAnomaly::SeveralNames {
+                email: email.clone(),
+                names: names.into_values().collect(),
+                entries: entries.into_iter().cloned().collect(),
+            });
+        }
+    }
+    anomalies
+}
+
+/// A report of register entries which look wrong, for the ops team to check and fix in the register.
+pub async fn get_data_quality(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    // The register is staff-only, so reading it with the user's own credentials keeps this staff-only too.
+    let sheets_client = sheets_client(
+        &session,
+        server_state.clone(),
+        headers,
+        original_uri.clone(),
+    )
+    .await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let course_schedule = server_state
+        .config
+        .get_course_schedule_with_register_sheet_id(course.clone(), &batch_github_slug)
+        .ok_or_else(|| Error::UserFacing(format!("Course not found: {course}")))?;
+    let course = course_schedule
+        .with_assignments(
+            &octocrab,
+            &server_state.in_flight,
+            &server_state.config.github_org,
+        )
+        .await?;
+    let register = get_register(
+        sheets_client,
+        course
+            .register_sheet_id
+            .clone()
+            .context("Course has no register")?,
+        course.start_date,
+        course.end_date,
+    )
+    .await?;
+    let anomalies = find_register_anomalies(&course, &register.entries);
+    Ok(Html(
+        DataQualityTemplate {
+            duplicate_register_entries: course.duplicate_register_entries,
+            course_name: course.name,
+            batch_github_slug,
+            anomalies,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "data-quality.html")]
+struct DataQualityTemplate {
+    course_name: String,
+    duplicate_register_entries: DuplicateRegisterEntries,
+    batch_github_slug: String,
+    anomalies: Vec<RegisterAnomaly>,
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use chrono::{DateTime, TimeDelta, Utc};
+    use email_address::EmailAddress;
+
+    use super::{RegisterAnomaly, duplicates, rapid_registrations, several_names};
+    use crate::register::{Attendance, RegisterEntry};
+
+    fn entry(name: &str, email: &str, region: &str, minutes: i64) -> RegisterEntry {
+        RegisterEntry {
+            module_name: "Module-Onboarding".to_owned(),
+            sprint_number: 1,
+            attendance: Attendance {
+                name: name.to_owned(),
+                email: EmailAddress::from_str(email).unwrap(),
+                timestamp: DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap()
+                    + TimeDelta::minutes(minutes),
+                region: region.to_owned(),
+                register_url: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn flags_rapid_registrations_from_different_regions() {
+        let entries = [
+            entry("Jane Doe", "jane@example.com", "London", 0),
+            entry("Jane Doe", "jane@example.com", "Cape Town", 3),
+            // Re-registering from the same place is just a double-submitted form.
+            entry("Sam Smith", "sam@example.com", "London", 0),
+            entry("Sam Smith", "sam@example.com", "London", 1),
+            // Far enough apart to be different days of class.
+            entry("Alex Roe", "alex@example.com", "London", 0),
+            entry("Alex Roe", "alex@example.com", "Glasgow", 60 * 24 * 7),
+        ];
+        let anomalies = rapid_registrations(&entries);
+        assert_eq!(anomalies.len(), 1);
+        let RegisterAnomaly::RapidRegistrations { email, entries } = &anomalies[0] else {
+            panic!("Expected rapid registrations, got {:?}", anomalies[0]);
+        };
+        assert_eq!(email.as_str(), "jane@example.com");
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn flags_duplicate_entries_for_the_same_class() {
+        let mut next_sprint = entry("Jane Doe", "jane@example.com", "London", 60 * 24 * 7);
+        next_sprint.sprint_number = 2;
+        let entries = [
+            entry("Jane Doe", "jane@example.com", "London", 0),
+            entry("Jane Doe", "jane@example.com", "London", 30),
+            next_sprint,
+            entry("Sam Smith", "sam@example.com", "London", 0),
+        ];
+        let anomalies = duplicates(&entries);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].entries().len(), 2);
+    }
+
+    #[test]
+    fn flags_emails_used_with_several_names() {
+        let entries = [
+            entry("Jane Doe", "jane@example.com", "London", 0),
+            entry("jane  doe", "jane@example.com", "London", 60 * 24 * 7),
+            entry("Sam Smith", "shared@example.com", "London", 0),
+            entry("Alex Roe", "shared@example.com", "London", 1),
+        ];
+        let anomalies = several_names(&entries);
+        assert_eq!(anomalies.len(), 1);
+        let RegisterAnomaly::SeveralNames { email, names, .. } = &anomalies[0] else {
+            panic!("Expected several names, got {:?}", anomalies[0]);
+        };
+        assert_eq!(email.as_str(), "shared@example.com");
+        assert_eq!(names, &["Alex Roe", "Sam Smith"]);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    response::Html,
+};
+use futures::future::join_all;
+use http::HeaderMap;
+use octocrab::Octocrab;
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::{Assignment, Batch, Course, Submission, SubmissionState},
+    frontend::get_course_and_batch,
+    octocrab::octocrab,
+    prs::{Pr, get_pr_patch},
+    similarity::{jaccard, shingles},
+};
+
+/// Pairs at least this similar are shown unless another threshold is asked for.
+pub const DEFAULT_DIFF_SIMILARITY_THRESHOLD: f64 = 0.6;
+
+/// Diffs with fewer shingles than this are too small to compare meaningfully (e.g. a one-line fix).
+const MIN_SHINGLES: usize = 20;
+
+/// Two PRs for the same assignment whose changes are suspiciously alike.
+/// This is only a prompt for a human to look at them: e.g. very small or very prescriptive assignments naturally look alike.
+#[derive(Clone, Debug)]
+pub struct SimilarPair {
+    pub module_name: String,
+    pub assignment_title: String,
+    pub left: Pr,
+    pub right: Pr,
+    /// From 0 to 1.
+    pub similarity: f64,
+}
+
+impl SimilarPair {
+    pub fn percent(&self) -> f64 {
+        (self.similarity * 100.0).round()
+    }
+}
+
+/// Only the lines a patch adds, since the lines it removes or keeps come from the assignment's starting code.
+fn added_lines(patch: &str) -> String {
+    patch
+        .lines()
+        .filter(|line| line.starts_with('+') && !line.starts_with("+++"))
+        .map(|line| &line[1..])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn assignment_title(course: &Course, module_name: &str, assignment_issue_id: u64) -> String {
+    course
+        .modules
+        .get(module_name)
+        .into_iter()
+        .flat_map(|module| module.sprints.iter())
+        .flat_map(|sprint| sprint.assignments.iter())
+        .find_map(|assignment| match assignment {
+            Assignment::ExpectedPullRequest {
+                title,
+                assignment_issue_id: id,
+                ..
+            } if *id == assignment_issue_id => Some(title.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| format!("Issue {}", assignment_issue_id))
+}
+
+/// Compares the diffs of every pair of the batch's PRs which were matched to the same assignment.
+pub async fn find_similar_diffs(
+    octocrab: &Octocrab,
+    github_org: &str,
+    course: &Course,
+    batch: &Batch,
+    threshold: f64,
+) -> Result<Vec<SimilarPair>, Error> {
+    let mut prs_by_assignment: BTreeMap<(String, u64), Vec<Pr>> = BTreeMap::new();
+    for trainee in &batch.trainees {
+        for (module_name, module) in &trainee.modules {
+            for sprint in &module.sprints {
+                for submission in &sprint.submissions {
+                    if let SubmissionState::Some(Submission::PullRequest {
+                        pull_request,
+                        assignment_issue_id,
+                        ..
+                    }) = submission
+                    {
+                        prs_by_assignment
+                            .entry((module_name.clone(), *assignment_issue_id))
+                            .or_default()
+                            .push(pull_request.clone());
+                    }
+                }
+            }
+        }
+    }
+    prs_by_assignment.retain(|_, prs| prs.len() > 1);
+
+    let prs = prs_by_assignment.values().flatten().collect::<Vec<_>>();
+    let patches = join_all(
+        prs.iter()
+            .map(|pr| get_pr_patch(octocrab, github_org, &pr.repo_name, pr.number)),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, Error>>()?;
+    let shingles_by_url = prs
+        .iter()
+        .zip(patches)
+        .map(|(pr, patch)| (pr.url.clone(), shingles(&added_lines(&patch))))
+        .collect::<BTreeMap<_, BTreeSet<u64>>>();
+
+    let mut pairs = Vec::new();
+    for ((module_name, assignment_issue_id), prs) in &prs_by_assignment {
+        for (index, left) in prs.iter().enumerate() {
+            for right in &prs[index + 1..] {
+                let (Some(left_shingles), Some(right_shingles)) = (
+                    shingles_by_url.get(&left.url),
+                    shingles_by_url.get(&right.url),
+                ) else {
+                    continue;
+                };
+                if left_shingles.len() < MIN_SHINGLES || right_shingles.len() < MIN_SHINGLES {
+                    continue;
+                }
+                let similarity = jaccard(left_shingles, right_shingles);
+                if similarity >= threshold {
+                    pairs.push(SimilarPair {
+                        module_name: module_name.clone(),
+                        assignment_title: assignment_title(
+                            course,
+                            module_name,
+                            *assignment_issue_id,
+                        ),
+                        left: left.clone(),
+                        right: right.clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+    }
+    pairs.sort_by(|left, right| right.similarity.total_cmp(&left.similarity));
+    Ok(pairs)
+}
+
+#[derive(Deserialize)]
+pub struct DiffSimilarityParams {
+    threshold: Option<f64>,
+}
+
+pub async fn get_diff_similarity(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+    Query(params): Query<DiffSimilarityParams>,
+) -> Result<Html<String>, Error> {
+    let threshold = params
+        .threshold
+        .unwrap_or(DEFAULT_DIFF_SIMILARITY_THRESHOLD);
+    // Loading the batch needs the staff-only GitHub email mapping sheet, so this is staff-only too.
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri.clone(),
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let pairs = find_similar_diffs(
+        &octocrab,
+        &server_state.config.github_org,
+        &course,
+        &batch,
+        threshold,
+    )
+    .await?;
+    Ok(Html(
+        DiffSimilarityTemplate {
+            course_name: course.name,
+            batch_name: batch.name,
+            threshold,
+            pairs,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "diff-similarity.html")]
+struct DiffSimilarityTemplate {
+    course_name: String,
+    batch_name: String,
+    threshold: f64,
+    pairs: Vec<SimilarPair>,
+}
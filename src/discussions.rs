@@ -0,0 +1,161 @@
+use anyhow::Context;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::future::join_all;
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, newtypes::GithubLogin, pr_comments::graphql};
+
+/// Used if the config doesn't set help_request_age_hours.
+pub const DEFAULT_HELP_REQUEST_AGE_HOURS: i64 = 24;
+
+/// A question a trainee asked in a module repo's Discussions which nobody has answered yet.
+#[derive(Clone, Debug, Serialize)]
+pub struct HelpRequest {
+    pub module_name: String,
+    pub title: String,
+    pub url: String,
+    pub author: Option<GithubLogin>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl HelpRequest {
+    pub fn age(&self, now: DateTime<Utc>) -> TimeDelta {
+        now - self.created_at
+    }
+}
+
+/// Unanswered discussions older than `min_age` in each module repo, oldest first.
+///
+/// In answerable categories (e.g. Q&A) a discussion counts as answered once an answer is marked.
+/// Elsewhere, any comment counts.
+pub async fn get_unanswered_help_requests(
+    octocrab: &Octocrab,
+    github_org: &str,
+    module_names: &[String],
+    min_age: TimeDelta,
+) -> Result<Vec<HelpRequest>, Error> {
+    let now = Utc::now();
+    let modules = join_all(
+        module_names
+            .iter()
+            .map(|module_name| get_module_discussions(octocrab, github_org, module_name)),
+    )
+    .await;
+    let mut help_requests = Vec::new();
+    for (module_name, discussions) in module_names.iter().zip(modules) {
+        for discussion in discussions? {
+            let is_answered = if discussion.category.is_answerable {
+                discussion.is_answered.unwrap_or(false)
+            } else {
+                discussion.comments.total_count > 0
+            };
+            if is_answered || now - discussion.created_at < min_age {
+                continue;
+            }
+            help_requests.push(HelpRequest {
+                module_name: module_name.clone(),
+                title: discussion.title,
+                url: discussion.url,
+                author: discussion
+                    .author
+                    .map(|author| GithubLogin::from(author.login)),
+                created_at: discussion.created_at,
+            });
+        }
+    }
+    help_requests.sort_by_key(|help_request| help_request.created_at);
+    Ok(help_requests)
+}
+
+async fn get_module_discussions(
+    octocrab: &Octocrab,
+    github_org: &str,
+    module_name: &str,
+) -> Result<Vec<Discussion>, Error> {
+    let response: graphql::Result<DiscussionsResponse> = octocrab
+        .graphql(&serde_json::json!({
+            "query": DISCUSSIONS_QUERY,
+            "variables": {"owner": github_org, "repo": module_name},
+        }))
+        .await
+        .with_context(|| format!("Failed to get discussions for {}", module_name))?;
+    let discussions = response
+        .into_result()?
+        .and_then(|data| data.repository)
+        .map(|repository| repository.discussions.nodes)
+        .unwrap_or_default();
+    Ok(discussions)
+}
+
+// There's no REST API for discussions.
+// Only the most recent 100 are fetched - anything older than that has been waiting far too long to need listing individually.
+const DISCUSSIONS_QUERY: &str = r#"
+query($owner: String!, $repo: String!) {
+  repository(owner: $owner, name: $repo) {
+    discussions(first: 100, orderBy: {field: CREATED_AT, direction: DESC}) {
+      nodes {
+        title
+        url
+        createdAt
+        isAnswered
+        author {
+          login
+        }
+        category {
+          isAnswerable
+        }
+        comments {
+          totalCount
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+struct DiscussionsResponse {
+    // None if the repo doesn't exist.
+    repository: Option<DiscussionsRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscussionsRepository {
+    discussions: DiscussionConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscussionConnection {
+    nodes: Vec<Discussion>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Discussion {
+    title: String,
+    url: String,
+    created_at: DateTime<Utc>,
+    is_answered: Option<bool>,
+    // None if the author's account has been deleted.
+    author: Option<DiscussionAuthor>,
+    category: DiscussionCategory,
+    comments: DiscussionComments,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscussionAuthor {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscussionCategory {
+    is_answerable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscussionComments {
+    total_count: usize,
+}
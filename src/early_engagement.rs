@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::NaiveDate;
+use http::HeaderMap;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::{Attendance, Batch, Course, Submission, SubmissionState, TraineeWithSubmissions},
+    frontend::get_course_and_batch,
+    newtypes::{GithubLogin, Region},
+};
+
+/// How soon after the course started a trainee first engaged with it.
+/// Early engagement is the strongest predictor staff have of how a trainee will get on.
+pub struct TraineeEarlyEngagement {
+    pub name: String,
+    pub github_login: GithubLogin,
+    pub region: Region,
+    /// Days after the course start that the trainee opened their first PR, if they have.
+    pub days_to_first_pr: Option<i64>,
+    /// Days after the course start of the first class the trainee attended, if they have.
+    pub days_to_first_attendance: Option<i64>,
+}
+
+impl TraineeEarlyEngagement {
+    pub fn new(course: &Course, trainee: &TraineeWithSubmissions) -> Self {
+        let region = &trainee.trainee.region;
+        let first_pr = trainee
+            .modules
+            .values()
+            .flat_map(|module| {
+                module
+                    .sprints
+                    .iter()
+                    .flat_map(|sprint| &sprint.submissions)
+                    .filter_map(|submission| match submission {
+                        SubmissionState::Some(Submission::PullRequest { pull_request, .. }) => {
+                            Some(pull_request)
+                        }
+                        _ => None,
+                    })
+                    .chain(&module.unknown_prs)
+            })
+            .map(|pr| pr.created_at.date_naive())
+            .min();
+        let first_attendance = course
+            .modules
+            .iter()
+            .filter_map(|(module_name, module)| {
+                Some(
+                    module
+                        .sprints
+                        .iter()
+                        .zip(&trainee.modules.get(module_name)?.sprints),
+                )
+            })
+            .flatten()
+            .filter(|(_, sprint)| {
+                sprint.submissions.iter().any(|submission| {
+                    matches!(
+                        submission,
+                        SubmissionState::Some(Submission::Attendance(
+                            Attendance::OnTime { .. }
+                                | Attendance::Late { .. }
+                                | Attendance::VeryLate { .. }
+                        ))
+                    )
+                })
+            })
+            // Trainees who attended another region's class still attended, so fall back to the earliest class anywhere.
+            .filter_map(|(sprint, _)| {
+                sprint
+                    .dates
+                    .get(region)
+                    .or_else(|| sprint.dates.values().min())
+                    .copied()
+            })
+            .min();
+        let days_after_start = |date: NaiveDate| (date - course.start_date).num_days();
+        TraineeEarlyEngagement {
+            name: trainee.trainee.display_name().to_owned(),
+            github_login: trainee.trainee.github_login.clone(),
+            region: region.clone(),
+            days_to_first_pr: first_pr.map(days_after_start),
+            days_to_first_attendance: first_attendance.map(days_after_start),
+        }
+    }
+}
+
+/// Early engagement across a group of trainees, e.g. a region.
+pub struct EarlyEngagementSummary {
+    pub label: String,
+    pub trainee_count: usize,
+    pub with_pr: usize,
+    pub with_attendance: usize,
+    pub median_days_to_first_pr: Option<i64>,
+    pub median_days_to_first_attendance: Option<i64>,
+}
+
+impl EarlyEngagementSummary {
+    fn new<'a>(
+        label: String,
+        trainees: impl IntoIterator<Item = &'a TraineeEarlyEngagement> + Clone,
+    ) -> Self {
+        let days_to_first_pr = trainees
+            .clone()
+            .into_iter()
+            .filter_map(|trainee| trainee.days_to_first_pr)
+            .collect::<Vec<_>>();
+        let days_to_first_attendance = trainees
+            .clone()
+            .into_iter()
+            .filter_map(|trainee| trainee.days_to_first_attendance)
+            .collect::<Vec<_>>();
+        EarlyEngagementSummary {
+            label,
+            trainee_count: trainees.into_iter().count(),
+            with_pr: days_to_first_pr.len(),
+            with_attendance: days_to_first_attendance.len(),
+            median_days_to_first_pr: median(days_to_first_pr),
+            median_days_to_first_attendance: median(days_to_first_attendance),
+        }
+    }
+}
+
+fn median(mut values: Vec<i64>) -> Option<i64> {
+    values.sort();
+    let middle = values.len() / 2;
+    match values.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((values[middle - 1] + values[middle]) / 2),
+        _ => Some(values[middle]),
+    }
+}
+
+pub struct EarlyEngagement {
+    /// The whole batch first, then each region.
+    pub summaries: Vec<EarlyEngagementSummary>,
+    /// Trainees who haven't engaged yet first, then the slowest to.
+    pub trainees: Vec<TraineeEarlyEngagement>,
+}
+
+impl EarlyEngagement {
+    pub fn new(course: &Course, batch: &Batch) -> Self {
+        let mut trainees = batch
+            .trainees
+            .iter()
+            .map(|trainee| TraineeEarlyEngagement::new(course, trainee))
+            .collect::<Vec<_>>();
+        // None sorts before Some, and the larger day counts need to come first among the Somes.
+        trainees.sort_by_key(|trainee| {
+            (
+                trainee.days_to_first_pr.map(std::cmp::Reverse),
+                trainee.days_to_first_attendance.map(std::cmp::Reverse),
+            )
+        });
+        let mut by_region: BTreeMap<&Region, Vec<&TraineeEarlyEngagement>> = BTreeMap::new();
+        for trainee in &trainees {
+            by_region.entry(&trainee.region).or_default().push(trainee);
+        }
+        let mut summaries = vec![EarlyEngagementSummary::new(
+            "Whole batch".to_owned(),
+            &trainees,
+        )];
+        summaries.extend(by_region.into_iter().map(|(region, trainees)| {
+            EarlyEngagementSummary::new(region.to_string(), trainees.iter().copied())
+        }));
+        EarlyEngagement {
+            summaries,
+            trainees,
+        }
+    }
+}
+
+pub async fn get_early_engagement(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let early_engagement = EarlyEngagement::new(&course, &batch);
+    Ok(Html(
+        EarlyEngagementTemplate {
+            course_name: course.name,
+            course_start_date: course.start_date,
+            batch_name: batch.name,
+            batch_github_slug: batch.github_team_slug,
+            early_engagement,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "early-engagement.html")]
+struct EarlyEngagementTemplate {
+    course_name: String,
+    course_start_date: NaiveDate,
+    batch_name: String,
+    batch_github_slug: String,
+    early_engagement: EarlyEngagement,
+}
+
+#[cfg(test)]
+mod test {
+    use super::median;
+
+    #[test]
+    fn median_of_days() {
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![9, 1, 4]), Some(4));
+        assert_eq!(median(vec![7, 1, 3, 5]), Some(4));
+    }
+}
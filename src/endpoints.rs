@@ -7,22 +7,30 @@ use ::octocrab::models::{Author, teams::RequestedTeam};
 use anyhow::Context;
 use axum::{
     Json,
-    extract::{OriginalUri, Path, State},
-    response::IntoResponse,
+    extract::{OriginalUri, Path, Query, State},
+    response::{IntoResponse, Response},
 };
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
 use futures::future::join_all;
 use http::HeaderMap;
 use indexmap::IndexMap;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 
 use crate::{
     Error, ServerState,
+    config::CourseScheduleWithRegisterSheetId,
+    course::{
+        Assignment, AssignmentOptionality, Batch as BatchWithSubmissions, Course, Submission,
+        SubmissionState,
+    },
+    discussions::{HelpRequest, get_unanswered_help_requests},
+    frontend::{CourseReviewers, Csv, get_course_and_batch, get_course_reviewers},
     github_accounts::get_trainees,
+    mentors::{MentorRoster, get_mentor_roster},
     newtypes::GithubLogin,
-    octocrab::{all_pages, octocrab, octocrab_for_maybe_token},
-    prs::{PrWithReviews, fill_in_reviewers, get_prs},
+    octocrab::{all_pages, octocrab, octocrab_for_maybe_token, octocrab_or_shared},
+    prs::{PrState, PrWithReviews, ReviewerInfo, fill_in_reviewers, get_prs},
     register::{Attendance, get_register},
     sheets::sheets_client,
 };
@@ -72,7 +80,7 @@ pub async fn courses(State(server_state): State<ServerState>) -> Json<Courses> {
                 .get_index(0)
                 .map(|(_batch_name, course_schedule)| {
                     (
-                        course_name,
+                        String::from(course_name),
                         course_schedule.sprints.keys().cloned().collect::<Vec<_>>(),
                     )
                 })
@@ -163,13 +171,13 @@ pub async fn course_prs(
     OriginalUri(original_uri): OriginalUri,
     Path(course): Path<String>,
 ) -> Result<Json<PrList>, Error> {
-    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let octocrab = octocrab_or_shared(&session, &server_state, original_uri).await?;
 
     let mut futures = Vec::new();
     let course = server_state
         .config
         .courses
-        .get(&course)
+        .get(course.as_str())
         .ok_or_else(|| Error::Fatal(anyhow::anyhow!("Course not found: {course}")))?;
     for module in course
         .batches
@@ -178,9 +186,10 @@ pub async fn course_prs(
         .flat_map(|(_batch_name, course_schedule)| course_schedule.sprints.keys().cloned())
     {
         let octocrab = octocrab.clone();
+        let in_flight = &server_state.in_flight;
         let github_org = &server_state.config.github_org;
         futures.push(async move {
-            let prs = get_prs(&octocrab, github_org, &module, true).await?;
+            let prs = get_prs(&octocrab, in_flight, github_org, &module, true).await?;
             fill_in_reviewers(octocrab.clone(), github_org.to_owned(), prs).await
         });
     }
@@ -191,6 +200,185 @@ pub async fn course_prs(
     Ok(Json(PrList { prs }))
 }
 
+#[derive(Serialize)]
+pub struct ModuleAssignments {
+    assignments: Vec<ModuleAssignment>,
+}
+
+#[derive(Serialize)]
+pub struct ModuleAssignment {
+    title: String,
+    sprint: usize,
+    optionality: AssignmentOptionality,
+    /// What the issue's `Submit:` label says to submit (e.g. "PR"), or "Repo" for project repos from the config.
+    submit_type: String,
+    /// None for project repos, which aren't issues.
+    issue_url: Option<String>,
+}
+
+/// The schedule of the course's first batch, for things which don't depend on the batch (e.g. how assignments are parsed),
+/// but which need a schedule to know e.g. how many sprints each module has.
+pub(crate) fn first_batch_course_schedule(
+    server_state: &ServerState,
+    course: &str,
+) -> Result<CourseScheduleWithRegisterSheetId, Error> {
+    server_state
+        .config
+        .courses
+        .get(course)
+        .and_then(|course_info| course_info.batches.get_index(0))
+        .and_then(|(batch, _)| {
+            server_state
+                .config
+                .get_course_schedule_with_register_sheet_id(course.to_owned(), batch)
+        })
+        .ok_or_else(|| Error::UserFacing(format!("Unknown course {course}")))
+}
+
+/// A module's assignments as the tracker parses them from its issues' labels, so that curriculum tools don't need to parse labels themselves.
+pub async fn module_assignments(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, module)): Path<(String, String)>,
+) -> Result<Json<ModuleAssignments>, Error> {
+    let course_schedule = first_batch_course_schedule(&server_state, &course)?;
+    let sprint_count = course_schedule
+        .course_schedule
+        .sprints
+        .get(&module)
+        .map(|sprints| sprints.len())
+        .ok_or_else(|| Error::UserFacing(format!("Unknown module {module} in course {course}")))?;
+
+    let octocrab = octocrab_or_shared(&session, &server_state, original_uri).await?;
+    let (sprints, _unhandled_submit_labels) =
+        CourseScheduleWithRegisterSheetId::fetch_module_assignments(
+            &octocrab,
+            &server_state.in_flight,
+            &server_state.config.github_org,
+            &module,
+            course_schedule.module_repos(&module),
+            sprint_count,
+            &course_schedule.submit_labels,
+            course_schedule
+                .module_settings
+                .get(&module)
+                .map(|settings| settings.sprint_source)
+                .unwrap_or_default(),
+        )
+        .await?;
+
+    let project_repos = course_schedule
+        .course_schedule
+        .project_repos
+        .iter()
+        .filter(|project_repo| project_repo.module == module)
+        .map(|project_repo| (project_repo.sprint.get(), project_repo.assignment()));
+    let assignments = sprints
+        .into_iter()
+        .enumerate()
+        .flat_map(|(sprint_index, assignments)| {
+            assignments
+                .into_iter()
+                .map(move |assignment| (sprint_index + 1, assignment))
+        })
+        .chain(project_repos)
+        .filter_map(|(sprint, assignment)| match assignment {
+            Assignment::Attendance { .. } => None,
+            Assignment::ExpectedPullRequest {
+                title,
+                html_url,
+                optionality,
+                submit_type,
+                ..
+            } => Some(ModuleAssignment {
+                title,
+                sprint,
+                optionality,
+                submit_type,
+                issue_url: Some(html_url.to_string()),
+            }),
+            Assignment::ProjectRepo {
+                title, optionality, ..
+            } => Some(ModuleAssignment {
+                title,
+                sprint,
+                optionality,
+                submit_type: "Repo".to_owned(),
+                issue_url: None,
+            }),
+        })
+        .collect();
+    Ok(Json(ModuleAssignments { assignments }))
+}
+
+pub async fn mentors(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Json<MentorRoster>, Error> {
+    let sheets_client = sheets_client(
+        &session,
+        server_state.clone(),
+        headers,
+        original_uri.clone(),
+    )
+    .await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let roster = get_mentor_roster(octocrab, sheets_client, &server_state.config, &course).await?;
+    Ok(Json(roster))
+}
+
+#[derive(Serialize)]
+pub struct ReviewerList {
+    reviewers: BTreeSet<ReviewerInfo>,
+    /// Trainees who have reviewed other trainees' PRs.
+    peer_reviewers: BTreeSet<ReviewerInfo>,
+}
+
+/// The reviewers page's data, for spreadsheet automations.
+/// Like the page, `staff_only_details` is only filled in for users who can read the reviewer staff info sheet.
+pub async fn reviewers(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Json<ReviewerList>, Error> {
+    let CourseReviewers {
+        reviewers,
+        peer_reviewers,
+        ..
+    } = get_course_reviewers(&session, headers, &server_state, original_uri, &course).await?;
+    Ok(Json(ReviewerList {
+        reviewers,
+        peer_reviewers,
+    }))
+}
+
+pub async fn help_requests(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Json<Vec<HelpRequest>>, Error> {
+    let module_names = server_state
+        .config
+        .get_course_module_names(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let help_requests = get_unanswered_help_requests(
+        &octocrab,
+        &server_state.config.github_org,
+        &module_names,
+        server_state.config.help_request_age(),
+    )
+    .await?;
+    Ok(Json(help_requests))
+}
+
 #[derive(Serialize)]
 pub struct Region {
     region: Option<crate::newtypes::Region>,
@@ -251,18 +439,21 @@ pub async fn fetch_attendance(
         for batch_name in course_info.batches.keys() {
             let course_schedule = server_state
                 .config
-                .get_course_schedule_with_register_sheet_id(course_name.clone(), batch_name)
+                .get_course_schedule_with_register_sheet_id(course_name.to_string(), batch_name)
                 .ok_or_else(|| Error::Fatal(anyhow::anyhow!("Course not found: {course_name}")))?;
             let register_future = get_register(
                 sheets_client.clone(),
-                course_schedule.register_sheet_id.clone(),
+                course_schedule
+                    .register_sheet_id
+                    .clone()
+                    .context("Course has no register")?,
                 course_schedule.course_schedule.start,
                 course_schedule.course_schedule.end,
             );
             register_futures.push(async move {
                 (
-                    course_name.clone(),
-                    batch_name.clone(),
+                    course_name.to_string(),
+                    batch_name.to_string(),
                     register_future.await,
                 )
             });
@@ -277,7 +468,11 @@ pub async fn fetch_attendance(
         for (module_name, sprint_info) in register.modules {
             for (sprint_number, attendance_info) in sprint_info.attendance.iter().enumerate() {
                 let sprint_name = format!("Sprint-{}", sprint_number + 1);
-                for attendance in attendance_info.values() {
+                // Only each trainee's first entry is listed, as class times (needed to pick between duplicates) aren't known here.
+                for attendance in attendance_info
+                    .values()
+                    .filter_map(|entries| entries.first())
+                {
                     registered_attendance.push(AttendanceResponse {
                         attendance: attendance.clone(),
                         sprint: sprint_name.clone(),
@@ -309,9 +504,19 @@ pub async fn expected_attendance(
         for (cohort, schedule) in course_info.batches {
             let mut region_to_expected_classes: BTreeMap<crate::newtypes::Region, usize> =
                 BTreeMap::new();
-            for (_module_name, sprints) in schedule.sprints {
-                for sprint in sprints {
-                    for (region, date) in sprint {
+            for session_dates in &schedule.onboarding_sessions {
+                for (region, date) in session_dates {
+                    if region.class_start_time(date) < now {
+                        region_to_expected_classes
+                            .entry(region.clone())
+                            .or_default()
+                            .add_assign(1);
+                    }
+                }
+            }
+            for (module_name, sprints) in &schedule.sprints {
+                for sprint_index in 0..sprints.len() {
+                    for (region, date) in schedule.class_dates(module_name, sprint_index) {
                         let start_time = region.class_start_time(&date);
                         if start_time < now {
                             region_to_expected_classes
@@ -324,8 +529,8 @@ pub async fn expected_attendance(
             }
             for (region, expected_classes) in region_to_expected_classes {
                 expected_attendance.push(ExpectedAttendance {
-                    course: course.clone(),
-                    cohort: cohort.clone(),
+                    course: course.to_string(),
+                    cohort: cohort.to_string(),
                     region,
                     expected_classes,
                 })
@@ -361,3 +566,214 @@ pub async fn started_itp(
         .collect();
     Ok(Json(usernames))
 }
+
+#[derive(Deserialize)]
+pub struct FlatReportParams {
+    /// Only include this course. All courses are included by default.
+    course: Option<String>,
+    /// Only include this batch (by GitHub team slug).
+    batch: Option<String>,
+    #[serde(default)]
+    format: FlatReportFormat,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlatReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// One trainee's state for one assignment.
+/// Deliberately flat (no nesting, every row has every field) so that BI tools like Looker Studio can consume it directly.
+#[derive(Serialize)]
+pub struct FlatSubmission {
+    course: String,
+    batch: String,
+    github_login: GithubLogin,
+    trainee_name: String,
+    region: crate::newtypes::Region,
+    module: String,
+    sprint: usize,
+    assignment: String,
+    assignment_url: Option<String>,
+    optionality: AssignmentOptionality,
+    state: &'static str,
+    submission_url: Option<String>,
+    /// The class date for the trainee's region.
+    sprint_date: Option<NaiveDate>,
+    submitted_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+pub async fn flat_submissions(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Query(params): Query<FlatReportParams>,
+) -> Result<Response, Error> {
+    let mut rows = Vec::new();
+    for (course_name, course_info) in &server_state.config.courses {
+        if params
+            .course
+            .as_ref()
+            .is_some_and(|course| course_name != course)
+        {
+            continue;
+        }
+        for batch_github_slug in course_info.batches.keys() {
+            if params
+                .batch
+                .as_ref()
+                .is_some_and(|batch| batch_github_slug != batch)
+            {
+                continue;
+            }
+            let (course, batch, _sheets_client) = get_course_and_batch(
+                &session,
+                headers.clone(),
+                &server_state,
+                original_uri.clone(),
+                course_name.to_string(),
+                batch_github_slug,
+            )
+            .await?;
+            rows.extend(flatten_submissions(&course, &batch));
+        }
+    }
+    Ok(match params.format {
+        FlatReportFormat::Json => Json(rows).into_response(),
+        FlatReportFormat::Csv => Csv(flat_submissions_csv(&rows)).into_response(),
+    })
+}
+
+pub fn flatten_submissions(course: &Course, batch: &BatchWithSubmissions) -> Vec<FlatSubmission> {
+    let mut rows = Vec::new();
+    for trainee in &batch.trainees {
+        for (module_name, module) in &course.modules {
+            let Some(module_with_submissions) = trainee.modules.get(module_name) else {
+                continue;
+            };
+            for (sprint_index, (sprint, sprint_with_submissions)) in module
+                .sprints
+                .iter()
+                .zip(&module_with_submissions.sprints)
+                .enumerate()
+            {
+                for (assignment, submission) in sprint
+                    .assignments
+                    .iter()
+                    .zip(&sprint_with_submissions.submissions)
+                {
+                    let (assignment_name, assignment_url) = match assignment {
+                        Assignment::Attendance { .. } => ("Attendance".to_owned(), None),
+                        Assignment::ExpectedPullRequest {
+                            title, html_url, ..
+                        } => (title.clone(), Some(html_url.to_string())),
+                        Assignment::ProjectRepo { title, .. } => (title.clone(), None),
+                    };
+                    let pull_request = match submission {
+                        SubmissionState::Some(Submission::PullRequest { pull_request, .. }) => {
+                            Some(pull_request)
+                        }
+                        _ => None,
+                    };
+                    rows.push(FlatSubmission {
+                        course: course.name.clone(),
+                        batch: batch.github_team_slug.clone(),
+                        github_login: trainee.trainee.github_login.clone(),
+                        trainee_name: trainee.trainee.name.clone(),
+                        region: trainee.trainee.region.clone(),
+                        module: module_name.clone(),
+                        sprint: sprint_index + 1,
+                        assignment: assignment_name,
+                        assignment_url,
+                        optionality: assignment.optionality(),
+                        state: flat_state(submission),
+                        submission_url: match submission {
+                            SubmissionState::Some(submission) => Some(submission.link()),
+                            _ => None,
+                        },
+                        sprint_date: sprint.dates.get(&trainee.trainee.region).copied(),
+                        submitted_at: pull_request.map(|pr| pr.created_at),
+                        updated_at: pull_request.map(|pr| pr.updated_at),
+                    });
+                }
+            }
+        }
+    }
+    rows
+}
+
+fn flat_state(submission: &SubmissionState) -> &'static str {
+    match submission {
+        SubmissionState::Some(Submission::Attendance(attendance)) => match attendance {
+            crate::course::Attendance::Absent { .. } => "absent",
+            crate::course::Attendance::OnTime { .. } => "on_time",
+            crate::course::Attendance::Late { .. } => "late",
+            crate::course::Attendance::VeryLate { .. } => "very_late",
+            crate::course::Attendance::WrongDay { .. } => "wrong_day",
+        },
+        SubmissionState::Some(Submission::PullRequest { pull_request, .. }) => {
+            match pull_request.state {
+                PrState::NeedsReview => "needs_review",
+                PrState::Reviewed => "reviewed",
+                PrState::Complete => "complete",
+                PrState::Unknown => "unknown",
+            }
+        }
+        SubmissionState::Some(Submission::ProjectRepo(project_repo)) => {
+            if project_repo.is_complete() {
+                "repo_complete"
+            } else {
+                "repo_incomplete"
+            }
+        }
+        SubmissionState::MissingButExpected(_) => "missing",
+        SubmissionState::MissingStretch(_) => "missing_stretch",
+        SubmissionState::MissingButNotExpected(_) => "not_due",
+    }
+}
+
+pub fn flat_submissions_csv(rows: &[FlatSubmission]) -> String {
+    let mut out = String::from(
+        "course,batch,github_login,trainee_name,region,module,sprint,assignment,assignment_url,optionality,state,submission_url,sprint_date,submitted_at,updated_at\n",
+    );
+    for row in rows {
+        let fields = [
+            row.course.clone(),
+            row.batch.clone(),
+            row.github_login.to_string(),
+            row.trainee_name.clone(),
+            row.region.to_string(),
+            row.module.clone(),
+            row.sprint.to_string(),
+            row.assignment.clone(),
+            row.assignment_url.clone().unwrap_or_default(),
+            format!("{:?}", row.optionality),
+            row.state.to_owned(),
+            row.submission_url.clone().unwrap_or_default(),
+            row.sprint_date
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            row.submitted_at
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+            row.updated_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+        ];
+        out += &fields.map(|field| csv_escape(&field)).join(",");
+        out += "\n";
+    }
+    out
+}
+
+// Trainee names and assignment titles may contain commas or quotes.
+pub(crate) fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
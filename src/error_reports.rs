@@ -0,0 +1,275 @@
+//! Sending fatal errors and panics to a Sentry-compatible error-reporting service, so they're noticed without someone reading logs.
+//!
+//! Nothing is sent unless a DSN (the URL Sentry gives each project, with its key as the username) is configured,
+//! with `error_report_dsn` in the config, or `ERROR_REPORT_DSN` for binaries which don't read a config file.
+
+use std::{backtrace::Backtrace, sync::OnceLock, time::Duration};
+
+use anyhow::Context;
+use axum::{extract::Request, middleware::Next, response::Response};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use tracing::{error, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::Error;
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+static REPORTER: OnceLock<ErrorReporter> = OnceLock::new();
+
+struct ErrorReporter {
+    /// Which binary is reporting, e.g. `trainee-tracker` or `pr-metadata-validator`.
+    source: &'static str,
+    store_url: Url,
+    public_key: String,
+    client: reqwest::Client,
+}
+
+/// Where an error happened, beyond its message.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorContext {
+    /// For errors serving a request.
+    pub method: Option<String>,
+    /// For errors serving a request. Without its query string, which may hold secrets like OAuth codes.
+    pub url: Option<String>,
+    /// For errors in CLI binaries, what they were run with.
+    pub args: Vec<String>,
+}
+
+impl ErrorContext {
+    pub fn from_args() -> Self {
+        ErrorContext {
+            args: std::env::args().collect(),
+            ..ErrorContext::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ErrorReport {
+    /// "Fatal" or "Panic".
+    pub kind: &'static str,
+    /// What went wrong, on one line.
+    pub summary: String,
+    /// Everything known about what went wrong, e.g. the whole chain of causes, and a backtrace if there is one.
+    pub details: String,
+    pub context: ErrorContext,
+}
+
+impl ErrorReport {
+    pub fn fatal(err: &anyhow::Error, context: ErrorContext) -> Self {
+        ErrorReport {
+            kind: "Fatal",
+            summary: format!("{:#}", err),
+            details: format!("{:?}", err),
+            context,
+        }
+    }
+
+    /// The event to send, in the shape Sentry's store endpoint takes.
+    fn to_event(&self, source: &str, event_id: Uuid, timestamp: DateTime<Utc>) -> Value {
+        let mut event = json!({
+            "event_id": event_id.simple().to_string(),
+            "timestamp": timestamp.to_rfc3339(),
+            "platform": "other",
+            "level": if self.kind == "Panic" { "fatal" } else { "error" },
+            "logger": source,
+            "release": concat!("trainee-tracker@", env!("CARGO_PKG_VERSION")),
+            "tags": { "source": source },
+            "exception": { "values": [{ "type": self.kind, "value": self.summary }] },
+            "extra": { "details": self.details },
+        });
+        if self.context.method.is_some() || self.context.url.is_some() {
+            event["request"] = json!({
+                "method": self.context.method,
+                "url": self.context.url,
+            });
+        }
+        if !self.context.args.is_empty() {
+            event["extra"]["args"] = json!(self.context.args);
+        }
+        event
+    }
+}
+
+impl ErrorReporter {
+    fn from_dsn(source: &'static str, dsn: &str) -> Result<Self, Error> {
+        let dsn = Url::parse(dsn).context("Error report DSN isn't a URL")?;
+        let public_key = dsn.username().to_owned();
+        let (prefix, project_id) = dsn
+            .path()
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .context("Error report DSN doesn't end with a project ID")?;
+        if public_key.is_empty() || project_id.is_empty() {
+            return Err(Error::Fatal(anyhow::anyhow!(
+                "Error report DSN should look like https://key@host/project-id"
+            )));
+        }
+        let mut store_url = dsn.clone();
+        store_url
+            .set_username("")
+            .and_then(|()| store_url.set_password(None))
+            .map_err(|()| anyhow::anyhow!("Error report DSN can't have credentials removed"))?;
+        store_url.set_path(&format!("{}/api/{}/store/", prefix, project_id));
+        Ok(ErrorReporter {
+            source,
+            store_url,
+            public_key,
+            client: new_client(),
+        })
+    }
+
+    async fn send(&self, client: &reqwest::Client, report: &ErrorReport) -> Result<(), Error> {
+        let auth = format!(
+            "Sentry sentry_version=7, sentry_client=trainee-tracker/{}, sentry_key={}",
+            env!("CARGO_PKG_VERSION"),
+            self.public_key
+        );
+        client
+            .post(self.store_url.clone())
+            .header("X-Sentry-Auth", auth)
+            .json(&report.to_event(self.source, Uuid::new_v4(), Utc::now()))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context("Failed to send error report")?;
+        Ok(())
+    }
+}
+
+/// Starts reporting errors from this binary, including panics, if there's a DSN to send them to.
+/// `source` names the binary in reports - use `env!("CARGO_BIN_NAME")`.
+pub fn init_error_reports(source: &'static str, dsn: Option<&String>) {
+    let Some(dsn) = dsn.filter(|dsn| !dsn.is_empty()) else {
+        return;
+    };
+    let reporter = match ErrorReporter::from_dsn(source, dsn) {
+        Ok(reporter) => reporter,
+        Err(err) => {
+            warn!("Not reporting errors: {}", err);
+            return;
+        }
+    };
+    if REPORTER.set(reporter).is_err() {
+        return;
+    }
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let summary = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Panicked".to_owned());
+        let location = info.location().map(ToString::to_string).unwrap_or_default();
+        let report = ErrorReport {
+            kind: "Panic",
+            summary,
+            details: format!("At {}\n{}", location, Backtrace::force_capture()),
+            context: ErrorContext::from_args(),
+        };
+        // The panicking thread may be running an async runtime, which can't block on another, so send from a thread of our own.
+        let _ = std::thread::spawn(move || {
+            let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            else {
+                return;
+            };
+            // Clients can't be shared between runtimes, as their pooled connections belong to the runtime which made them.
+            if let Some(reporter) = REPORTER.get()
+                && let Err(err) = runtime.block_on(reporter.send(&new_client(), &report))
+            {
+                error!("{:?}", err);
+            }
+        })
+        .join();
+    }));
+}
+
+/// Sends the report, if error reports are set up. Failing to send is only logged, as there's nowhere else to tell.
+pub async fn send_error_report(report: ErrorReport) {
+    let Some(reporter) = REPORTER.get() else {
+        return;
+    };
+    if let Err(err) = reporter.send(&reporter.client, &report).await {
+        error!("{:?}", err);
+    }
+}
+
+fn new_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(SEND_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Middleware which reports any fatal error a response was made from (see `impl IntoResponse for Error`), with the request it was for.
+pub async fn report_fatal_errors(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_owned();
+    let response = next.run(request).await;
+    if let Some(report) = response.extensions().get::<ErrorReport>() {
+        let mut report = report.clone();
+        report.context.method = Some(method);
+        report.context.url = Some(path);
+        tokio::spawn(send_error_report(report));
+    }
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{ErrorContext, ErrorReport, ErrorReporter};
+
+    #[test]
+    fn store_url_from_dsn() {
+        let reporter =
+            ErrorReporter::from_dsn("test", "https://abc123@errors.example.com/sentry/42").unwrap();
+        assert_eq!(
+            reporter.store_url.as_str(),
+            "https://errors.example.com/sentry/api/42/store/"
+        );
+        assert_eq!(reporter.public_key, "abc123");
+
+        assert!(ErrorReporter::from_dsn("test", "https://errors.example.com/42").is_err());
+    }
+
+    #[test]
+    fn event_includes_request() {
+        let report = ErrorReport {
+            kind: "Fatal",
+            summary: "Failed to get PRs: rate limited".to_owned(),
+            details: "Failed to get PRs\n\nCaused by:\n    rate limited".to_owned(),
+            context: ErrorContext {
+                method: Some("GET".to_owned()),
+                url: Some("/courses/itp".to_owned()),
+                args: vec![],
+            },
+        };
+        let event = report.to_event(
+            "trainee-tracker",
+            Uuid::nil(),
+            Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap(),
+        );
+        assert_eq!(event["event_id"], json!("00000000000000000000000000000000"));
+        assert_eq!(event["level"], json!("error"));
+        assert_eq!(
+            event["request"],
+            json!({ "method": "GET", "url": "/courses/itp" })
+        );
+        assert_eq!(
+            event["exception"]["values"][0]["value"],
+            json!("Failed to get PRs: rate limited")
+        );
+        assert!(event["extra"].get("args").is_none());
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::{
+    Error,
+    absence_streaks::AbsenceStreakPolicy,
+    config::EventWebhook,
+    course::{Batch, Course, TraineeStatus},
+    newtypes::GithubLogin,
+    persistence::RecordStore,
+};
+
+const PUBLISHED_EVENTS_TAB: &str = "Published events";
+const PUBLISHED_EVENTS_HEADINGS: [&str; 5] = ["Recorded", "Course", "Batch", "Key", "Value"];
+
+// Mirrors GitHub's webhook headers, so receivers can reuse the same verification code.
+const SIGNATURE_HEADER: &str = "x-trainee-tracker-signature-256";
+const EVENT_HEADER: &str = "x-trainee-tracker-event";
+
+/// Something which happened in the tracker that other systems may want to react to.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TrackerEvent {
+    TraineeStatusChanged {
+        course: String,
+        batch: String,
+        github_login: GithubLogin,
+        previous_status: String,
+        status: String,
+    },
+    /// A trainee opened a PR which couldn't be matched to any assignment.
+    NewUnknownPr {
+        course: String,
+        batch: String,
+        github_login: GithubLogin,
+        pr_url: String,
+        title: String,
+    },
+    /// A trainee has missed at least the configured number of their region's classes in a row.
+    AbsenceStreak {
+        course: String,
+        batch: String,
+        github_login: GithubLogin,
+        consecutive_absences: usize,
+    },
+    /// A sprint's last class (across all regions) has happened.
+    SprintCompleted {
+        course: String,
+        batch: String,
+        module: String,
+        sprint: usize,
+    },
+}
+
+impl TrackerEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TraineeStatusChanged { .. } => "trainee_status_changed",
+            Self::NewUnknownPr { .. } => "new_unknown_pr",
+            Self::AbsenceStreak { .. } => "absence_streak",
+            Self::SprintCompleted { .. } => "sprint_completed",
+        }
+    }
+}
+
+/// A change since the last time events were published for a batch.
+#[derive(Clone, Debug)]
+pub struct PendingEvent {
+    /// Identifies what changed, e.g. `status:<login>`, so we only publish each change once.
+    pub key: String,
+    pub value: String,
+    /// None if the change is only being recorded as a baseline, rather than announced.
+    pub event: Option<TrackerEvent>,
+}
+
+fn status_key(status: TraineeStatus) -> &'static str {
+    match status {
+        TraineeStatus::OnTrack => "on_track",
+        TraineeStatus::Behind => "behind",
+        TraineeStatus::AtRisk => "at_risk",
+    }
+}
+
+/// Works out what has changed in a batch since `published` (the latest value recorded for each key).
+///
+/// The first time a batch is seen, everything is recorded as a baseline without being announced,
+/// so that turning webhooks on doesn't send an event for every trainee and past sprint.
+pub fn pending_events(
+    course: &Course,
+    batch: &Batch,
+    published: &BTreeMap<String, String>,
+    absence_streak: Option<&AbsenceStreakPolicy>,
+    today: NaiveDate,
+) -> Vec<PendingEvent> {
+    // Each change as (key, value, event), where the event is None if it isn't worth announcing.
+    let mut changes = Vec::new();
+
+    for trainee in &batch.trainees {
+        let github_login = &trainee.trainee.github_login;
+        let status = status_key(trainee.status());
+        let key = format!("status:{}", github_login);
+        // A trainee we haven't seen before (e.g. who just joined) has no status to have changed from.
+        let event = published
+            .get(&key)
+            .map(|previous_status| TrackerEvent::TraineeStatusChanged {
+                course: course.name.clone(),
+                batch: batch.github_team_slug.clone(),
+                github_login: github_login.clone(),
+                previous_status: previous_status.clone(),
+                status: status.to_owned(),
+            });
+        changes.push((key, status.to_owned(), event));
+
+        if let Some(policy) = absence_streak {
+            // Only announced when a streak starts; it ending is recorded so the next one is announced too.
+            let streak = policy.streak(course, trainee);
+            changes.push((
+                format!("absence-streak:{}", github_login),
+                streak.is_some().to_string(),
+                streak.map(|consecutive_absences| TrackerEvent::AbsenceStreak {
+                    course: course.name.clone(),
+                    batch: batch.github_team_slug.clone(),
+                    github_login: github_login.clone(),
+                    consecutive_absences,
+                }),
+            ));
+        }
+    }
+
+    for pr in batch.unknown_prs() {
+        changes.push((
+            format!("unknown-pr:{}", pr.url),
+            String::new(),
+            Some(TrackerEvent::NewUnknownPr {
+                course: course.name.clone(),
+                batch: batch.github_team_slug.clone(),
+                github_login: pr.author.clone(),
+                pr_url: pr.url.clone(),
+                title: pr.title.clone(),
+            }),
+        ));
+    }
+
+    for (module_name, module) in &course.modules {
+        for (sprint_index, sprint) in module.sprints.iter().enumerate() {
+            if sprint
+                .dates
+                .values()
+                .max()
+                .is_none_or(|last_class| *last_class >= today)
+            {
+                continue;
+            }
+            changes.push((
+                format!("sprint-completed:{}:{}", module_name, sprint_index + 1),
+                String::new(),
+                Some(TrackerEvent::SprintCompleted {
+                    course: course.name.clone(),
+                    batch: batch.github_team_slug.clone(),
+                    module: module_name.clone(),
+                    sprint: sprint_index + 1,
+                }),
+            ));
+        }
+    }
+
+    let is_baseline = published.is_empty();
+    changes
+        .into_iter()
+        .filter(|(key, value, _)| published.get(key) != Some(value))
+        .map(|(key, value, event)| PendingEvent {
+            key,
+            value,
+            event: event.filter(|_| !is_baseline),
+        })
+        .collect()
+}
+
+/// The latest value recorded for each key for a batch.
+pub async fn published_events(
+    store: &RecordStore,
+    course_name: &str,
+    batch_github_slug: &str,
+) -> Result<BTreeMap<String, String>, Error> {
+    Ok(store
+        .read(PUBLISHED_EVENTS_TAB)
+        .await?
+        .into_iter()
+        .filter_map(|row| match row.as_slice() {
+            [_recorded, course, batch, key, value]
+                if course == course_name && batch == batch_github_slug =>
+            {
+                Some((key.clone(), value.clone()))
+            }
+            // Sheets drops trailing empty cells.
+            [_recorded, course, batch, key]
+                if course == course_name && batch == batch_github_slug =>
+            {
+                Some((key.clone(), String::new()))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+pub async fn record_published_event(
+    store: &RecordStore,
+    course_name: &str,
+    batch_github_slug: &str,
+    pending: &PendingEvent,
+    recorded_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    store
+        .append(
+            PUBLISHED_EVENTS_TAB,
+            &PUBLISHED_EVENTS_HEADINGS,
+            vec![
+                recorded_at.to_rfc3339(),
+                course_name.to_owned(),
+                batch_github_slug.to_owned(),
+                pending.key.clone(),
+                pending.value.clone(),
+            ],
+        )
+        .await
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    sent_at: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a TrackerEvent,
+}
+
+/// POSTs the event as JSON, signed with the webhook's secret in the same way GitHub signs its webhooks.
+pub async fn publish_event(
+    client: &reqwest::Client,
+    webhook: &EventWebhook,
+    event: &TrackerEvent,
+) -> Result<(), Error> {
+    let body = serde_json::to_vec(&Envelope {
+        sent_at: Utc::now(),
+        event,
+    })
+    .context("Failed to serialize event")?;
+    client
+        .post(webhook.url.as_str())
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event.name())
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, &body))
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to publish {} event", event.name()))?;
+    Ok(())
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::sign;
+
+    // Same example as GitHub's, since we sign the same way: https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("It's a Secret to Everybody", "Hello, World!".as_bytes()),
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Context;
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::{DateTime, Utc};
+use octocrab::{Octocrab, models::commits::GithubCommitStatus};
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState, frontend::record_store, newtypes::GithubLogin, octocrab::octocrab,
+    persistence::RecordStore, pr_comments::PullRequest,
+};
+
+const FORCE_PUSHES_TAB: &str = "Force pushes";
+const FORCE_PUSHES_HEADINGS: [&str; 6] = ["Recorded", "Repo", "PR", "Author", "Before", "After"];
+
+/// A PR whose history was rewritten (e.g. rebased and force-pushed) after it had been reviewed.
+/// Review comments on the old commits may no longer show up against the code, so a mentor should re-check it.
+#[derive(Clone, Debug)]
+pub struct ForcePush {
+    pub recorded_at: DateTime<Utc>,
+    pub repo: String,
+    pub number: u64,
+    pub author: GithubLogin,
+    pub before: String,
+    pub after: String,
+}
+
+impl ForcePush {
+    pub fn pr_url(&self, github_org: &str) -> String {
+        PullRequest {
+            org: github_org.to_owned(),
+            repo: self.repo.clone(),
+            number: self.number,
+        }
+        .html_url()
+    }
+
+    pub fn short_before(&self) -> &str {
+        &self.before[..self.before.len().min(7)]
+    }
+}
+
+pub async fn record_force_push(store: &RecordStore, force_push: &ForcePush) -> Result<(), Error> {
+    store
+        .append(
+            FORCE_PUSHES_TAB,
+            &FORCE_PUSHES_HEADINGS,
+            vec![
+                force_push.recorded_at.to_rfc3339(),
+                force_push.repo.clone(),
+                force_push.number.to_string(),
+                force_push.author.to_string(),
+                force_push.before.clone(),
+                force_push.after.clone(),
+            ],
+        )
+        .await
+}
+
+/// Recorded force pushes, newest first.
+pub async fn get_force_pushes(store: &RecordStore) -> Result<Vec<ForcePush>, Error> {
+    let mut force_pushes = store
+        .read(FORCE_PUSHES_TAB)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let [recorded_at, repo, number, author, before, after] = row.as_slice() else {
+                return Err(anyhow::anyhow!("Expected 6 columns, got {}", row.len()));
+            };
+            Ok(ForcePush {
+                recorded_at: DateTime::parse_from_rfc3339(recorded_at)
+                    .context("Failed to parse recorded time")?
+                    .to_utc(),
+                repo: repo.clone(),
+                number: number.parse().context("Failed to parse PR number")?,
+                author: GithubLogin::from(author.clone()),
+                before: before.clone(),
+                after: after.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .context("Failed to read force pushes")?;
+    force_pushes.reverse();
+    Ok(force_pushes)
+}
+
+/// Whether going from `before` to `after` rewrote history, rather than just adding commits.
+pub(crate) async fn is_history_rewrite(
+    octocrab: &Octocrab,
+    pull_request: &PullRequest,
+    before: &str,
+    after: &str,
+) -> Result<bool, Error> {
+    let comparison = octocrab
+        .commits(&pull_request.org, &pull_request.repo)
+        .compare(before, after)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to compare {}...{} on PR {}",
+                before,
+                after,
+                pull_request.html_url()
+            )
+        })?;
+    Ok(!matches!(
+        comparison.status,
+        GithubCommitStatus::Ahead | GithubCommitStatus::Identical
+    ))
+}
+
+pub(crate) async fn has_reviews(
+    octocrab: &Octocrab,
+    pull_request: &PullRequest,
+) -> Result<bool, Error> {
+    let reviews = octocrab
+        .pulls(&pull_request.org, &pull_request.repo)
+        .list_reviews(pull_request.number)
+        .per_page(1)
+        .send()
+        .await
+        .with_context(|| format!("Failed to get reviews for PR {}", pull_request.html_url()))?;
+    Ok(!reviews.items.is_empty())
+}
+
+/// Force pushes recorded on the course's PRs, newest first, for mentors to re-check.
+pub async fn get_course_force_pushes(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Html<String>, Error> {
+    // Like the course's other pages, this is only for people signed in to GitHub.
+    octocrab(&session, &server_state, original_uri).await?;
+    let module_names = server_state
+        .config
+        .get_course_module_names(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let store = record_store(&server_state).await?;
+    let force_pushes = get_force_pushes(&store)
+        .await?
+        .into_iter()
+        .filter(|force_push| module_names.contains(&force_push.repo))
+        .collect();
+    Ok(Html(
+        ForcePushesTemplate {
+            course,
+            github_org: server_state.config.github_org.clone(),
+            force_pushes,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "force-pushes.html")]
+struct ForcePushesTemplate {
+    course: String,
+    github_org: String,
+    force_pushes: Vec<ForcePush>,
+}
@@ -0,0 +1,122 @@
+use crate::course::{
+    Assignment, AssignmentOptionality, ModuleWithSubmissions, Submission, SubmissionState,
+    TraineeWithSubmissions,
+};
+
+/// A projection of whether a trainee will have submitted all of a module's mandatory PRs by its last sprint,
+/// if they carry on submitting at the rate they have so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleForecast {
+    pub module_name: String,
+    /// Mandatory PRs submitted so far.
+    pub submitted: usize,
+    /// Mandatory PRs still to submit, whether or not they're due yet.
+    pub remaining: usize,
+    pub elapsed_sprints: usize,
+    pub remaining_sprints: usize,
+}
+
+impl ModuleForecast {
+    /// How many more PRs the trainee will submit before the module ends at their current pace.
+    pub fn projected_submissions(&self) -> usize {
+        self.submitted * self.remaining_sprints / self.elapsed_sprints
+    }
+
+    pub fn is_on_pace(&self) -> bool {
+        // Cross-multiplied to compare paces without rounding.
+        self.submitted * self.remaining_sprints >= self.remaining * self.elapsed_sprints
+    }
+}
+
+/// Forecasts for the trainee's modules which are underway, i.e. have had at least one sprint and have at least one to go.
+///
+/// Modules which have ended are left out, as their outstanding work is already in the catch-up plan.
+pub fn module_forecasts(trainee: &TraineeWithSubmissions) -> Vec<ModuleForecast> {
+    trainee
+        .modules
+        .iter()
+        .filter_map(|(module_name, module)| module_forecast(module_name, module))
+        .collect()
+}
+
+fn module_forecast(module_name: &str, module: &ModuleWithSubmissions) -> Option<ModuleForecast> {
+    let elapsed_sprints = module
+        .sprints
+        .iter()
+        .filter(|sprint| {
+            sprint.submissions.iter().any(|submission| {
+                matches!(
+                    submission,
+                    SubmissionState::MissingButExpected(_)
+                        | SubmissionState::MissingStretch(_)
+                        | SubmissionState::Some(Submission::Attendance(_))
+                )
+            })
+        })
+        .count();
+    let remaining_sprints = module.sprints.len() - elapsed_sprints;
+    if elapsed_sprints == 0 || remaining_sprints == 0 {
+        return None;
+    }
+
+    let mut submitted = 0;
+    let mut remaining = 0;
+    for submission in module
+        .sprints
+        .iter()
+        .flat_map(|sprint| sprint.submissions.iter())
+    {
+        match submission {
+            SubmissionState::Some(Submission::PullRequest {
+                optionality: AssignmentOptionality::Mandatory,
+                ..
+            }) => submitted += 1,
+            SubmissionState::MissingButExpected(Assignment::ExpectedPullRequest { .. }) => {
+                remaining += 1
+            }
+            SubmissionState::MissingButNotExpected(Assignment::ExpectedPullRequest {
+                optionality: AssignmentOptionality::Mandatory,
+                ..
+            }) => remaining += 1,
+            _ => {}
+        }
+    }
+    if remaining == 0 {
+        return None;
+    }
+
+    Some(ModuleForecast {
+        module_name: module_name.to_owned(),
+        submitted,
+        remaining,
+        elapsed_sprints,
+        remaining_sprints,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::ModuleForecast;
+
+    fn forecast(submitted: usize, remaining: usize, elapsed_sprints: usize) -> ModuleForecast {
+        ModuleForecast {
+            module_name: "Module-Data-Groups".to_owned(),
+            submitted,
+            remaining,
+            elapsed_sprints,
+            remaining_sprints: 4 - elapsed_sprints,
+        }
+    }
+
+    #[test]
+    fn pace() {
+        // 3 PRs a sprint, with 6 to go in 2 sprints.
+        assert!(forecast(6, 6, 2).is_on_pace());
+        assert_eq!(forecast(6, 6, 2).projected_submissions(), 6);
+        // 1 PR a sprint, with 5 to go in 1 sprint.
+        assert!(!forecast(3, 5, 3).is_on_pace());
+        assert_eq!(forecast(3, 5, 3).projected_submissions(), 1);
+        // Nothing yet.
+        assert!(!forecast(0, 1, 1).is_on_pace());
+    }
+}
@@ -1,41 +1,138 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 
 use anyhow::Context;
 use askama::Template;
 use axum::{
+    Form,
     extract::{OriginalUri, Path, Query, State},
     response::{Html, IntoResponse, Response},
 };
 use chrono::TimeDelta;
 use futures::future::join_all;
 use http::{HeaderMap, StatusCode, Uri, header::CONTENT_TYPE};
+use octocrab::Octocrab;
 use serde::Deserialize;
 use tower_sessions::Session;
 
 use crate::{
     Error, ServerState,
+    absence_streaks::AbsenceStreakPolicy,
+    admissions::{MaybeEntryAssessment, get_entry_assessments_if_permitted},
+    api_v1::{TraineeProgressV1, load_trainee_progress},
+    attendance_policy::{AttendancePolicy, PolicyBreach},
+    burndown::{SprintBurndown, current_sprint},
     config::CourseScheduleWithRegisterSheetId,
     course::{
-        Attendance, Batch, BatchMetadata, Course, Submission, TraineeStatus, fetch_batch_metadata,
+        Assignment, Attendance, Batch, BatchMetadata, Course, Fraction, Submission,
+        SubmissionState, TraineeStatus, TraineeWithSubmissions, fetch_batch_metadata,
         get_batch_with_submissions,
     },
+    discussions::{HelpRequest, get_unanswered_help_requests},
+    endpoints::csv_escape,
+    forecast::{ModuleForecast, module_forecasts},
+    goals::{SprintGoals, find_current_sprint_for_trainee, get_sprint_goals, record_sprint_goals},
     google_groups::{GoogleGroup, get_groups, groups_client},
-    octocrab::octocrab,
+    groups::GroupProgress,
+    i18n::{Language, LanguageParams, language},
+    impersonation::{ViewAs, forbid_while_viewing_as, view_as},
+    lookup::pr_cell_id,
+    mentoring::{MentoringSession, get_mentoring_sessions},
+    mentors::{MentorRoster, get_mentor_roster},
+    newtypes::{DisplayTimezone, GithubLogin},
+    octocrab::{
+        SecondaryRateLimit, last_secondary_rate_limit, octocrab, octocrab_or_shared,
+        shared_token_uses,
+    },
+    pdf::{DEFAULT_WEASYPRINT_PATH, Pdf, render_pdf},
+    persistence::RecordStore,
     prs::{
-        AggregatePrMetrics, MaybeReviewerStaffOnlyDetails, PrMetrics, PrState, ReviewerInfo,
-        get_prs,
+        AggregatePrMetrics, BallInCourt, MaybeReviewerStaffOnlyDetails, Pr, PrMetrics, PrState,
+        ReviewerInfo, get_ball_in_court, get_prs,
     },
+    review_threads::{ReviewConversationSummary, get_review_conversation_summaries},
     reviewer_staff_info::get_reviewer_staff_info,
-    sheets::sheets_client,
-    slack::list_groups_with_members,
+    risk_rules::{AtRiskRule, triggered_rules},
+    sheets::{SheetsClient, sheets_client},
+    slack::{find_slack_profile_url, list_groups_with_members, slack_client_if_signed_in},
+    snapshots::read_sprint_burndown,
+    staff_notes::{StaffNote, add_staff_note, get_staff_notes},
+    status::{GIT_COMMIT, RefreshStatus, UpstreamHealth, VERSION, check_upstreams, record_refresh},
 };
 
+/// Deliberately doesn't require logging in, so that anyone can tell whether the tracker itself is having problems.
+pub async fn get_status(
+    State(server_state): State<ServerState>,
+    Query(params): Query<TimezoneParams>,
+) -> Result<Html<String>, Error> {
+    let mut courses = Vec::new();
+    for course in server_state.config.courses.keys() {
+        let refresh = server_state.course_refreshes.get(course.as_str()).await;
+        courses.push((course.to_string(), refresh));
+    }
+    let upstreams = check_upstreams(&server_state.http_clients.upstream_checks).await;
+    let trainee_directory_loaded_at = server_state
+        .trainee_directory
+        .loaded_at(&server_state.config.github_email_mapping_sheet_id)
+        .await;
+    let token_uses = shared_token_uses();
+    let shared_github_tokens = server_state
+        .config
+        .shared_github_tokens
+        .iter()
+        .map(|shared_token| {
+            (
+                shared_token.name.clone(),
+                token_uses
+                    .get(&shared_token.name)
+                    .copied()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect();
+    Ok(Html(
+        StatusTemplate {
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+            courses,
+            upstreams,
+            trainee_directory_loaded_at,
+            shared_github_tokens,
+            github_secondary_rate_limit: last_secondary_rate_limit(),
+            timezone: params.tz,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "status.html")]
+struct StatusTemplate {
+    version: &'static str,
+    git_commit: Option<&'static str>,
+    courses: Vec<(String, Option<RefreshStatus>)>,
+    upstreams: Vec<UpstreamHealth>,
+    /// None if nobody has needed the GitHub email mapping sheet since it last expired.
+    trainee_directory_loaded_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Name of each shared token, and how many logged-out page loads it has backed.
+    shared_github_tokens: Vec<(String, u64)>,
+    github_secondary_rate_limit: Option<SecondaryRateLimit>,
+    timezone: DisplayTimezone,
+}
+
+/// `?tz=Africa/Johannesburg` shows times in that timezone rather than UK time.
+#[derive(Deserialize)]
+pub struct TimezoneParams {
+    #[serde(default)]
+    tz: DisplayTimezone,
+}
+
 pub async fn list_courses(
     session: Session,
     State(server_state): State<ServerState>,
     OriginalUri(original_uri): OriginalUri,
 ) -> Result<Html<String>, Error> {
-    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let octocrab = octocrab_or_shared(&session, &server_state, original_uri).await?;
     let courses = &server_state.config.courses;
     let github_org = server_state.config.github_org.clone();
     let batch_metadata = join_all(
@@ -63,13 +160,17 @@ pub async fn list_courses(
                             |(_batch_name, course_schedule)| CourseScheduleWithBatchMetadata {
                                 course: CourseScheduleWithRegisterSheetId {
                                     course_schedule: course_schedule.clone(),
-                                    name: course_name.clone(),
-                                    register_sheet_id: course.register_sheet_id.clone(),
+                                    name: course_name.to_string(),
+                                    register_sheet_id: Some(course.register_sheet_id.clone()),
+                                    submit_labels: server_state.config.submit_labels.clone(),
+                                    module_settings: server_state.config.module_settings.clone(),
+                                    duplicate_register_entries: course.duplicate_register_entries,
+                                    lateness: course.lateness,
                                 },
                                 batch_metadata: batch_metadata
                                     .into_iter()
                                     .filter(|batch| {
-                                        course.batches.contains_key(&batch.github_team_slug)
+                                        course.batches.contains_key(batch.github_team_slug.as_str())
                                     })
                                     .collect(),
                             },
@@ -97,46 +198,660 @@ struct CourseScheduleWithBatchMetadata {
     pub batch_metadata: Vec<BatchMetadata>,
 }
 
+#[derive(Default, Deserialize)]
+pub struct TraineeBatchParams {
+    #[serde(default)]
+    palette: Palette,
+    #[serde(default)]
+    print: u8,
+    #[serde(default)]
+    tz: DisplayTimezone,
+    #[serde(default)]
+    photos: Option<u8>,
+    /// `?summary=1` collapses each module into one completion cell per trainee, for screens too narrow for every assignment.
+    #[serde(default)]
+    summary: u8,
+}
+
+/// `?print=1` shows a condensed black-and-white layout, for progression board packs which are printed or saved as PDFs.
+#[derive(Deserialize)]
+pub struct PrintParams {
+    #[serde(default)]
+    print: u8,
+    #[serde(default)]
+    photos: Option<u8>,
+}
+
+const SHOW_PHOTOS_SESSION_KEY: &str = "show_photos";
+
+/// Whether to show trainees' GitHub avatars, to help class-day staff put names to faces.
+/// They're off unless someone turns them on with `?photos=1` (which lasts for the rest of their session, until `?photos=0`),
+/// so photos aren't shown on a projected screen by surprise.
+async fn show_photos(session: &Session, requested: Option<u8>) -> Result<bool, Error> {
+    if let Some(requested) = requested {
+        session
+            .insert(SHOW_PHOTOS_SESSION_KEY, requested != 0)
+            .await
+            .context("Session insert error")?;
+        return Ok(requested != 0);
+    }
+    Ok(session
+        .get(SHOW_PHOTOS_SESSION_KEY)
+        .await
+        .context("Session load error")?
+        .unwrap_or(false))
+}
+
+/// Colours for the status of each cell.
+///
+/// Several volunteers can't tell the default red and green apart, so there's also a colour-blind-safe palette,
+/// which marks missing work with a pattern as well as a colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    #[default]
+    Default,
+    HighContrast,
+}
+
+impl Palette {
+    fn css_class(&self) -> &'static str {
+        match self {
+            Palette::Default => "palette-default",
+            Palette::HighContrast => "palette-high-contrast",
+        }
+    }
+}
+
 pub async fn get_trainee_batch(
     session: Session,
     headers: HeaderMap,
     State(server_state): State<ServerState>,
     OriginalUri(original_uri): OriginalUri,
     Path((course, batch_github_slug)): Path<(String, String)>,
+    Query(params): Query<TraineeBatchParams>,
 ) -> Result<Html<String>, Error> {
-    let sheets_client = sheets_client(
+    render_trainee_batch(
         &session,
-        server_state.clone(),
         headers,
-        original_uri.clone(),
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+        params,
+    )
+    .await
+    .map(Html)
+}
+
+/// The printable batch page, as a PDF for progression board packs.
+pub async fn get_trainee_batch_pdf(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Pdf, Error> {
+    let html = render_trainee_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+        TraineeBatchParams {
+            palette: Palette::Default,
+            print: 1,
+            tz: DisplayTimezone::default(),
+            photos: Some(0),
+            summary: 0,
+        },
+    )
+    .await?;
+    render_pdf_for_config(&server_state, html).await
+}
+
+async fn render_trainee_batch(
+    session: &Session,
+    headers: HeaderMap,
+    server_state: &ServerState,
+    original_uri: Uri,
+    course: String,
+    batch_github_slug: &str,
+    mut params: TraineeBatchParams,
+) -> Result<String, Error> {
+    params.photos = Some(show_photos(session, params.photos).await? as u8);
+    let (course, batch, sheets_client) = get_course_and_batch(
+        session,
+        headers,
+        server_state,
+        original_uri,
+        course,
+        batch_github_slug,
+    )
+    .await?;
+    let burndown =
+        get_burndown_if_permitted(server_state, &sheets_client, &course, batch_github_slug).await?;
+    let attendance_policy = server_state.config.attendance_policy(&course.name);
+    Ok(render_trainee_batch_page(
+        course,
+        batch,
+        server_state.config.at_risk_rules.clone(),
+        server_state.config.absence_streak.clone(),
+        attendance_policy,
+        burndown,
+        params,
+    ))
+}
+
+/// The current sprint's burndown, if the snapshot job has recorded one and the user can read the snapshot sheet.
+async fn get_burndown_if_permitted(
+    server_state: &ServerState,
+    sheets_client: &SheetsClient,
+    course: &Course,
+    batch_github_slug: &str,
+) -> Result<Option<SprintBurndown>, Error> {
+    let Some(snapshot_sheet_id) = &server_state.config.snapshot_sheet_id else {
+        return Ok(None);
+    };
+    let Some(sprint) = current_sprint(course, chrono::Utc::now().date_naive()) else {
+        return Ok(None);
+    };
+    match read_sprint_burndown(
+        sheets_client,
+        snapshot_sheet_id,
+        &course.name,
+        batch_github_slug,
+        sprint,
+    )
+    .await
+    {
+        Err(Error::PermissionDenied(_)) => Ok(None),
+        result => result,
+    }
+}
+
+/// Renders the batch page from already-fetched data, e.g. for exports which don't go through the web app.
+pub fn render_trainee_batch_page(
+    course: Course,
+    mut batch: Batch,
+    at_risk_rules: Vec<AtRiskRule>,
+    absence_streak: Option<AbsenceStreakPolicy>,
+    attendance_policy: Option<AttendancePolicy>,
+    burndown: Option<SprintBurndown>,
+    params: TraineeBatchParams,
+) -> String {
+    batch
+        .trainees
+        .sort_by_cached_key(|trainee| trainee.progress_score());
+    batch.trainees.reverse();
+    TraineeBatchTemplate {
+        course,
+        batch,
+        at_risk_rules,
+        absence_streak,
+        attendance_policy,
+        burndown,
+        today: chrono::Utc::now().date_naive(),
+        palette: params.palette,
+        print: params.print != 0,
+        timezone: params.tz,
+        show_photos: params.photos == Some(1) && params.print == 0,
+        summary: params.summary != 0,
+    }
+    .render()
+    .unwrap()
+}
+
+async fn render_pdf_for_config(server_state: &ServerState, html: String) -> Result<Pdf, Error> {
+    let weasyprint_path = server_state
+        .config
+        .weasyprint_path
+        .as_deref()
+        .unwrap_or(DEFAULT_WEASYPRINT_PATH);
+    render_pdf(weasyprint_path, &server_state.config.public_base_url, html).await
+}
+
+/// One row per trainee with due mandatory work they haven't submitted, for regional teams' existing mail-merge outreach.
+pub async fn get_missing_work_mail_merge_csv(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Csv, Error> {
+    let (_course, batch, _sheets_client) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
     )
     .await?;
+
+    let mut out = String::from("email,first_name,missing_count,missing_assignments\n");
+    for trainee in &batch.trainees {
+        let missing = trainee.missing_mandatory_pull_requests();
+        if missing.is_empty() {
+            continue;
+        }
+        let missing_assignments = missing
+            .iter()
+            .filter_map(|assignment| match assignment {
+                Assignment::ExpectedPullRequest {
+                    title, html_url, ..
+                } => Some(format!("- {}: {}", title, html_url)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let first_name = trainee.trainee.first_name();
+        let fields = [
+            trainee.trainee.email.to_string(),
+            first_name.to_owned(),
+            missing.len().to_string(),
+            missing_assignments,
+        ];
+        out += &fields.map(|field| csv_escape(&field)).join(",");
+        out += "\n";
+    }
+    Ok(Csv(out))
+}
+
+pub(crate) async fn get_course_and_batch(
+    session: &Session,
+    headers: HeaderMap,
+    server_state: &ServerState,
+    original_uri: Uri,
+    course: String,
+    batch_github_slug: &str,
+) -> Result<(Course, Batch, SheetsClient), Error> {
+    let sheets_client =
+        sheets_client(session, server_state.clone(), headers, original_uri.clone()).await?;
     let github_org = &server_state.config.github_org;
     let course_schedule = server_state
         .config
-        .get_course_schedule_with_register_sheet_id(course.clone(), &batch_github_slug)
+        .get_course_schedule_with_register_sheet_id(course.clone(), batch_github_slug)
         .ok_or_else(|| Error::Fatal(anyhow::anyhow!("Course not found: {course}")))?;
-    let octocrab = octocrab(&session, &server_state, original_uri).await?;
-    let course = course_schedule
-        .with_assignments(&octocrab, github_org)
+    let octocrab = octocrab(session, server_state, original_uri).await?;
+    let result = async {
+        let course = course_schedule
+            .with_assignments(&octocrab, &server_state.in_flight, github_org)
+            .await?;
+        let batch = get_batch_with_submissions(
+            &octocrab,
+            sheets_client.clone(),
+            &server_state.config.github_email_mapping_sheet_id,
+            &server_state.config.mentoring_records_sheet_id,
+            github_org,
+            batch_github_slug,
+            &course,
+        )
         .await?;
-    let mut batch = get_batch_with_submissions(
-        &octocrab,
-        sheets_client,
-        &server_state.config.github_email_mapping_sheet_id,
-        &server_state.config.mentoring_records_sheet_id,
-        github_org,
+        Ok::<_, Error>((course, batch, sheets_client))
+    }
+    .await;
+    record_refresh(server_state, &course_schedule.name, &result).await;
+    result
+}
+
+pub async fn get_trainee(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug, github_login)): Path<(String, String, String)>,
+    Query(params): Query<PrintParams>,
+) -> Result<Html<String>, Error> {
+    let show_photos = show_photos(&session, params.photos).await? && params.print == 0;
+    render_trainee(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        (course, batch_github_slug, github_login),
+        params.print != 0,
+        show_photos,
+    )
+    .await
+    .map(Html)
+}
+
+#[derive(Deserialize)]
+pub struct StaffNoteForm {
+    note: String,
+}
+
+pub async fn post_trainee_note(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug, github_login)): Path<(String, String, String)>,
+    Form(form): Form<StaffNoteForm>,
+) -> Result<axum::response::Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    // Only staff can read the GitHub email mapping sheet, and so see the trainee page.
+    let sheets_client = sheets_client(
+        &session,
+        server_state.clone(),
+        headers,
+        original_uri.clone(),
+    )
+    .await?;
+    match sheets_client
+        .sheet_titles(&server_state.config.github_email_mapping_sheet_id)
+        .await
+    {
+        Ok(_) => {}
+        Err(Error::PermissionDenied(denied)) => {
+            return Err(Error::UserFacing(format!(
+                "Only staff can add notes about trainees. {}",
+                denied.user_facing_message()
+            )));
+        }
+        Err(err) => return Err(err),
+    }
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let author = current_github_login(&octocrab).await?;
+    let store = record_store(&server_state).await?;
+    add_staff_note(
+        &store,
+        &StaffNote {
+            trainee: GithubLogin::from(github_login.clone()),
+            author,
+            recorded_at: chrono::Utc::now(),
+            text: form.note.trim().to_owned(),
+        },
+    )
+    .await?;
+    Ok(axum::response::Redirect::to(&format!(
+        "/courses/{}/batches/{}/trainees/{}",
+        course, batch_github_slug, github_login
+    )))
+}
+
+/// The printable trainee page, as a PDF.
+pub async fn get_trainee_pdf(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug, github_login)): Path<(String, String, String)>,
+) -> Result<Pdf, Error> {
+    let html = render_trainee(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        (course, batch_github_slug, github_login),
+        true,
+        false,
+    )
+    .await?;
+    render_pdf_for_config(&server_state, html).await
+}
+
+async fn render_trainee(
+    session: &Session,
+    headers: HeaderMap,
+    server_state: &ServerState,
+    original_uri: Uri,
+    (course, batch_github_slug, github_login): (String, String, String),
+    print: bool,
+    show_photos: bool,
+) -> Result<String, Error> {
+    let (course, batch, sheets_client) = get_course_and_batch(
+        session,
+        headers,
+        server_state,
+        original_uri.clone(),
+        course,
         &batch_github_slug,
-        &course,
     )
     .await?;
-    batch
+    let github_login = GithubLogin::from(github_login);
+    let batch_name = batch.name;
+    let groups = batch
+        .groups
+        .into_iter()
+        .filter(|group| group.includes(&github_login))
+        .collect();
+    let trainee = batch
         .trainees
-        .sort_by_cached_key(|trainee| trainee.progress_score());
-    batch.trainees.reverse();
-    Ok(Html(
-        TraineeBatchTemplate { course, batch }.render().unwrap(),
-    ))
+        .into_iter()
+        .find(|trainee| trainee.trainee.github_login == github_login)
+        .ok_or_else(|| {
+            Error::UserFacing(format!(
+                "Couldn't find trainee {} in batch {}",
+                github_login, batch_name
+            ))
+        })?;
+
+    let view_as = view_as(session).await?;
+    let viewer_is_staff = view_as.as_ref().is_none_or(ViewAs::is_staff);
+
+    let mentoring_sessions = if viewer_is_staff {
+        match get_mentoring_sessions(
+            sheets_client.clone(),
+            &server_state.config.mentoring_records_sheet_id,
+        )
+        .await
+        {
+            Ok(sessions) => {
+                // Mentoring records are kept by the trainee's name, as the batch page matches them.
+                let mut sessions = sessions
+                    .into_iter()
+                    .filter(|session| session.trainee_name == trainee.trainee.name)
+                    .collect::<Vec<_>>();
+                sessions.sort_by(|a, b| b.date.cmp(&a.date));
+                Some(sessions)
+            }
+            Err(Error::PermissionDenied(_)) => None,
+            Err(err) => return Err(err),
+        }
+    } else {
+        None
+    };
+
+    let entry_assessment = match &server_state.config.admissions_sheet_id {
+        Some(admissions_sheet_id) if viewer_is_staff => {
+            match get_entry_assessments_if_permitted(sheets_client, admissions_sheet_id).await? {
+                Some(mut entry_assessments) => {
+                    match entry_assessments.remove(&trainee.trainee.email) {
+                        Some(entry_assessment) => MaybeEntryAssessment::Some(entry_assessment),
+                        None => MaybeEntryAssessment::Unknown,
+                    }
+                }
+                None => MaybeEntryAssessment::Hidden,
+            }
+        }
+        _ => MaybeEntryAssessment::Hidden,
+    };
+
+    let (goals, notes) = match RecordStore::from_config(server_state).await? {
+        Some(store) => (
+            get_sprint_goals(&store, &github_login).await?,
+            if viewer_is_staff {
+                Some(get_staff_notes(&store, &github_login).await?)
+            } else {
+                None
+            },
+        ),
+        None => (Vec::new(), None),
+    };
+
+    let slack_profile_url = match slack_client_if_signed_in(session, server_state).await? {
+        Some(slack_client) => find_slack_profile_url(&slack_client, &trainee.trainee.email).await,
+        None => None,
+    };
+
+    let octocrab = octocrab(session, server_state, original_uri).await?;
+    let review_conversations =
+        get_review_conversation_summaries(&octocrab, &server_state.config.github_org, &trainee)
+            .await?;
+
+    Ok(TraineeTemplate {
+        course,
+        batch_name,
+        batch_github_slug,
+        trainee,
+        entry_assessment,
+        slack_profile_url,
+        groups,
+        goals,
+        notes,
+        mentoring_sessions,
+        review_conversations,
+        at_risk_rules: server_state.config.at_risk_rules.clone(),
+        absence_streak: server_state.config.absence_streak.clone(),
+        attendance_policy: server_state.config.attendance_policy(&course.name),
+        today: chrono::Utc::now().date_naive(),
+        print,
+        view_as,
+        viewer_is_staff,
+        show_photos,
+    }
+    .render()
+    .unwrap())
+}
+
+// A long list is overwhelming, and the first few steps are what matter.
+const MAX_CATCH_UP_STEPS: usize = 5;
+
+#[derive(Template)]
+#[template(path = "trainee.html")]
+struct TraineeTemplate {
+    course: Course,
+    batch_name: String,
+    batch_github_slug: String,
+    trainee: TraineeWithSubmissions,
+    entry_assessment: MaybeEntryAssessment,
+    /// None if the viewer isn't signed in to Slack, or the trainee's email isn't on Slack.
+    slack_profile_url: Option<String>,
+    /// Groups the trainee is a member of.
+    groups: Vec<GroupProgress>,
+    /// Sprint goals the trainee has recorded, newest first.
+    goals: Vec<SprintGoals>,
+    /// Staff notes about the trainee, newest first. None if the tracker isn't configured to store records.
+    notes: Option<Vec<StaffNote>>,
+    /// Mentoring sessions logged for the trainee, newest first. None if the viewer can't read the mentoring records sheet.
+    mentoring_sessions: Option<Vec<MentoringSession>>,
+    /// Review threads on each of the trainee's reviewed PRs, so mentors have context before a 1:1.
+    review_conversations: Vec<ReviewConversationSummary>,
+    at_risk_rules: Vec<AtRiskRule>,
+    absence_streak: Option<AbsenceStreakPolicy>,
+    attendance_policy: Option<AttendancePolicy>,
+    today: chrono::NaiveDate,
+    print: bool,
+    view_as: Option<ViewAs>,
+    /// Whether to show staff-only details, e.g. the trainee's form name.
+    viewer_is_staff: bool,
+    show_photos: bool,
+}
+
+impl TraineeTemplate {
+    fn css_classes_for_trainee_status(&self, trainee_status: &TraineeStatus) -> String {
+        css_classes_for_trainee_status(trainee_status)
+    }
+
+    fn triggered_rules(&self) -> Vec<&AtRiskRule> {
+        triggered_rules(&self.at_risk_rules, &self.trainee, self.today)
+    }
+
+    fn module_forecasts(&self) -> Vec<ModuleForecast> {
+        module_forecasts(&self.trainee)
+    }
+
+    fn css_classes_for_submission(&self, submission: &Submission) -> String {
+        css_classes_for_submission(submission)
+    }
+
+    /// Each of the module's sprints, with every assignment in it (as HTML) and what the trainee has done about it.
+    fn module_sprints(&self, module_name: &str) -> Vec<Vec<(String, &SubmissionState)>> {
+        let (Some(module), Some(trainee_module)) = (
+            self.course.modules.get(module_name),
+            self.trainee.modules.get(module_name),
+        ) else {
+            return Vec::new();
+        };
+        module
+            .sprints
+            .iter()
+            .zip(&trainee_module.sprints)
+            .map(|(sprint, trainee_sprint)| {
+                sprint
+                    .assignments
+                    .iter()
+                    .zip(&trainee_sprint.submissions)
+                    .map(|(assignment, submission)| {
+                        let heading = match assignment {
+                            Assignment::Attendance { class_dates } => {
+                                match class_dates.get(&self.trainee.trainee.region) {
+                                    Some(date) => format!("Attendance ({})", date),
+                                    None => assignment.heading(),
+                                }
+                            }
+                            _ => assignment.heading(),
+                        };
+                        (heading, submission)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// When the module's last class is in the trainee's region, if we know.
+    fn module_end_date(&self, module_name: &str) -> Option<chrono::NaiveDate> {
+        self.course
+            .modules
+            .get(module_name)?
+            .sprints
+            .last()?
+            .dates
+            .get(&self.trainee.trainee.region)
+            .copied()
+    }
+
+    fn absence_streak(&self) -> Option<usize> {
+        self.absence_streak
+            .as_ref()
+            .and_then(|policy| policy.streak(&self.course, &self.trainee))
+    }
+
+    fn policy_breaches(&self) -> Vec<PolicyBreach> {
+        self.attendance_policy
+            .as_ref()
+            .map(|policy| policy.breaches(&self.trainee))
+            .unwrap_or_default()
+    }
+
+    /// A message staff can send when reaching out to a trainee, listing the work they're missing.
+    fn intro_message(&self) -> String {
+        let first_name = self.trainee.trainee.first_name();
+        let mut message = format!(
+            "Hi {}, I'm checking in to see how {} is going for you.",
+            first_name, self.course.name
+        );
+        let missing = self.trainee.missing_mandatory_pull_requests();
+        if !missing.is_empty() {
+            message += " I noticed we haven't seen these assignments from you yet:\n";
+            for assignment in missing {
+                if let Assignment::ExpectedPullRequest {
+                    title, html_url, ..
+                } = assignment
+                {
+                    message += &format!("\n- {} ({})", title, html_url);
+                }
+            }
+            message += "\n\n";
+        } else {
+            message += " ";
+        }
+        message += "Is there anything getting in the way which we could help with?";
+        message
+    }
 }
 
 #[derive(Template)]
@@ -144,33 +859,61 @@ pub async fn get_trainee_batch(
 struct TraineeBatchTemplate {
     course: Course,
     batch: Batch,
+    at_risk_rules: Vec<AtRiskRule>,
+    absence_streak: Option<AbsenceStreakPolicy>,
+    attendance_policy: Option<AttendancePolicy>,
+    burndown: Option<SprintBurndown>,
+    today: chrono::NaiveDate,
+    palette: Palette,
+    print: bool,
+    timezone: DisplayTimezone,
+    show_photos: bool,
+    summary: bool,
 }
 
 impl TraineeBatchTemplate {
-    fn css_classes_for_submission(&self, submission: &Submission) -> String {
+    fn triggered_rules(&self, trainee: &TraineeWithSubmissions) -> Vec<&AtRiskRule> {
+        triggered_rules(&self.at_risk_rules, trainee, self.today)
+    }
+
+    fn absence_streak(&self, trainee: &TraineeWithSubmissions) -> Option<usize> {
+        self.absence_streak
+            .as_ref()
+            .and_then(|policy| policy.streak(&self.course, trainee))
+    }
+
+    fn policy_breaches(&self, trainee: &TraineeWithSubmissions) -> Vec<PolicyBreach> {
+        self.attendance_policy
+            .as_ref()
+            .map(|policy| policy.breaches(trainee))
+            .unwrap_or_default()
+    }
+
+    /// Lets `/lookup?pr=...` link straight to a PR's cell.
+    fn cell_id(&self, submission: &Submission) -> Option<String> {
         match submission {
-            Submission::Attendance(Attendance::Absent { .. }) => String::from("attendance-absent"),
-            Submission::Attendance(Attendance::OnTime { .. }) => String::from("attendance-present"),
-            Submission::Attendance(Attendance::Late { .. }) => String::from("attendance-late"),
-            Submission::Attendance(Attendance::WrongDay { .. }) => {
-                String::from("attendance-wrong-day")
+            Submission::PullRequest { pull_request, .. } => {
+                Some(pr_cell_id(&pull_request.repo_name, pull_request.number))
             }
-            Submission::PullRequest { pull_request, .. } => match pull_request.state {
-                PrState::NeedsReview => "pr-needs-review".to_owned(),
-                PrState::Reviewed => "pr-reviewed".to_owned(),
-                PrState::Complete => "pr-complete".to_owned(),
-                PrState::Unknown => "pr-unknown".to_owned(),
-            },
+            _ => None,
         }
     }
 
-    fn css_classes_for_trainee_status(&self, trainee_status: &TraineeStatus) -> String {
-        match trainee_status {
-            TraineeStatus::OnTrack => "trainee-on-track",
-            TraineeStatus::Behind => "trainee-behind",
-            TraineeStatus::AtRisk => "trainee-at-risk",
+    fn css_classes_for_module_completion(&self, completion: &Fraction) -> &'static str {
+        match completion.percent() {
+            None => "",
+            Some(100) => "pr-complete",
+            Some(50..) => "pr-reviewed",
+            Some(_) => "pr-missing",
         }
-        .to_owned()
+    }
+
+    fn css_classes_for_submission(&self, submission: &Submission) -> String {
+        css_classes_for_submission(submission)
+    }
+
+    fn css_classes_for_trainee_status(&self, trainee_status: &TraineeStatus) -> String {
+        css_classes_for_trainee_status(trainee_status)
     }
 
     fn on_track_and_total_for_region(&self, region: Option<&str>) -> (usize, usize) {
@@ -191,12 +934,168 @@ impl TraineeBatchTemplate {
     }
 }
 
+fn css_classes_for_submission(submission: &Submission) -> String {
+    match submission {
+        Submission::Attendance(Attendance::Absent { .. }) => String::from("attendance-absent"),
+        Submission::Attendance(Attendance::OnTime { .. }) => String::from("attendance-present"),
+        Submission::Attendance(Attendance::Late { .. }) => String::from("attendance-late"),
+        Submission::Attendance(Attendance::VeryLate { .. }) => String::from("attendance-very-late"),
+        Submission::Attendance(Attendance::WrongDay { .. }) => String::from("attendance-wrong-day"),
+        Submission::PullRequest { pull_request, .. } => match pull_request.state {
+            PrState::NeedsReview => "pr-needs-review".to_owned(),
+            PrState::Reviewed => "pr-reviewed".to_owned(),
+            PrState::Complete => "pr-complete".to_owned(),
+            PrState::Unknown => "pr-unknown".to_owned(),
+        },
+        Submission::ProjectRepo(project_repo) => {
+            if project_repo.is_complete() {
+                "pr-complete".to_owned()
+            } else {
+                "pr-reviewed".to_owned()
+            }
+        }
+    }
+}
+
+fn css_classes_for_trainee_status(trainee_status: &TraineeStatus) -> String {
+    match trainee_status {
+        TraineeStatus::OnTrack => "trainee-on-track",
+        TraineeStatus::Behind => "trainee-behind",
+        TraineeStatus::AtRisk => "trainee-at-risk",
+    }
+    .to_owned()
+}
+
+/// A course's reviewers, with staff-only details (e.g. DBS checks) filled in only if the user can read the staff info sheet.
+pub(crate) struct CourseReviewers {
+    pub reviewers: BTreeSet<ReviewerInfo>,
+    /// Trainees who have reviewed other trainees' PRs - kept separate so they don't count towards mentor capacity.
+    pub peer_reviewers: BTreeSet<ReviewerInfo>,
+    pub view_as: Option<ViewAs>,
+    /// Why staff-only details aren't filled in, if the user can't read the staff info sheet.
+    pub staff_details_unavailable: Option<String>,
+}
+
+pub(crate) async fn get_course_reviewers(
+    session: &Session,
+    headers: HeaderMap,
+    server_state: &ServerState,
+    original_uri: Uri,
+    course: &str,
+) -> Result<CourseReviewers, Error> {
+    let sheets_client =
+        sheets_client(session, server_state.clone(), headers, original_uri.clone()).await?;
+    let (staff_details, staff_details_denied) = match get_reviewer_staff_info(
+        sheets_client,
+        &server_state.config.reviewer_staff_info_sheet_id,
+    )
+    .await
+    {
+        Ok(staff_details) => (Some(staff_details), None),
+        Err(Error::PermissionDenied(denied)) => (None, Some(denied)),
+        Err(err) => return Err(err),
+    };
+    let view_as = view_as(session).await?;
+    let is_staff = staff_details.is_some() && view_as.as_ref().is_none_or(ViewAs::is_staff);
+    // Someone viewing as a trainee expects not to see staff details, so don't explain why they're missing.
+    let staff_details_unavailable = staff_details_denied
+        .filter(|_| view_as.is_none())
+        .map(|denied| denied.user_facing_message());
+    let mut staff_details = staff_details.unwrap_or_default();
+
+    let octocrab = octocrab(session, server_state, original_uri).await?;
+    let github_org = &server_state.config.github_org;
+    let module_names = server_state
+        .config
+        .get_course_module_names(course)
+        .ok_or_else(|| Error::Fatal(anyhow::anyhow!("Course not found: {course}")))?;
+    let batch_github_slugs = server_state
+        .config
+        .courses
+        .get(course)
+        .map(|course| course.batches.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let trainee_logins =
+        crate::github_accounts::get_trainee_logins(&octocrab, github_org, &batch_github_slugs)
+            .await?;
+    let (peer_reviewers, reviewers): (BTreeSet<_>, BTreeSet<_>) =
+        crate::prs::get_reviewers(octocrab, &server_state.in_flight, github_org, &module_names)
+            .await?
+            .into_iter()
+            .map(|mut reviewer| {
+                reviewer.staff_only_details = if is_staff {
+                    match staff_details.remove(&reviewer.login) {
+                        Some(details) => MaybeReviewerStaffOnlyDetails::Some(details),
+                        None => MaybeReviewerStaffOnlyDetails::Unknown,
+                    }
+                } else {
+                    MaybeReviewerStaffOnlyDetails::NotAuthenticated
+                };
+                reviewer
+            })
+            .partition(|reviewer| trainee_logins.contains(&reviewer.login));
+
+    Ok(CourseReviewers {
+        reviewers,
+        peer_reviewers,
+        view_as,
+        staff_details_unavailable,
+    })
+}
+
 pub async fn get_reviewers(
     session: Session,
     headers: HeaderMap,
     State(server_state): State<ServerState>,
     OriginalUri(original_uri): OriginalUri,
     Path(course): Path<String>,
+    Query(params): Query<TimezoneParams>,
+) -> Result<Html<String>, Error> {
+    let CourseReviewers {
+        reviewers,
+        peer_reviewers,
+        view_as,
+        staff_details_unavailable,
+    } = get_course_reviewers(&session, headers, &server_state, original_uri, &course).await?;
+
+    let now = chrono::Utc::now();
+
+    Ok(Html(
+        ReviewersTemplate {
+            course,
+            reviewers,
+            peer_reviewers,
+            now,
+            timezone: params.tz,
+            view_as,
+            staff_details_unavailable,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "reviewers.html")]
+struct ReviewersTemplate {
+    pub course: String,
+    pub reviewers: BTreeSet<ReviewerInfo>,
+    /// Trainees who have reviewed other trainees' PRs - kept separate so they don't count towards mentor capacity.
+    pub peer_reviewers: BTreeSet<ReviewerInfo>,
+    pub now: chrono::DateTime<chrono::Utc>,
+    pub timezone: DisplayTimezone,
+    pub view_as: Option<ViewAs>,
+    /// Why staff-only details (e.g. DBS checks) aren't shown, if the viewer can't read the staff info sheet.
+    pub staff_details_unavailable: Option<String>,
+}
+
+pub async fn get_mentors(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+    Query(params): Query<TimezoneParams>,
 ) -> Result<Html<String>, Error> {
     let sheets_client = sheets_client(
         &session,
@@ -205,49 +1104,58 @@ pub async fn get_reviewers(
         original_uri.clone(),
     )
     .await?;
-    let mut is_staff = true;
-    let mut staff_details = get_reviewer_staff_info(
-        sheets_client,
-        &server_state.config.reviewer_staff_info_sheet_id,
-    )
-    .await
-    .or_else(|err| match err {
-        Error::PotentiallyIgnorablePermissions(_) => {
-            is_staff = false;
-            Ok(BTreeMap::new())
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let roster = get_mentor_roster(octocrab, sheets_client, &server_state.config, &course).await?;
+
+    let now = chrono::Utc::now();
+
+    Ok(Html(
+        MentorsTemplate {
+            course,
+            roster,
+            now,
+            timezone: params.tz,
         }
-        err => Err(err),
-    })?;
+        .render()
+        .unwrap(),
+    ))
+}
 
-    let octocrab = octocrab(&session, &server_state, original_uri).await?;
-    let github_org = &server_state.config.github_org;
+#[derive(Template)]
+#[template(path = "mentors.html")]
+struct MentorsTemplate {
+    pub course: String,
+    pub roster: MentorRoster,
+    pub now: chrono::DateTime<chrono::Utc>,
+    pub timezone: DisplayTimezone,
+}
+
+pub async fn get_needs_attention(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Html<String>, Error> {
     let module_names = server_state
         .config
         .get_course_module_names(&course)
-        .ok_or_else(|| Error::Fatal(anyhow::anyhow!("Course not found: {course}")))?;
-    let reviewers = crate::prs::get_reviewers(octocrab, github_org, &module_names)
-        .await?
-        .into_iter()
-        .map(|mut reviewer| {
-            reviewer.staff_only_details = if is_staff {
-                match staff_details.remove(&reviewer.login) {
-                    Some(details) => MaybeReviewerStaffOnlyDetails::Some(details),
-                    None => MaybeReviewerStaffOnlyDetails::Unknown,
-                }
-            } else {
-                MaybeReviewerStaffOnlyDetails::NotAuthenticated
-            };
-            reviewer
-        })
-        .collect();
-
-    let now = chrono::Utc::now();
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let min_age = server_state.config.help_request_age();
+    let help_requests = get_unanswered_help_requests(
+        &octocrab,
+        &server_state.config.github_org,
+        &module_names,
+        min_age,
+    )
+    .await?;
 
     Ok(Html(
-        ReviewersTemplate {
+        NeedsAttentionTemplate {
             course,
-            reviewers,
-            now,
+            min_age_hours: min_age.num_hours(),
+            help_requests,
+            now: chrono::Utc::now(),
         }
         .render()
         .unwrap(),
@@ -255,10 +1163,11 @@ pub async fn get_reviewers(
 }
 
 #[derive(Template)]
-#[template(path = "reviewers.html")]
-struct ReviewersTemplate {
+#[template(path = "needs-attention.html")]
+struct NeedsAttentionTemplate {
     pub course: String,
-    pub reviewers: BTreeSet<ReviewerInfo>,
+    pub min_age_hours: i64,
+    pub help_requests: Vec<HelpRequest>,
     pub now: chrono::DateTime<chrono::Utc>,
 }
 
@@ -280,6 +1189,7 @@ pub async fn get_review_metrics(
         .map(async |module_name| {
             let prs = get_prs(
                 &octocrab,
+                &server_state.in_flight,
                 &server_state.config.github_org,
                 &module_name,
                 false,
@@ -288,18 +1198,40 @@ pub async fn get_review_metrics(
             let metrics_futures: Vec<_> = prs
                 .into_iter()
                 .map(async |pr| {
-                    crate::prs::get_review_metrics(&octocrab, &server_state.config.github_org, pr)
-                        .await
+                    let ball_in_court =
+                        get_ball_in_court(&octocrab, &server_state.config.github_org, &pr).await?;
+                    let metrics = crate::prs::get_review_metrics(
+                        &octocrab,
+                        &server_state.config.github_org,
+                        pr,
+                    )
+                    .await?;
+                    Ok::<_, Error>((metrics, ball_in_court))
                 })
                 .collect();
             let metrics = join_all(metrics_futures).await;
             let metrics = metrics.into_iter().collect::<Result<Vec<_>, _>>()?;
+            let mut awaiting_trainee = Vec::new();
+            let mut awaiting_reviewer = Vec::new();
+            for (metrics, ball_in_court) in &metrics {
+                match ball_in_court {
+                    Some(BallInCourt::Trainee) => awaiting_trainee.push(metrics.pr.clone()),
+                    Some(BallInCourt::Reviewer) => awaiting_reviewer.push(metrics.pr.clone()),
+                    None => {}
+                }
+            }
+            let metrics = metrics
+                .into_iter()
+                .map(|(metrics, _)| metrics)
+                .collect::<Vec<_>>();
             let aggregate_metrics = AggregatePrMetrics::new(&metrics);
 
             Ok::<_, Error>(ModuleReviewMetrics {
                 name: module_name,
                 metrics,
                 aggregate_metrics,
+                awaiting_trainee,
+                awaiting_reviewer,
             })
         })
         .collect::<Vec<_>>();
@@ -334,6 +1266,10 @@ pub struct ModuleReviewMetrics {
     pub name: String,
     pub metrics: Vec<PrMetrics>,
     pub aggregate_metrics: AggregatePrMetrics,
+    /// Open PRs which have had changes requested, and the trainee hasn't responded since.
+    pub awaiting_trainee: Vec<Pr>,
+    /// Open PRs where the trainee has responded to requested changes, and needs a reviewer to look again.
+    pub awaiting_reviewer: Vec<Pr>,
 }
 
 impl ReviewMetricsTemplate {
@@ -357,6 +1293,152 @@ pub async fn index() -> Html<String> {
 #[template(path = "index.html")]
 struct Index {}
 
+#[derive(Deserialize)]
+pub struct MeParams {
+    #[serde(default)]
+    saved: bool,
+    #[serde(default)]
+    lang: Option<Language>,
+}
+
+/// A trainee's own page, where they record their goals and confidence for each sprint.
+pub async fn get_me(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Query(params): Query<MeParams>,
+) -> Result<Html<String>, Error> {
+    let lang = language(&session, &headers, params.lang).await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let view_as = view_as(&session).await?;
+    let github_login = match &view_as {
+        Some(view_as) => view_as.github_login.clone(),
+        None => current_github_login(&octocrab).await?,
+    };
+    let store = record_store(&server_state).await?;
+    let current_sprint = find_current_sprint_for_trainee(
+        &octocrab,
+        &server_state.config,
+        &github_login,
+        chrono::Utc::now().date_naive(),
+    )
+    .await?;
+    let goals = get_sprint_goals(&store, &github_login).await?;
+    Ok(Html(
+        MeTemplate {
+            github_login,
+            current_sprint,
+            goals,
+            saved: params.saved,
+            view_as,
+            lang,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+/// A trainee's own progress, laid out for their phone.
+pub async fn get_me_progress(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Query(params): Query<LanguageParams>,
+) -> Result<Html<String>, Error> {
+    let lang = language(&session, &headers, params.lang).await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let view_as = view_as(&session).await?;
+    let github_login = match &view_as {
+        Some(view_as) => view_as.github_login.clone(),
+        None => current_github_login(&octocrab).await?,
+    };
+    let progress = load_trainee_progress(&server_state, github_login).await?;
+    Ok(Html(
+        MeProgressTemplate {
+            progress,
+            view_as,
+            lang,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct SprintGoalsForm {
+    module_name: String,
+    sprint_number: usize,
+    goals: String,
+    confidence: u8,
+}
+
+pub async fn post_me_goals(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Form(form): Form<SprintGoalsForm>,
+) -> Result<axum::response::Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let github_login = current_github_login(&octocrab).await?;
+    let store = record_store(&server_state).await?;
+    record_sprint_goals(
+        &store,
+        &SprintGoals {
+            github_login,
+            module_name: form.module_name,
+            sprint_number: form.sprint_number,
+            recorded_at: chrono::Utc::now(),
+            goals: form.goals.trim().to_owned(),
+            confidence: form.confidence,
+        },
+    )
+    .await?;
+    Ok(axum::response::Redirect::to("/me?saved=true"))
+}
+
+pub(crate) async fn current_github_login(octocrab: &Octocrab) -> Result<GithubLogin, Error> {
+    let user = octocrab
+        .current()
+        .user()
+        .await
+        .context("Failed to get current user")?;
+    Ok(GithubLogin::from(user.login))
+}
+
+pub(crate) async fn record_store(server_state: &ServerState) -> Result<RecordStore, Error> {
+    RecordStore::from_config(server_state)
+        .await?
+        .ok_or_else(|| {
+            Error::UserFacing(
+                "The trainee tracker isn't configured to store records, so can't save this."
+                    .to_owned(),
+            )
+        })
+}
+
+#[derive(Template)]
+#[template(path = "me.html")]
+struct MeTemplate {
+    github_login: GithubLogin,
+    /// Module and sprint the trainee's batch is on, to pre-fill the form.
+    current_sprint: Option<(String, usize)>,
+    goals: Vec<SprintGoals>,
+    saved: bool,
+    view_as: Option<ViewAs>,
+    lang: Language,
+}
+
+#[derive(Template)]
+#[template(path = "me-progress.html")]
+struct MeProgressTemplate {
+    progress: TraineeProgressV1,
+    view_as: Option<ViewAs>,
+    lang: Language,
+}
+
 #[derive(Template)]
 #[template(path = "redirect.html")]
 pub(crate) struct Redirect {
@@ -437,7 +1519,7 @@ pub async fn list_google_groups_csv(
     Ok(Csv(out))
 }
 
-pub struct Csv(String);
+pub struct Csv(pub(crate) String);
 
 impl IntoResponse for Csv {
     fn into_response(self) -> axum::response::Response {
@@ -0,0 +1,209 @@
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::NaiveDate;
+use http::HeaderMap;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::{Batch, Course, Sprint, SprintWithSubmissions, SubmissionState},
+    endpoints::csv_escape,
+    frontend::Csv,
+    newtypes::Region,
+    sheets::sheets_client,
+    snapshots::read_funnel,
+};
+
+/// How far a batch's trainees have got through one module, as of one day.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunnelPoint {
+    pub date: NaiveDate,
+    pub module_name: String,
+    pub trainees: usize,
+    /// Trainees who have submitted anything for the module, including attending its classes.
+    pub started: usize,
+    /// Trainees who have submitted anything for either of the module's two most recently started sprints.
+    pub active: usize,
+    /// Trainees who, once every sprint of the module has started, have no mandatory work missing from it.
+    pub completed: usize,
+}
+
+/// The funnel for each module of the course which has started by `date`, in course order.
+pub fn funnel_points(course: &Course, batch: &Batch, date: NaiveDate) -> Vec<FunnelPoint> {
+    let mut points = Vec::new();
+    for (module_name, module) in &course.modules {
+        if !module
+            .sprints
+            .iter()
+            .any(|sprint| sprint.dates.values().any(|start| *start <= date))
+        {
+            continue;
+        }
+        let mut point = FunnelPoint {
+            date,
+            module_name: module_name.clone(),
+            trainees: batch.trainees.len(),
+            started: 0,
+            active: 0,
+            completed: 0,
+        };
+        for trainee in &batch.trainees {
+            let Some(trainee_module) = trainee.modules.get(module_name) else {
+                continue;
+            };
+            let region = &trainee.trainee.region;
+            let started_sprints = module
+                .sprints
+                .iter()
+                .zip(&trainee_module.sprints)
+                .filter(|(sprint, _)| {
+                    sprint_start(sprint, region).is_some_and(|start| start <= date)
+                })
+                .map(|(_, trainee_sprint)| trainee_sprint)
+                .collect::<Vec<_>>();
+            if !trainee_module.unknown_prs.is_empty()
+                || trainee_module.sprints.iter().any(submitted_anything)
+            {
+                point.started += 1;
+            } else {
+                continue;
+            }
+            if started_sprints
+                .iter()
+                .rev()
+                .take(2)
+                .any(|trainee_sprint| submitted_anything(trainee_sprint))
+            {
+                point.active += 1;
+            }
+            if started_sprints.len() == module.sprints.len()
+                && !trainee_module
+                    .sprints
+                    .iter()
+                    .flat_map(|sprint| &sprint.submissions)
+                    .any(|submission| matches!(submission, SubmissionState::MissingButExpected(_)))
+            {
+                point.completed += 1;
+            }
+        }
+        points.push(point);
+    }
+    points
+}
+
+/// When the sprint started for the region, falling back to its earliest start for regions without their own classes.
+fn sprint_start(sprint: &Sprint, region: &Region) -> Option<NaiveDate> {
+    sprint
+        .dates
+        .get(region)
+        .or_else(|| sprint.dates.values().min())
+        .copied()
+}
+
+fn submitted_anything(sprint: &SprintWithSubmissions) -> bool {
+    sprint
+        .submissions
+        .iter()
+        .any(|submission| matches!(submission, SubmissionState::Some(_)))
+}
+
+/// A batch's recorded funnel, oldest first.
+pub struct BatchFunnel {
+    pub batch_github_slug: String,
+    pub points: Vec<FunnelPoint>,
+}
+
+/// Every batch of the course's recorded funnel, read from the snapshot sheet with the user's own credentials, so only staff who can read it see the report.
+async fn get_course_funnel(
+    session: &Session,
+    headers: HeaderMap,
+    server_state: &ServerState,
+    original_uri: http::Uri,
+    course_name: &str,
+) -> Result<Vec<BatchFunnel>, Error> {
+    let course_info = server_state
+        .config
+        .courses
+        .get(course_name)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let Some(snapshot_sheet_id) = &server_state.config.snapshot_sheet_id else {
+        return Err(Error::UserFacing(
+            "The funnel report needs snapshot_sheet_id to be configured".to_owned(),
+        ));
+    };
+    let sheets_client = sheets_client(session, server_state.clone(), headers, original_uri).await?;
+    let mut funnels = Vec::new();
+    for batch_github_slug in course_info.batches.keys() {
+        let points = read_funnel(
+            &sheets_client,
+            snapshot_sheet_id,
+            course_name,
+            batch_github_slug,
+        )
+        .await?
+        .unwrap_or_default();
+        funnels.push(BatchFunnel {
+            batch_github_slug: batch_github_slug.clone(),
+            points,
+        });
+    }
+    Ok(funnels)
+}
+
+pub async fn get_funnel(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course_name): Path<String>,
+) -> Result<Html<String>, Error> {
+    let funnels =
+        get_course_funnel(&session, headers, &server_state, original_uri, &course_name).await?;
+    Ok(Html(
+        FunnelTemplate {
+            course_name,
+            funnels,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+/// The same numbers as the funnel page, one row per batch, day and module, for pasting into reporting decks.
+pub async fn get_funnel_csv(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course_name): Path<String>,
+) -> Result<Csv, Error> {
+    let funnels =
+        get_course_funnel(&session, headers, &server_state, original_uri, &course_name).await?;
+    let mut out = String::from("batch,date,module,trainees,started,active,completed\n");
+    for funnel in &funnels {
+        for point in &funnel.points {
+            let fields = [
+                funnel.batch_github_slug.clone(),
+                point.date.to_string(),
+                point.module_name.clone(),
+                point.trainees.to_string(),
+                point.started.to_string(),
+                point.active.to_string(),
+                point.completed.to_string(),
+            ];
+            out += &fields.map(|field| csv_escape(&field)).join(",");
+            out += "\n";
+        }
+    }
+    Ok(Csv(out))
+}
+
+#[derive(Template)]
+#[template(path = "funnel.html")]
+struct FunnelTemplate {
+    course_name: String,
+    funnels: Vec<BatchFunnel>,
+}
@@ -1,19 +1,60 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use anyhow::Context;
 use email_address::EmailAddress;
+use google_sheets4::api::CellData;
+use octocrab::Octocrab;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     Error,
-    newtypes::{GithubLogin, Region, new_case_insensitive_email_address},
+    newtypes::{GithubLogin, Region, TeamSlug, new_case_insensitive_email_address},
+    octocrab::all_pages,
     sheets::{Sheet, SheetsClient, cell_string},
 };
 
-// TODO: Replace this with a serde implementation from a Google Sheet.
+/// Everyone in any of the given batches' GitHub teams.
+pub async fn get_trainee_logins(
+    octocrab: &Octocrab,
+    github_org: &str,
+    batch_github_slugs: &[TeamSlug],
+) -> Result<BTreeSet<GithubLogin>, Error> {
+    let mut logins = BTreeSet::new();
+    for batch_github_slug in batch_github_slugs {
+        let members = all_pages("members", octocrab, async || {
+            octocrab
+                .teams(github_org)
+                .members(batch_github_slug.as_str())
+                .send()
+                .await
+        })
+        .await?;
+        logins.extend(
+            members
+                .into_iter()
+                .map(|member| GithubLogin::from(member.login)),
+        );
+    }
+    Ok(logins)
+}
+
+/// The trainees in the GitHub email mapping sheet, from the shared `TraineeDirectory` if it's been read recently.
 pub(crate) async fn get_trainees(
     client: SheetsClient,
     sheet_id: &str,
+) -> Result<BTreeMap<GithubLogin, Trainee>, Error> {
+    let directory = client.server_state().trainee_directory.clone();
+    let trainees = directory.get(client, sheet_id).await?;
+    Ok(Arc::unwrap_or_clone(trainees))
+}
+
+// TODO: Replace this with a serde implementation from a Google Sheet.
+pub(crate) async fn fetch_trainees(
+    client: SheetsClient,
+    sheet_id: &str,
 ) -> Result<BTreeMap<GithubLogin, Trainee>, Error> {
     const EXPECTED_SHEET_NAME: &str = "Form responses 1";
     let data = client.get(sheet_id).await.map_err(|err| {
@@ -43,10 +84,48 @@ pub(crate) async fn get_trainees(
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Trainee {
+    /// The name the trainee gave on the sign-up form, which may be their legal name.
+    /// This is what registers and other sheets know them by, but only staff should see it - display `display_name()` instead.
     pub name: String,
     pub region: Region,
     pub github_login: GithubLogin,
     pub email: EmailAddress,
+    #[serde(default)]
+    pub preferred_name: Option<String>,
+    #[serde(default)]
+    pub pronouns: Option<String>,
+}
+
+impl Trainee {
+    /// The name to show the trainee as: their preferred name if they've given one, otherwise their form name.
+    pub fn display_name(&self) -> &str {
+        self.preferred_name.as_deref().unwrap_or(&self.name)
+    }
+
+    pub fn first_name(&self) -> &str {
+        self.display_name()
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// The trainee's GitHub avatar, at `size` pixels square.
+    pub fn avatar_url(&self, size: u32) -> String {
+        format!("https://github.com/{}.png?size={}", self.github_login, size)
+    }
+
+    /// Whether the form name differs from what we display, so is worth showing staff separately.
+    pub fn has_different_form_name(&self) -> bool {
+        self.display_name() != self.name
+    }
+}
+
+/// Optional columns after the required ones, which are empty if a trainee hasn't filled them in.
+fn optional_cell(cells: &[CellData], index: usize) -> Option<String> {
+    cells
+        .get(index)
+        .map(|cell| cell_string(cell).trim().to_owned())
+        .filter(|value| !value.is_empty())
 }
 
 fn trainees_from_sheet(sheet: &Sheet) -> Result<BTreeMap<GithubLogin, Trainee>, Error> {
@@ -74,6 +153,8 @@ fn trainees_from_sheet(sheet: &Sheet) -> Result<BTreeMap<GithubLogin, Trainee>,
                 github_login,
                 email: new_case_insensitive_email_address(&email)
                     .with_context(|| format!("Failed to parse trainee email {}", email))?,
+                preferred_name: optional_cell(cells, 5),
+                pronouns: optional_cell(cells, 6),
             },
         );
     }
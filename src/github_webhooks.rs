@@ -0,0 +1,438 @@
+use std::fmt::Debug;
+
+use anyhow::Context;
+use axum::{Json, body::Bytes, extract::State};
+use hmac::{Hmac, Mac};
+use http::{HeaderMap, StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use tracing::{debug, error};
+
+use crate::{
+    Error, ServerState,
+    complete_suggestions::{complete_suggestion, mark_complete},
+    error_reports::{ErrorContext, ErrorReport, send_error_report},
+    force_pushes::{ForcePush, has_reviews, is_history_rewrite, record_force_push},
+    newtypes::GithubLogin,
+    octocrab::octocrab_for_token,
+    persistence::RecordStore,
+    pr_comments::{PullRequest, close_existing_comments, has_tagged_comment, leave_tagged_comment},
+    pr_validator::{
+        KnownRegions, PR_METADATA_VALIDATOR_LABEL, ValidationResult, module_course, validate_pr,
+    },
+};
+
+// See https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries
+const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const EVENT_HEADER: &str = "x-github-event";
+
+const REVIEW_CHECKLIST_TAG: &str = "review-checklist";
+
+/// Where the webhook is routed, for error reports.
+const WEBHOOK_PATH: &str = "/api/github/webhook";
+
+#[derive(Deserialize)]
+struct PullRequestEvent {
+    action: String,
+    assignee: Option<User>,
+    /// For synchronize events, the head commit before and after the push.
+    before: Option<String>,
+    after: Option<String>,
+    /// For edited events, which fields were edited.
+    changes: Option<PullRequestChanges>,
+    pull_request: PullRequestDetails,
+    repository: Repository,
+}
+
+/// Each field is the value before the edit, if it was edited.
+#[derive(Deserialize)]
+struct PullRequestChanges {
+    title: Option<Value>,
+    body: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct PullRequestReviewEvent {
+    action: String,
+    review: ReviewDetails,
+    pull_request: PullRequestDetails,
+    repository: Repository,
+}
+
+#[derive(Deserialize)]
+struct ReviewDetails {
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequestDetails {
+    number: u64,
+    user: User,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    name: String,
+    owner: User,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+pub async fn handle_github_webhook(
+    State(server_state): State<ServerState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<Value>), Error> {
+    verify_github_signature(&server_state, &headers, &body)?;
+    let event = headers
+        .get(EVENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let queued = match event {
+        "pull_request" => {
+            let event: PullRequestEvent =
+                serde_json::from_slice(&body).context("Failed to parse pull_request event")?;
+            if event.action == "synchronize" {
+                handle_in_background("check for a force push", async move {
+                    maybe_record_force_push(&server_state, event).await
+                });
+                "force_push_check"
+            } else if event.action == "edited" {
+                handle_in_background("re-run the PR validator", async move {
+                    maybe_rerun_pr_validator(&server_state, event).await
+                });
+                "pr_validator"
+            } else {
+                handle_in_background("post a review checklist", async move {
+                    maybe_post_review_checklist(&server_state, event).await
+                });
+                "review_checklist"
+            }
+        }
+        "pull_request_review" => {
+            let event: PullRequestReviewEvent = serde_json::from_slice(&body)
+                .context("Failed to parse pull_request_review event")?;
+            handle_in_background("mark a PR Complete", async move {
+                maybe_mark_complete(&server_state, event).await
+            });
+            "mark_complete"
+        }
+        // GitHub sends a ping when the webhook is first set up, and we may be subscribed to more events than we handle.
+        _ => return Ok((StatusCode::OK, Json(json!({ "ignored": event })))),
+    };
+    Ok((StatusCode::ACCEPTED, Json(json!({ "queued": queued }))))
+}
+
+/// Handles an event after we've responded. GitHub counts deliveries which take more than 10 seconds as failed (and redelivers them),
+/// and handling them within the request would mean GitHub disconnecting cancels the work part-way through.
+fn handle_in_background<T: Debug + Send + 'static>(
+    description: &'static str,
+    work: impl Future<Output = Result<T, Error>> + Send + 'static,
+) {
+    tokio::spawn(async move {
+        match work.await {
+            Ok(outcome) => debug!("Finished trying to {}: {:?}", description, outcome),
+            Err(err) => {
+                error!("Failed to {} for GitHub webhook: {:?}", description, err);
+                // There's no response for the error to be reported from, so report it here.
+                if let Error::Fatal(err) = &err {
+                    send_error_report(ErrorReport::fatal(
+                        err,
+                        ErrorContext {
+                            method: Some("POST".to_owned()),
+                            url: Some(WEBHOOK_PATH.to_owned()),
+                            ..ErrorContext::default()
+                        },
+                    ))
+                    .await;
+                }
+            }
+        }
+    });
+}
+
+/// When a mentor claims a PR by assigning themselves, posts the module's review rubric as a collapsed checklist.
+/// Returns whether a checklist was posted.
+async fn maybe_post_review_checklist(
+    server_state: &ServerState,
+    event: PullRequestEvent,
+) -> Result<bool, Error> {
+    let Some(assignee) = event.assignee.filter(|_| event.action == "assigned") else {
+        return Ok(false);
+    };
+    // Trainees are asked to assign themselves to their own PRs, which isn't a claim.
+    if assignee.login == event.pull_request.user.login {
+        return Ok(false);
+    }
+    let Some(checklist) = server_state
+        .config
+        .review_checklists
+        .get(&event.repository.name)
+    else {
+        return Ok(false);
+    };
+    let Some(service_credentials) = &server_state.config.service_credentials else {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "Can't post review checklists without service credentials"
+        )));
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let pull_request = PullRequest {
+        org: event.repository.owner.login,
+        repo: event.repository.name,
+        number: event.pull_request.number,
+    };
+    // PRs get reassigned, and one checklist is enough.
+    if has_tagged_comment(&octocrab, &pull_request, REVIEW_CHECKLIST_TAG).await? {
+        return Ok(false);
+    }
+    let body = review_checklist_comment(&assignee.login, &pull_request.repo, checklist);
+    leave_tagged_comment(&octocrab, &pull_request, &[REVIEW_CHECKLIST_TAG], body).await?;
+    Ok(true)
+}
+
+/// When a reviewed PR's history is rewritten (e.g. rebased and force-pushed), records it so mentors know to re-check it,
+/// as review comments on the old commits may no longer show up against the code.
+/// Returns whether a force push was recorded.
+async fn maybe_record_force_push(
+    server_state: &ServerState,
+    event: PullRequestEvent,
+) -> Result<bool, Error> {
+    let (Some(before), Some(after)) = (event.before, event.after) else {
+        return Ok(false);
+    };
+    let Some(service_credentials) = &server_state.config.service_credentials else {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "Can't check for force pushes without service credentials"
+        )));
+    };
+    // Tracking force pushes is optional, and every push to a PR sends a synchronize event.
+    let Some(store) = RecordStore::from_config(server_state).await? else {
+        return Ok(false);
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let pull_request = PullRequest {
+        org: event.repository.owner.login,
+        repo: event.repository.name,
+        number: event.pull_request.number,
+    };
+    // Most pushes just add commits, and rewriting history before anyone has reviewed doesn't lose anything.
+    if !is_history_rewrite(&octocrab, &pull_request, &before, &after).await?
+        || !has_reviews(&octocrab, &pull_request).await?
+    {
+        return Ok(false);
+    }
+    record_force_push(
+        &store,
+        &ForcePush {
+            recorded_at: chrono::Utc::now(),
+            repo: pull_request.repo,
+            number: pull_request.number,
+            author: GithubLogin::from(event.pull_request.user.login),
+            before,
+            after,
+        },
+    )
+    .await?;
+    Ok(true)
+}
+
+/// When a trainee fixes the title or description of a PR the validator has commented on, validates it again,
+/// and hides the validator's comments if it now passes.
+/// Returns whether it passed, or None if it wasn't validated.
+/// If it still fails, its existing comment stands until the validator next runs on a push.
+async fn maybe_rerun_pr_validator(
+    server_state: &ServerState,
+    event: PullRequestEvent,
+) -> Result<Option<bool>, Error> {
+    let config = &server_state.config;
+    let edited_title_or_body = event
+        .changes
+        .is_some_and(|changes| changes.title.is_some() || changes.body.is_some());
+    if !config.rerun_pr_validator_on_edit || !edited_title_or_body {
+        return Ok(None);
+    }
+    let is_module = config.courses.keys().any(|course| {
+        config
+            .get_course_module_names(course)
+            .is_some_and(|module_names| module_names.contains(&event.repository.name))
+    });
+    if !is_module {
+        return Ok(None);
+    }
+    let Some(service_credentials) = &config.service_credentials else {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "Can't re-run the PR validator without service credentials"
+        )));
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let pull_request = PullRequest {
+        org: event.repository.owner.login,
+        repo: event.repository.name,
+        number: event.pull_request.number,
+    };
+    // PRs which have never failed validation have nothing to hide, so aren't worth the API calls.
+    if !has_tagged_comment(&octocrab, &pull_request, PR_METADATA_VALIDATOR_LABEL).await? {
+        return Ok(None);
+    }
+    let (result, _) = validate_pr(
+        &octocrab,
+        &server_state.in_flight,
+        module_course(pull_request.repo.clone()),
+        &pull_request.repo,
+        &pull_request.org,
+        pull_request.number,
+        &KnownRegions::default(),
+        // Checking links would mean requesting whatever URLs the trainee wrote from the tracker's network.
+        false,
+    )
+    .await?;
+    if !matches!(result, ValidationResult::Ok) {
+        return Ok(Some(false));
+    }
+    close_existing_comments(&octocrab, &pull_request, PR_METADATA_VALIDATOR_LABEL).await?;
+    Ok(Some(true))
+}
+
+/// When a Reviewed PR is approved after the trainee has pushed changes, flips its label to Complete, if auto_complete_approved_prs is set.
+/// Returns whether the PR was marked Complete.
+async fn maybe_mark_complete(
+    server_state: &ServerState,
+    event: PullRequestReviewEvent,
+) -> Result<bool, Error> {
+    let config = &server_state.config;
+    if !config.auto_complete_approved_prs
+        || event.action != "submitted"
+        || event.review.state != "approved"
+        || !event
+            .pull_request
+            .labels
+            .iter()
+            .any(|label| label.name == "Reviewed")
+    {
+        return Ok(false);
+    }
+    // Other repos in the org (e.g. group projects) don't use the review labels in the same way.
+    let is_module = config.courses.keys().any(|course| {
+        config
+            .get_course_module_names(course)
+            .is_some_and(|module_names| module_names.contains(&event.repository.name))
+    });
+    if !is_module {
+        return Ok(false);
+    }
+    let Some(service_credentials) = &config.service_credentials else {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "Can't mark PRs Complete without service credentials"
+        )));
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let pull_request = PullRequest {
+        org: event.repository.owner.login,
+        repo: event.repository.name,
+        number: event.pull_request.number,
+    };
+    let author = GithubLogin::from(event.pull_request.user.login);
+    if complete_suggestion(&octocrab, &pull_request, &author)
+        .await?
+        .is_none()
+    {
+        return Ok(false);
+    }
+    mark_complete(&octocrab, &pull_request).await?;
+    Ok(true)
+}
+
+fn review_checklist_comment(reviewer: &str, module: &str, checklist: &[String]) -> String {
+    let mut body = format!(
+        "@{} is reviewing this PR.\n\n<details>\n<summary>Review checklist for {}</summary>\n\n",
+        reviewer, module
+    );
+    for item in checklist {
+        body.push_str(&format!("- [ ] {}\n", item));
+    }
+    body.push_str("\n</details>\n");
+    body
+}
+
+fn verify_github_signature(
+    server_state: &ServerState,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), Error> {
+    let Some(secret) = &server_state.config.github_webhook_secret else {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "Received GitHub webhook but no GitHub webhook secret is configured"
+        )));
+    };
+    let Some(signature) = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Err(Error::UserFacing(format!(
+            "Missing {} header",
+            SIGNATURE_HEADER
+        )));
+    };
+    if is_valid_github_signature(secret, body, signature) {
+        Ok(())
+    } else {
+        Err(Error::UserFacing(
+            "Invalid GitHub webhook signature".to_owned(),
+        ))
+    }
+}
+
+fn is_valid_github_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("sha256=")
+        .and_then(|signature| hex::decode(signature).ok())
+    else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_valid_github_signature;
+
+    // Example taken from https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &str = "Hello, World!";
+    const SIGNATURE: &str =
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    #[test]
+    fn test_valid_signature() {
+        assert!(is_valid_github_signature(
+            SECRET,
+            BODY.as_bytes(),
+            SIGNATURE
+        ));
+    }
+
+    #[test]
+    fn test_tampered_body() {
+        assert!(!is_valid_github_signature(
+            SECRET,
+            "Hello, World?".as_bytes(),
+            SIGNATURE
+        ));
+    }
+}
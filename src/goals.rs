@@ -0,0 +1,145 @@
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Utc};
+use octocrab::Octocrab;
+
+use crate::{
+    Config, Error, github_accounts::get_trainee_logins, newtypes::GithubLogin,
+    persistence::RecordStore,
+};
+
+const GOALS_TAB: &str = "Sprint goals";
+const GOALS_HEADINGS: [&str; 6] = [
+    "GitHub",
+    "Module",
+    "Sprint",
+    "Recorded",
+    "Goals",
+    "Confidence",
+];
+
+/// How confident a trainee feels about a sprint, from 1 to 5, with the wording shown to staff.
+pub const CONFIDENCE_LEVELS: [(u8, &str); 5] = [
+    (1, "Very unsure"),
+    (2, "Unsure"),
+    (3, "Okay"),
+    (4, "Confident"),
+    (5, "Very confident"),
+];
+
+/// The id of a confidence level's wording in the trainee page catalogues (see `i18n`).
+pub fn confidence_message_id(level: impl std::fmt::Display) -> String {
+    format!("confidence-{level}")
+}
+
+/// What a trainee wants to achieve in a sprint, and how confident they feel about it, in their own words.
+/// Mentors use this alongside submissions and attendance when checking in with trainees.
+#[derive(Clone, Debug)]
+pub struct SprintGoals {
+    pub github_login: GithubLogin,
+    pub module_name: String,
+    /// 1-based, as in sprint labels.
+    pub sprint_number: usize,
+    pub recorded_at: DateTime<Utc>,
+    pub goals: String,
+    pub confidence: u8,
+}
+
+impl SprintGoals {
+    pub fn confidence_text(&self) -> &'static str {
+        CONFIDENCE_LEVELS
+            .iter()
+            .find(|(level, _)| *level == self.confidence)
+            .map(|(_, text)| *text)
+            .unwrap_or("Unknown")
+    }
+}
+
+pub async fn record_sprint_goals(store: &RecordStore, goals: &SprintGoals) -> Result<(), Error> {
+    if !CONFIDENCE_LEVELS
+        .iter()
+        .any(|(level, _)| *level == goals.confidence)
+    {
+        return Err(Error::UserFacing(format!(
+            "Confidence must be between 1 and {}",
+            CONFIDENCE_LEVELS.len()
+        )));
+    }
+    store
+        .append(
+            GOALS_TAB,
+            &GOALS_HEADINGS,
+            vec![
+                goals.github_login.to_string(),
+                goals.module_name.clone(),
+                goals.sprint_number.to_string(),
+                goals.recorded_at.to_rfc3339(),
+                goals.goals.clone(),
+                goals.confidence.to_string(),
+            ],
+        )
+        .await
+}
+
+/// Everything the trainee has recorded, newest first.
+/// If they recorded goals for the same sprint more than once, each entry is kept, as changing their mind is worth knowing about too.
+pub async fn get_sprint_goals(
+    store: &RecordStore,
+    github_login: &GithubLogin,
+) -> Result<Vec<SprintGoals>, Error> {
+    let mut goals = store
+        .read(GOALS_TAB)
+        .await?
+        .into_iter()
+        .filter(|row| {
+            row.first()
+                .is_some_and(|login| GithubLogin::from(login.clone()) == *github_login)
+        })
+        .map(|row| {
+            let [
+                github_login,
+                module_name,
+                sprint_number,
+                recorded_at,
+                goals,
+                confidence,
+            ] = row.as_slice()
+            else {
+                return Err(anyhow::anyhow!("Expected 6 columns, got {}", row.len()));
+            };
+            Ok(SprintGoals {
+                github_login: GithubLogin::from(github_login.clone()),
+                module_name: module_name.clone(),
+                sprint_number: sprint_number.parse().context("Failed to parse sprint")?,
+                recorded_at: DateTime::parse_from_rfc3339(recorded_at)
+                    .context("Failed to parse recorded time")?
+                    .to_utc(),
+                goals: goals.clone(),
+                confidence: confidence.parse().context("Failed to parse confidence")?,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .context("Failed to read sprint goals")?;
+    goals.reverse();
+    Ok(goals)
+}
+
+/// The module and sprint the trainee's batch is currently on, for pre-filling the goals form.
+/// Returns None if the trainee isn't in any batch, or their batch hasn't started yet.
+pub async fn find_current_sprint_for_trainee(
+    octocrab: &Octocrab,
+    config: &Config,
+    github_login: &GithubLogin,
+    today: NaiveDate,
+) -> Result<Option<(String, usize)>, Error> {
+    for course_info in config.courses.values() {
+        for (batch_github_slug, course_schedule) in &course_info.batches {
+            let trainees =
+                get_trainee_logins(octocrab, &config.github_org, &[batch_github_slug.clone()])
+                    .await?;
+            if trainees.contains(github_login) {
+                return Ok(course_schedule.current_sprint(today));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -9,11 +9,14 @@ use gsuite_api::{
 };
 use http::Uri;
 use tower_sessions::Session;
+use tracing::warn;
 
 use crate::{
     Error, ServerState,
+    auth::{AuthProvider, ExpiredAuthorisation},
     google_auth::{GoogleScope, make_redirect_uri, redirect_endpoint},
     newtypes::new_case_insensitive_email_address,
+    upstream::GOOGLE_DIRECTORY,
 };
 
 pub async fn groups_client(
@@ -29,13 +32,19 @@ pub async fn groups_client(
     let redirect_endpoint = redirect_endpoint(&server_state);
 
     if let Some(token) = maybe_token {
-        let client = Client::new(
-            server_state.config.google_apis_client_id.clone(),
-            server_state.config.google_apis_client_secret.to_string(),
-            &redirect_endpoint,
-            token,
-            "",
-        );
+        let client = server_state
+            .http_clients
+            .google_directory
+            .get_with(token.clone(), async {
+                Client::new(
+                    server_state.config.google_apis_client_id.clone(),
+                    server_state.config.google_apis_client_secret.to_string(),
+                    &redirect_endpoint,
+                    token,
+                    "",
+                )
+            })
+            .await;
         Ok(client)
     } else {
         Err(Error::Redirect(
@@ -70,22 +79,29 @@ pub(crate) struct GoogleGroups {
 }
 
 pub(crate) async fn get_groups(client: &Client) -> Result<GoogleGroups, Error> {
-    let groups_response = client
-        .groups()
-        .list_all(
-            "my_customer",
-            "codeyourfuture.io",
-            gsuite_api::types::DirectoryGroupsListOrderBy::Email,
-            "",
-            gsuite_api::types::SortOrder::Ascending,
-            "",
+    // Error statuses come back as successful responses (see error_for_status), so any error is a transport-level failure.
+    let groups_response = GOOGLE_DIRECTORY
+        .call(
+            client.groups().list_all(
+                "my_customer",
+                "codeyourfuture.io",
+                gsuite_api::types::DirectoryGroupsListOrderBy::Email,
+                "",
+                gsuite_api::types::SortOrder::Ascending,
+                "",
+            ),
+            |_| true,
         )
-        .await
+        .await?
         .context("Failed to list Google groups")?;
     let groups = error_for_status(groups_response)?;
     let group_member_futures = groups
         .iter()
-        .map(|Group { id, .. }| async { client.members().list_all(id, false, "").await })
+        .map(|Group { id, .. }| async {
+            GOOGLE_DIRECTORY
+                .call(client.members().list_all(id, false, ""), |_| true)
+                .await
+        })
         .collect::<Vec<_>>();
     let group_members = join_all(group_member_futures).await;
 
@@ -94,7 +110,7 @@ pub(crate) async fn get_groups(client: &Client) -> Result<GoogleGroups, Error> {
         .zip(group_members.into_iter())
         .map(|(group, members)| {
             let members =
-                error_for_status(members.context("Failed to list Google group members")?)?;
+                error_for_status(members?.context("Failed to list Google group members")?)?;
             Ok(GoogleGroup {
                 email: new_case_insensitive_email_address(&group.email).with_context(|| {
                     format!("Failed to parse group email address {}", group.email)
@@ -168,7 +184,13 @@ impl GoogleGroups {
 }
 
 fn error_for_status<T: std::fmt::Debug>(response: Response<T>) -> Result<T, Error> {
-    if !response.status.is_success() {
+    if response.status.as_u16() == 401 {
+        warn!("Google Groups rejected our token: {:?}", response);
+        Err(ExpiredAuthorisation {
+            provider: AuthProvider::GoogleGroups,
+        }
+        .into())
+    } else if !response.status.is_success() {
         Err(Error::Fatal(anyhow::anyhow!(
             "Got bad response from Google Groups API: {:?}",
             response
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use octocrab::Octocrab;
+use octocrab::models::IssueState;
+use octocrab::params::State;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, newtypes::GithubLogin, octocrab::all_pages};
+
+/// A group of trainees working together in one repo (e.g. on a final project).
+///
+/// Group work doesn't fit the one-PR-per-trainee-per-assignment model, so a group's repo activity is attributed to every member.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GroupAssignment {
+    pub name: String,
+    /// Either a repo in the configured GitHub org, or "owner/repo" for a repo elsewhere (e.g. in a trainee's account).
+    pub repo: String,
+    pub members: Vec<GithubLogin>,
+}
+
+impl GroupAssignment {
+    fn owner_and_repo<'a>(&'a self, github_org: &'a str) -> (&'a str, &'a str) {
+        self.repo
+            .split_once('/')
+            .unwrap_or((github_org, self.repo.as_str()))
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GroupProgress {
+    pub group: GroupAssignment,
+    pub repo_url: String,
+    pub merged_pull_requests: usize,
+    pub open_pull_requests: usize,
+    /// Commits on the default branch, by member. Members who haven't committed are included with 0.
+    pub member_commits: BTreeMap<GithubLogin, usize>,
+    /// Commits on the default branch which can't be attributed to a member (e.g. by staff, or from unlinked email addresses).
+    pub other_commits: usize,
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+impl GroupProgress {
+    pub fn includes(&self, github_login: &GithubLogin) -> bool {
+        self.member_commits.contains_key(github_login)
+    }
+}
+
+pub async fn get_group_progress(
+    octocrab: &Octocrab,
+    github_org: &str,
+    groups: &[GroupAssignment],
+) -> Result<Vec<GroupProgress>, Error> {
+    join_all(
+        groups
+            .iter()
+            .map(|group| get_single_group_progress(octocrab, github_org, group)),
+    )
+    .await
+    .into_iter()
+    .collect()
+}
+
+async fn get_single_group_progress(
+    octocrab: &Octocrab,
+    github_org: &str,
+    group: &GroupAssignment,
+) -> Result<GroupProgress, Error> {
+    let (owner, repo) = group.owner_and_repo(github_org);
+
+    let pull_requests = all_pages("group pull requests", octocrab, async || {
+        octocrab
+            .pulls(owner, repo)
+            .list()
+            .state(State::All)
+            .send()
+            .await
+    })
+    .await
+    .map_err(|err| err.with_context(|| format!("Failed to get PRs for group {}", group.name)))?;
+    let commits = all_pages("group commits", octocrab, async || {
+        octocrab.repos(owner, repo).list_commits().send().await
+    })
+    .await
+    .map_err(|err| {
+        err.with_context(|| format!("Failed to get commits for group {}", group.name))
+    })?;
+
+    let merged_pull_requests = pull_requests
+        .iter()
+        .filter(|pr| pr.merged_at.is_some())
+        .count();
+    let open_pull_requests = pull_requests
+        .iter()
+        .filter(|pr| pr.state == Some(IssueState::Open))
+        .count();
+
+    let mut member_commits: BTreeMap<GithubLogin, usize> = group
+        .members
+        .iter()
+        .map(|member| (member.clone(), 0))
+        .collect();
+    let mut other_commits = 0;
+    for commit in &commits {
+        // Commits whose email isn't linked to a GitHub account have no author.
+        let author = commit
+            .author
+            .as_ref()
+            .map(|author| GithubLogin::from(author.login.clone()));
+        match author.and_then(|author| member_commits.get_mut(&author)) {
+            Some(count) => *count += 1,
+            None => other_commits += 1,
+        }
+    }
+
+    let last_commit = commits
+        .iter()
+        .filter_map(|commit| commit.commit.author.as_ref()?.date)
+        .max();
+    let last_pull_request_update = pull_requests.iter().filter_map(|pr| pr.updated_at).max();
+
+    Ok(GroupProgress {
+        group: group.clone(),
+        repo_url: format!("https://github.com/{}/{}", owner, repo),
+        merged_pull_requests,
+        open_pull_requests,
+        member_commits,
+        other_commits,
+        last_activity: last_commit.max(last_pull_request_update),
+    })
+}
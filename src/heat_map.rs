@@ -0,0 +1,298 @@
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use http::HeaderMap;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::{
+        Assignment, AssignmentOptionality, Attendance, Batch, Course, Fraction,
+        SprintWithSubmissions, Submission, SubmissionState,
+    },
+    frontend::get_course_and_batch,
+};
+
+const CELL_WIDTH: usize = 24;
+const CELL_HEIGHT: usize = 16;
+const LABEL_WIDTH: usize = 200;
+// Tall enough for the sprint labels, which are rotated to fit above their narrow columns.
+const HEADER_HEIGHT: usize = 80;
+
+/// How a trainee engaged with one sprint, combining whether they came to class with whether they submitted the coursework.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprintEngagement {
+    /// Attended and submitted at least half of what was due.
+    Engaged,
+    AttendingOnly,
+    SubmittingOnly,
+    Disengaged,
+    /// Nothing in the sprint is due yet.
+    NotDue,
+}
+
+impl SprintEngagement {
+    fn for_sprint(attendance: &Fraction, submissions: &Fraction) -> Self {
+        // A sprint with no register or no mandatory coursework is judged on the half it does have.
+        let attending = attendance.percent().map(|percent| percent >= 50);
+        let submitting = submissions.percent().map(|percent| percent >= 50);
+        match (attending, submitting) {
+            (None, None) => Self::NotDue,
+            (Some(true), Some(true) | None) | (None, Some(true)) => Self::Engaged,
+            (Some(true), Some(false)) => Self::AttendingOnly,
+            (Some(false), Some(true)) => Self::SubmittingOnly,
+            (Some(false), Some(false) | None) | (None, Some(false)) => Self::Disengaged,
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            Self::Engaged => "engaged",
+            Self::AttendingOnly => "attending-only",
+            Self::SubmittingOnly => "submitting-only",
+            Self::Disengaged => "disengaged",
+            Self::NotDue => "not-due",
+        }
+    }
+
+    pub fn display_text(&self) -> &'static str {
+        match self {
+            Self::Engaged => "Attending and submitting",
+            Self::AttendingOnly => "Attending but not submitting",
+            Self::SubmittingOnly => "Submitting but not attending",
+            Self::Disengaged => "Neither attending nor submitting",
+            Self::NotDue => "Not due yet",
+        }
+    }
+}
+
+pub struct HeatMapCell {
+    pub engagement: SprintEngagement,
+    /// Classes attended (on time or late) out of those which have happened.
+    pub attendance: Fraction,
+    /// Mandatory coursework submitted out of that which is due.
+    pub submissions: Fraction,
+}
+
+impl HeatMapCell {
+    fn for_sprint(sprint: &SprintWithSubmissions) -> Self {
+        let mut attendance = Fraction {
+            numerator: 0,
+            denominator: 0,
+        };
+        let mut submissions = Fraction {
+            numerator: 0,
+            denominator: 0,
+        };
+        for submission in &sprint.submissions {
+            match submission {
+                SubmissionState::Some(Submission::Attendance(attended)) => {
+                    attendance.denominator += 1;
+                    if matches!(
+                        attended,
+                        Attendance::OnTime { .. }
+                            | Attendance::Late { .. }
+                            | Attendance::VeryLate { .. }
+                    ) {
+                        attendance.numerator += 1;
+                    }
+                }
+                SubmissionState::MissingButExpected(Assignment::Attendance { .. }) => {
+                    attendance.denominator += 1;
+                }
+                SubmissionState::Some(Submission::PullRequest {
+                    optionality: AssignmentOptionality::Mandatory,
+                    ..
+                }) => {
+                    submissions.numerator += 1;
+                    submissions.denominator += 1;
+                }
+                SubmissionState::Some(Submission::ProjectRepo(project_repo))
+                    if project_repo.optionality == AssignmentOptionality::Mandatory =>
+                {
+                    submissions.numerator += 1;
+                    submissions.denominator += 1;
+                }
+                SubmissionState::MissingButExpected(assignment)
+                    if assignment.optionality() == AssignmentOptionality::Mandatory =>
+                {
+                    submissions.denominator += 1;
+                }
+                _ => {}
+            }
+        }
+        HeatMapCell {
+            engagement: SprintEngagement::for_sprint(&attendance, &submissions),
+            attendance,
+            submissions,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let describe = |fraction: &Fraction| match fraction.percent() {
+            Some(_) => format!("{}/{}", fraction.numerator, fraction.denominator),
+            None => "none due".to_owned(),
+        };
+        format!(
+            "{}: attended {}, submitted {}",
+            self.engagement.display_text(),
+            describe(&self.attendance),
+            describe(&self.submissions)
+        )
+    }
+}
+
+pub struct HeatMapRow {
+    pub trainee_name: String,
+    pub cells: Vec<HeatMapCell>,
+}
+
+/// A batch's engagement, with a row per trainee and a column per sprint (in course order), for spotting patterns at a glance.
+pub struct HeatMap {
+    /// e.g. "JS1 S2", one per column.
+    pub sprint_labels: Vec<String>,
+    pub rows: Vec<HeatMapRow>,
+}
+
+impl HeatMap {
+    pub fn new(course: &Course, batch: &Batch) -> Self {
+        let sprint_labels = course
+            .modules
+            .iter()
+            .flat_map(|(module_name, module)| {
+                (1..=module.sprints.len())
+                    .map(move |sprint_number| format!("{} S{}", module_name, sprint_number))
+            })
+            .collect();
+        let rows = batch
+            .trainees
+            .iter()
+            .map(|trainee| HeatMapRow {
+                trainee_name: trainee.trainee.display_name().to_owned(),
+                cells: trainee
+                    .modules
+                    .values()
+                    .flat_map(|module| &module.sprints)
+                    .map(HeatMapCell::for_sprint)
+                    .collect(),
+            })
+            .collect();
+        HeatMap {
+            sprint_labels,
+            rows,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        LABEL_WIDTH + self.sprint_labels.len() * CELL_WIDTH
+    }
+
+    pub fn height(&self) -> usize {
+        HEADER_HEIGHT + self.rows.len() * CELL_HEIGHT
+    }
+
+    pub fn cell_width(&self) -> usize {
+        CELL_WIDTH
+    }
+
+    pub fn cell_height(&self) -> usize {
+        CELL_HEIGHT
+    }
+
+    pub fn column_x(&self, column: usize) -> usize {
+        LABEL_WIDTH + column * CELL_WIDTH
+    }
+
+    pub fn row_y(&self, row: usize) -> usize {
+        HEADER_HEIGHT + row * CELL_HEIGHT
+    }
+}
+
+pub async fn get_heat_map(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let heat_map = HeatMap::new(&course, &batch);
+    Ok(Html(
+        HeatMapTemplate {
+            course_name: course.name,
+            batch_name: batch.name,
+            batch_github_slug: batch.github_team_slug,
+            heat_map,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "heat-map.html")]
+struct HeatMapTemplate {
+    course_name: String,
+    batch_name: String,
+    batch_github_slug: String,
+    heat_map: HeatMap,
+}
+
+#[cfg(test)]
+mod test {
+    use super::SprintEngagement;
+    use crate::course::Fraction;
+
+    fn fraction(numerator: usize, denominator: usize) -> Fraction {
+        Fraction {
+            numerator,
+            denominator,
+        }
+    }
+
+    #[test]
+    fn combines_attendance_and_submissions() {
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(1, 1), &fraction(2, 3)),
+            SprintEngagement::Engaged
+        );
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(1, 1), &fraction(0, 3)),
+            SprintEngagement::AttendingOnly
+        );
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(0, 1), &fraction(3, 3)),
+            SprintEngagement::SubmittingOnly
+        );
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(0, 1), &fraction(1, 3)),
+            SprintEngagement::Disengaged
+        );
+    }
+
+    #[test]
+    fn judges_on_whatever_is_due() {
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(0, 0), &fraction(0, 0)),
+            SprintEngagement::NotDue
+        );
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(1, 1), &fraction(0, 0)),
+            SprintEngagement::Engaged
+        );
+        assert_eq!(
+            SprintEngagement::for_sprint(&fraction(0, 0), &fraction(0, 2)),
+            SprintEngagement::Disengaged
+        );
+    }
+}
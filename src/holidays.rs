@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate};
+use serde::Deserialize;
+
+use crate::newtypes::Region;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// Days each region doesn't hold classes (e.g. UK or South African bank holidays), which generated schedules avoid.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct HolidayCalendar(pub BTreeMap<Region, Vec<Holiday>>);
+
+/// A class which would have been on a holiday, so was moved.
+#[derive(Clone, Debug)]
+pub struct HolidayConflict {
+    pub module: String,
+    /// 1-based, as in sprint labels.
+    pub sprint: usize,
+    pub region: Region,
+    pub holiday: String,
+    pub date: NaiveDate,
+    pub moved_to: NaiveDate,
+}
+
+impl HolidayCalendar {
+    pub fn holiday(&self, region: &Region, date: NaiveDate) -> Option<&Holiday> {
+        self.0
+            .get(region)?
+            .iter()
+            .find(|holiday| holiday.date == date)
+    }
+
+    /// Moves any of a sprint's classes which fall on their region's holidays to the next day which isn't one,
+    /// returning what was moved so staff can check the new dates work.
+    pub fn avoid_holidays(
+        &self,
+        module: &str,
+        sprint: usize,
+        dates: &mut BTreeMap<Region, NaiveDate>,
+    ) -> Vec<HolidayConflict> {
+        let mut conflicts = Vec::new();
+        for (region, date) in dates.iter_mut() {
+            let Some(holiday) = self.holiday(region, *date) else {
+                continue;
+            };
+            let mut moved_to = *date;
+            while self.holiday(region, moved_to).is_some() {
+                let Some(next_day) = moved_to.checked_add_days(Days::new(1)) else {
+                    break;
+                };
+                moved_to = next_day;
+            }
+            conflicts.push(HolidayConflict {
+                module: module.to_owned(),
+                sprint,
+                region: region.clone(),
+                holiday: holiday.name.clone(),
+                date: *date,
+                moved_to,
+            });
+            *date = moved_to;
+        }
+        conflicts
+    }
+}
+
+impl std::fmt::Display for HolidayConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} sprint {} in {} was on {} ({}), so moved to {}",
+            self.module, self.sprint, self.region, self.date, self.holiday, self.moved_to
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use maplit::btreemap;
+
+    use super::{Holiday, HolidayCalendar};
+    use crate::newtypes::Region;
+
+    #[test]
+    fn moves_classes_off_consecutive_holidays() {
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let london = Region("London".to_owned());
+        let cape_town = Region("Cape Town".to_owned());
+        let calendar = HolidayCalendar(btreemap! {
+            london.clone() => vec![
+                Holiday { date: date("2027-12-25"), name: "Christmas Day".to_owned() },
+                Holiday { date: date("2027-12-26"), name: "Boxing Day".to_owned() },
+            ],
+        });
+        let mut dates = btreemap! {
+            london.clone() => date("2027-12-25"),
+            cape_town.clone() => date("2027-12-25"),
+        };
+        let conflicts = calendar.avoid_holidays("Module-Legacy", 2, &mut dates);
+        assert_eq!(dates[&london], date("2027-12-27"));
+        // Holidays are per region.
+        assert_eq!(dates[&cape_town], date("2027-12-25"));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Module-Legacy sprint 2 in London was on 2027-12-25 (Christmas Day), so moved to 2027-12-27"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! HTTP clients shared between requests, so that connections (and their TLS handshakes) to the APIs data-heavy pages call most
+//! are reused, rather than every request setting up its own.
+
+use std::time::Duration;
+
+use hyper_rustls::HttpsConnector;
+use hyper_util::client::legacy::connect::HttpConnector;
+use moka::future::Cache;
+
+pub type SheetsHttpClient = google_sheets4::common::Client<HttpsConnector<HttpConnector>>;
+
+#[derive(Clone)]
+pub struct HttpClients {
+    /// Each user's Sheets client is made on this, with their own token.
+    pub sheets: SheetsHttpClient,
+    /// For Slack's API, including OAuth and responding to commands.
+    pub slack: reqwest::Client,
+    /// For Codewars' public API, which batch pages call once per trainee.
+    pub codewars: reqwest::Client,
+    /// For exchanging GitHub OAuth codes for tokens, which octocrab doesn't do.
+    pub github_oauth: reqwest::Client,
+    /// For Codility's API, e.g. sending assessment invitations.
+    pub codility: reqwest::Client,
+    /// For the status page's checks of whether the APIs we depend on are up.
+    pub upstream_checks: reqwest::Client,
+    /// Google Directory clients can't be given an HTTP client to use, so instead each user's is kept, keyed by their token,
+    /// and reused while they're active.
+    pub google_directory: Cache<String, gsuite_api::Client>,
+}
+
+impl HttpClients {
+    pub fn new() -> Self {
+        HttpClients {
+            sheets: hyper_util::client::legacy::Client::builder(
+                hyper_util::rt::TokioExecutor::new(),
+            )
+            .build(
+                hyper_rustls::HttpsConnectorBuilder::new()
+                    .with_native_roots()
+                    .unwrap()
+                    .https_only()
+                    .enable_http1()
+                    .enable_http2()
+                    .build(),
+            ),
+            slack: reqwest::Client::new(),
+            codewars: reqwest::Client::new(),
+            github_oauth: reqwest::Client::new(),
+            codility: reqwest::Client::new(),
+            upstream_checks: reqwest::Client::new(),
+            google_directory: Cache::builder()
+                .time_to_idle(Duration::from_secs(300))
+                .build(),
+        }
+    }
+}
+
+impl Default for HttpClients {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,256 @@
+//! Translations of the text on the pages trainees use (and the PR validator's comments), as many trainees are more comfortable reading instructions in their first language.
+//!
+//! Each language has a catalogue in `locales/`, built into the binary. Staff-facing pages are English-only.
+
+use std::{collections::BTreeMap, fmt::Display, sync::LazyLock};
+
+use anyhow::Context;
+use http::{HeaderMap, header::ACCEPT_LANGUAGE};
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+use crate::Error;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "ar")]
+    Arabic,
+}
+
+static ENGLISH: LazyLock<BTreeMap<&'static str, String>> =
+    LazyLock::new(|| parse_catalogue(include_str!("../locales/en.ftl")));
+static ARABIC: LazyLock<BTreeMap<&'static str, String>> =
+    LazyLock::new(|| parse_catalogue(include_str!("../locales/ar.ftl")));
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Arabic];
+
+    /// BCP 47 code, for `?lang=`, `Accept-Language` and `<html lang>`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Arabic => "ar",
+        }
+    }
+
+    /// The language's name in itself, for the language picker.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Arabic => "العربية",
+        }
+    }
+
+    /// For `<html dir>`.
+    pub fn direction(&self) -> &'static str {
+        match self {
+            Language::English => "ltr",
+            Language::Arabic => "rtl",
+        }
+    }
+
+    fn catalogue(&self) -> &'static BTreeMap<&'static str, String> {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Arabic => &ARABIC,
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code().eq_ignore_ascii_case(code))
+    }
+
+    /// The first language we have a catalogue for in an `Accept-Language` header, e.g. `ar-EG,ar;q=0.9,en;q=0.8`.
+    /// Browsers list languages in order of preference, so the q-values are ignored.
+    fn from_accept_language(header: &str) -> Option<Self> {
+        header.split(',').find_map(|range| {
+            let tag = range.split(';').next().unwrap_or_default().trim();
+            let primary = tag.split('-').next().unwrap_or_default();
+            Self::from_code(primary)
+        })
+    }
+
+    /// Looks up a message, falling back to English for messages which haven't been translated yet.
+    pub fn t(&self, id: &str) -> String {
+        self.t_with(id, std::iter::empty::<(&str, String)>())
+    }
+
+    /// Looks up a message and fills in its `{ $name }` placeholders.
+    pub fn t_with<'a, V: Display>(
+        &self,
+        id: &str,
+        args: impl IntoIterator<Item = (&'a str, V)>,
+    ) -> String {
+        let Some(message) = self.catalogue().get(id).or_else(|| ENGLISH.get(id)) else {
+            // Shows up on the page, rather than failing the whole page over a typo.
+            return id.to_owned();
+        };
+        fill_placeholders(message, args)
+    }
+}
+
+/// Fills in a message's `{ $name }` placeholders, for messages which don't come from a catalogue (e.g. overrides in a module's metadata file).
+pub fn fill_placeholders<'a, V: Display>(
+    message: &str,
+    args: impl IntoIterator<Item = (&'a str, V)>,
+) -> String {
+    let mut message = message.to_owned();
+    for (name, value) in args {
+        message = message.replace(&format!("{{ ${name} }}"), &value.to_string());
+    }
+    message
+}
+
+/// As in Fluent, a message continues over any indented lines after it, with blank lines between them kept as paragraph breaks.
+fn parse_catalogue(source: &'static str) -> BTreeMap<&'static str, String> {
+    let mut catalogue = BTreeMap::new();
+    let mut current: Option<(&'static str, String)> = None;
+    let mut blank_lines = 0;
+    for line in source.lines() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            let (_, message) = current.as_mut().unwrap_or_else(|| {
+                panic!("Indented catalogue line isn't part of a message: {line}")
+            });
+            if !message.is_empty() {
+                message.push_str(&"\n".repeat(blank_lines + 1));
+            }
+            message.push_str(line.trim());
+        } else {
+            catalogue.extend(current.take());
+            if !line.starts_with('#') {
+                let (id, message) = line
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("Catalogue line isn't `id = message`: {line}"));
+                current = Some((id.trim(), message.trim().to_owned()));
+            }
+        }
+        blank_lines = 0;
+    }
+    catalogue.extend(current);
+    catalogue
+}
+
+const LANGUAGE_SESSION_KEY: &str = "language";
+
+/// `?lang=` on any trainee page, e.g. `?lang=ar`, sets the language for the rest of the session.
+#[derive(Deserialize)]
+pub struct LanguageParams {
+    #[serde(default)]
+    pub lang: Option<Language>,
+}
+
+/// The language to show a trainee page in: whichever they last picked this session, otherwise their browser's preference, otherwise English.
+pub(crate) async fn language(
+    session: &Session,
+    headers: &HeaderMap,
+    requested: Option<Language>,
+) -> Result<Language, Error> {
+    if let Some(requested) = requested {
+        session
+            .insert(LANGUAGE_SESSION_KEY, requested)
+            .await
+            .context("Session insert error")?;
+        return Ok(requested);
+    }
+    if let Some(language) = session
+        .get(LANGUAGE_SESSION_KEY)
+        .await
+        .context("Session load error")?
+    {
+        return Ok(language);
+    }
+    Ok(headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|header| header.to_str().ok())
+        .and_then(Language::from_accept_language)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{ENGLISH, Language, parse_catalogue};
+
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message
+            .split("{ $")
+            .skip(1)
+            .filter_map(|rest| rest.split_once(" }").map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn catalogues_translate_every_message_with_the_same_placeholders() {
+        for language in Language::ALL {
+            let catalogue = language.catalogue();
+            for (id, english) in ENGLISH.iter() {
+                let translated = catalogue
+                    .get(id)
+                    .unwrap_or_else(|| panic!("{} is missing {}", language.code(), id));
+                assert_eq!(
+                    placeholders(translated),
+                    placeholders(english),
+                    "Placeholders differ for {} in {}",
+                    id,
+                    language.code()
+                );
+            }
+            for id in catalogue.keys() {
+                assert!(
+                    ENGLISH.contains_key(id),
+                    "{} has {} which English doesn't",
+                    language.code(),
+                    id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parses_multi_line_messages() {
+        let catalogue = parse_catalogue(
+            "one = First line.\n\n    Second paragraph:\n    - a list\n# A comment\ntwo = Just one line\n",
+        );
+        assert_eq!(
+            catalogue["one"],
+            "First line.\n\nSecond paragraph:\n- a list"
+        );
+        assert_eq!(catalogue["two"], "Just one line");
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        assert_eq!(
+            Language::English.t_with("progress-attended", [("attended", 3), ("expected", 4)]),
+            "Attended 3 of 4 classes."
+        );
+        assert_eq!(
+            Language::Arabic.t_with("greeting", [("login", "octocat")]),
+            "مرحباً @octocat"
+        );
+        assert_eq!(Language::Arabic.t("no-such-message"), "no-such-message");
+    }
+
+    #[test]
+    fn picks_first_supported_language_from_accept_language() {
+        assert_eq!(
+            Language::from_accept_language("ar-EG,ar;q=0.9,en;q=0.8"),
+            Some(Language::Arabic)
+        );
+        assert_eq!(
+            Language::from_accept_language("fr-FR, en-GB;q=0.8"),
+            Some(Language::English)
+        );
+        assert_eq!(Language::from_accept_language("fr"), None);
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Context;
+use askama::Template;
+use axum::{
+    Form,
+    extract::{OriginalUri, State},
+    response::{Html, Redirect},
+};
+use http::Uri;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    audit_log::{AuditEvent, record_audit_event},
+    frontend::{current_github_login, record_store},
+    newtypes::GithubLogin,
+    octocrab::octocrab,
+};
+
+const VIEW_AS_SESSION_KEY: &str = "view_as";
+
+/// An admin looking at the tracker as someone else would see it, to debug displays which depend on the viewer's permissions.
+///
+/// We don't have the other person's tokens, so this is an approximation: pages use the admin's own access to load data,
+/// but hide whatever the other person's role wouldn't be able to see, and refuse to make changes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewAs {
+    pub github_login: GithubLogin,
+    pub role: ViewAsRole,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewAsRole {
+    /// Can't read any of the staff sheets.
+    Trainee,
+    /// Can read the staff sheets.
+    Mentor,
+}
+
+impl ViewAsRole {
+    pub fn display_text(&self) -> &'static str {
+        match self {
+            ViewAsRole::Trainee => "trainee",
+            ViewAsRole::Mentor => "mentor",
+        }
+    }
+}
+
+impl ViewAs {
+    /// Whether the person being viewed as would be able to see things only staff can.
+    pub fn is_staff(&self) -> bool {
+        self.role == ViewAsRole::Mentor
+    }
+}
+
+pub(crate) async fn view_as(session: &Session) -> Result<Option<ViewAs>, Error> {
+    Ok(session
+        .get(VIEW_AS_SESSION_KEY)
+        .await
+        .context("Session load error")?)
+}
+
+/// Viewing as someone is read-only, so that nothing is recorded as having been done by the wrong person.
+pub(crate) async fn forbid_while_viewing_as(session: &Session) -> Result<(), Error> {
+    match view_as(session).await? {
+        Some(view_as) => Err(Error::UserFacing(format!(
+            "You're viewing the tracker as @{}, which is read-only - stop viewing as them to make changes.",
+            view_as.github_login
+        ))),
+        None => Ok(()),
+    }
+}
+
+pub(crate) async fn require_admin(
+    session: &Session,
+    server_state: &ServerState,
+    original_uri: Uri,
+) -> Result<GithubLogin, Error> {
+    let octocrab = octocrab(session, server_state, original_uri).await?;
+    let github_login = current_github_login(&octocrab).await?;
+    if !server_state.config.admins.contains(&github_login) {
+        return Err(Error::UserFacing(
+            "Only admins can use admin tools".to_owned(),
+        ));
+    }
+    Ok(github_login)
+}
+
+pub async fn get_view_as(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+) -> Result<Html<String>, Error> {
+    require_admin(&session, &server_state, original_uri).await?;
+    Ok(Html(
+        ViewAsTemplate {
+            view_as: view_as(&session).await?,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ViewAsForm {
+    github_login: String,
+    role: ViewAsRole,
+}
+
+pub async fn post_view_as(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Form(form): Form<ViewAsForm>,
+) -> Result<Redirect, Error> {
+    let admin = require_admin(&session, &server_state, original_uri).await?;
+    let view_as = ViewAs {
+        github_login: GithubLogin::from(
+            form.github_login.trim().trim_start_matches('@').to_owned(),
+        ),
+        role: form.role,
+    };
+    // Record before starting, so that there's never an unrecorded impersonation.
+    record_audit_event(
+        &record_store(&server_state).await?,
+        &AuditEvent {
+            recorded_at: chrono::Utc::now(),
+            actor: admin,
+            action: "start viewing as",
+            detail: format!(
+                "@{} ({})",
+                view_as.github_login,
+                view_as.role.display_text()
+            ),
+        },
+    )
+    .await?;
+    session
+        .insert(VIEW_AS_SESSION_KEY, view_as)
+        .await
+        .context("Session insert error")?;
+    Ok(Redirect::to("/courses"))
+}
+
+pub async fn post_stop_viewing_as(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+) -> Result<Redirect, Error> {
+    let admin = require_admin(&session, &server_state, original_uri).await?;
+    let previous: Option<ViewAs> = session
+        .remove(VIEW_AS_SESSION_KEY)
+        .await
+        .context("Session remove error")?;
+    if let Some(previous) = previous {
+        record_audit_event(
+            &record_store(&server_state).await?,
+            &AuditEvent {
+                recorded_at: chrono::Utc::now(),
+                actor: admin,
+                action: "stop viewing as",
+                detail: format!("@{}", previous.github_login),
+            },
+        )
+        .await?;
+    }
+    Ok(Redirect::to("/admin/view-as"))
+}
+
+#[derive(Template)]
+#[template(path = "view-as.html")]
+struct ViewAsTemplate {
+    view_as: Option<ViewAs>,
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use futures::future::join_all;
+use octocrab::Octocrab;
+use octocrab::models::IssueState;
+use octocrab::models::issues::Issue;
+
+use crate::{Error, newtypes::GithubLogin, octocrab::all_pages};
+
+// Lets us recognise our own comments, so that re-running the sweeper doesn't comment twice.
+const REDIRECT_COMMENT_MARKER: &str = "<!--CYFTT misfiled issue-->";
+
+/// An issue in a module repo opened by a trainee, rather than by the curriculum team.
+///
+/// These are almost always step submissions which should have been opened in the trainee's fork.
+/// Because they have no curriculum labels, they also stop the module's assignments from being parsed.
+#[derive(Clone, Debug)]
+pub struct MisfiledIssue {
+    pub module_name: String,
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    pub author: GithubLogin,
+}
+
+/// Whether an issue has any of the labels the curriculum team gives to coursework (or to explicitly mark something as not coursework).
+fn has_curriculum_labels(issue: &Issue) -> bool {
+    issue.labels.iter().any(|label| {
+        label.name == "NotCoursework"
+            || label.name.starts_with("📅 Sprint ")
+            || label.name.starts_with("Submit:")
+            || label.name.starts_with("🏕 Priority")
+            || label.name.starts_with("🏝️ Priority")
+    })
+}
+
+pub async fn find_misfiled_issues(
+    octocrab: &Octocrab,
+    github_org: &str,
+    module_names: &[String],
+    trainee_logins: &BTreeSet<GithubLogin>,
+) -> Result<Vec<MisfiledIssue>, Error> {
+    let module_issues = join_all(module_names.iter().map(|module_name| {
+        all_pages("issues", octocrab, async || {
+            octocrab.issues(github_org, module_name).list().send().await
+        })
+    }))
+    .await;
+
+    let mut misfiled_issues = Vec::new();
+    for (module_name, issues) in module_names.iter().zip(module_issues) {
+        let issues = issues.map_err(|err| {
+            err.with_context(|| format!("Failed to list issues in {}", module_name))
+        })?;
+        for issue in issues {
+            let author = GithubLogin::from(issue.user.login.clone());
+            if issue.pull_request.is_some()
+                || has_curriculum_labels(&issue)
+                || !trainee_logins.contains(&author)
+            {
+                continue;
+            }
+            misfiled_issues.push(MisfiledIssue {
+                module_name: module_name.clone(),
+                number: issue.number,
+                title: issue.title,
+                html_url: issue.html_url.to_string(),
+                author,
+            });
+        }
+    }
+    Ok(misfiled_issues)
+}
+
+/// Comments on a misfiled issue explaining where it should have been opened, and optionally closes it.
+/// Returns false if we had already commented.
+pub async fn redirect_misfiled_issue(
+    octocrab: &Octocrab,
+    github_org: &str,
+    issue: &MisfiledIssue,
+    close: bool,
+) -> Result<bool, Error> {
+    let issues = octocrab.issues(github_org, &issue.module_name);
+    let comments = all_pages("issue comments", octocrab, async || {
+        issues.list_comments(issue.number).send().await
+    })
+    .await?;
+    let already_commented = comments.iter().any(|comment| {
+        comment
+            .body
+            .as_deref()
+            .is_some_and(|body| body.contains(REDIRECT_COMMENT_MARKER))
+    });
+    if !already_commented {
+        issues
+            .create_comment(issue.number, redirect_comment(github_org, issue, close))
+            .await
+            .with_context(|| format!("Failed to comment on {}", issue.html_url))?;
+    }
+    if close {
+        issues
+            .update(issue.number)
+            .state(IssueState::Closed)
+            .send()
+            .await
+            .with_context(|| format!("Failed to close {}", issue.html_url))?;
+    }
+    Ok(!already_commented)
+}
+
+fn redirect_comment(github_org: &str, issue: &MisfiledIssue, close: bool) -> String {
+    let mut body = format!(
+        "Hi @{}! It looks like this issue was opened in the {} {} repo, which is shared by everyone on the course.\n\n\
+         Issues for tracking your own work should be opened in your fork: https://github.com/{}/{}/issues\n\n\
+         To do this, copy the issue from the coursework into your fork, rather than clicking \"Create issue\" here.",
+        issue.author, github_org, issue.module_name, issue.author, issue.module_name,
+    );
+    if close {
+        body.push_str("\n\nI've closed this issue - please open it again in your fork.");
+    }
+    body.push('\n');
+    body.push_str(REDIRECT_COMMENT_MARKER);
+    body
+}
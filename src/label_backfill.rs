@@ -0,0 +1,138 @@
+use anyhow::Context;
+use octocrab::Octocrab;
+use octocrab::models::issues::Issue;
+
+use crate::{Error, course::sprint_number_in, octocrab::all_pages};
+
+const SPRINT_LABEL_PREFIX: &str = "📅 Sprint ";
+const MANDATORY_LABEL: &str = "🏕 Priority Mandatory";
+const STRETCH_LABEL: &str = "🏝️ Priority Stretch";
+
+/// Labels we would add to a module issue so that its assignment can be parsed, and where we got them from.
+#[derive(Clone, Debug)]
+pub struct LabelBackfill {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    /// (label, reason) pairs.
+    pub labels_to_add: Vec<(String, String)>,
+    /// Labels which are missing, but which we couldn't work out, so a human needs to add.
+    pub unresolved: Vec<&'static str>,
+}
+
+/// Looks for coursework issues in the module which are missing a sprint or priority label, and works out what they should be from the issue's title or milestone.
+///
+/// Only issues with a `Submit:` label are considered, as that's what marks an issue as coursework - anything else may be a trainee's misfiled issue, which triage-issues deals with.
+pub async fn find_label_backfills(
+    octocrab: &Octocrab,
+    github_org: &str,
+    module_name: &str,
+) -> Result<Vec<LabelBackfill>, Error> {
+    let issues = all_pages("issues", octocrab, async || {
+        octocrab.issues(github_org, module_name).list().send().await
+    })
+    .await
+    .map_err(|err| err.with_context(|| format!("Failed to list issues in {}", module_name)))?;
+    Ok(issues.iter().filter_map(label_backfill).collect())
+}
+
+fn label_backfill(issue: &Issue) -> Option<LabelBackfill> {
+    let label_names = issue
+        .labels
+        .iter()
+        .map(|label| label.name.as_str())
+        .collect::<Vec<_>>();
+    if issue.pull_request.is_some()
+        || label_names.contains(&"NotCoursework")
+        || !label_names.iter().any(|name| name.starts_with("Submit:"))
+    {
+        return None;
+    }
+
+    let milestone_title = issue
+        .milestone
+        .as_ref()
+        .map(|milestone| milestone.title.as_str());
+    let mut labels_to_add = Vec::new();
+    let mut unresolved = Vec::new();
+
+    if !label_names
+        .iter()
+        .any(|name| name.starts_with(SPRINT_LABEL_PREFIX))
+    {
+        let sprint = sprint_number_in(&issue.title)
+            .map(|sprint| (sprint, "title"))
+            .or_else(|| {
+                milestone_title
+                    .and_then(sprint_number_in)
+                    .map(|sprint| (sprint, "milestone"))
+            });
+        match sprint {
+            Some((sprint, source)) => labels_to_add.push((
+                format!("{}{}", SPRINT_LABEL_PREFIX, sprint),
+                format!("from {}", source),
+            )),
+            None => unresolved.push("sprint"),
+        }
+    }
+
+    if !label_names.contains(&MANDATORY_LABEL) && !label_names.contains(&STRETCH_LABEL) {
+        let priority = priority_label_in(&issue.title)
+            .map(|label| (label, "title"))
+            .or_else(|| {
+                milestone_title
+                    .and_then(priority_label_in)
+                    .map(|label| (label, "milestone"))
+            });
+        match priority {
+            Some((label, source)) => {
+                labels_to_add.push((label.to_owned(), format!("from {}", source)))
+            }
+            None => unresolved.push("priority"),
+        }
+    }
+
+    if labels_to_add.is_empty() && unresolved.is_empty() {
+        return None;
+    }
+    Some(LabelBackfill {
+        number: issue.number,
+        title: issue.title.clone(),
+        html_url: issue.html_url.to_string(),
+        labels_to_add,
+        unresolved,
+    })
+}
+
+fn priority_label_in(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    if lower.contains("stretch") || lower.contains("optional") {
+        Some(STRETCH_LABEL)
+    } else if lower.contains("mandatory") || lower.contains("required") {
+        Some(MANDATORY_LABEL)
+    } else {
+        None
+    }
+}
+
+pub async fn apply_label_backfill(
+    octocrab: &Octocrab,
+    github_org: &str,
+    module_name: &str,
+    backfill: &LabelBackfill,
+) -> Result<(), Error> {
+    if backfill.labels_to_add.is_empty() {
+        return Ok(());
+    }
+    let labels = backfill
+        .labels_to_add
+        .iter()
+        .map(|(label, _reason)| label.clone())
+        .collect::<Vec<_>>();
+    octocrab
+        .issues(github_org, module_name)
+        .add_labels(backfill.number, &labels)
+        .await
+        .with_context(|| format!("Failed to add labels to {}", backfill.html_url))?;
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    response::Html,
+};
+use http::HeaderMap;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::{
+        Assignment, AssignmentOptionality, Batch, SprintWithSubmissions, Submission,
+        SubmissionState, TraineeWithSubmissions, find_batch_of_trainee,
+    },
+    frontend::{current_github_login, get_course_and_batch},
+    i18n::{Language, LanguageParams, language},
+    impersonation::{ViewAs, view_as},
+    newtypes::GithubLogin,
+    octocrab::{octocrab, octocrab_for_token},
+    sheets::SheetsClient,
+};
+
+/// A trainee's run of sprints with all their mandatory work in.
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub github_login: GithubLogin,
+    /// Consecutive sprints, up to the most recent one with work due, with all mandatory work submitted.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+/// The batch's trainees, longest current streak first.
+pub fn leaderboard(batch: &Batch) -> Vec<LeaderboardEntry> {
+    let mut entries = batch
+        .trainees
+        .iter()
+        .map(|trainee| {
+            let (current_streak, longest_streak) = sprint_streaks(trainee);
+            LeaderboardEntry {
+                name: trainee.trainee.display_name().to_owned(),
+                github_login: trainee.trainee.github_login.clone(),
+                current_streak,
+                longest_streak,
+            }
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| {
+        (b.current_streak, b.longest_streak)
+            .cmp(&(a.current_streak, a.longest_streak))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entries
+}
+
+/// The trainee's current and longest streaks of sprints with all mandatory work submitted.
+/// Sprints with no mandatory work due yet neither extend nor break a streak.
+fn sprint_streaks(trainee: &TraineeWithSubmissions) -> (usize, usize) {
+    let mut current = 0;
+    let mut longest = 0;
+    for sprint in trainee
+        .modules
+        .values()
+        .flat_map(|module| &module.sprints)
+        .filter(|sprint| has_mandatory_work_due(sprint))
+    {
+        if sprint.submissions.iter().any(is_missing_work) {
+            current = 0;
+        } else {
+            current += 1;
+            longest = longest.max(current);
+        }
+    }
+    (current, longest)
+}
+
+fn has_mandatory_work_due(sprint: &SprintWithSubmissions) -> bool {
+    sprint.submissions.iter().any(|submission| {
+        is_missing_work(submission)
+            || matches!(
+                submission,
+                SubmissionState::Some(Submission::PullRequest {
+                    optionality: AssignmentOptionality::Mandatory,
+                    ..
+                }) | SubmissionState::Some(Submission::ProjectRepo(_))
+            )
+    })
+}
+
+/// Missed classes don't count against a streak, which is about coursework.
+fn is_missing_work(submission: &SubmissionState) -> bool {
+    matches!(
+        submission,
+        SubmissionState::MissingButExpected(
+            Assignment::ExpectedPullRequest { .. } | Assignment::ProjectRepo { .. }
+        )
+    )
+}
+
+/// The staff view of an opted-in batch's leaderboard, with every trainee.
+pub async fn get_batch_leaderboard(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    if !server_state
+        .config
+        .courses
+        .get(course.as_str())
+        .and_then(|course_info| course_info.batches.get(batch_github_slug.as_str()))
+        .is_some_and(|schedule| schedule.leaderboard)
+    {
+        return Err(Error::UserFacing(
+            "This batch hasn't opted in to a leaderboard - set leaderboard on it in the config"
+                .to_owned(),
+        ));
+    }
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    Ok(Html(
+        LeaderboardTemplate {
+            course_name: course.name,
+            batch_name: batch.name.clone(),
+            batch_github_slug: batch.github_team_slug.clone(),
+            entries: leaderboard(&batch),
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+/// The trainee view of their own batch's leaderboard, if it has opted in.
+/// Only shows names and streaks, and is read with the service credentials, since trainees can't read the sheets batches are built from.
+pub async fn get_me_leaderboard(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Query(params): Query<LanguageParams>,
+) -> Result<Html<String>, Error> {
+    let lang = language(&session, &headers, params.lang).await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let view_as = view_as(&session).await?;
+    let github_login = match &view_as {
+        Some(view_as) => view_as.github_login.clone(),
+        None => current_github_login(&octocrab).await?,
+    };
+    let Some(service_credentials) = &server_state.config.service_credentials else {
+        return Err(Error::UserFacing(
+            "The trainee tracker isn't configured with service credentials, so can't show leaderboards.".to_owned(),
+        ));
+    };
+    let service_octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await?;
+    let Some((_course, batch)) = find_batch_of_trainee(
+        &service_octocrab,
+        sheets_client,
+        &server_state.config,
+        &github_login,
+        |schedule| schedule.leaderboard,
+    )
+    .await?
+    else {
+        return Err(Error::UserFacing(lang.t("leaderboard-not-opted-in")));
+    };
+    Ok(Html(
+        MeLeaderboardTemplate {
+            batch_name: batch.name.clone(),
+            entries: leaderboard(&batch),
+            github_login,
+            view_as,
+            lang,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "leaderboard.html")]
+struct LeaderboardTemplate {
+    course_name: String,
+    batch_name: String,
+    batch_github_slug: String,
+    entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Template)]
+#[template(path = "me-leaderboard.html")]
+struct MeLeaderboardTemplate {
+    batch_name: String,
+    entries: Vec<LeaderboardEntry>,
+    /// The trainee viewing the leaderboard, whose row is highlighted.
+    github_login: GithubLogin,
+    view_as: Option<ViewAs>,
+    lang: Language,
+}
@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::sync::Arc;
 use std::time::Duration;
 
 use askama::Template;
@@ -6,33 +8,100 @@ use axum::http::{StatusCode, Uri};
 use axum::response::{Html, IntoResponse, Response};
 use moka::future::Cache;
 use slack_with_types::client::RateLimiter;
-use tracing::error;
+use tracing::{error, warn};
 use tracing_subscriber::Layer;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use uuid::Uuid;
 
+pub mod absence_streaks;
+pub mod admissions;
+pub mod api_v1;
+pub mod assessments;
+pub mod assignment_diff;
+pub mod attendance_policy;
+pub mod audit_log;
 pub mod auth;
+pub mod burndown;
+pub mod check_in;
 pub mod config;
+pub mod copied_bodies;
 pub use config::Config;
 
+use crate::auth::{ExpiredAuthorisation, PermissionDenied, expired_authorisation};
 use crate::google_auth::GoogleScope;
+use crate::status::RefreshStatus;
+use crate::upstream::UpstreamUnavailable;
+pub mod codewars;
 pub mod codility;
+pub mod commit_authorship;
+pub mod complete_suggestions;
 pub mod course;
+pub mod course_definitions;
+pub mod data_health;
+pub mod data_quality;
+pub mod diff_similarity;
+pub mod discussions;
+pub mod early_engagement;
 pub mod endpoints;
+pub mod error_reports;
+pub mod event_webhooks;
+pub mod force_pushes;
+pub mod forecast;
 pub mod frontend;
+pub mod funnel;
 pub mod github_accounts;
+pub mod github_webhooks;
+pub mod goals;
 pub mod google_auth;
 pub mod google_groups;
+pub mod groups;
+pub mod heat_map;
+pub mod holidays;
+pub mod http_clients;
+pub mod i18n;
+pub mod impersonation;
+pub mod issue_triage;
+pub mod label_backfill;
+pub mod leaderboard;
+pub mod lookup;
+pub mod mentor_home;
 pub mod mentoring;
+pub mod mentors;
+pub mod misfiled_prs;
+pub mod module_discovery;
 pub mod newtypes;
 pub mod octocrab;
+pub mod on_call;
+pub mod pdf;
+pub mod peer_review;
+pub mod persistence;
 pub mod pr_comments;
+pub mod pr_template;
+pub mod pr_validator;
+pub mod project_repos;
 pub mod prs;
 pub mod register;
+pub mod review_sla;
+pub mod review_threads;
+pub mod reviewer_alerts;
 pub mod reviewer_staff_info;
+pub mod risk_rules;
 pub mod sheets;
+pub mod similarity;
+pub mod single_flight;
 pub mod slack;
+pub mod slack_channel_audit;
+pub mod slack_commands;
+pub mod slack_usergroups;
+pub mod snapshots;
+pub mod sprint_summaries;
+pub mod staff_notes;
+pub mod staff_workload;
+pub mod status;
+pub mod timeline;
+pub mod trainee_directory;
+pub mod upstream;
 
 #[derive(Clone)]
 pub struct ServerState {
@@ -40,6 +109,20 @@ pub struct ServerState {
     pub google_auth_state_cache: Cache<Uuid, GoogleAuthState>,
     pub slack_auth_state_cache: Cache<Uuid, Uri>,
     pub slack_rate_limiters: Cache<String, RateLimiter>,
+    /// Course name -> outcome of loading its data, for the status page.
+    pub course_refreshes: Cache<String, RefreshStatus>,
+    /// Check-in code -> the class it checks trainees in to. Codes expire quickly, so they can't be passed on to people who aren't in class.
+    pub check_in_codes: Cache<String, check_in::CheckInClass>,
+    /// GitHub login -> how many wrong check-in codes they've entered recently, so codes can't be guessed.
+    pub wrong_check_in_codes: Cache<newtypes::GithubLogin, u32>,
+    /// Codewars username -> their progress, so profiles aren't fetched again on every page load.
+    pub codewars_progress: Cache<String, codewars::CodewarsProgress>,
+    /// `org/repo` -> the Codewars usernames trainees gave in its issues.
+    pub codewars_issue_usernames: Cache<String, Arc<BTreeMap<newtypes::GithubLogin, String>>>,
+    /// Fetches currently being made, so that people loading the same page at once share them.
+    pub in_flight: single_flight::InFlight,
+    pub trainee_directory: trainee_directory::TraineeDirectory,
+    pub http_clients: http_clients::HttpClients,
     pub config: Config,
 }
 
@@ -52,6 +135,24 @@ impl ServerState {
             slack_rate_limiters: Cache::builder()
                 .time_to_idle(Duration::from_secs(300))
                 .build(),
+            course_refreshes: Cache::new(1_000),
+            check_in_codes: Cache::builder()
+                .time_to_live(check_in::CHECK_IN_CODE_LIFETIME)
+                .build(),
+            wrong_check_in_codes: Cache::builder()
+                .time_to_live(check_in::WRONG_CHECK_IN_CODES_LIFETIME)
+                .build(),
+            codewars_progress: Cache::builder()
+                .time_to_live(codewars::CODEWARS_PROGRESS_CACHE_DURATION)
+                .build(),
+            codewars_issue_usernames: Cache::builder()
+                .time_to_live(codewars::CODEWARS_USERNAMES_CACHE_DURATION)
+                .build(),
+            in_flight: single_flight::InFlight::default(),
+            trainee_directory: trainee_directory::TraineeDirectory::new(
+                config.trainee_directory_cache_ttl(),
+            ),
+            http_clients: http_clients::HttpClients::new(),
             config,
         }
     }
@@ -72,9 +173,10 @@ pub enum Error {
     /// An error which cannot be rectified by the user directly, or where we don't know if the error contains sensitive data.
     /// Never display this directly to a user. It can be logged on the server side, but if you're going to show something to a user, instead use a UserFacing.
     Fatal(anyhow::Error),
-    /// An error message which was caused by a lack of permissions, and where the caller _may_ want to ignore the lack of data.
-    /// It is up to the caller to decide whether to treat this error as fatal, or whether to e.g. fall back to default data.
-    PotentiallyIgnorablePermissions(anyhow::Error),
+    /// An error which was caused by a lack of permissions, and where the caller _may_ want to ignore the lack of data.
+    /// It is up to the caller to decide whether to treat this error as fatal, or whether to e.g. fall back to default data,
+    /// in which case it should consider telling the user what they're missing (see `PermissionDenied::user_facing_message`).
+    PermissionDenied(PermissionDenied),
     /// An instruction that we should redirect the user to another page.
     /// Not really an error as such. This tends to be returned by code which require auth to say "please authenticate via OAuth somewhere, and try again".
     Redirect(Uri),
@@ -85,8 +187,8 @@ impl Error {
         match self {
             Self::UserFacing(message) => Self::UserFacing(message),
             Self::Fatal(err) => Self::Fatal(err.context(context)),
-            Self::PotentiallyIgnorablePermissions(err) => {
-                Self::PotentiallyIgnorablePermissions(err.context(context))
+            Self::PermissionDenied(denied) => {
+                Self::PermissionDenied(denied.map_source(|err| err.context(context)))
             }
             Self::Redirect(redirect) => Self::Redirect(redirect),
         }
@@ -96,8 +198,8 @@ impl Error {
         match self {
             Self::UserFacing(message) => Self::UserFacing(message),
             Self::Fatal(err) => Self::Fatal(err.context(f())),
-            Self::PotentiallyIgnorablePermissions(err) => {
-                Self::PotentiallyIgnorablePermissions(err.context(f()))
+            Self::PermissionDenied(denied) => {
+                Self::PermissionDenied(denied.map_source(|err| err.context(f())))
             }
             Self::Redirect(redirect) => Self::Redirect(redirect),
         }
@@ -107,10 +209,48 @@ impl Error {
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         match self {
-            // We handle PotentiallyIgnorablePermissions like a Fatal error because if it was ignorable, we assume some code would have handled it before we got to making a response.
-            Error::Fatal(err) | Error::PotentiallyIgnorablePermissions(err) => {
+            // If it was ignorable, we assume some code would have handled it before we got to making a response, so here it's fatal - but we can say why.
+            Error::PermissionDenied(denied) => {
+                error!("Permission denied: {error:?}", error = denied.source);
+                (StatusCode::FORBIDDEN, denied.user_facing_message()).into_response()
+            }
+            Error::Fatal(err) => {
+                if let Some(provider) = expired_authorisation(&err) {
+                    warn!("Token expired mid-session: {error:?}", error = err);
+                    let rendered = ReauthoriseTemplate {
+                        provider_name: provider.display_name(),
+                        provider_slug: provider.slug(),
+                    }
+                    .render();
+                    return match rendered {
+                        Ok(str) => (StatusCode::UNAUTHORIZED, Html(str)).into_response(),
+                        Err(err) => Error::Fatal(err.into())
+                            .context("Failed to render Reauthorise")
+                            .into_response(),
+                    };
+                }
                 error!("Fatal error: {error:?}", error = err);
-                (StatusCode::INTERNAL_SERVER_ERROR, "An error occurred").into_response()
+                // We wrote these messages, so they're safe to show, and tell the user it's not their fault.
+                let mut response = if let Some(unavailable) = err
+                    .chain()
+                    .find_map(|err| err.downcast_ref::<UpstreamUnavailable>())
+                {
+                    (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        format!("An error occurred: {unavailable}"),
+                    )
+                        .into_response()
+                } else {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "An error occurred").into_response()
+                };
+                // For error_reports::report_fatal_errors, which knows which request this was.
+                response
+                    .extensions_mut()
+                    .insert(error_reports::ErrorReport::fatal(
+                        &err,
+                        error_reports::ErrorContext::default(),
+                    ));
+                response
             }
             Error::UserFacing(message) => {
                 error!("Fatal user-facing error: {message}");
@@ -133,10 +273,19 @@ impl IntoResponse for Error {
     }
 }
 
+/// Shown in place of a page whose data couldn't be loaded because a token expired, linking to reconnect and come back to the same page.
+#[derive(Template)]
+#[template(path = "reauthorise.html")]
+struct ReauthoriseTemplate {
+    provider_name: &'static str,
+    provider_slug: &'static str,
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::Fatal(err) | Error::PotentiallyIgnorablePermissions(err) => err.fmt(f),
+            Error::Fatal(err) => err.fmt(f),
+            Error::PermissionDenied(denied) => denied.source.fmt(f),
             Error::UserFacing(message) => write!(f, "{}", message),
             Error::Redirect(_) => write!(f, "<redirect>"),
         }
@@ -149,6 +298,18 @@ impl From<anyhow::Error> for Error {
     }
 }
 
+impl From<UpstreamUnavailable> for Error {
+    fn from(error: UpstreamUnavailable) -> Self {
+        Error::Fatal(error.into())
+    }
+}
+
+impl From<ExpiredAuthorisation> for Error {
+    fn from(error: ExpiredAuthorisation) -> Self {
+        Error::Fatal(error.into())
+    }
+}
+
 pub fn setup_logging() {
     let stderr_log_level = tracing_subscriber::filter::LevelFilter::INFO;
     let stderr_layer = tracing_subscriber::fmt::layer()
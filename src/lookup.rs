@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Redirect,
+};
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState, github_accounts::get_trainee_logins, newtypes::GithubLogin,
+    octocrab::octocrab, pr_comments::PullRequest,
+};
+
+/// The HTML id of a PR's cell on the batch page, so that it can be linked to.
+pub fn pr_cell_id(repo_name: &str, number: u64) -> String {
+    format!("pr-{}-{}", repo_name, number)
+}
+
+#[derive(Deserialize)]
+pub struct LookupParams {
+    pr: String,
+}
+
+/// `/lookup?pr=<url>` redirects to the cell for a PR on its author's batch page, for mentors who start from a PR and want context.
+pub async fn lookup(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Query(params): Query<LookupParams>,
+) -> Result<Redirect, Error> {
+    let pull_request = PullRequest::from_html_url(params.pr.trim().trim_end_matches('/'))?;
+    let github_org = &server_state.config.github_org;
+    if &pull_request.org != github_org {
+        return Err(Error::UserFacing(format!(
+            "{} isn't in the {} GitHub org, so the tracker doesn't know about it",
+            pull_request.html_url(),
+            github_org
+        )));
+    }
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let pr = octocrab
+        .pulls(&pull_request.org, &pull_request.repo)
+        .get(pull_request.number)
+        .await
+        .with_context(|| format!("Failed to get PR {}", pull_request.html_url()))?;
+    let author = GithubLogin::from(
+        pr.user
+            .ok_or_else(|| Error::UserFacing("PR has no author".to_owned()))?
+            .login,
+    );
+
+    for (course_name, course_info) in &server_state.config.courses {
+        for batch_github_slug in course_info.batches.keys() {
+            // UNWRAP: We're iterating over the config's own courses and batches.
+            let course_schedule = server_state
+                .config
+                .get_course_schedule_with_register_sheet_id(
+                    course_name.to_string(),
+                    batch_github_slug,
+                )
+                .unwrap();
+            let repos = course_schedule
+                .course_schedule
+                .sprints
+                .keys()
+                .flat_map(|module_name| course_schedule.module_repos(module_name))
+                .collect::<BTreeSet<_>>();
+            if !repos.contains(&pull_request.repo) {
+                continue;
+            }
+            let trainee_logins = get_trainee_logins(
+                &octocrab,
+                github_org,
+                std::slice::from_ref(batch_github_slug),
+            )
+            .await?;
+            if trainee_logins.contains(&author) {
+                return Ok(Redirect::to(&format!(
+                    "/courses/{}/batches/{}#{}",
+                    course_name,
+                    batch_github_slug,
+                    pr_cell_id(&pull_request.repo, pull_request.number)
+                )));
+            }
+        }
+    }
+    Err(Error::UserFacing(format!(
+        "Couldn't find a batch with @{} as a trainee which has {} as a module",
+        author, pull_request.repo
+    )))
+}
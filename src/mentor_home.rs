@@ -0,0 +1,338 @@
+use std::collections::BTreeSet;
+
+use askama::Template;
+use axum::{
+    Form,
+    extract::{OriginalUri, Path, Query, State},
+    response::{Html, Redirect},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::future::join_all;
+use http::HeaderMap;
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::TraineeStatus,
+    discussions::{HelpRequest, get_unanswered_help_requests},
+    frontend::{current_github_login, get_course_and_batch, record_store},
+    impersonation::{forbid_while_viewing_as, view_as},
+    mentoring::MentoringRecord,
+    newtypes::{GithubLogin, Region},
+    octocrab::octocrab,
+    persistence::RecordStore,
+    prs::{Pr, PrState, get_prs},
+    risk_rules::triggered_rules,
+};
+
+const PREFERENCES_TAB: &str = "Mentor preferences";
+const PREFERENCES_HEADINGS: [&str; 5] = ["GitHub", "Course", "Recorded", "Regions", "Modules"];
+
+/// Which of a course's regions and modules a volunteer looks after, to narrow down their homepage.
+/// Empty means all of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MentorPreferences {
+    pub regions: BTreeSet<Region>,
+    pub modules: BTreeSet<String>,
+}
+
+impl MentorPreferences {
+    pub fn includes_region(&self, region: &Region) -> bool {
+        self.regions.is_empty() || self.regions.contains(region)
+    }
+
+    pub fn includes_module(&self, module_name: &str) -> bool {
+        self.modules.is_empty() || self.modules.contains(module_name)
+    }
+}
+
+/// The volunteer's most recently saved preferences for the course, or the defaults if they haven't saved any.
+pub async fn get_mentor_preferences(
+    store: &RecordStore,
+    github_login: &GithubLogin,
+    course_name: &str,
+) -> Result<MentorPreferences, Error> {
+    let Some(row) = store
+        .read(PREFERENCES_TAB)
+        .await?
+        .into_iter()
+        .rev()
+        .find(|row| {
+            row.first()
+                .is_some_and(|login| GithubLogin::from(login.clone()) == *github_login)
+                && row.get(1).is_some_and(|course| course == course_name)
+        })
+    else {
+        return Ok(MentorPreferences::default());
+    };
+    let list = |index: usize| {
+        row.get(index)
+            .map(|cell| {
+                cell.split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_owned)
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default()
+    };
+    Ok(MentorPreferences {
+        regions: list(3).into_iter().map(Region).collect(),
+        modules: list(4),
+    })
+}
+
+pub async fn record_mentor_preferences(
+    store: &RecordStore,
+    github_login: &GithubLogin,
+    course_name: &str,
+    preferences: &MentorPreferences,
+) -> Result<(), Error> {
+    store
+        .append(
+            PREFERENCES_TAB,
+            &PREFERENCES_HEADINGS,
+            vec![
+                github_login.to_string(),
+                course_name.to_owned(),
+                Utc::now().to_rfc3339(),
+                preferences
+                    .regions
+                    .iter()
+                    .map(|region| region.0.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                preferences
+                    .modules
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ],
+        )
+        .await
+}
+
+/// A trainee who is at risk and hasn't had a mentoring check-in recently.
+#[derive(Clone, Debug)]
+pub struct UnmentoredTrainee {
+    pub name: String,
+    pub github_login: GithubLogin,
+    pub region: Region,
+    pub batch_github_slug: String,
+    /// The trainee's at-risk badges, or "At risk" if only their progress score puts them at risk.
+    pub reasons: Vec<String>,
+    pub last_mentored: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct MentorHomeParams {
+    #[serde(default)]
+    saved: bool,
+}
+
+/// What a volunteer could usefully do this week in a course: PRs to review, at-risk trainees to check in with, and unanswered questions.
+pub async fn get_mentor_home(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+    Query(params): Query<MentorHomeParams>,
+) -> Result<Html<String>, Error> {
+    let config = &server_state.config;
+    let course_info = config
+        .courses
+        .get(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let module_names = config
+        .get_course_module_names(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let regions = course_info
+        .batches
+        .values()
+        .flat_map(|schedule| schedule.sprints.values().flatten())
+        .flat_map(|dates| dates.keys().cloned())
+        .collect::<BTreeSet<_>>();
+
+    let octocrab = octocrab(&session, &server_state, original_uri.clone()).await?;
+    let github_login = match view_as(&session).await? {
+        Some(view_as) => view_as.github_login,
+        None => current_github_login(&octocrab).await?,
+    };
+    // Without a record store, nobody can save preferences, so everyone sees everything.
+    let store = RecordStore::from_config(&server_state).await?;
+    let preferences = match &store {
+        Some(store) => get_mentor_preferences(store, &github_login, &course).await?,
+        None => MentorPreferences::default(),
+    };
+    let my_modules = module_names
+        .iter()
+        .filter(|module_name| preferences.includes_module(module_name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let pr_futures = my_modules.iter().map(async |module_name| {
+        let prs = get_prs(
+            &octocrab,
+            &server_state.in_flight,
+            &config.github_org,
+            module_name,
+            false,
+        )
+        .await?;
+        Ok::<_, Error>(
+            prs.into_iter()
+                .filter(|pr| pr.state == PrState::NeedsReview)
+                .collect::<Vec<_>>(),
+        )
+    });
+    let mut prs_needing_review = Vec::new();
+    for result in join_all(pr_futures).await {
+        prs_needing_review.extend(result?);
+    }
+    prs_needing_review.sort_by_key(|pr| pr.updated_at);
+
+    let help_requests = get_unanswered_help_requests(
+        &octocrab,
+        &config.github_org,
+        &my_modules,
+        config.help_request_age(),
+    )
+    .await?;
+
+    let today = Utc::now().date_naive();
+    let running_batches = course_info
+        .batches
+        .iter()
+        .filter(|(_, schedule)| schedule.start <= today && today <= schedule.end)
+        .map(|(batch_github_slug, _)| batch_github_slug.to_string());
+    let batch_futures = running_batches.map(async |batch_github_slug| {
+        let (_, batch, _) = get_course_and_batch(
+            &session,
+            headers.clone(),
+            &server_state,
+            original_uri.clone(),
+            course.clone(),
+            &batch_github_slug,
+        )
+        .await?;
+        Ok::<_, Error>(
+            batch
+                .trainees
+                .into_iter()
+                .filter(|trainee| preferences.includes_region(&trainee.trainee.region))
+                .filter(|trainee| {
+                    !trainee
+                        .mentoring_record
+                        .as_ref()
+                        .is_some_and(MentoringRecord::is_recent)
+                })
+                .filter_map(|trainee| {
+                    let mut reasons = triggered_rules(&config.at_risk_rules, &trainee, today)
+                        .into_iter()
+                        .map(|rule| rule.name.clone())
+                        .collect::<Vec<_>>();
+                    if reasons.is_empty() && trainee.status() == TraineeStatus::AtRisk {
+                        reasons.push("At risk".to_owned());
+                    }
+                    (!reasons.is_empty()).then(|| UnmentoredTrainee {
+                        name: trainee.trainee.display_name().to_owned(),
+                        github_login: trainee.trainee.github_login.clone(),
+                        region: trainee.trainee.region.clone(),
+                        batch_github_slug: batch_github_slug.clone(),
+                        reasons,
+                        last_mentored: trainee
+                            .mentoring_record
+                            .as_ref()
+                            .map(|record| record.last_date),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )
+    });
+    let mut unmentored_trainees = Vec::new();
+    for result in join_all(batch_futures).await {
+        unmentored_trainees.extend(result?);
+    }
+    // Never mentored first, then whoever has gone longest without.
+    unmentored_trainees.sort_by_key(|trainee| trainee.last_mentored);
+
+    Ok(Html(
+        MentorHomeTemplate {
+            course,
+            github_login,
+            regions,
+            module_names,
+            preferences,
+            can_save_preferences: store.is_some(),
+            saved: params.saved,
+            prs_needing_review,
+            unmentored_trainees,
+            help_requests,
+            now: Utc::now(),
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+/// Checkboxes named `region` and `module`, one pair per ticked box.
+pub async fn post_mentor_home_preferences(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Result<Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let module_names = server_state
+        .config
+        .get_course_module_names(&course)
+        .ok_or(Error::UserFacing("Unknown course".to_owned()))?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let github_login = current_github_login(&octocrab).await?;
+    let mut preferences = MentorPreferences::default();
+    for (key, value) in form {
+        match key.as_str() {
+            "region" => {
+                preferences.regions.insert(Region(value));
+            }
+            "module" if module_names.contains(&value) => {
+                preferences.modules.insert(value);
+            }
+            _ => {}
+        }
+    }
+    let store = record_store(&server_state).await?;
+    record_mentor_preferences(&store, &github_login, &course, &preferences).await?;
+    Ok(Redirect::to(&format!(
+        "/courses/{}/this-week?saved=true",
+        course
+    )))
+}
+
+#[derive(Template)]
+#[template(path = "mentor-home.html")]
+struct MentorHomeTemplate {
+    course: String,
+    github_login: GithubLogin,
+    /// Every region with classes in the course, for the preferences form.
+    regions: BTreeSet<Region>,
+    module_names: Vec<String>,
+    preferences: MentorPreferences,
+    can_save_preferences: bool,
+    saved: bool,
+    /// Oldest first.
+    prs_needing_review: Vec<Pr>,
+    unmentored_trainees: Vec<UnmentoredTrainee>,
+    help_requests: Vec<HelpRequest>,
+    now: DateTime<Utc>,
+}
+
+impl MentorHomeTemplate {
+    fn days_waiting(&self, pr: &Pr) -> i64 {
+        (self.now - pr.updated_at).num_days()
+    }
+}
@@ -13,6 +13,8 @@ use crate::{
 
 pub struct MentoringRecords {
     records: BTreeMap<String, MentoringRecord>,
+    /// Why there are no records, if the current user can't read the mentoring records sheet.
+    pub unavailable: Option<String>,
 }
 
 impl MentoringRecords {
@@ -38,10 +40,21 @@ pub async fn get_mentoring_records(
     client: SheetsClient,
     mentoring_records_sheet_id: &str,
 ) -> Result<MentoringRecords, Error> {
-    let sheet_data = get_mentoring_records_grid_data(client, mentoring_records_sheet_id).await?;
+    let sheet_data = match get_mentoring_records_grid_data(client, mentoring_records_sheet_id).await
+    {
+        Ok(sheet_data) => sheet_data,
+        Err(Error::PermissionDenied(denied)) => {
+            return Ok(MentoringRecords {
+                records: BTreeMap::new(),
+                unavailable: Some(denied.user_facing_message()),
+            });
+        }
+        Err(err) => return Err(err),
+    };
 
     let mut mentoring_records = MentoringRecords {
         records: BTreeMap::new(),
+        unavailable: None,
     };
 
     for (row_number, cells) in sheet_data.into_iter().enumerate() {
@@ -94,6 +107,48 @@ pub async fn get_mentoring_records(
     Ok(mentoring_records)
 }
 
+/// One row of the mentoring records sheet.
+#[derive(Clone, Debug)]
+pub struct MentoringSession {
+    pub trainee_name: String,
+    pub date: NaiveDate,
+    /// Whoever logged the session, as they wrote their name.
+    pub staff: String,
+    pub notes: String,
+}
+
+/// Every logged session, for seeing who has been doing the mentoring rather than who has been mentored.
+pub async fn get_mentoring_sessions(
+    client: SheetsClient,
+    mentoring_records_sheet_id: &str,
+) -> Result<Vec<MentoringSession>, Error> {
+    let sheet_data = get_mentoring_records_grid_data(client, mentoring_records_sheet_id).await?;
+    let mut sessions = Vec::new();
+    // The headings are checked by get_mentoring_records.
+    for (row_number, cells) in sheet_data.into_iter().enumerate().skip(1) {
+        if cells.len() < 6 {
+            continue;
+        }
+        if cells[0].effective_value.is_none() {
+            break;
+        }
+        let date = cell_date(&cells[2]).with_context(|| {
+            format!(
+                "Failed to parse date from row {} in sheet ID {}",
+                row_number + 1,
+                mentoring_records_sheet_id
+            )
+        })?;
+        sessions.push(MentoringSession {
+            trainee_name: cell_string(&cells[0]),
+            date,
+            staff: cell_string(&cells[3]).trim().to_owned(),
+            notes: cell_string(&cells[5]).trim().to_owned(),
+        });
+    }
+    Ok(sessions)
+}
+
 async fn get_mentoring_records_grid_data(
     client: SheetsClient,
     mentoring_records_sheet_id: &str,
@@ -102,9 +157,6 @@ async fn get_mentoring_records_grid_data(
     let data_result = client.get(mentoring_records_sheet_id).await;
     let mut data = match data_result {
         Ok(data) => data,
-        Err(Error::PotentiallyIgnorablePermissions(_)) => {
-            return Ok(Vec::new());
-        }
         Err(err) => {
             let err = err.with_context(|| {
                 format!(
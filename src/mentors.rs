@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use octocrab::{Octocrab, models::Author};
+use serde::Serialize;
+
+use crate::{
+    Config, Error,
+    github_accounts::get_trainees,
+    newtypes::{GithubLogin, Region},
+    octocrab::all_pages,
+    prs::{MaybeReviewerStaffOnlyDetails, get_reviewers},
+    reviewer_staff_info::get_reviewer_staff_info_if_permitted,
+    sheets::SheetsClient,
+};
+
+#[derive(Serialize)]
+pub struct MentorRoster {
+    pub(crate) regions: BTreeMap<Region, Vec<MentorInfo>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct MentorInfo {
+    pub login: GithubLogin,
+    pub name: Option<String>,
+    pub last_review: Option<DateTime<Utc>>,
+    pub reviewed_pr_count: usize,
+    pub reviews_days_in_last_28_days: u8,
+    pub staff_only_details: MaybeReviewerStaffOnlyDetails,
+}
+
+impl MentorInfo {
+    pub fn days_since_last_review(&self, now: &DateTime<Utc>) -> Option<i64> {
+        self.last_review
+            .map(|last_review| (*now - last_review).num_days())
+    }
+}
+
+/// Lists the members of the course's mentors team, grouped by region, with their review activity.
+///
+/// Regions come from the GitHub email mapping sheet - mentors who aren't listed there are grouped under "Unknown".
+pub(crate) async fn get_mentor_roster(
+    octocrab: Octocrab,
+    sheets_client: SheetsClient,
+    config: &Config,
+    course: &str,
+) -> Result<MentorRoster, Error> {
+    let module_names = config
+        .get_course_module_names(course)
+        .ok_or_else(|| Error::UserFacing(format!("Unknown course {}", course)))?;
+
+    let mentors_team = format!("{}-mentors", course);
+    let mentors = all_pages("mentors team members", &octocrab, async || {
+        octocrab
+            .teams(&config.github_org)
+            .members(&mentors_team)
+            .send()
+            .await
+    })
+    .await?;
+
+    let staff_details = get_reviewer_staff_info_if_permitted(
+        sheets_client.clone(),
+        &config.reviewer_staff_info_sheet_id,
+    )
+    .await?;
+
+    let in_flight = sheets_client.server_state().in_flight.clone();
+    let accounts = get_trainees(sheets_client, &config.github_email_mapping_sheet_id).await?;
+
+    let mut reviewers: BTreeMap<_, _> = get_reviewers(
+        octocrab.clone(),
+        &in_flight,
+        &config.github_org,
+        &module_names,
+    )
+    .await?
+    .into_iter()
+    .map(|reviewer| (reviewer.login.clone(), reviewer))
+    .collect();
+
+    let mut regions: BTreeMap<Region, Vec<MentorInfo>> = BTreeMap::new();
+    for Author { login, .. } in mentors {
+        let login = GithubLogin::from(login);
+        let reviewer = reviewers.remove(&login);
+        let account = accounts.get(&login);
+
+        let staff_only_details = match &staff_details {
+            Some(staff_details) => match staff_details.get(&login) {
+                Some(details) => MaybeReviewerStaffOnlyDetails::Some(details.clone()),
+                None => MaybeReviewerStaffOnlyDetails::Unknown,
+            },
+            None => MaybeReviewerStaffOnlyDetails::NotAuthenticated,
+        };
+        let name = match &staff_only_details {
+            MaybeReviewerStaffOnlyDetails::Some(details) => Some(details.name.clone()),
+            _ => account.map(|account| account.name.clone()),
+        };
+        let region = account.map_or_else(
+            || Region("Unknown".to_owned()),
+            |account| account.region.clone(),
+        );
+
+        regions.entry(region).or_default().push(MentorInfo {
+            login,
+            name,
+            last_review: reviewer.as_ref().map(|reviewer| reviewer.last_review),
+            reviewed_pr_count: reviewer.as_ref().map_or(0, |reviewer| reviewer.prs.len()),
+            reviews_days_in_last_28_days: reviewer
+                .as_ref()
+                .map_or(0, |reviewer| reviewer.reviews_days_in_last_28_days),
+            staff_only_details,
+        });
+    }
+
+    for mentors in regions.values_mut() {
+        // Most recently active first, never-reviewed last.
+        mentors.sort_by(|a, b| b.last_review.cmp(&a.last_review));
+    }
+
+    Ok(MentorRoster { regions })
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use futures::future::join_all;
+use octocrab::Octocrab;
+
+use crate::{
+    Error,
+    course::{Course, ONBOARDING_MODULE_NAME, match_prs_to_assignments},
+    newtypes::{GithubLogin, Region},
+    octocrab::all_pages,
+    prs::{Pr, get_prs},
+    single_flight::InFlight,
+};
+
+/// An open PR by a trainee, which looks like coursework, but which was opened against a repo that isn't one of their course's modules.
+///
+/// The tracker only looks in module repos, so these show up as missing work even though the trainee did submit them.
+#[derive(Clone, Debug)]
+pub struct MisfiledPr {
+    pub pr: Pr,
+    /// The course module whose assignments the PR's title matches, if any.
+    pub suggested_module: Option<String>,
+}
+
+/// Whether a PR title looks like the coursework title format (e.g. "London | 25-ITP-Sep | Jo Bloggs | Sprint 1 | Form controls").
+pub fn is_coursework_like_title(title: &str) -> bool {
+    let parts = title.split('|').map(str::trim).collect::<Vec<_>>();
+    parts.len() >= 3
+        && parts.iter().any(|part| {
+            let part = part.to_lowercase();
+            part.starts_with("sprint") || part.starts_with("week")
+        })
+}
+
+/// Scans every non-archived repo in the org (not just the course's modules) for open coursework-like PRs by the given trainees, which aren't in any of the course's module repos.
+pub async fn find_misfiled_prs(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    github_org: &str,
+    course: &Course,
+    trainee_logins: &BTreeSet<GithubLogin>,
+) -> Result<Vec<MisfiledPr>, Error> {
+    let module_repos = course
+        .modules
+        .values()
+        .flat_map(|module| module.repos.iter().cloned())
+        .collect::<BTreeSet<_>>();
+
+    let repos = all_pages("repos", octocrab, async || {
+        octocrab.orgs(github_org).list_repos().send().await
+    })
+    .await
+    .map_err(|err| err.context("Failed to list org repos"))?;
+    let repo_names = repos
+        .into_iter()
+        .filter(|repo| !repo.archived.unwrap_or_default())
+        .map(|repo| repo.name)
+        .filter(|name| !module_repos.contains(name))
+        .collect::<Vec<_>>();
+
+    let repo_prs = join_all(
+        repo_names
+            .iter()
+            .map(|repo_name| get_prs(octocrab, in_flight, github_org, repo_name, false)),
+    )
+    .await;
+
+    let mut misfiled_prs = Vec::new();
+    for (repo_name, prs) in repo_names.iter().zip(repo_prs) {
+        let prs =
+            prs.map_err(|err| err.with_context(|| format!("Failed to list PRs in {}", repo_name)))?;
+        for pr in prs {
+            if !trainee_logins.contains(&pr.author) || !is_coursework_like_title(&pr.title) {
+                continue;
+            }
+            let suggested_module = suggest_module(course, &pr);
+            misfiled_prs.push(MisfiledPr {
+                pr,
+                suggested_module,
+            });
+        }
+    }
+    Ok(misfiled_prs)
+}
+
+/// The first module with an assignment the PR would have been matched to, had it been opened in that module's repo.
+fn suggest_module(course: &Course, pr: &Pr) -> Option<String> {
+    course
+        .modules
+        .iter()
+        .filter(|(module_name, _)| *module_name != ONBOARDING_MODULE_NAME)
+        .find(|(_, module)| {
+            match_prs_to_assignments(module, vec![pr.clone()], Vec::new(), &Region(String::new()))
+                .is_ok_and(|matched| matched.unknown_prs.is_empty())
+        })
+        .map(|(module_name, _)| module_name.clone())
+}
@@ -0,0 +1,356 @@
+//! Finding a course's modules from GitHub, rather than listing every module repo in the config.
+//!
+//! Module repos are tagged with the course's `module_topic`, and describe themselves in a `tracker.toml` at their root,
+//! so the teams who own a module's content can change how it's tracked without touching the tracker's config.
+//! Modules listed in a batch's `sprints` in the config still take precedence, so dates can be set by hand where needed.
+
+use std::{collections::BTreeMap, num::NonZeroUsize};
+
+use anyhow::Context;
+use chrono::{Days, NaiveDate};
+use indexmap::IndexMap;
+use octocrab::Octocrab;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::{
+    Config, Error,
+    config::{CourseSchedule, SubmitLabelHandler},
+    holidays::HolidayCalendar,
+    newtypes::Region,
+    octocrab::{all_pages, octocrab_for_maybe_token},
+    pr_validator::ValidatorComments,
+};
+
+pub const MODULE_METADATA_PATH: &str = "tracker.toml";
+
+/// The contents of a module repo's `tracker.toml`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ModuleMetadata {
+    pub sprints: usize,
+    /// Where the module comes in the course. Modules without one come after those with, by repo name.
+    #[serde(default)]
+    pub order: Option<u32>,
+    /// How to handle the module's `Submit:` labels (without the prefix), as in the config's `submit_labels`, which take precedence.
+    #[serde(default)]
+    pub submit_types: BTreeMap<String, SubmitLabelHandler>,
+    /// Assignment issue number -> regex for the files PRs for it should change.
+    /// Takes precedence over a `CHANGE_DIR=` line in the issue's body.
+    #[serde(default)]
+    pub expected_files: BTreeMap<String, String>,
+    #[serde(default)]
+    pub title_format: TitleFormat,
+    /// Assignment issue numbers in the order they should be shown within each sprint.
+    /// Issues which aren't listed come after those which are, by issue number.
+    #[serde(default)]
+    pub assignment_order: Vec<u64>,
+    #[serde(default)]
+    pub validator_comments: ValidatorComments,
+}
+
+impl ModuleMetadata {
+    pub fn expected_files(&self, assignment_issue_id: u64) -> Option<&str> {
+        self.expected_files
+            .get(&assignment_issue_id.to_string())
+            .map(String::as_str)
+    }
+}
+
+/// What the validator expects PR titles to look like, e.g. `London | 25-ITP-Sep | Jane Doe | Sprint 2 | Implement cowsay`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TitleFormat {
+    /// How many parts, separated by |s, titles have.
+    pub parts: usize,
+    /// Which part (counting from 1) is the trainee's region.
+    pub region_part: NonZeroUsize,
+    /// Which part (counting from 1) is the sprint, e.g. "Sprint 2".
+    pub sprint_part: NonZeroUsize,
+}
+
+impl Default for TitleFormat {
+    fn default() -> Self {
+        TitleFormat {
+            parts: 5,
+            region_part: NonZeroUsize::MIN,
+            // UNWRAP: 4 isn't 0.
+            sprint_part: NonZeroUsize::new(4).unwrap(),
+        }
+    }
+}
+
+/// A module repo's metadata file, or None if it doesn't have one.
+pub async fn get_module_metadata(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo: &str,
+) -> Result<Option<ModuleMetadata>, Error> {
+    let Some(text) = get_repo_file(octocrab, github_org, repo, MODULE_METADATA_PATH).await? else {
+        return Ok(None);
+    };
+    let metadata = basic_toml::from_str(&text)
+        .with_context(|| format!("Failed to parse {} in {}", MODULE_METADATA_PATH, repo))?;
+    Ok(Some(metadata))
+}
+
+/// The text of a file on a repo's default branch, or None if there's no such file.
+pub async fn get_repo_file(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo: &str,
+    path: &str,
+) -> Result<Option<String>, Error> {
+    let contents = match octocrab
+        .repos(github_org, repo)
+        .get_content()
+        .path(path)
+        .send()
+        .await
+    {
+        Ok(contents) => contents,
+        Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 404 => {
+            return Ok(None);
+        }
+        Err(err) => {
+            return Err(Error::Fatal(
+                anyhow::Error::from(err).context(format!("Failed to get {} from {}", path, repo)),
+            ));
+        }
+    };
+    Ok(contents
+        .items
+        .into_iter()
+        .next()
+        .and_then(|item| item.decoded_content()))
+}
+
+/// The org's repos tagged with the topic which have metadata files, in course order.
+/// Tagged repos without a metadata file are skipped, as we can't tell how many sprints they have.
+pub async fn discover_modules(
+    octocrab: &Octocrab,
+    github_org: &str,
+    topic: &str,
+) -> Result<Vec<(String, ModuleMetadata)>, Error> {
+    let query = format!("org:{} topic:{}", github_org, topic);
+    let repos = all_pages("module repos", octocrab, async || {
+        octocrab.search().repositories(&query).send().await
+    })
+    .await?;
+    let mut modules = Vec::new();
+    for repo in repos {
+        match get_module_metadata(octocrab, github_org, &repo.name).await? {
+            Some(metadata) => modules.push((repo.name, metadata)),
+            None => warn!(
+                "Ignoring {}, which has the {} topic but no {}",
+                repo.name, topic, MODULE_METADATA_PATH
+            ),
+        }
+    }
+    modules.sort_by(|(a_name, a), (b_name, b)| {
+        // None sorts first, but modules without an order should come last.
+        (a.order.is_none(), a.order, a_name).cmp(&(b.order.is_none(), b.order, b_name))
+    });
+    Ok(modules)
+}
+
+/// Fills in the batch's sprints from the discovered modules, giving their classes weekly dates from `weekly_classes_from`.
+/// Modules already listed in the batch's `sprints` keep their configured dates, but still take up their weeks, so later modules' dates don't depend on which are overridden.
+/// Modules which are only configured come after the discovered ones.
+/// Generated classes which would be on one of their region's holidays are moved off it, with a warning.
+pub fn apply_discovered_modules(
+    schedule: &mut CourseSchedule,
+    modules: &[(String, ModuleMetadata)],
+    holidays: &HolidayCalendar,
+) {
+    let mut configured = std::mem::take(&mut schedule.sprints);
+    let mut sprints = IndexMap::new();
+    let mut week = 0;
+    for (module_name, metadata) in modules {
+        let dates = configured.shift_remove(module_name).unwrap_or_else(|| {
+            (week..week + metadata.sprints)
+                .enumerate()
+                .map(|(sprint_index, week)| {
+                    let mut dates = weekly_class_dates(&schedule.weekly_classes_from, week);
+                    for conflict in
+                        holidays.avoid_holidays(module_name, sprint_index + 1, &mut dates)
+                    {
+                        warn!("{}", conflict);
+                    }
+                    dates
+                })
+                .collect()
+        });
+        week += metadata.sprints;
+        // A module with no dates (e.g. because weekly_classes_from isn't set) would look like it has no sprints.
+        if dates.iter().all(BTreeMap::is_empty) {
+            warn!(
+                "Not adding discovered module {} to a batch without weekly_classes_from",
+                module_name
+            );
+            continue;
+        }
+        sprints.insert(module_name.clone(), dates);
+    }
+    sprints.extend(configured);
+    schedule.sprints = sprints;
+}
+
+fn weekly_class_dates(
+    weekly_classes_from: &BTreeMap<Region, NaiveDate>,
+    week: usize,
+) -> BTreeMap<Region, NaiveDate> {
+    weekly_classes_from
+        .iter()
+        .filter_map(|(region, first_class)| {
+            let date = first_class.checked_add_days(Days::new(7 * week as u64))?;
+            Some((region.clone(), date))
+        })
+        .collect()
+}
+
+/// Adds the modules found by each course's `module_topic` to all of its batches.
+/// Should be called once, straight after loading the config, so everything else sees the same modules.
+pub async fn discover_course_modules(config: &mut Config) -> Result<(), Error> {
+    if config
+        .courses
+        .values()
+        .all(|course_info| course_info.module_topic.is_none())
+    {
+        return Ok(());
+    }
+    // Module repos are public, so this works without credentials, but rate limits are much higher with them.
+    let octocrab = octocrab_for_maybe_token(
+        config
+            .service_credentials
+            .as_ref()
+            .map(|service_credentials| service_credentials.github_token.to_string()),
+    )?;
+    for course_info in config.courses.values_mut() {
+        let Some(topic) = &course_info.module_topic else {
+            continue;
+        };
+        let modules = discover_modules(&octocrab, &config.github_org, topic).await?;
+        for schedule in course_info.batches.values_mut() {
+            apply_discovered_modules(schedule, &modules, &config.holidays);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::NaiveDate;
+
+    use super::{ModuleMetadata, TitleFormat, apply_discovered_modules};
+    use crate::{
+        config::{CourseSchedule, SubmitLabelHandler},
+        holidays::HolidayCalendar,
+        newtypes::Region,
+    };
+
+    #[test]
+    fn parses_metadata_file() {
+        let metadata: ModuleMetadata = basic_toml::from_str(
+            r#"
+sprints = 3
+
+[submit_types]
+Video = "ignore"
+
+[expected_files]
+12 = "^Sprint-1/prep/"
+
+[title_format]
+sprint_part = 3
+"#,
+        )
+        .unwrap();
+        assert_eq!(metadata.sprints, 3);
+        assert_eq!(metadata.order, None);
+        assert_eq!(
+            metadata.submit_types.get("Video"),
+            Some(&SubmitLabelHandler::Ignore)
+        );
+        assert_eq!(metadata.expected_files(12), Some("^Sprint-1/prep/"));
+        assert_eq!(metadata.expected_files(13), None);
+        assert_eq!(metadata.title_format.parts, TitleFormat::default().parts);
+        assert_eq!(metadata.title_format.sprint_part.get(), 3);
+    }
+
+    #[test]
+    fn rejects_title_part_zero() {
+        // Parts count from 1, so 0 can only be a mistake.
+        let parse = |region_part| {
+            basic_toml::from_str::<ModuleMetadata>(&format!(
+                "sprints = 3\n[title_format]\nregion_part = {}\n",
+                region_part
+            ))
+        };
+        assert!(parse(1).is_ok());
+        assert!(parse(0).is_err());
+    }
+
+    #[test]
+    fn configured_dates_override_discovered_ones() {
+        let mut schedule: CourseSchedule = serde_json::from_value(serde_json::json!({
+            "start": "2026-09-01",
+            "end": "2026-12-01",
+            "sprints": {
+                "Module-Data-Groups": [{ "London": "2026-10-03" }],
+                "Module-Legacy": [{ "London": "2026-11-28" }],
+            },
+            "weekly_classes_from": { "London": "2026-09-05" },
+        }))
+        .unwrap();
+        let module = |sprints| ModuleMetadata {
+            sprints,
+            order: None,
+            submit_types: BTreeMap::new(),
+            expected_files: BTreeMap::new(),
+            title_format: TitleFormat::default(),
+            assignment_order: Vec::new(),
+            validator_comments: ValidatorComments::default(),
+        };
+        apply_discovered_modules(
+            &mut schedule,
+            &[
+                ("Module-Onboarding".to_owned(), module(2)),
+                ("Module-Data-Groups".to_owned(), module(1)),
+                ("Module-Structuring-and-Testing-Data".to_owned(), module(1)),
+            ],
+            &HolidayCalendar::default(),
+        );
+        let london = Region("London".to_owned());
+        let dates = schedule
+            .sprints
+            .iter()
+            .map(|(module_name, sprints)| {
+                (
+                    module_name.as_str(),
+                    sprints
+                        .iter()
+                        .map(|dates| dates[&london])
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(
+            dates,
+            vec![
+                (
+                    "Module-Onboarding",
+                    vec![date("2026-09-05"), date("2026-09-12")]
+                ),
+                ("Module-Data-Groups", vec![date("2026-10-03")]),
+                (
+                    "Module-Structuring-and-Testing-Data",
+                    vec![date("2026-09-26")]
+                ),
+                ("Module-Legacy", vec![date("2026-11-28")]),
+            ]
+        );
+    }
+}
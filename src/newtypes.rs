@@ -1,9 +1,9 @@
-use std::{fmt::Display, str::FromStr};
+use std::{borrow::Borrow, fmt::Display, ops::Deref, str::FromStr};
 
 use case_insensitive_string::CaseInsensitiveString;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use email_address::EmailAddress;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 pub fn new_case_insensitive_email_address(s: &str) -> Result<EmailAddress, email_address::Error> {
     EmailAddress::from_str(&s.to_ascii_lowercase())
@@ -54,6 +54,10 @@ impl Region {
         self.0.as_str()
     }
 
+    pub fn display_timezone(&self) -> DisplayTimezone {
+        DisplayTimezone(self.timezone())
+    }
+
     pub fn class_start_time(&self, date: &NaiveDate) -> DateTime<Utc> {
         let offset = self.timezone().offset_from_utc_date(date);
         DateTime::<chrono_tz::Tz>::from_naive_utc_and_offset(
@@ -66,3 +70,199 @@ impl Region {
         .to_utc()
     }
 }
+
+/// A timezone to show times in.
+///
+/// Times are always labelled with their zone, because staff in one region often look at another region's trainees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisplayTimezone(pub chrono_tz::Tz);
+
+impl Default for DisplayTimezone {
+    fn default() -> Self {
+        DisplayTimezone(chrono_tz::Europe::London)
+    }
+}
+
+impl Display for DisplayTimezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for DisplayTimezone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        chrono_tz::Tz::from_str(&name)
+            .map(DisplayTimezone)
+            .map_err(|_| serde::de::Error::custom(format!("Unknown timezone: {}", name)))
+    }
+}
+
+impl DisplayTimezone {
+    /// e.g. "2025-05-03 14:05 SAST"
+    pub fn format_datetime(&self, time: &DateTime<Utc>) -> String {
+        time.with_timezone(&self.0)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string()
+    }
+
+    /// e.g. "2025-05-03 (SAST)" - the zone matters for times near midnight.
+    pub fn format_date(&self, time: &DateTime<Utc>) -> String {
+        time.with_timezone(&self.0)
+            .format("%Y-%m-%d (%Z)")
+            .to_string()
+    }
+}
+
+/// Declares a string newtype for an identifier read from config, which is checked when the config is loaded.
+/// Mixing these up (e.g. pasting a team slug where a sheet ID should go) otherwise only shows up as a confusing 404 from an API at runtime.
+macro_rules! validated_string_newtype {
+    ($name:ident, $validate:expr) => {
+        #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String")]
+        pub struct $name(String);
+
+        impl TryFrom<String> for $name {
+            type Error = String;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                let validate: fn(&str) -> Result<(), String> = $validate;
+                validate(&value).map_err(|reason| {
+                    format!(
+                        "{:?} isn't a valid {}: {}",
+                        value,
+                        stringify!($name),
+                        reason
+                    )
+                })?;
+                Ok($name(value))
+            }
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::try_from(s.to_owned())
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+validated_string_newtype!(SheetId, |id| {
+    if id.contains('/') {
+        return Err("expected just the ID from the sheet's URL, not the whole URL".to_owned());
+    }
+    // Real IDs are 44 characters, but we don't need to be that strict to catch slugs and names in the wrong place.
+    if id.len() < 20 {
+        return Err("too short to be a Google Sheets ID".to_owned());
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Google Sheets IDs only contain letters, numbers, - and _".to_owned());
+    }
+    Ok(())
+});
+
+validated_string_newtype!(TeamSlug, validate_slug);
+
+validated_string_newtype!(CourseName, validate_slug);
+
+/// GitHub team slugs, and our course names (which are used to build team slugs like `itp-trainees`), are lower-case words separated by -s.
+fn validate_slug(slug: &str) -> Result<(), String> {
+    if slug.is_empty() {
+        return Err("can't be empty".to_owned());
+    }
+    if !slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err("expected only lower-case letters, numbers, - and _".to_owned());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CourseName, SheetId, TeamSlug};
+
+    #[test]
+    fn sheet_ids_are_validated() {
+        assert!(
+            "1aBcD_efGhIjKlMnOpQrStUvWxYz-0123456789abcd"
+                .parse::<SheetId>()
+                .is_ok()
+        );
+        assert!("itp-2025-05".parse::<SheetId>().is_err());
+        assert!(
+            "https://docs.google.com/spreadsheets/d/1aBcD_efGhIjKlMnOpQrStUvWxYz-0123456789abcd/edit"
+                .parse::<SheetId>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn slugs_are_validated() {
+        assert!("itp-2025-05".parse::<TeamSlug>().is_ok());
+        assert!(
+            "1aBcD_efGhIjKlMnOpQrStUvWxYz-0123456789abcd"
+                .parse::<TeamSlug>()
+                .is_err()
+        );
+        assert!("itp".parse::<CourseName>().is_ok());
+        assert!("".parse::<CourseName>().is_err());
+        assert!("ITP 2025".parse::<CourseName>().is_err());
+    }
+}
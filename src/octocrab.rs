@@ -1,22 +1,37 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use anyhow::Context;
-use http::{HeaderValue, Uri, header::USER_AGENT};
+use chrono::{DateTime, Utc};
+use http::{
+    HeaderValue, Request, Response, StatusCode, Uri,
+    header::{RETRY_AFTER, USER_AGENT},
+};
 use hyper_rustls::HttpsConnectorBuilder;
 use octocrab::{
     AuthState, Octocrab, OctocrabBuilder,
+    body::OctoBody,
     service::middleware::{
         auth_header::AuthHeaderLayer, base_uri::BaseUriLayer, extra_headers::ExtraHeadersLayer,
-        retry::RetryConfig,
     },
 };
 use serde::de::DeserializeOwned;
-use tower::retry::RetryLayer;
+use tower::retry::{Policy, RetryLayer};
 use tower_sessions::Session;
+use tracing::{info, warn};
 
 use crate::{
     Error, ServerState,
     auth::{GITHUB_ACCESS_TOKEN_SESSION_KEY, github_auth_redirect_url},
+    upstream::{CircuitBreakerLayer, GITHUB},
 };
 
 pub(crate) async fn octocrab(
@@ -38,6 +53,54 @@ pub(crate) async fn octocrab(
     }
 }
 
+static NEXT_SHARED_TOKEN: AtomicUsize = AtomicUsize::new(0);
+
+static SHARED_TOKEN_USES: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Like `octocrab`, but for pages which only show public data.
+/// If the user hasn't logged in with GitHub, one of the config's `shared_github_tokens` is used instead of sending them to log in.
+pub(crate) async fn octocrab_or_shared(
+    session: &Session,
+    server_state: &ServerState,
+    original_uri: Uri,
+) -> Result<Octocrab, Error> {
+    let maybe_token: Option<String> = session
+        .get(GITHUB_ACCESS_TOKEN_SESSION_KEY)
+        .await
+        .context("Session load error")?;
+    if let Some(token) = maybe_token {
+        return octocrab_for_token(token);
+    }
+
+    let shared_tokens = &server_state.config.shared_github_tokens;
+    if shared_tokens.is_empty() {
+        return Err(Error::Redirect(
+            github_auth_redirect_url(server_state, original_uri).await?,
+        ));
+    }
+
+    let shared_token =
+        &shared_tokens[NEXT_SHARED_TOKEN.fetch_add(1, Ordering::Relaxed) % shared_tokens.len()];
+    info!(
+        token = %shared_token.name,
+        uri = %original_uri,
+        "Using shared GitHub token for logged-out user"
+    );
+    // UNWRAP: We never panic while holding the lock.
+    *SHARED_TOKEN_USES
+        .lock()
+        .unwrap()
+        .entry(shared_token.name.clone())
+        .or_default() += 1;
+    octocrab_for_token(shared_token.token.to_string())
+}
+
+/// How many times each shared token has been used for a logged-out user since the tracker was last restarted.
+pub fn shared_token_uses() -> BTreeMap<String, u64> {
+    // UNWRAP: We never panic while holding the lock.
+    SHARED_TOKEN_USES.lock().unwrap().clone()
+}
+
 pub fn octocrab_for_token(token: String) -> Result<Octocrab, Error> {
     octocrab_for_maybe_token(Some(token))
 }
@@ -66,6 +129,7 @@ pub fn octocrab_for_maybe_token(token: Option<String>) -> Result<Octocrab, Error
 
     let octocrab = OctocrabBuilder::new_empty()
         .with_service(client)
+        .with_layer(&CircuitBreakerLayer(&GITHUB))
         .with_layer(&BaseUriLayer::new(Uri::from_static(GITHUB_BASE_URI)))
         .with_layer(&octocrab_rate_limiter::AccessTokenRateLimitLayer::new(
             // Keep rate limit semaphores around for 5 minutes.
@@ -73,7 +137,7 @@ pub fn octocrab_for_maybe_token(token: Option<String>) -> Result<Octocrab, Error
             // If our rate limit enforcement starts looking back over more data (e.g. hour-long request counts), we may want to increase this.
             Duration::from_secs(300),
         ))
-        .with_layer(&RetryLayer::new(RetryConfig::Simple(3)))
+        .with_layer(&RetryLayer::new(RetryPolicy { remaining: 3 }))
         .with_layer(&tower_http::follow_redirect::FollowRedirectLayer::new())
         .with_layer(&ExtraHeadersLayer::new(Arc::new(vec![(
             USER_AGENT,
@@ -91,6 +155,111 @@ pub fn octocrab_for_maybe_token(token: Option<String>) -> Result<Octocrab, Error
     Ok(octocrab)
 }
 
+/// The longest we'll wait when GitHub asks us to back off, so that a page doesn't hang indefinitely.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+static LAST_SECONDARY_RATE_LIMIT: Mutex<Option<SecondaryRateLimit>> = Mutex::new(None);
+
+/// The most recent time GitHub told us to back off because of a secondary rate limit.
+#[derive(Clone, Copy, Debug)]
+pub struct SecondaryRateLimit {
+    pub at: DateTime<Utc>,
+    pub waited: Duration,
+}
+
+pub fn last_secondary_rate_limit() -> Option<SecondaryRateLimit> {
+    // UNWRAP: We never panic while holding the lock.
+    *LAST_SECONDARY_RATE_LIMIT.lock().unwrap()
+}
+
+/// Like octocrab's RetryConfig::Simple, but when GitHub tells us how long to wait (as it does for secondary rate limits),
+/// waits that long before retrying, rather than immediately retrying and being rate limited again.
+///
+/// Only rate limited and server error responses are retried.
+#[derive(Clone)]
+struct RetryPolicy {
+    remaining: usize,
+}
+
+impl<B, E> Policy<Request<OctoBody>, Response<B>, E> for RetryPolicy {
+    type Future = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn retry(
+        &mut self,
+        request: &mut Request<OctoBody>,
+        result: &mut Result<Response<B>, E>,
+    ) -> Option<Self::Future> {
+        let delay = match result {
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                match retry_after {
+                    Some(retry_after)
+                        if status == StatusCode::FORBIDDEN
+                            || status == StatusCode::TOO_MANY_REQUESTS =>
+                    {
+                        let waited = retry_after.min(MAX_RETRY_AFTER);
+                        warn!(
+                            "GitHub secondary rate limit hit for {} {} - waiting {:?} (asked to wait {:?}) before retrying",
+                            request.method(),
+                            request.uri(),
+                            waited,
+                            retry_after
+                        );
+                        // UNWRAP: We never panic while holding the lock.
+                        *LAST_SECONDARY_RATE_LIMIT.lock().unwrap() = Some(SecondaryRateLimit {
+                            at: Utc::now(),
+                            waited,
+                        });
+                        waited
+                    }
+                    _ if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+                        Duration::ZERO
+                    }
+                    _ => return None,
+                }
+            }
+            // Errors are timeouts, an open circuit breaker or failing to connect at all:
+            // retrying a timed out request makes the user wait for it all over again, and retrying past an open breaker defeats its point.
+            Err(_) => return None,
+        };
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(Box::pin(tokio::time::sleep(delay)))
+    }
+
+    fn clone_request(&mut self, request: &Request<OctoBody>) -> Option<Request<OctoBody>> {
+        let body = request.body().try_clone()?;
+        let mut clone = Request::new(body);
+        *clone.method_mut() = request.method().clone();
+        *clone.uri_mut() = request.uri().clone();
+        *clone.version_mut() = request.version();
+        *clone.headers_mut() = request.headers().clone();
+        Some(clone)
+    }
+}
+
+/// Checks this client can see the repo. Much cheaper than listing anything in it, for when someone else did the listing.
+pub(crate) async fn check_repo_access(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo: &str,
+) -> Result<(), Error> {
+    octocrab
+        .repos(github_org, repo)
+        .get()
+        .await
+        .with_context(|| format!("Failed to get repo {}/{}", github_org, repo))?;
+    Ok(())
+}
+
 pub async fn all_pages<T: DeserializeOwned>(
     description: &str,
     octocrab: &Octocrab,
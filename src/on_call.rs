@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_env_field::EnvField;
+
+use crate::{
+    Error, event_webhooks::TrackerEvent, github_accounts::Trainee, newtypes::Region,
+    slack::post_to_slack_webhook,
+};
+
+/// Which member of staff handles escalations (e.g. a trainee becoming at risk) each week, per region,
+/// so they go to one person who knows it's their job this week, rather than a shared channel everyone ignores.
+#[derive(Clone, Default, Deserialize)]
+pub struct OnCallRota {
+    /// Staff who take turns being on call, keyed by the name `weeks` refers to them by.
+    #[serde(default)]
+    pub staff: BTreeMap<String, OnCallStaff>,
+    #[serde(default)]
+    pub weeks: Vec<OnCallWeek>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct OnCallStaff {
+    /// Slack incoming webhook which messages this person directly, rather than a channel.
+    pub slack_webhook_url: EnvField<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct OnCallWeek {
+    /// The person is on call from this date until the next week listed for the same region starts.
+    pub starting: NaiveDate,
+    /// If unset, covers every region which doesn't have its own entry, e.g. for escalations which aren't about a particular trainee.
+    #[serde(default)]
+    pub region: Option<Region>,
+    /// Key into `staff`.
+    pub staff: String,
+}
+
+impl OnCallRota {
+    /// Who's on call for the region on the date, falling back to whoever covers all regions.
+    pub fn on_call(
+        &self,
+        region: Option<&Region>,
+        date: NaiveDate,
+    ) -> Result<Option<(&str, &OnCallStaff)>, Error> {
+        let latest_week = |region: Option<&Region>| {
+            self.weeks
+                .iter()
+                .filter(|week| week.region.as_ref() == region && week.starting <= date)
+                .max_by_key(|week| week.starting)
+        };
+        let Some(week) = region
+            .and_then(|region| latest_week(Some(region)))
+            .or_else(|| latest_week(None))
+        else {
+            return Ok(None);
+        };
+        let staff = self.staff.get(&week.staff).ok_or_else(|| {
+            anyhow!(
+                "On-call rota week starting {} names {}, who isn't in the rota's staff",
+                week.starting,
+                week.staff
+            )
+        })?;
+        Ok(Some((week.staff.as_str(), staff)))
+    }
+
+    /// Messages whoever is on call. Returns who that was, or None if the rota doesn't cover the date, so callers can fall back to however they alerted before.
+    pub async fn notify(
+        &self,
+        client: &reqwest::Client,
+        region: Option<&Region>,
+        date: NaiveDate,
+        text: &str,
+    ) -> Result<Option<String>, Error> {
+        let Some((name, staff)) = self.on_call(region, date)? else {
+            return Ok(None);
+        };
+        post_to_slack_webhook(client, &staff.slack_webhook_url, text).await?;
+        Ok(Some(name.to_owned()))
+    }
+}
+
+/// The escalation to send for an event, if it needs one, with the region of the trainee it's about.
+pub fn escalation_for_event<'a>(
+    event: &TrackerEvent,
+    trainees: impl IntoIterator<Item = &'a Trainee>,
+    public_base_url: &str,
+) -> Option<(Option<Region>, String)> {
+    let (course, batch, github_login, what) = match event {
+        TrackerEvent::TraineeStatusChanged {
+            course,
+            batch,
+            github_login,
+            previous_status,
+            status,
+        } if status == "at_risk" => (
+            course,
+            batch,
+            github_login,
+            format!("is now at risk (was {})", previous_status.replace('_', " ")),
+        ),
+        TrackerEvent::AbsenceStreak {
+            course,
+            batch,
+            github_login,
+            consecutive_absences,
+        } => (
+            course,
+            batch,
+            github_login,
+            format!(
+                "has missed {} classes in a row, so needs following up under the safeguarding policy",
+                consecutive_absences
+            ),
+        ),
+        _ => return None,
+    };
+    let region = trainees
+        .into_iter()
+        .find(|trainee| trainee.github_login == *github_login)
+        .map(|trainee| trainee.region.clone());
+    Some((
+        region,
+        format!(
+            "{} in {} {} {}: {}/courses/{}/batches/{}/trainees/{}",
+            github_login, course, batch, what, public_base_url, course, batch, github_login
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::OnCallRota;
+    use crate::newtypes::Region;
+
+    fn rota() -> OnCallRota {
+        serde_json::from_value(serde_json::json!({
+            "staff": {
+                "ali": { "slack_webhook_url": "https://hooks.slack.com/services/ali" },
+                "sam": { "slack_webhook_url": "https://hooks.slack.com/services/sam" },
+                "jo": { "slack_webhook_url": "https://hooks.slack.com/services/jo" },
+            },
+            "weeks": [
+                { "starting": "2026-09-07", "staff": "ali" },
+                { "starting": "2026-09-14", "staff": "sam" },
+                { "starting": "2026-09-07", "region": "Cape Town", "staff": "jo" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn on_call(rota: &OnCallRota, region: Option<&str>, date: &str) -> Option<String> {
+        let region = region.map(|region| Region(region.to_owned()));
+        rota.on_call(
+            region.as_ref(),
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+        )
+        .unwrap()
+        .map(|(name, _)| name.to_owned())
+    }
+
+    #[test]
+    fn picks_latest_started_week_for_region() {
+        let rota = rota();
+        assert_eq!(on_call(&rota, None, "2026-09-06"), None);
+        assert_eq!(on_call(&rota, None, "2026-09-10"), Some("ali".to_owned()));
+        assert_eq!(
+            on_call(&rota, Some("London"), "2026-09-20"),
+            Some("sam".to_owned())
+        );
+        assert_eq!(
+            on_call(&rota, Some("Cape Town"), "2026-09-20"),
+            Some("jo".to_owned())
+        );
+    }
+}
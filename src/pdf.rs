@@ -0,0 +1,75 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use axum::response::{IntoResponse, Response};
+use http::{StatusCode, header::CONTENT_TYPE};
+
+use crate::Error;
+
+/// Used if the config doesn't set weasyprint_path, in which case weasyprint must be on the PATH.
+pub const DEFAULT_WEASYPRINT_PATH: &str = "weasyprint";
+
+pub struct Pdf(Vec<u8>);
+
+impl IntoResponse for Pdf {
+    fn into_response(self) -> Response {
+        Response::builder()
+            .header(CONTENT_TYPE, "application/pdf")
+            .status(StatusCode::OK)
+            .body(axum::body::Body::from(self.0))
+            .expect("Failed to build response")
+    }
+}
+
+/// Converts a page rendered for printing to a PDF, using weasyprint.
+///
+/// Browsers each print slightly differently (and some ignore our page size entirely), so rendering on the server gives staff the same document every time.
+/// `base_url` is used to resolve relative links in the page.
+pub async fn render_pdf(weasyprint_path: &str, base_url: &str, html: String) -> Result<Pdf, Error> {
+    let weasyprint_path = weasyprint_path.to_owned();
+    let base_url = base_url.to_owned();
+    // Rendering takes a while, so wait for weasyprint off the async runtime.
+    tokio::task::spawn_blocking(move || run_weasyprint(&weasyprint_path, &base_url, &html))
+        .await
+        .context("weasyprint task panicked")?
+}
+
+fn run_weasyprint(weasyprint_path: &str, base_url: &str, html: &str) -> Result<Pdf, Error> {
+    let mut child = Command::new(weasyprint_path)
+        .args(["--base-url", base_url, "-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to run {} - is weasyprint installed?",
+                weasyprint_path
+            )
+        })?;
+    // UNWRAP: stdin was piped above.
+    let mut stdin = child.stdin.take().unwrap();
+    let (written, output) = std::thread::scope(|scope| {
+        // Write from another thread so that weasyprint filling its stdout or stderr pipe can't deadlock us.
+        let writer = scope.spawn(move || {
+            // Dropping stdin when this returns tells weasyprint the page is complete.
+            stdin.write_all(html.as_bytes())
+        });
+        let output = child.wait_with_output();
+        // UNWRAP: write_all doesn't panic.
+        (writer.join().unwrap(), output)
+    });
+    written.context("Failed to send page to weasyprint")?;
+    let output = output.context("Failed to wait for weasyprint")?;
+    if !output.status.success() {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "weasyprint failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(Pdf(output.stdout))
+}
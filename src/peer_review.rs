@@ -0,0 +1,216 @@
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+use octocrab::Octocrab;
+
+use crate::{
+    Error,
+    config::CourseSchedule,
+    github_accounts::get_trainee_logins,
+    newtypes::{GithubLogin, TeamSlug},
+    prs::get_reviewers,
+    single_flight::InFlight,
+};
+
+/// One trainee asked to review another trainee's PRs for a sprint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerReviewPairing {
+    pub reviewer: GithubLogin,
+    pub author: GithubLogin,
+}
+
+/// Which sprint of the whole course this is (0 for the first sprint of the first module), so that each sprint gets different pairings.
+pub fn peer_review_round(
+    course_schedule: &CourseSchedule,
+    module: &str,
+    sprint_number: usize,
+) -> Option<usize> {
+    let mut round = 0;
+    for (module_name, sprints) in &course_schedule.sprints {
+        if module_name == module {
+            return (1..=sprints.len())
+                .contains(&sprint_number)
+                .then_some(round + sprint_number - 1);
+        }
+        round += sprints.len();
+    }
+    None
+}
+
+/// The earliest date any region started the sprint - reviews from before this don't count as the sprint's peer review.
+pub fn sprint_start(
+    course_schedule: &CourseSchedule,
+    module: &str,
+    sprint_number: usize,
+) -> Option<NaiveDate> {
+    course_schedule
+        .sprints
+        .get(module)?
+        .get(sprint_number.checked_sub(1)?)?
+        .values()
+        .min()
+        .copied()
+}
+
+/// Round-robin: each trainee reviews the trainee `offset` places after them (in login order), with the offset changing each round.
+///
+/// Everyone reviews exactly one person and is reviewed by exactly one person, and nobody is asked to review the same person again until every other trainee has had a turn.
+/// Pairings only depend on the trainees and the round, so re-running (e.g. to check what was assigned) gives the same answer.
+pub fn assign_peer_reviewers(
+    trainees: &BTreeSet<GithubLogin>,
+    round: usize,
+) -> Vec<PeerReviewPairing> {
+    let trainees = trainees.iter().collect::<Vec<_>>();
+    if trainees.len() < 2 {
+        return Vec::new();
+    }
+    let offset = 1 + round % (trainees.len() - 1);
+    trainees
+        .iter()
+        .enumerate()
+        .map(|(index, reviewer)| PeerReviewPairing {
+            reviewer: (*reviewer).clone(),
+            author: trainees[(index + offset) % trainees.len()].clone(),
+        })
+        .collect()
+}
+
+pub async fn get_peer_review_pairings(
+    octocrab: &Octocrab,
+    github_org: &str,
+    batch_github_slug: &TeamSlug,
+    round: usize,
+) -> Result<Vec<PeerReviewPairing>, Error> {
+    let trainees = get_trainee_logins(octocrab, github_org, &[batch_github_slug.clone()]).await?;
+    Ok(assign_peer_reviewers(&trainees, round))
+}
+
+pub struct PeerReviewStatus {
+    pub pairing: PeerReviewPairing,
+    /// URLs of the author's PRs in the module which the reviewer reviewed since the sprint started.
+    pub reviewed_pr_urls: Vec<String>,
+}
+
+impl PeerReviewStatus {
+    pub fn happened(&self) -> bool {
+        !self.reviewed_pr_urls.is_empty()
+    }
+}
+
+/// Looks through the module's PR reviews to see which pairings actually resulted in a review.
+pub async fn check_peer_reviews(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    github_org: &str,
+    module: &str,
+    since: NaiveDate,
+    pairings: Vec<PeerReviewPairing>,
+) -> Result<Vec<PeerReviewStatus>, Error> {
+    let since = since
+        .and_hms_opt(0, 0, 0)
+        .expect("Known time failed to parse")
+        .and_utc();
+    let reviewers = get_reviewers(
+        octocrab.clone(),
+        in_flight,
+        github_org,
+        &[module.to_owned()],
+    )
+    .await?;
+    Ok(pairings
+        .into_iter()
+        .map(|pairing| {
+            let reviewed_pr_urls = reviewers
+                .iter()
+                .filter(|reviewer| reviewer.login == pairing.reviewer)
+                .flat_map(|reviewer| &reviewer.prs)
+                .filter(|reviewed_pr| {
+                    reviewed_pr.pr.author == pairing.author
+                        && reviewed_pr.latest_review_time >= since
+                })
+                .map(|reviewed_pr| reviewed_pr.pr.url.clone())
+                .collect();
+            PeerReviewStatus {
+                pairing,
+                reviewed_pr_urls,
+            }
+        })
+        .collect())
+}
+
+/// For posting to a Slack channel, where GitHub usernames need to be links to be useful.
+pub fn pairings_slack_text(
+    module: &str,
+    sprint_number: usize,
+    pairings: &[PeerReviewPairing],
+) -> String {
+    let mut text = format!(
+        "Peer review for {} sprint {}! Please review (at least) one PR by the trainee you've been paired with:",
+        module, sprint_number
+    );
+    for pairing in pairings {
+        text.push_str(&format!(
+            "\n• <https://github.com/{reviewer}|@{reviewer}> reviews <https://github.com/{author}/{module}/pulls|@{author}>",
+            reviewer = pairing.reviewer,
+            author = pairing.author,
+            module = module,
+        ));
+    }
+    text
+}
+
+/// For posting as a GitHub issue comment, where @-mentions notify each trainee.
+pub fn pairings_issue_comment(
+    module: &str,
+    sprint_number: usize,
+    pairings: &[PeerReviewPairing],
+) -> String {
+    let mut body = format!(
+        "## Peer review for {} sprint {}\n\nPlease review (at least) one PR by the trainee you've been paired with:\n",
+        module, sprint_number
+    );
+    for pairing in pairings {
+        body.push_str(&format!(
+            "\n- @{} reviews @{}",
+            pairing.reviewer, pairing.author
+        ));
+    }
+    body.push('\n');
+    body
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::newtypes::GithubLogin;
+
+    use super::assign_peer_reviewers;
+
+    #[test]
+    fn pairings_do_not_repeat_until_everyone_has_been_paired() {
+        let trainees = ["alice", "bob", "carol", "dave", "erin"]
+            .into_iter()
+            .map(|login| GithubLogin::from(login.to_owned()))
+            .collect::<BTreeSet<_>>();
+
+        let mut seen = BTreeSet::new();
+        for round in 0..trainees.len() - 1 {
+            let pairings = assign_peer_reviewers(&trainees, round);
+            assert_eq!(pairings.len(), trainees.len());
+            let authors = pairings
+                .iter()
+                .map(|pairing| pairing.author.clone())
+                .collect::<BTreeSet<_>>();
+            assert_eq!(authors, trainees);
+            for pairing in pairings {
+                assert_ne!(pairing.reviewer, pairing.author);
+                assert!(seen.insert((pairing.reviewer, pairing.author)));
+            }
+        }
+        assert_eq!(
+            assign_peer_reviewers(&trainees, trainees.len() - 1),
+            assign_peer_reviewers(&trainees, 0)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Error, ServerState, newtypes::SheetId, sheets::SheetsClient};
+
+/// Records which the tracker itself is the source of truth for (e.g. trainees' sprint goals), rather than reading from GitHub or sheets owned by other teams.
+///
+/// Each kind of record is an append-only tab of `persistence_sheet_id`, written by the service account, so users don't need edit access to the sheet.
+pub struct RecordStore {
+    sheets_client: SheetsClient,
+    sheet_id: SheetId,
+}
+
+impl RecordStore {
+    /// Returns None if the config doesn't have both a persistence sheet and service credentials.
+    pub async fn from_config(server_state: &ServerState) -> Result<Option<RecordStore>, Error> {
+        let (Some(sheet_id), Some(service_credentials)) = (
+            &server_state.config.persistence_sheet_id,
+            &server_state.config.service_credentials,
+        ) else {
+            return Ok(None);
+        };
+        let sheets_client = SheetsClient::for_service_account(
+            server_state.clone(),
+            &service_credentials.google_service_account_key_path,
+        )
+        .await?;
+        Ok(Some(RecordStore {
+            sheets_client,
+            sheet_id: sheet_id.clone(),
+        }))
+    }
+
+    /// Adds a record to the end of a tab, creating the tab (with a heading row) if this is the first record of its kind.
+    pub(crate) async fn append(
+        &self,
+        tab: &str,
+        headings: &[&str],
+        row: Vec<String>,
+    ) -> Result<(), Error> {
+        let range = format!("'{}'", tab);
+        if !self
+            .sheets_client
+            .sheet_titles(&self.sheet_id)
+            .await?
+            .iter()
+            .any(|title| title == tab)
+        {
+            self.sheets_client.add_sheet(&self.sheet_id, tab).await?;
+            self.sheets_client
+                .write_rows(
+                    &self.sheet_id,
+                    &range,
+                    vec![
+                        headings
+                            .iter()
+                            .map(|heading| (*heading).to_owned())
+                            .collect(),
+                    ],
+                )
+                .await?;
+        }
+        self.sheets_client
+            .append_rows(&self.sheet_id, &range, vec![row])
+            .await
+    }
+
+    /// All records in a tab, oldest first, without the heading row.
+    /// Returns no records if nothing has been stored in the tab yet.
+    pub(crate) async fn read(&self, tab: &str) -> Result<Vec<Vec<String>>, Error> {
+        if !self
+            .sheets_client
+            .sheet_titles(&self.sheet_id)
+            .await?
+            .iter()
+            .any(|title| title == tab)
+        {
+            return Ok(Vec::new());
+        }
+        let rows = self
+            .sheets_client
+            .read_rows(&self.sheet_id, &format!("'{}'", tab))
+            .await?;
+        Ok(rows.into_iter().skip(1).collect())
+    }
+}
@@ -25,6 +25,32 @@ pub async fn leave_tagged_comment<S: AsRef<str>>(
     Ok(())
 }
 
+/// Whether we've already left a comment with the given tag, e.g. to avoid posting the same thing twice.
+pub async fn has_tagged_comment(
+    octocrab: &Octocrab,
+    pull_request: &PullRequest,
+    tag: &str,
+) -> Result<bool, Error> {
+    let comments = all_pages("getting PR comments", octocrab, async || {
+        octocrab
+            .issues(&pull_request.org, &pull_request.repo)
+            .list_comments(pull_request.number)
+            .send()
+            .await
+    })
+    .await
+    .map_err(|err| {
+        err.with_context(|| format!("Getting comments for PR {}", pull_request.html_url()))
+    })?;
+    Ok(comments.iter().any(|comment| {
+        comment
+            .body
+            .as_deref()
+            .unwrap_or("")
+            .contains(&format!("{TAG_PREFIX}{tag}{TAG_SUFFIX}"))
+    }))
+}
+
 pub async fn close_existing_comments(
     octocrab: &Octocrab,
     pull_request: &PullRequest,
@@ -110,7 +136,7 @@ const TAG_SUFFIX: &str = "-->";
 
 // Unfortunately there's no "hide comment" REST API so we need a whole GraphQL thing...
 #[allow(unused)]
-mod graphql {
+pub(crate) mod graphql {
     use serde::Deserialize;
 
     pub const HIDE_COMMENT_MUTATION: &str = r#"
@@ -0,0 +1,486 @@
+//! Checking PR descriptions against the PR template they were started from.
+//!
+//! Nothing about a particular template is hard-coded: a module's template is read from its repo (or the org's `.github` repo,
+//! as GitHub itself does), and split into sections by its headings. A section with a checklist must be kept, with every item ticked.
+//! Any other section prompts for an answer, so the prompt must be replaced with one, or the section removed.
+
+use std::{collections::BTreeSet, net::IpAddr, sync::LazyLock, time::Duration};
+
+use futures::future::join_all;
+use octocrab::Octocrab;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{Error, module_discovery::get_repo_file};
+
+/// Where GitHub looks for a repo's PR template, in the order it looks.
+const TEMPLATE_PATHS: [&str; 6] = [
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+    "docs/PULL_REQUEST_TEMPLATE.md",
+];
+
+/// The org-wide repo GitHub falls back to for templates repos don't have themselves.
+const ORG_DEFAULTS_REPO: &str = ".github";
+
+const LINK_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prompts from the template modules used before they had their own, which descriptions are checked for when there's no template.
+const UNTEMPLATED_PROMPTS: [&str; 2] = [
+    "Briefly explain your PR.",
+    "Ask any questions you have for your reviewer.",
+];
+
+static HTML_COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static HEADING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^#{1,6}\s+(.*?)\s*#*$").unwrap());
+static CHECKLIST_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[-*+]\s+\[([ xX])\]\s+(.*)$").unwrap());
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s()<>\[\]"'`]+"#).unwrap());
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TemplateSection {
+    /// Empty for any text before the template's first heading.
+    pub heading: String,
+    pub checklist: Vec<String>,
+    /// Lines of text prompting for an answer. Only sections without a checklist have any: in a checklist section, text is a label.
+    pub prompts: Vec<String>,
+}
+
+/// A PR template, broken down into what a filled-in description needs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrTemplate {
+    pub sections: Vec<TemplateSection>,
+}
+
+/// Something in a PR description which shows its template wasn't filled in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind")]
+pub enum TemplateProblem {
+    MissingSection { heading: String },
+    UntickedItem { item: String },
+    PromptLeftIn { prompt: String },
+    BrokenLink { url: String },
+}
+
+impl PrTemplate {
+    pub fn parse(template: &str) -> Self {
+        let template = HTML_COMMENT.replace_all(template, "");
+        let mut sections = vec![TemplateSection::default()];
+        let mut prose = vec![Vec::new()];
+        for line in template.lines().map(normalise) {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(captures) = HEADING.captures(&line) {
+                sections.push(TemplateSection {
+                    heading: captures[1].to_owned(),
+                    ..TemplateSection::default()
+                });
+                prose.push(Vec::new());
+            } else if let Some(captures) = CHECKLIST_ITEM.captures(&line) {
+                sections
+                    .last_mut()
+                    .unwrap()
+                    .checklist
+                    .push(captures[2].to_owned());
+            } else {
+                prose.last_mut().unwrap().push(line);
+            }
+        }
+        for (section, prose) in sections.iter_mut().zip(prose) {
+            if section.checklist.is_empty() {
+                section.prompts = prose;
+            }
+        }
+        PrTemplate {
+            sections: sections
+                .into_iter()
+                .filter(|section| {
+                    !section.heading.is_empty()
+                        || !section.checklist.is_empty()
+                        || !section.prompts.is_empty()
+                })
+                .collect(),
+        }
+    }
+
+    /// Every problem with the description which can be found without going online, in the template's order.
+    pub fn problems(&self, body: &str) -> Vec<TemplateProblem> {
+        let body = HTML_COMMENT.replace_all(body, "");
+        let lines = body.lines().map(normalise).collect::<Vec<_>>();
+        let headings = lines
+            .iter()
+            .filter_map(|line| HEADING.captures(line))
+            .map(|captures| captures[1].to_lowercase())
+            .collect::<BTreeSet<_>>();
+        let ticked = lines
+            .iter()
+            .filter_map(|line| CHECKLIST_ITEM.captures(line))
+            .filter(|captures| &captures[1] != " ")
+            .map(|captures| captures[2].to_lowercase())
+            .collect::<BTreeSet<_>>();
+        let lines = lines
+            .iter()
+            .map(|line| line.to_lowercase())
+            .collect::<BTreeSet<_>>();
+
+        let mut problems = Vec::new();
+        for section in &self.sections {
+            if section.checklist.is_empty() {
+                problems.extend(
+                    section
+                        .prompts
+                        .iter()
+                        .filter(|prompt| lines.contains(&prompt.to_lowercase()))
+                        .map(|prompt| TemplateProblem::PromptLeftIn {
+                            prompt: prompt.clone(),
+                        }),
+                );
+                continue;
+            }
+            if !section.heading.is_empty() && !headings.contains(&section.heading.to_lowercase()) {
+                problems.push(TemplateProblem::MissingSection {
+                    heading: section.heading.clone(),
+                });
+                continue;
+            }
+            problems.extend(
+                section
+                    .checklist
+                    .iter()
+                    .filter(|item| !ticked.contains(&item.to_lowercase()))
+                    .map(|item| TemplateProblem::UntickedItem { item: item.clone() }),
+            );
+        }
+        problems
+    }
+}
+
+/// Links the trainee added to the description. Links which were already in the template aren't theirs to fix.
+pub fn added_links(template: &str, body: &str) -> BTreeSet<String> {
+    let template_links = links(template);
+    links(body)
+        .into_iter()
+        .filter(|url| !template_links.contains(url))
+        .collect()
+}
+
+/// The module's PR template, falling back to the org's, or None if neither has one.
+pub async fn get_pr_template(
+    octocrab: &Octocrab,
+    github_org: &str,
+    module_name: &str,
+) -> Result<Option<String>, Error> {
+    for repo in [module_name, ORG_DEFAULTS_REPO] {
+        for path in TEMPLATE_PATHS {
+            if let Some(template) = get_repo_file(octocrab, github_org, repo, path).await? {
+                return Ok(Some(template));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Every problem with a PR description compared to its template.
+///
+/// If check_links is set, this includes links the trainee added which don't load. That means requesting whatever URLs a trainee wrote,
+/// so it's only for the validator binary: the tracker itself shouldn't be made to request things on its own network.
+pub async fn check_pr_description(
+    template: &str,
+    body: &str,
+    check_links: bool,
+) -> Vec<TemplateProblem> {
+    let pr_template = PrTemplate::parse(template);
+    let mut problems = pr_template.problems(body);
+    if !check_links {
+        return problems;
+    }
+    let link_checks = added_links(template, body)
+        .into_iter()
+        .map(async |url| (!link_resolves(&url).await).then_some(url));
+    problems.extend(
+        join_all(link_checks)
+            .await
+            .into_iter()
+            .flatten()
+            .map(|url| TemplateProblem::BrokenLink { url }),
+    );
+    problems
+}
+
+/// Problems with a PR description when there's no template to compare it to: prompts from the old default template left in, and unticked checklist items.
+pub fn untemplated_problems(body: &str) -> Vec<TemplateProblem> {
+    let prompts_left_in = UNTEMPLATED_PROMPTS
+        .into_iter()
+        .filter(|prompt| body.contains(prompt))
+        .map(|prompt| TemplateProblem::PromptLeftIn {
+            prompt: prompt.to_owned(),
+        });
+    let unticked_items = body
+        .lines()
+        .map(normalise)
+        .filter_map(|line| {
+            CHECKLIST_ITEM
+                .captures(&line)
+                .filter(|captures| &captures[1] == " ")
+                .map(|captures| captures[2].to_owned())
+        })
+        .map(|item| TemplateProblem::UntickedItem { item });
+    prompts_left_in.chain(unticked_items).collect()
+}
+
+/// Only counts a link as broken if the site doesn't exist or says the page doesn't, as plenty of sites refuse requests which don't come from a browser.
+///
+/// Links to anything other than a public address (e.g. localhost, or a cloud metadata endpoint) count as broken without being requested -
+/// a reviewer couldn't open them either. Redirects aren't followed, as they could lead anywhere.
+async fn link_resolves(url: &str) -> bool {
+    let Ok(url) = url::Url::parse(url) else {
+        return false;
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let Some(host) = url.host_str().map(str::to_owned) else {
+        return false;
+    };
+    let to_resolve = url.clone();
+    let Ok(Ok(addresses)) =
+        tokio::task::spawn_blocking(move || to_resolve.socket_addrs(|| None)).await
+    else {
+        return false;
+    };
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public(address.ip())) {
+        return false;
+    }
+    // Connect to the addresses we checked, rather than letting the client look the host up again and maybe get different ones.
+    let Ok(client) = reqwest::Client::builder()
+        .timeout(LINK_CHECK_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(&host, &addresses)
+        .build()
+    else {
+        return false;
+    };
+    match client.get(url).send().await {
+        Ok(response) => !matches!(
+            response.status(),
+            reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE
+        ),
+        Err(err) => err.is_timeout(),
+    }
+}
+
+/// Whether an address is on the public internet, rather than e.g. a private network, loopback or link-local.
+fn is_public(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let [first, second, ..] = address.octets();
+            !(address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+                || address.is_documentation()
+                || address.is_multicast()
+                || first == 0
+                // Carrier-grade NAT (100.64.0.0/10).
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(address) => match address.to_ipv4_mapped() {
+            Some(address) => is_public(IpAddr::V4(address)),
+            None => {
+                let first_segment = address.segments()[0];
+                !(address.is_loopback()
+                    || address.is_unspecified()
+                    || address.is_multicast()
+                    // Unique local (fc00::/7).
+                    || first_segment & 0xfe00 == 0xfc00
+                    // Link-local (fe80::/10).
+                    || first_segment & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+fn links(text: &str) -> BTreeSet<String> {
+    let text = HTML_COMMENT.replace_all(text, "");
+    URL.find_iter(&text)
+        .map(|url| {
+            url.as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?'])
+                .to_owned()
+        })
+        .collect()
+}
+
+/// Trims a line, and collapses runs of whitespace, so that re-indenting or re-wrapping doesn't change what it says.
+fn normalise(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        PrTemplate, TemplateProblem, TemplateSection, added_links, is_public, untemplated_problems,
+    };
+
+    const TEMPLATE: &str = r#"<!--
+Fill in the template below - remove any sections that don't apply.
+-->
+
+## Learners, PR Template
+
+Self checklist
+
+- [ ] I have titled my PR with Region | Cohort | FirstName LastName | Sprint | Assignment Title
+- [ ] My changes meet the requirements of the task
+- [ ] My changes follow the [style guide](https://curriculum.codeyourfuture.io/guides/reviewing/style-guide)
+
+## Changelist
+
+Briefly explain your PR.
+
+## Questions
+
+Ask any questions you have for your reviewer.
+"#;
+
+    #[test]
+    fn parses_sections() {
+        let template = PrTemplate::parse(TEMPLATE);
+        assert_eq!(
+            template.sections,
+            vec![
+                TemplateSection {
+                    heading: "Learners, PR Template".to_owned(),
+                    checklist: vec![
+                        "I have titled my PR with Region | Cohort | FirstName LastName | Sprint | Assignment Title".to_owned(),
+                        "My changes meet the requirements of the task".to_owned(),
+                        "My changes follow the [style guide](https://curriculum.codeyourfuture.io/guides/reviewing/style-guide)".to_owned(),
+                    ],
+                    prompts: vec![],
+                },
+                TemplateSection {
+                    heading: "Changelist".to_owned(),
+                    checklist: vec![],
+                    prompts: vec!["Briefly explain your PR.".to_owned()],
+                },
+                TemplateSection {
+                    heading: "Questions".to_owned(),
+                    checklist: vec![],
+                    prompts: vec!["Ask any questions you have for your reviewer.".to_owned()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn filled_in_template_has_no_problems() {
+        let body = r#"## Learners, PR Template
+
+Self checklist
+
+- [x] I have titled my PR with Region | Cohort | FirstName LastName | Sprint | Assignment Title
+- [X] My changes meet the requirements of the task
+- [x] My changes follow the [style guide](https://curriculum.codeyourfuture.io/guides/reviewing/style-guide)
+
+## Changelist
+
+I made the alarm clock count down.
+"#;
+        assert_eq!(PrTemplate::parse(TEMPLATE).problems(body), vec![]);
+    }
+
+    #[test]
+    fn finds_unfilled_parts() {
+        let body = r#"## Learners, PR Template
+
+- [x] I have titled my PR with Region | Cohort | FirstName LastName | Sprint | Assignment Title
+- [ ] My changes meet the requirements of the task
+
+## Changelist
+
+Briefly   explain your PR.
+
+## Questions
+
+Why does the alarm go off twice?
+"#;
+        assert_eq!(
+            PrTemplate::parse(TEMPLATE).problems(body),
+            vec![
+                TemplateProblem::UntickedItem {
+                    item: "My changes meet the requirements of the task".to_owned()
+                },
+                TemplateProblem::UntickedItem {
+                    item: "My changes follow the [style guide](https://curriculum.codeyourfuture.io/guides/reviewing/style-guide)".to_owned()
+                },
+                TemplateProblem::PromptLeftIn {
+                    prompt: "Briefly explain your PR.".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checklist_sections_are_required() {
+        assert_eq!(
+            PrTemplate::parse(TEMPLATE).problems("## Changelist\n\nI did the thing."),
+            vec![TemplateProblem::MissingSection {
+                heading: "Learners, PR Template".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn only_checks_links_the_trainee_added() {
+        let body = "See https://example.com/my-deploy. Following [the style guide](https://curriculum.codeyourfuture.io/guides/reviewing/style-guide)";
+        assert_eq!(
+            added_links(TEMPLATE, body).into_iter().collect::<Vec<_>>(),
+            vec!["https://example.com/my-deploy".to_owned()]
+        );
+    }
+
+    #[test]
+    fn untemplated_descriptions_are_checked_for_the_old_prompts() {
+        let body = "Briefly explain your PR.\n\n- [x] I have titled my PR correctly\n-  [ ]  My changes meet the requirements of the task";
+        assert_eq!(
+            untemplated_problems(body),
+            vec![
+                TemplateProblem::PromptLeftIn {
+                    prompt: "Briefly explain your PR.".to_owned()
+                },
+                TemplateProblem::UntickedItem {
+                    item: "My changes meet the requirements of the task".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn only_public_addresses_are_public() {
+        for address in ["8.8.8.8", "140.82.112.3", "2606:4700::1111"] {
+            assert!(is_public(address.parse().unwrap()), "{address}");
+        }
+        for address in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(address.parse().unwrap()), "{address}");
+        }
+    }
+}
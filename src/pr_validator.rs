@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use indexmap::IndexMap;
+use maplit::btreemap;
+use octocrab::Octocrab;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error,
+    config::{CourseSchedule, CourseScheduleWithRegisterSheetId},
+    course::{get_sprint_and_descriptor_id_for_pr, match_prs_to_assignments},
+    i18n::{Language, fill_placeholders},
+    module_discovery::{ModuleMetadata, get_module_metadata},
+    newtypes::Region,
+    octocrab::all_pages,
+    pr_template::{TemplateProblem, check_pr_description, get_pr_template, untemplated_problems},
+    prs::get_prs,
+    single_flight::InFlight,
+};
+
+/// Tags every comment the validator leaves about a problem with a PR, so they can all be hidden once it's fixed.
+pub const PR_METADATA_VALIDATOR_LABEL: &str = "pr-metadata-validator";
+
+const ARBITRARY_REGION: Region = Region(String::new());
+
+/// What the validator binaries exit with, so workflows wrapping them can tell a PR which failed validation from the tool failing.
+/// Any other code (e.g. 101, from a panic) also means the tool failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidatorExitCode {
+    Passed = 0,
+    /// The tool couldn't validate the PR, e.g. because of bad arguments or a GitHub error. Nothing should be assumed about the PR.
+    Error = 1,
+    /// The PR was validated, and has problems which the trainee has been told about.
+    FailedValidation = 2,
+}
+
+impl ValidatorExitCode {
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
+
+/// Serialized (e.g. for the validator's `--json` output) with its variant name, which is also its `Display`, under `result`.
+#[derive(Serialize, strum_macros::Display)]
+#[serde(tag = "result")]
+pub enum ValidationResult {
+    Ok,
+    BodyTemplateNotFilledOut { problems: Vec<TemplateProblem> },
+    CouldNotMatch,
+    BadTitleFormat { reason: BadTitleReason },
+    UnknownRegion,
+    WrongFiles { expected_files_pattern: String },
+    NoFiles,
+    TooManyFiles,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+pub enum BadTitleReason {
+    WrongNumberOfParts,
+    BadSprint { sprint_part: String },
+    AllUppercase,
+}
+
+/// How a module's validator comments are worded, from the `[validator_comments]` table of its metadata file.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ValidatorComments {
+    /// Which of the built-in catalogues (see `i18n`) to comment in.
+    #[serde(default)]
+    pub language: Language,
+    /// Message id (without the `validator-` prefix, e.g. `unknown-region`) -> text to use instead of the catalogue's, e.g. in simplified English.
+    /// Overrides can use the same `{ $name }` placeholders as the message they replace.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl ValidatorComments {
+    fn message(&self, id: &str, args: Vec<(&str, String)>) -> String {
+        match self.overrides.get(id) {
+            Some(text) => fill_placeholders(text, args),
+            None => self.language.t_with(&format!("validator-{id}"), args),
+        }
+    }
+
+    /// The comment to leave on a PR which failed validation, or None if it passed.
+    pub fn comment(
+        &self,
+        result: &ValidationResult,
+        give_more_specific_comment_for_earlier_learners: bool,
+    ) -> Option<String> {
+        let message = match result {
+            ValidationResult::Ok => return None,
+            ValidationResult::CouldNotMatch => self.message("could-not-match", vec![]),
+            ValidationResult::BodyTemplateNotFilledOut { problems } => {
+                let message = if give_more_specific_comment_for_earlier_learners {
+                    self.message("body-template-not-filled-out-specific", vec![])
+                } else {
+                    self.message("body-template-not-filled-out", vec![])
+                };
+                let problems = problems
+                    .iter()
+                    .map(|problem| format!("- {}", self.template_problem(problem)))
+                    .collect::<Vec<_>>();
+                format!("{}\n\n{}", message, problems.join("\n"))
+            }
+            ValidationResult::BadTitleFormat { reason } => {
+                let reason = match reason {
+                    BadTitleReason::WrongNumberOfParts => {
+                        self.message("title-wrong-number-of-parts", vec![])
+                    }
+                    BadTitleReason::BadSprint { sprint_part } => {
+                        self.message("title-bad-sprint", vec![("sprint", sprint_part.clone())])
+                    }
+                    BadTitleReason::AllUppercase => self.message("title-uppercase", vec![]),
+                };
+                self.message("bad-title", vec![("reason", reason)])
+            }
+            ValidationResult::UnknownRegion => self.message("unknown-region", vec![]),
+            ValidationResult::WrongFiles {
+                expected_files_pattern,
+            } => self.message(
+                "wrong-files",
+                vec![("expected_files", expected_files_pattern.clone())],
+            ),
+            ValidationResult::NoFiles => self.message("no-files", vec![]),
+            ValidationResult::TooManyFiles => self.message("too-many-files", vec![]),
+        };
+        Some(format!("{}\n\n{}", message, self.message("footer", vec![])))
+    }
+
+    fn template_problem(&self, problem: &TemplateProblem) -> String {
+        match problem {
+            TemplateProblem::MissingSection { heading } => self.message(
+                "template-missing-section",
+                vec![("heading", heading.clone())],
+            ),
+            TemplateProblem::UntickedItem { item } => {
+                self.message("template-unticked-item", vec![("item", item.clone())])
+            }
+            TemplateProblem::PromptLeftIn { prompt } => {
+                self.message("template-prompt-left-in", vec![("prompt", prompt.clone())])
+            }
+            TemplateProblem::BrokenLink { url } => {
+                self.message("template-broken-link", vec![("url", url.clone())])
+            }
+        }
+    }
+}
+
+/// The assignment a PR which passed validation was matched to.
+#[derive(Serialize)]
+pub struct MatchedAssignment {
+    pub sprint_number: usize,
+    pub assignment_issue_id: u64,
+}
+
+/// Returns the assignment the PR was matched to alongside the result, if it was matched.
+///
+/// check_links makes links the trainee added to the description count as problems if they don't load (see `check_pr_description`).
+pub async fn validate_pr(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    course_schedule: CourseScheduleWithRegisterSheetId,
+    module_name: &str,
+    github_org_name: &str,
+    pr_number: u64,
+    known_region_aliases: &KnownRegions,
+    check_links: bool,
+) -> Result<(ValidationResult, Option<MatchedAssignment>), Error> {
+    let course = course_schedule
+        .with_assignments(octocrab, in_flight, github_org_name)
+        .await
+        .map_err(|err| err.context("Failed to get assignments"))?;
+
+    let module_prs = get_prs(octocrab, in_flight, github_org_name, module_name, false)
+        .await
+        .map_err(|err| err.context("Failed to get PRs"))?;
+    let pr_in_question = module_prs
+        .iter()
+        .find(|pr| pr.number == pr_number)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Failed to find PR {} in list of PRs for module {}",
+                pr_number,
+                module_name
+            )
+        })?
+        .clone();
+
+    if pr_in_question.labels.contains("NotCoursework") {
+        return Ok((ValidationResult::Ok, None));
+    }
+
+    let user_prs: Vec<_> = module_prs
+        .into_iter()
+        .filter(|pr| pr.author == pr_in_question.author)
+        .collect();
+    let matched = match_prs_to_assignments(
+        &course.modules[module_name],
+        user_prs,
+        Vec::new(),
+        &ARBITRARY_REGION,
+    )
+    .map_err(|err| err.context("Failed to match PRs to assignments"))?;
+
+    for pr in matched.unknown_prs {
+        if pr.number == pr_number {
+            return Ok((ValidationResult::CouldNotMatch, None));
+        }
+    }
+
+    // The module's tracker.toml can change what titles look like, and which files each assignment's PRs should change.
+    let metadata = get_module_metadata(octocrab, github_org_name, module_name).await?;
+    let title_format = metadata
+        .as_ref()
+        .map(|metadata| metadata.title_format.clone())
+        .unwrap_or_default();
+
+    let title_sections: Vec<&str> = pr_in_question.title.split("|").collect();
+    if title_sections.len() != title_format.parts {
+        return Ok((
+            ValidationResult::BadTitleFormat {
+                reason: BadTitleReason::WrongNumberOfParts,
+            },
+            None,
+        ));
+    }
+
+    if let Some(region_section) = title_sections.get(title_format.region_part.get() - 1)
+        && !known_region_aliases.is_known_ignoring_case(region_section.trim())
+    {
+        return Ok((ValidationResult::UnknownRegion, None));
+    }
+
+    // TODO: Validate cohorts when they're known (1)
+    let sprint_regex = Regex::new(r"^(S|s)print \d+$").unwrap();
+    if let Some(sprint_section) = title_sections.get(title_format.sprint_part.get() - 1)
+        && !sprint_regex.is_match(sprint_section.trim())
+    {
+        return Ok((
+            ValidationResult::BadTitleFormat {
+                reason: BadTitleReason::BadSprint {
+                    sprint_part: sprint_section.trim().to_owned(),
+                },
+            },
+            None,
+        ));
+    }
+
+    if pr_in_question.title.to_ascii_uppercase() == pr_in_question.title {
+        return Ok((
+            ValidationResult::BadTitleFormat {
+                reason: BadTitleReason::AllUppercase,
+            },
+            None,
+        ));
+    }
+
+    let problems = match get_pr_template(octocrab, github_org_name, module_name).await? {
+        Some(template) => check_pr_description(&template, &pr_in_question.body, check_links).await,
+        // Without a template, there's nothing to say what a filled-in description looks like, so look for the usual signs it wasn't.
+        None => untemplated_problems(&pr_in_question.body),
+    };
+    if !problems.is_empty() {
+        return Ok((
+            ValidationResult::BodyTemplateNotFilledOut { problems },
+            None,
+        ));
+    }
+
+    // By this point the PR has been matched with an assignment, and assignments always have an issue, so this should always be found.
+    let (sprint_index, assignment_issue_id) =
+        get_sprint_and_descriptor_id_for_pr(&matched.sprints, pr_number).ok_or_else(|| {
+            anyhow::anyhow!(
+                "PR {} in {} was matched to an assignment, but its assignment couldn't be found",
+                pr_number,
+                module_name
+            )
+        })?;
+
+    let result = check_pr_file_changes(
+        octocrab,
+        github_org_name,
+        module_name,
+        pr_number,
+        assignment_issue_id,
+        metadata.as_ref(),
+    )
+    .await?;
+    Ok((
+        result,
+        Some(MatchedAssignment {
+            sprint_number: sprint_index + 1,
+            assignment_issue_id,
+        }),
+    ))
+}
+
+// Check the changed files in a pull request match what is expected for that sprint task
+async fn check_pr_file_changes(
+    octocrab: &Octocrab,
+    org_name: &str,
+    module_name: &str,
+    pr_number: u64,
+    task_issue_number: u64,
+    metadata: Option<&ModuleMetadata>,
+) -> Result<ValidationResult, Error> {
+    let directory_description_regex = if let Some(expected_files) =
+        metadata.and_then(|metadata| metadata.expected_files(task_issue_number))
+    {
+        expected_files.to_owned()
+    } else {
+        // Get the Sprint Task's description of expected changes
+        let Ok(task_issue) = octocrab
+            .issues(org_name, module_name)
+            .get(task_issue_number)
+            .await
+        else {
+            return Ok(ValidationResult::CouldNotMatch); // Failed to find the right task
+        };
+
+        let task_issue_body = task_issue.body.unwrap_or_default();
+
+        let directory_description = Regex::new("CHANGE_DIR=(.+)\\n").map_err(|err| {
+            Error::UserFacing(format!("Known good regex failed to compile: {}", err))
+        })?;
+        let Some(directory) = directory_description
+            .captures(&task_issue_body)
+            .and_then(|captures| captures.get(1))
+        else {
+            return Ok(ValidationResult::Ok); // There is no match defined for this task, don't do any more checks
+        };
+        directory.as_str().to_owned() // Only allows a single directory for now
+    };
+
+    let directory_matcher = Regex::new(&directory_description_regex).map_err(|err| {
+        Error::UserFacing(format!(
+            "Failed to compile expected files regex {} for assignment #{}: {}",
+            directory_description_regex, task_issue_number, err
+        ))
+    })?;
+
+    // Get all of the changed files
+    let pr_files = all_pages("changed files in pull request", octocrab, async || {
+        octocrab
+            .pulls(org_name, module_name)
+            .list_files(pr_number)
+            .await
+    })
+    .await?;
+    if pr_files.is_empty() {
+        return Ok(ValidationResult::NoFiles); // no files committed
+    }
+
+    if pr_files.len() > 100 {
+        return Ok(ValidationResult::TooManyFiles); // too many files probably a venv or npm cache
+    }
+
+    // check each file and error if one is in unexpected place
+    for pr_file in pr_files {
+        if pr_file.filename == ".gitignore" {
+            continue; // always allow top-level gitignore changes
+        }
+        if !directory_matcher.is_match(&pr_file.filename) {
+            return Ok(ValidationResult::WrongFiles {
+                expected_files_pattern: directory_description_regex,
+            });
+        }
+    }
+
+    Ok(ValidationResult::Ok)
+}
+
+/// Region names trainees might put in their PR titles, with the other ways they spell them.
+pub struct KnownRegions(pub BTreeMap<&'static str, Vec<&'static str>>);
+
+impl Default for KnownRegions {
+    fn default() -> Self {
+        // TODO: Fetch this from classplanner or somewhere when we have access to a useful API.
+        KnownRegions(btreemap! {
+            "Cape Town" => vec!["South Africa", "SouthAfrica", "ZA", "ZA Cape Town"],
+            "Glasgow" => vec!["Scotland"],
+            "London" => vec![],
+            "North West" => vec!["NW", "Manchester"],
+            "Sheffield" => vec![],
+            "West Midlands" => vec!["WM", "WestMidlands", "West-Midlands", "Birmingham"],
+        })
+    }
+}
+
+impl KnownRegions {
+    pub fn is_known_ignoring_case(&self, possible_region: &str) -> bool {
+        let possible_region_lower = possible_region.to_ascii_lowercase();
+        for (known_region, known_region_aliases) in &self.0 {
+            if known_region.to_ascii_lowercase() == possible_region_lower {
+                return true;
+            }
+            for known_region_alias in known_region_aliases {
+                if known_region_alias.to_ascii_lowercase() == possible_region_lower {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+fn make_fake_course_schedule(module_name: String) -> CourseSchedule {
+    let fixed_date = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+    let mut sprints = IndexMap::new();
+    sprints.insert(
+        module_name,
+        // Further sprints are added based on the sprint labels on the module's issues.
+        vec![btreemap![ARBITRARY_REGION => fixed_date]],
+    );
+    CourseSchedule {
+        start: fixed_date,
+        end: fixed_date,
+        sprints,
+        groups: Vec::new(),
+        project_repos: Vec::new(),
+        deadline_extensions: Vec::new(),
+        onboarding_sessions: Vec::new(),
+        peer_review_slack_webhook_url: None,
+        slack_channels: Vec::new(),
+        slack_usergroup_handle: None,
+        leaderboard: false,
+        weekly_classes_from: BTreeMap::new(),
+        assessment_windows: Vec::new(),
+    }
+}
+
+/// A stand-in course containing just the module, which is all validation needs.
+pub fn module_course(module_name: String) -> CourseScheduleWithRegisterSheetId {
+    CourseScheduleWithRegisterSheetId {
+        name: "itp".to_owned(),
+        register_sheet_id: None,
+        course_schedule: make_fake_course_schedule(module_name),
+        submit_labels: Default::default(),
+        module_settings: Default::default(),
+        duplicate_register_entries: Default::default(),
+        lateness: Default::default(),
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, num::NonZeroUsize};
+
+use anyhow::Context;
+use futures::future::join_all;
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error,
+    course::{
+        Assignment, AssignmentOptionality, Module, ModuleWithSubmissions, Submission,
+        SubmissionState,
+    },
+    newtypes::GithubLogin,
+    octocrab::all_pages,
+};
+
+/// An assignment whose submission is a whole repo the trainee created (e.g. a final project), rather than a PR to a module repo.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProjectRepoAssignment {
+    pub title: String,
+    pub module: String,
+    /// 1-based, as in sprint labels.
+    pub sprint: NonZeroUsize,
+    /// Where we expect to find each trainee's repo - "{github_login}" is replaced with the trainee's GitHub login.
+    /// If there's no "/", the repo is expected to be in the trainee's account.
+    pub repo_name: String,
+    /// Trainees who submitted a link to a repo which doesn't follow the naming convention, as GitHub login -> "owner/repo".
+    #[serde(default)]
+    pub submitted_repos: BTreeMap<GithubLogin, String>,
+    #[serde(default = "default_min_commits")]
+    pub min_commits: usize,
+    #[serde(default = "default_optionality")]
+    pub optionality: AssignmentOptionality,
+}
+
+fn default_min_commits() -> usize {
+    10
+}
+
+fn default_optionality() -> AssignmentOptionality {
+    AssignmentOptionality::Mandatory
+}
+
+impl ProjectRepoAssignment {
+    pub fn assignment(&self) -> Assignment {
+        Assignment::ProjectRepo {
+            title: self.title.clone(),
+            repo_name: self.repo_name.clone(),
+            submitted_repos: self.submitted_repos.clone(),
+            min_commits: self.min_commits,
+            optionality: self.optionality,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProjectRepoSubmission {
+    pub owner: String,
+    pub repo: String,
+    pub url: String,
+    pub has_readme: bool,
+    pub has_deployments: bool,
+    pub commit_count: usize,
+    pub min_commits: usize,
+    pub optionality: AssignmentOptionality,
+}
+
+impl ProjectRepoSubmission {
+    pub const CHECK_COUNT: usize = 3;
+
+    pub fn checks_passed(&self) -> usize {
+        [
+            self.has_readme,
+            self.has_deployments,
+            self.commit_count >= self.min_commits,
+        ]
+        .into_iter()
+        .filter(|passed| *passed)
+        .count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.checks_passed() == Self::CHECK_COUNT
+    }
+}
+
+/// Replaces the placeholder states of a trainee's project repo assignments with what we find on GitHub.
+/// Repos which don't exist are left as missing.
+pub async fn fill_in_project_repos(
+    octocrab: &Octocrab,
+    github_login: &GithubLogin,
+    module: &Module,
+    module_with_submissions: &mut ModuleWithSubmissions,
+) -> Result<(), Error> {
+    let mut futures = Vec::new();
+    for (sprint_index, sprint) in module.sprints.iter().enumerate() {
+        for (assignment_index, assignment) in sprint.assignments.iter().enumerate() {
+            if let Assignment::ProjectRepo {
+                repo_name,
+                submitted_repos,
+                min_commits,
+                optionality,
+                ..
+            } = assignment
+            {
+                let repo_path = submitted_repos
+                    .get(github_login)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        repo_name.replace("{github_login}", &github_login.to_string())
+                    });
+                let (owner, repo) = match repo_path.split_once('/') {
+                    Some((owner, repo)) => (owner.to_owned(), repo.to_owned()),
+                    None => (github_login.to_string(), repo_path),
+                };
+                futures.push(async move {
+                    let submission =
+                        check_project_repo(octocrab, owner, repo, *min_commits, *optionality).await;
+                    (sprint_index, assignment_index, submission)
+                });
+            }
+        }
+    }
+    for (sprint_index, assignment_index, submission) in join_all(futures).await {
+        if let Some(submission) = submission? {
+            module_with_submissions.sprints[sprint_index].submissions[assignment_index] =
+                SubmissionState::Some(Submission::ProjectRepo(submission));
+        }
+    }
+    Ok(())
+}
+
+async fn check_project_repo(
+    octocrab: &Octocrab,
+    owner: String,
+    repo: String,
+    min_commits: usize,
+    optionality: AssignmentOptionality,
+) -> Result<Option<ProjectRepoSubmission>, Error> {
+    let repository = match octocrab.repos(&owner, &repo).get().await {
+        Ok(repository) => repository,
+        Err(octocrab::Error::GitHub { source, .. }) if source.status_code == 404 => {
+            return Ok(None);
+        }
+        Err(err) => {
+            return Err(Error::Fatal(
+                anyhow::Error::from(err).context(format!("Failed to get repo {owner}/{repo}")),
+            ));
+        }
+    };
+
+    let has_readme = octocrab
+        .repos(&owner, &repo)
+        .get_readme()
+        .send()
+        .await
+        .is_ok();
+    let deployments: Vec<serde_json::Value> = octocrab
+        .get(format!("/repos/{owner}/{repo}/deployments"), None::<&()>)
+        .await
+        .with_context(|| format!("Failed to list deployments for {owner}/{repo}"))?;
+    // Repos with no commits at all 409 rather than returning an empty list.
+    let commit_count = all_pages("repo commits", octocrab, async || {
+        octocrab.repos(&owner, &repo).list_commits().send().await
+    })
+    .await
+    .map_or(0, |commits| commits.len());
+
+    Ok(Some(ProjectRepoSubmission {
+        url: repository
+            .html_url
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| format!("https://github.com/{owner}/{repo}")),
+        owner,
+        repo,
+        has_readme,
+        has_deployments: !deployments.is_empty(),
+        commit_count,
+        min_commits,
+        optionality,
+    }))
+}
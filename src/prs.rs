@@ -1,10 +1,13 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use anyhow::Context;
 use chrono::{DateTime, TimeDelta};
 use futures::future::join_all;
 use octocrab::Octocrab;
-use octocrab::models::pulls::{Comment, PullRequest, Review as OctoReview};
+use octocrab::models::pulls::{Comment, PullRequest, Review as OctoReview, ReviewState};
 use octocrab::models::timelines::TimelineEvent;
 use octocrab::models::{Author, Event, IssueState};
 use octocrab::params::State;
@@ -12,7 +15,8 @@ use serde::Serialize;
 
 use crate::Error;
 use crate::newtypes::GithubLogin;
-use crate::octocrab::all_pages;
+use crate::octocrab::{all_pages, check_repo_access};
+use crate::single_flight::InFlight;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Pr {
@@ -63,7 +67,58 @@ pub struct Review {
     author: GithubLogin,
 }
 
+/// The unified diff of every file a PR changes, concatenated.
+/// GitHub leaves out patches for binary and very large files, so those aren't included.
+pub async fn get_pr_patch(
+    octocrab: &Octocrab,
+    org_name: &str,
+    repo_name: &str,
+    number: u64,
+) -> Result<String, Error> {
+    let files = all_pages("changed files in pull request", octocrab, async || {
+        octocrab.pulls(org_name, repo_name).list_files(number).await
+    })
+    .await
+    .map_err(|err| err.with_context(|| format!("Failed to get files for PR {}", number)))?;
+    Ok(files
+        .into_iter()
+        .filter_map(|file| file.patch)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
 pub async fn get_prs(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    org_name: &str,
+    module: &str,
+    include_complete_closed: bool,
+) -> Result<Vec<Pr>, Error> {
+    let key = format!(
+        "{}/{}?state={}",
+        org_name,
+        module,
+        if include_complete_closed {
+            "all"
+        } else {
+            "open"
+        }
+    );
+    let flight = in_flight
+        .prs
+        .run(&key, async || {
+            fetch_prs(octocrab, org_name, module, include_complete_closed)
+                .await
+                .map(Arc::new)
+        })
+        .await?;
+    if flight.shared {
+        check_repo_access(octocrab, org_name, module).await?;
+    }
+    Ok(Arc::unwrap_or_clone(flight.value))
+}
+
+async fn fetch_prs(
     octocrab: &Octocrab,
     org_name: &str,
     module: &str,
@@ -213,14 +268,14 @@ pub(crate) async fn fill_in_reviewers(
         .collect())
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub(crate) enum CheckStatus {
     CheckedAndOk,
     CheckedAndCheckAgain,
     Unchecked,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub(crate) struct ReviewerStaffOnlyDetails {
     pub(crate) name: String,
     pub(crate) attended_training: bool,
@@ -235,6 +290,9 @@ pub(crate) struct ReviewerInfo {
     pub prs: Vec<ReviewedPr>,
     pub login: GithubLogin,
     pub reviews_days_in_last_28_days: u8,
+    /// Distinct authors of the PRs this reviewer has reviewed - helping many trainees matters more than reviewing a lot.
+    pub trainees_reviewed: BTreeSet<GithubLogin>,
+    pub trainees_reviewed_in_last_28_days: BTreeSet<GithubLogin>,
     pub staff_only_details: MaybeReviewerStaffOnlyDetails,
 }
 
@@ -433,6 +491,7 @@ pub(crate) struct ReviewedPr {
 
 pub(crate) async fn get_reviewers(
     octocrab: Octocrab,
+    in_flight: &InFlight,
     github_org: &str,
     module_names: &[String],
 ) -> Result<BTreeSet<ReviewerInfo>, Error> {
@@ -441,7 +500,7 @@ pub(crate) async fn get_reviewers(
         let octocrab = octocrab.clone();
         let github_org = github_org.to_owned();
         futures.push(async move {
-            let prs = get_prs(&octocrab, &github_org, module, true).await?;
+            let prs = get_prs(&octocrab, in_flight, &github_org, module, true).await?;
             fill_in_reviewers(octocrab, github_org, prs).await
         });
     }
@@ -460,7 +519,8 @@ pub(crate) async fn get_reviewers(
                     continue;
                 }
 
-                if now - review.created_at <= TimeDelta::weeks(4) {
+                let is_recent = now - review.created_at <= TimeDelta::weeks(4);
+                if is_recent {
                     reviewers_to_recent_review_days
                         .entry(review.author.clone())
                         .or_default()
@@ -475,11 +535,21 @@ pub(crate) async fn get_reviewers(
                             prs: Vec::new(),
                             login: review.author.clone(),
                             reviews_days_in_last_28_days: 0,
+                            trainees_reviewed: BTreeSet::new(),
+                            trainees_reviewed_in_last_28_days: BTreeSet::new(),
                             staff_only_details: MaybeReviewerStaffOnlyDetails::NotAuthenticated,
                         });
                 if review.created_at > reviewer_info.last_review {
                     reviewer_info.last_review = review.created_at;
                 }
+                reviewer_info
+                    .trainees_reviewed
+                    .insert(pr_with_reviews.pr.author.clone());
+                if is_recent {
+                    reviewer_info
+                        .trainees_reviewed_in_last_28_days
+                        .insert(pr_with_reviews.pr.author.clone());
+                }
                 if *reviewers_to_latest_time
                     .entry(review.author.clone())
                     .or_insert(review.created_at)
@@ -568,6 +638,124 @@ pub(crate) async fn get_review_metrics(
     Ok(PrMetrics::new(pr, created_at, label_add_events))
 }
 
+/// Whose move it is on an open PR which has had changes requested.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum BallInCourt {
+    /// Changes were requested, and the trainee hasn't pushed commits or commented since.
+    Trainee,
+    /// The trainee has responded to the requested changes, so a reviewer needs to look again.
+    Reviewer,
+}
+
+/// Each review's author, state, and when it was submitted, oldest first.
+pub(crate) async fn get_review_states(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo_name: &str,
+    number: u64,
+) -> Result<Vec<(GithubLogin, ReviewState, DateTime<chrono::Utc>)>, Error> {
+    let reviews = all_pages("PR reviews", octocrab, async || {
+        octocrab
+            .pulls(github_org, repo_name)
+            .list_reviews(number)
+            .send()
+            .await
+    })
+    .await?;
+    Ok(reviews
+        .into_iter()
+        .filter_map(|review| {
+            let Author { login, .. } = review.user?;
+            Some((
+                GithubLogin::from(login),
+                review.state?,
+                review.submitted_at?,
+            ))
+        })
+        .collect())
+}
+
+/// When the PR's newest commit was committed.
+/// The committer date changes when commits are rebased or amended, so is closer to when they were pushed than the author date.
+pub(crate) async fn get_last_push(
+    octocrab: &Octocrab,
+    github_org: &str,
+    repo_name: &str,
+    number: u64,
+) -> Result<Option<DateTime<chrono::Utc>>, Error> {
+    let commits = all_pages("commits in pull request", octocrab, async || {
+        octocrab
+            .pulls(github_org, repo_name)
+            .pr_commits(number)
+            .send()
+            .await
+    })
+    .await?;
+    Ok(commits
+        .iter()
+        .filter_map(|commit| commit.commit.committer.as_ref()?.date)
+        .max())
+}
+
+/// Whose move it is on the PR, or None if it's closed or the latest approving or requesting review didn't request changes.
+pub(crate) async fn get_ball_in_court(
+    octocrab: &Octocrab,
+    github_org: &str,
+    pr: &Pr,
+) -> Result<Option<BallInCourt>, Error> {
+    if pr.is_closed {
+        return Ok(None);
+    }
+    let reviews = get_review_states(octocrab, github_org, &pr.repo_name, pr.number).await?;
+    let Some(changes_requested_at) = latest_changes_requested(&reviews, &pr.author) else {
+        return Ok(None);
+    };
+    let last_push = get_last_push(octocrab, github_org, &pr.repo_name, pr.number).await?;
+    let comments = all_pages("PR comments", octocrab, async || {
+        octocrab
+            .issues(github_org, &pr.repo_name)
+            .list_comments(pr.number)
+            .send()
+            .await
+    })
+    .await?;
+    // Replies to review comments show up as the trainee's own reviews.
+    let last_response = comments
+        .iter()
+        .filter(|comment| GithubLogin::from(comment.user.login.clone()) == pr.author)
+        .map(|comment| comment.created_at)
+        .chain(
+            reviews
+                .iter()
+                .filter(|(reviewer, _, _)| *reviewer == pr.author)
+                .map(|(_, _, submitted_at)| *submitted_at),
+        )
+        .chain(last_push)
+        .max();
+    Ok(Some(
+        if last_response.is_some_and(|last_response| last_response > changes_requested_at) {
+            BallInCourt::Reviewer
+        } else {
+            BallInCourt::Trainee
+        },
+    ))
+}
+
+/// When changes were requested, if the latest review from someone other than the author which approved or requested changes requested changes.
+fn latest_changes_requested(
+    reviews: &[(GithubLogin, ReviewState, DateTime<chrono::Utc>)],
+    author: &GithubLogin,
+) -> Option<DateTime<chrono::Utc>> {
+    let (_, state, submitted_at) = reviews
+        .iter()
+        .filter(|(reviewer, state, _)| {
+            reviewer != author
+                && matches!(state, ReviewState::Approved | ReviewState::ChangesRequested)
+        })
+        .max_by_key(|(_, _, submitted_at)| *submitted_at)?;
+    (*state == ReviewState::ChangesRequested).then_some(*submitted_at)
+}
+
 // Ideally this would be a more general shared function, but async closures aren't super stable yet.
 async fn get_full_page<S1: AsRef<str>, S2: AsRef<str>>(
     octocrab: Octocrab,
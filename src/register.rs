@@ -1,5 +1,8 @@
+use std::collections::BTreeSet;
+
 use anyhow::Context;
 use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use email_address::EmailAddress;
 use google_sheets4::api::CellData;
 use indexmap::IndexMap;
@@ -8,20 +11,134 @@ use tracing::warn;
 
 use crate::{
     Error,
-    newtypes::new_case_insensitive_email_address,
-    sheets::{SheetsClient, cell_string},
+    config::{DuplicateRegisterEntries, LatenessPolicy},
+    course::ONBOARDING_MODULE_NAME,
+    newtypes::{SheetId, new_case_insensitive_email_address},
+    sheets::{SheetsClient, cell_datetime, cell_string},
 };
 
 #[derive(Debug)]
 pub struct Register {
     // Module name -> Sprint -> Email -> Attendance
     pub modules: IndexMap<String, ModuleAttendance>,
+    pub unreadable_sheets: Vec<UnreadableRegisterSheet>,
+    /// Rows which were skipped because they couldn't be read, while the rest of their sheet was.
+    pub unreadable_rows: Vec<UnreadableRegisterRow>,
+    /// Every row we read, including duplicates, for checking the register's data quality.
+    pub entries: Vec<RegisterEntry>,
+}
+
+impl Register {
+    /// Adds attendance recorded somewhere other than the register sheet (e.g. by in-app check-in), as if it had been in the register.
+    pub(crate) fn add_entries(&mut self, entries: Vec<RegisterEntry>) {
+        for entry in entries {
+            let module = self
+                .modules
+                .entry(entry.module_name.clone())
+                .or_insert_with(|| ModuleAttendance {
+                    register_url: entry.attendance.register_url.clone(),
+                    attendance: Vec::new(),
+                });
+            insert_attendance(
+                &mut module.attendance,
+                entry.sprint_number,
+                entry.attendance.clone(),
+            );
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn is_unreadable(&self, module_name: &str) -> bool {
+        self.unreadable_sheets
+            .iter()
+            .any(|sheet| module_name_for_register_sheet_title(&sheet.title) == module_name)
+    }
+}
+
+/// A sheet which looks like a module register, but which we couldn't read.
+#[derive(Clone, Debug, Serialize)]
+pub struct UnreadableRegisterSheet {
+    pub title: String,
+    pub register_url: String,
+    pub error: String,
+}
+
+/// A row of a module register which we couldn't read, and so skipped.
+#[derive(Clone, Debug, Serialize)]
+pub struct UnreadableRegisterRow {
+    pub title: String,
+    pub register_url: String,
+    /// As numbered in the Sheets UI, i.e. the headings are row 1.
+    pub row_number: usize,
+    pub error: String,
 }
 
 #[derive(Debug)]
 pub struct ModuleAttendance {
     pub register_url: String,
-    pub attendance: Vec<IndexMap<EmailAddress, Attendance>>,
+    /// Sprint -> Email -> Entries, in the order they were registered in.
+    /// There is usually one entry, but trainees sometimes submit the register more than once.
+    pub attendance: Vec<IndexMap<EmailAddress, Vec<Attendance>>>,
+}
+
+/// How similar (from 0 to 1) a register name must be to a trainee's name to count as their attendance.
+/// High enough to allow a typo or two in a full name, but not to confuse different people.
+const NAME_MATCH_THRESHOLD: f64 = 0.85;
+
+impl ModuleAttendance {
+    /// Finds attendance in a sprint which was registered with an email we don't recognise, but a name similar to the trainee's.
+    ///
+    /// Returns the email's entries and how confident we are in the match, or None if there's no single good enough match.
+    pub(crate) fn find_by_name(
+        &self,
+        sprint_index: usize,
+        trainee_name: &str,
+        known_emails: &BTreeSet<EmailAddress>,
+    ) -> Option<(&[Attendance], f64)> {
+        let trainee_name = normalise_name(trainee_name);
+        let mut candidates = self
+            .attendance
+            .get(sprint_index)?
+            .iter()
+            .filter(|(email, _)| !known_emails.contains(*email))
+            .filter_map(|(_, entries)| {
+                let confidence = strsim::normalized_damerau_levenshtein(
+                    &trainee_name,
+                    &normalise_name(&entries.first()?.name),
+                );
+                Some((entries.as_slice(), confidence))
+            })
+            .filter(|(_, confidence)| *confidence >= NAME_MATCH_THRESHOLD)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        match candidates.as_slice() {
+            [] => None,
+            [only] => Some(*only),
+            [best, second, ..] => {
+                if best.1 > second.1 {
+                    Some(*best)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn normalise_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One row of a register, attributed to the module (or induction) and sprint (or session) it registers for.
+#[derive(Clone, Debug, Serialize)]
+pub struct RegisterEntry {
+    pub module_name: String,
+    /// 1-based.
+    pub sprint_number: usize,
+    pub attendance: Attendance,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -34,14 +151,25 @@ pub struct Attendance {
 }
 
 impl Attendance {
-    pub fn to_attendance_enum(&self, start_time: DateTime<Utc>) -> crate::course::Attendance {
+    pub fn to_attendance_enum(
+        &self,
+        start_time: DateTime<Utc>,
+        lateness: &LatenessPolicy,
+    ) -> crate::course::Attendance {
         if self.timestamp.date_naive() != start_time.date_naive() {
             return crate::course::Attendance::WrongDay {
                 register_url: self.register_url.clone(),
             };
         }
         let late_by = self.timestamp.signed_duration_since(start_time);
-        if late_by.num_minutes() > 10 {
+        if lateness
+            .very_late_after()
+            .is_some_and(|very_late_after| late_by > very_late_after)
+        {
+            crate::course::Attendance::VeryLate {
+                register_url: self.register_url.clone(),
+            }
+        } else if late_by > lateness.late_after() {
             crate::course::Attendance::Late {
                 register_url: self.register_url.clone(),
             }
@@ -53,13 +181,65 @@ impl Attendance {
     }
 }
 
+/// Picks which of a trainee's entries for a class counts, according to the course's configured strategy.
+pub(crate) fn choose_register_entry(
+    strategy: DuplicateRegisterEntries,
+    lateness: &LatenessPolicy,
+    entries: &[Attendance],
+    start_time: DateTime<Utc>,
+) -> Option<&Attendance> {
+    match strategy {
+        DuplicateRegisterEntries::KeepEarliest => {
+            entries.iter().min_by_key(|entry| entry.timestamp)
+        }
+        DuplicateRegisterEntries::KeepLatest => entries.iter().max_by_key(|entry| entry.timestamp),
+        DuplicateRegisterEntries::KeepMostOnTime => entries.iter().min_by_key(|entry| {
+            let rank = match entry.to_attendance_enum(start_time, lateness) {
+                crate::course::Attendance::OnTime { .. } => 0,
+                crate::course::Attendance::Late { .. } => 1,
+                crate::course::Attendance::VeryLate { .. } => 2,
+                crate::course::Attendance::WrongDay { .. }
+                | crate::course::Attendance::Absent { .. } => 3,
+            };
+            (rank, entry.timestamp)
+        }),
+    }
+}
+
+/// The headings register-sheet-setup writes for each module's register sheet.
+pub const REGISTER_HEADINGS: [&str; 7] = [
+    "Name",
+    "Email",
+    "Timestamp",
+    "Course",
+    "Module",
+    "Day",
+    "Location",
+];
+
+// TODO: Unify module names across sources (repo has Module-prefix, register does not)
+fn module_name_for_register_sheet_title(title: &str) -> String {
+    format!("Module-{}", title.replace(' ', "-"))
+}
+
+pub fn register_sheet_title_for_module(module_name: &str) -> String {
+    module_name
+        .strip_prefix("Module-")
+        .unwrap_or(module_name)
+        .replace('-', " ")
+}
+
 pub(crate) async fn get_register(
     client: SheetsClient,
-    register_sheet_id: String,
+    register_sheet_id: SheetId,
     start_date: NaiveDate,
     end_date: NaiveDate,
 ) -> Result<Register, Error> {
     let mut modules: IndexMap<String, ModuleAttendance> = IndexMap::new();
+    let mut onboarding: Option<ModuleAttendance> = None;
+    let mut unreadable_sheets = Vec::new();
+    let mut unreadable_rows = Vec::new();
+    let mut entries = Vec::new();
 
     let data = client.get(&register_sheet_id).await.map_err(|err| {
         err.with_context(|| format!("Failed to get spreadsheet with ID {}", register_sheet_id))
@@ -78,102 +258,309 @@ pub(crate) async fn get_register(
             if sheet.url.contains("?") { "&" } else { "?" },
             sheet.id
         );
-        let attendance = read_module(sheet.rows, register_url.clone(), start_date, end_date)
-            .with_context(|| {
-                format!(
-                    "Failed to read register sheet ID {} sheet {}",
-                    register_sheet_id, title
-                )
-            })?;
+        // One badly set up sheet shouldn't stop us showing attendance for every other module.
+        let attendance = match read_module(
+            sheet.rows,
+            sheet.time_zone,
+            register_url.clone(),
+            start_date,
+            end_date,
+        ) {
+            Ok(Some(attendance)) => attendance,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!(
+                    "Failed to read register sheet ID {} sheet {}: {:#}",
+                    register_sheet_id, title, err
+                );
+                unreadable_sheets.push(UnreadableRegisterSheet {
+                    title,
+                    register_url,
+                    error: format!("{:#}", err),
+                });
+                continue;
+            }
+        };
+        for (row_number, error) in attendance.unreadable_rows {
+            warn!(
+                "Skipping row {} of register sheet ID {} sheet {}: {}",
+                row_number, register_sheet_id, title, error
+            );
+            unreadable_rows.push(UnreadableRegisterRow {
+                title: title.clone(),
+                register_url: register_url.clone(),
+                row_number,
+                error,
+            });
+        }
+        let module_name = module_name_for_register_sheet_title(&title);
+        entries.extend(
+            attendance
+                .entries
+                .into_iter()
+                .map(|(day, attendance)| match day {
+                    RegisterDay::Sprint(sprint_number) => RegisterEntry {
+                        module_name: module_name.clone(),
+                        sprint_number,
+                        attendance,
+                    },
+                    RegisterDay::OnboardingSession(session_number) => RegisterEntry {
+                        module_name: ONBOARDING_MODULE_NAME.to_owned(),
+                        sprint_number: session_number,
+                        attendance,
+                    },
+                }),
+        );
+        // Induction sessions may be registered in any module's sheet.
+        if !attendance.onboarding_sessions.is_empty() {
+            let onboarding = onboarding.get_or_insert_with(|| ModuleAttendance {
+                register_url: register_url.clone(),
+                attendance: Vec::new(),
+            });
+            for (session_index, session) in attendance.onboarding_sessions.into_iter().enumerate() {
+                for session_attendance in session.into_values().flatten() {
+                    insert_attendance(
+                        &mut onboarding.attendance,
+                        session_index + 1,
+                        session_attendance,
+                    );
+                }
+            }
+        }
         let module = ModuleAttendance {
             register_url,
-            attendance,
+            attendance: attendance.sprints,
+        };
+        modules.insert(module_name, module);
+    }
+    if let Some(onboarding) = onboarding {
+        modules.insert(ONBOARDING_MODULE_NAME.to_owned(), onboarding);
+    }
+    Ok(Register {
+        modules,
+        unreadable_sheets,
+        unreadable_rows,
+        entries,
+    })
+}
+
+/// A layout the register has had, identified by its headings.
+struct RegisterSchema {
+    version: u8,
+    name: &'static str,
+    email: &'static str,
+    timestamp: &'static str,
+    day: &'static str,
+    location: &'static str,
+}
+
+/// Every register layout we know how to read, in order of preference.
+///
+/// Columns are found by heading, so they may be in any order, and extra columns are ignored.
+const KNOWN_SCHEMAS: [RegisterSchema; 2] = [
+    // The layout written by register-sheet-setup (see REGISTER_HEADINGS).
+    RegisterSchema {
+        version: 1,
+        name: "Name",
+        email: "Email",
+        timestamp: "Timestamp",
+        day: "Day",
+        location: "Location",
+    },
+    // A Google Form's own response sheet, which names the collected email column itself.
+    RegisterSchema {
+        version: 2,
+        name: "Name",
+        email: "Email address",
+        timestamp: "Timestamp",
+        day: "Day",
+        location: "Location",
+    },
+];
+
+#[derive(Debug, PartialEq, Eq)]
+struct RegisterColumns {
+    schema_version: u8,
+    name: usize,
+    email: usize,
+    timestamp: usize,
+    day: usize,
+    location: usize,
+}
+
+impl RegisterColumns {
+    fn detect(headings: &[String]) -> Result<RegisterColumns, anyhow::Error> {
+        let find = |heading: &str| {
+            headings
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(heading))
         };
-        // TODO: Unify module names across sources (repo has Module-prefix, register does not)
-        modules.insert(format!("Module-{}", title.replace(' ', "-")), module);
+        for schema in &KNOWN_SCHEMAS {
+            if let (Some(name), Some(email), Some(timestamp), Some(day), Some(location)) = (
+                find(schema.name),
+                find(schema.email),
+                find(schema.timestamp),
+                find(schema.day),
+                find(schema.location),
+            ) {
+                return Ok(RegisterColumns {
+                    schema_version: schema.version,
+                    name,
+                    email,
+                    timestamp,
+                    day,
+                    location,
+                });
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Unrecognised register layout with headings: {}",
+            headings.join(", ")
+        ))
+    }
+
+    fn required_len(&self) -> usize {
+        [
+            self.name,
+            self.email,
+            self.timestamp,
+            self.day,
+            self.location,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+            + 1
     }
-    Ok(Register { modules })
 }
 
+/// Reads a register sheet's headings, or returns None if the sheet isn't a register at all.
+fn read_headings(cells: &[CellData]) -> Option<Vec<String>> {
+    let headings = cells.iter().map(cell_string).collect::<Vec<_>>();
+    // Some sheets have documentation or pivot table
+    if headings
+        .iter()
+        .any(|heading| heading.trim().eq_ignore_ascii_case("Name"))
+    {
+        Some(headings)
+    } else {
+        None
+    }
+}
+
+/// Attendance read from one module's register sheet.
+struct ModuleRegister {
+    sprints: Vec<IndexMap<EmailAddress, Vec<Attendance>>>,
+    onboarding_sessions: Vec<IndexMap<EmailAddress, Vec<Attendance>>>,
+    entries: Vec<(RegisterDay, Attendance)>,
+    /// Row number (as numbered in the Sheets UI) -> why it couldn't be read.
+    unreadable_rows: Vec<(usize, String)>,
+}
+
+/// Returns None if the sheet doesn't look like a register (e.g. documentation), or an error if it does but its headings can't be read.
+/// Rows which can't be read are skipped, and listed in ModuleRegister::unreadable_rows.
 fn read_module(
     sheet_data: Vec<Vec<CellData>>,
+    time_zone: Tz,
     register_url: String,
     start_date: NaiveDate,
     end_date: NaiveDate,
-) -> Result<Vec<IndexMap<EmailAddress, Attendance>>, anyhow::Error> {
+) -> Result<Option<ModuleRegister>, anyhow::Error> {
+    let mut rows = sheet_data.into_iter().enumerate();
+    let Some(headings) = rows.next().and_then(|(_, cells)| read_headings(&cells)) else {
+        return Ok(None);
+    };
+    let columns = RegisterColumns::detect(&headings)?;
+
     let mut sprints = Vec::new();
-    for (row_number, cells) in sheet_data.into_iter().enumerate() {
-        // Some sheets have documentation or pivot table
-        if row_number == 0 && !cells.is_empty() && cell_string(&cells[0]) != "Name" {
-            return Ok(sprints);
-        }
-        if cells.len() < 7 {
-            return Err(anyhow::anyhow!(
-                "Not enough columns for row {} - expected at least 7, got {} containing: {}",
+    let mut onboarding_sessions = Vec::new();
+    let mut entries = Vec::new();
+    let mut unreadable_rows = Vec::new();
+    for (row_index, cells) in rows {
+        let is_blank = |column: usize| {
+            cells
+                .get(column)
+                .is_none_or(|cell| cell.effective_value.is_none())
+        };
+        if is_blank(columns.name) && is_blank(columns.email) {
+            break;
+        }
+        let row_number = row_index + 1;
+        if cells.len() < columns.required_len() {
+            unreadable_rows.push((
                 row_number,
-                cells.len(),
-                format!("{:#?}", cells),
+                format!(
+                    "Not enough columns - expected at least {}, got {}",
+                    columns.required_len(),
+                    cells.len()
+                ),
             ));
+            continue;
         }
-        if row_number == 0 {
-            let headings = cells.iter().take(7).map(cell_string).collect::<Vec<_>>();
-            if headings
-                != [
-                    "Name",
-                    "Email",
-                    "Timestamp",
-                    "Course",
-                    "Module",
-                    "Day",
-                    "Location",
-                ]
-            {
-                return Err(anyhow::anyhow!(
-                    "Register sheet contained wrong headings: {}",
-                    headings.join(", ")
+        let (day, attendance) = match read_row(&cells, &columns, time_zone, register_url.clone()) {
+            Ok(row) => row,
+            Err(err) => {
+                unreadable_rows.push((
+                    row_number,
+                    format!(
+                        "Failed to read attendance (register layout {}): {:#}",
+                        columns.schema_version, err
+                    ),
                 ));
-            }
-        } else {
-            if cells[0].effective_value.is_none() {
-                break;
-            }
-            let (sprint_number, attendance) = read_row(&cells, register_url.clone())
-                .with_context(|| format!("Failed to read attendance from row {}", row_number))?;
-            if attendance.timestamp.date_naive() <= start_date
-                || attendance.timestamp.date_naive() >= end_date
-            {
                 continue;
             }
-            let sprint_index = sprint_number - 1;
-            while sprints.len() < sprint_number {
-                sprints.push(IndexMap::new());
+        };
+        if attendance.timestamp.date_naive() <= start_date
+            || attendance.timestamp.date_naive() >= end_date
+        {
+            continue;
+        }
+        entries.push((day, attendance.clone()));
+        match day {
+            RegisterDay::Sprint(sprint_number) => {
+                insert_attendance(&mut sprints, sprint_number, attendance)
             }
-            if sprints[sprint_index].contains_key(&attendance.email) {
-                warn!(
-                    "Register sheet contained duplicate entry for sprint {} trainee {}",
-                    sprint_number, attendance.email
-                );
-            } else {
-                sprints[sprint_index].insert(attendance.email.clone(), attendance);
+            RegisterDay::OnboardingSession(session_number) => {
+                insert_attendance(&mut onboarding_sessions, session_number, attendance)
             }
         }
     }
-    Ok(sprints)
+    Ok(Some(ModuleRegister {
+        sprints,
+        onboarding_sessions,
+        entries,
+        unreadable_rows,
+    }))
+}
+
+/// Duplicate entries are all kept, for choose_register_entry to pick between and the data-quality report to list.
+fn insert_attendance(
+    attendance_by_number: &mut Vec<IndexMap<EmailAddress, Vec<Attendance>>>,
+    number: usize,
+    attendance: Attendance,
+) {
+    while attendance_by_number.len() < number {
+        attendance_by_number.push(IndexMap::new());
+    }
+    attendance_by_number[number - 1]
+        .entry(attendance.email.clone())
+        .or_default()
+        .push(attendance);
 }
 
 fn read_row(
     cells: &[CellData],
+    columns: &RegisterColumns,
+    time_zone: Tz,
     register_url: String,
-) -> Result<(usize, Attendance), anyhow::Error> {
-    let sprint_number = extract_sprint_number(&cell_string(&cells[5]))?;
-    let name = cell_string(&cells[0]);
-    let email = new_case_insensitive_email_address(&cell_string(&cells[1]))?;
-    let timestamp = DateTime::parse_from_rfc3339(&cell_string(&cells[2]))
-        .context("Failed to parse timestamp")?
-        .to_utc();
-    let region = cell_string(&cells[6]);
+) -> Result<(RegisterDay, Attendance), anyhow::Error> {
+    let day = extract_register_day(cell_string(&cells[columns.day]).trim())?;
+    let name = cell_string(&cells[columns.name]);
+    let email = new_case_insensitive_email_address(cell_string(&cells[columns.email]).trim())?;
+    let timestamp = cell_datetime(&cells[columns.timestamp], time_zone)?;
+    let region = cell_string(&cells[columns.location]);
     Ok((
-        sprint_number,
+        day,
         Attendance {
             name,
             email,
@@ -184,10 +571,23 @@ fn read_row(
     ))
 }
 
-fn extract_sprint_number(cell_str: &str) -> Result<usize, anyhow::Error> {
-    // TODO: Clean this up in the register.
-    if cell_str == "welcome-to-code-your-future" {
-        return Ok(1);
+/// The Day values induction sessions are registered under, in the order the sessions happen.
+/// These aren't sprints of the module whose sheet they're registered in, so are tracked under ONBOARDING_MODULE_NAME instead.
+const ONBOARDING_SESSION_DAYS: [&str; 1] = ["welcome-to-code-your-future"];
+
+/// What a register row's Day refers to. Both are 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RegisterDay {
+    Sprint(usize),
+    OnboardingSession(usize),
+}
+
+fn extract_register_day(cell_str: &str) -> Result<RegisterDay, anyhow::Error> {
+    if let Some(session_index) = ONBOARDING_SESSION_DAYS
+        .iter()
+        .position(|day| *day == cell_str)
+    {
+        return Ok(RegisterDay::OnboardingSession(session_index + 1));
     }
     let sprint_number_str = cell_str.strip_prefix("sprint-").ok_or_else(|| {
         anyhow::anyhow!(
@@ -204,6 +604,363 @@ fn extract_sprint_number(cell_str: &str) -> Result<usize, anyhow::Error> {
             number
         ))
     } else {
-        Ok(number)
+        Ok(RegisterDay::Sprint(number))
+    }
+}
+
+#[derive(Debug)]
+pub enum RegisterStructureProblem {
+    MissingSheet {
+        title: String,
+    },
+    MissingHeadings {
+        title: String,
+    },
+    WrongHeadings {
+        title: String,
+        headings: Vec<String>,
+    },
+    BadRow {
+        title: String,
+        row_number: usize,
+        error: String,
+    },
+}
+
+impl RegisterStructureProblem {
+    /// Whether the problem can be fixed by (re-)creating the sheet's headings.
+    /// We never touch rows which have already been filled in.
+    pub fn is_fixable(&self) -> bool {
+        matches!(
+            self,
+            RegisterStructureProblem::MissingSheet { .. }
+                | RegisterStructureProblem::MissingHeadings { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for RegisterStructureProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegisterStructureProblem::MissingSheet { title } => {
+                write!(f, "Missing sheet '{}'", title)
+            }
+            RegisterStructureProblem::MissingHeadings { title } => {
+                write!(f, "Sheet '{}' has no headings", title)
+            }
+            RegisterStructureProblem::WrongHeadings { title, headings } => write!(
+                f,
+                "Sheet '{}' has unrecognised headings '{}', expected '{}'",
+                title,
+                headings.join(", "),
+                REGISTER_HEADINGS.join(", ")
+            ),
+            RegisterStructureProblem::BadRow {
+                title,
+                row_number,
+                error,
+            } => write!(f, "Sheet '{}' row {}: {}", title, row_number, error),
+        }
+    }
+}
+
+/// Checks that the register has one correctly set up sheet per module, and that every row names a sprint which exists.
+///
+/// modules maps module name (as in the course config) to its number of sprints.
+pub async fn check_register_structure(
+    client: SheetsClient,
+    register_sheet_id: &SheetId,
+    modules: &IndexMap<String, usize>,
+) -> Result<Vec<RegisterStructureProblem>, Error> {
+    // SheetsClient::get skips empty sheets, so separately list all sheets to tell "missing" from "empty".
+    let all_titles = client
+        .sheet_titles(register_sheet_id)
+        .await
+        .map_err(|err| {
+            err.with_context(|| format!("Failed to list sheets in register {}", register_sheet_id))
+        })?;
+    let mut data = client.get(register_sheet_id).await.map_err(|err| {
+        err.with_context(|| format!("Failed to get spreadsheet with ID {}", register_sheet_id))
+    })?;
+
+    let mut problems = Vec::new();
+    for (module_name, sprint_count) in modules {
+        let title = register_sheet_title_for_module(module_name);
+        if !all_titles.contains(&title) {
+            problems.push(RegisterStructureProblem::MissingSheet { title });
+            continue;
+        }
+        let rows = data
+            .remove(&title)
+            .map(|sheet| sheet.rows)
+            .unwrap_or_default();
+        let Some(heading_cells) = rows.first().filter(|cells| !cells.is_empty()) else {
+            problems.push(RegisterStructureProblem::MissingHeadings { title });
+            continue;
+        };
+        let headings = heading_cells.iter().map(cell_string).collect::<Vec<_>>();
+        let Ok(columns) = RegisterColumns::detect(&headings) else {
+            problems.push(RegisterStructureProblem::WrongHeadings { title, headings });
+            continue;
+        };
+        for (row_index, cells) in rows.iter().enumerate().skip(1) {
+            if cells
+                .first()
+                .is_none_or(|cell| cell.effective_value.is_none())
+            {
+                break;
+            }
+            // Report rows using spreadsheet numbering, which starts at 1.
+            let row_number = row_index + 1;
+            let error = if cells.len() < columns.required_len() {
+                Some(format!(
+                    "Not enough columns - expected at least {}, got {}",
+                    columns.required_len(),
+                    cells.len()
+                ))
+            } else {
+                match extract_register_day(cell_string(&cells[columns.day]).trim()) {
+                    Ok(RegisterDay::Sprint(sprint_number)) if sprint_number > *sprint_count => {
+                        Some(format!(
+                            "Sprint {} is out of range - module has {} sprints",
+                            sprint_number, sprint_count
+                        ))
+                    }
+                    Ok(_) => None,
+                    Err(err) => Some(format!("{:#}", err)),
+                }
+            };
+            if let Some(error) = error {
+                problems.push(RegisterStructureProblem::BadRow {
+                    title: title.clone(),
+                    row_number,
+                    error,
+                });
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// Fixes a problem reported by check_register_structure, if it is fixable.
+pub async fn fix_register_structure_problem(
+    client: &SheetsClient,
+    register_sheet_id: &SheetId,
+    problem: &RegisterStructureProblem,
+) -> Result<(), Error> {
+    let title = match problem {
+        RegisterStructureProblem::MissingSheet { title } => {
+            client.add_sheet(register_sheet_id, title).await?;
+            title
+        }
+        RegisterStructureProblem::MissingHeadings { title } => title,
+        RegisterStructureProblem::WrongHeadings { .. }
+        | RegisterStructureProblem::BadRow { .. } => {
+            return Err(Error::UserFacing(format!(
+                "Can't automatically fix problem: {}",
+                problem
+            )));
+        }
+    };
+    client
+        .write_rows(
+            register_sheet_id,
+            &format!("'{}'!A1:G1", title),
+            vec![REGISTER_HEADINGS.iter().map(|h| (*h).to_owned()).collect()],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeSet, str::FromStr};
+
+    use chrono::{DateTime, TimeDelta, Utc};
+    use email_address::EmailAddress;
+    use indexmap::IndexMap;
+
+    use super::{
+        Attendance, ModuleAttendance, REGISTER_HEADINGS, RegisterColumns, RegisterDay,
+        choose_register_entry, extract_register_day, module_name_for_register_sheet_title,
+        register_sheet_title_for_module,
+    };
+    use crate::config::{DuplicateRegisterEntries, LatenessPolicy};
+
+    fn headings(headings: &[&str]) -> Vec<String> {
+        headings
+            .iter()
+            .map(|heading| (*heading).to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn detects_current_layout() {
+        let columns = RegisterColumns::detect(&headings(&REGISTER_HEADINGS)).unwrap();
+        assert_eq!(
+            columns,
+            RegisterColumns {
+                schema_version: 1,
+                name: 0,
+                email: 1,
+                timestamp: 2,
+                day: 5,
+                location: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn detects_reordered_layout_with_extra_columns() {
+        let columns = RegisterColumns::detect(&headings(&[
+            "Timestamp",
+            "Email address",
+            "Name",
+            "Notes",
+            "Location",
+            "Day",
+        ]))
+        .unwrap();
+        assert_eq!(
+            columns,
+            RegisterColumns {
+                schema_version: 2,
+                name: 2,
+                email: 1,
+                timestamp: 0,
+                day: 5,
+                location: 4,
+            }
+        );
+        assert_eq!(columns.required_len(), 6);
+    }
+
+    #[test]
+    fn rejects_unrecognised_layout() {
+        assert!(RegisterColumns::detect(&headings(&["Name", "Email", "Timestamp"])).is_err());
+    }
+
+    fn attendance(name: &str, email: &str) -> (EmailAddress, Vec<Attendance>) {
+        let email = EmailAddress::from_str(email).unwrap();
+        (
+            email.clone(),
+            vec![Attendance {
+                name: name.to_owned(),
+                email,
+                timestamp: Utc::now(),
+                region: "London".to_owned(),
+                register_url: String::new(),
+            }],
+        )
+    }
+
+    #[test]
+    fn find_by_name_matches_typos_from_unknown_emails() {
+        let module = ModuleAttendance {
+            register_url: String::new(),
+            attendance: vec![IndexMap::from([
+                attendance("Jon Smith", "jon.smiht@example.com"),
+                attendance("Jane Doe", "jane@example.com"),
+            ])],
+        };
+        let known_emails = BTreeSet::from([EmailAddress::from_str("jane@example.com").unwrap()]);
+
+        let (found, confidence) = module
+            .find_by_name(0, "John  smith", &known_emails)
+            .unwrap();
+        assert_eq!(found[0].name, "Jon Smith");
+        assert!(confidence < 1.0);
+
+        // Attendance registered with a known email belongs to that trainee, however similar the names.
+        assert!(module.find_by_name(0, "Jane Doe", &known_emails).is_none());
+        assert!(
+            module
+                .find_by_name(0, "Someone Else", &known_emails)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn chooses_between_duplicate_entries() {
+        let start_time = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        let entry = |minutes: i64| Attendance {
+            name: "Jane Doe".to_owned(),
+            email: EmailAddress::from_str("jane@example.com").unwrap(),
+            timestamp: start_time + TimeDelta::minutes(minutes),
+            region: "London".to_owned(),
+            register_url: String::new(),
+        };
+        // Registered late, the day before by mistake, and on time.
+        let entries = [entry(20), entry(-24 * 60), entry(5)];
+        let chosen = |strategy| {
+            (choose_register_entry(strategy, &LatenessPolicy::default(), &entries, start_time)
+                .unwrap()
+                .timestamp
+                - start_time)
+                .num_minutes()
+        };
+        assert_eq!(chosen(DuplicateRegisterEntries::KeepEarliest), -24 * 60);
+        assert_eq!(chosen(DuplicateRegisterEntries::KeepLatest), 20);
+        assert_eq!(chosen(DuplicateRegisterEntries::KeepMostOnTime), 5);
+    }
+
+    #[test]
+    fn applies_lateness_policy() {
+        let start_time = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        let registered_after = |minutes: i64, lateness: &LatenessPolicy| {
+            Attendance {
+                name: "Jane Doe".to_owned(),
+                email: EmailAddress::from_str("jane@example.com").unwrap(),
+                timestamp: start_time + TimeDelta::minutes(minutes),
+                region: "London".to_owned(),
+                register_url: String::new(),
+            }
+            .to_attendance_enum(start_time, lateness)
+        };
+        let default = LatenessPolicy::default();
+        assert!(matches!(
+            registered_after(10, &default),
+            crate::course::Attendance::OnTime { .. }
+        ));
+        assert!(matches!(
+            registered_after(90, &default),
+            crate::course::Attendance::Late { .. }
+        ));
+
+        let strict = LatenessPolicy {
+            late_after_minutes: Some(5),
+            very_late_after_minutes: Some(60),
+        };
+        assert!(matches!(
+            registered_after(10, &strict),
+            crate::course::Attendance::Late { .. }
+        ));
+        assert!(matches!(
+            registered_after(90, &strict),
+            crate::course::Attendance::VeryLate { .. }
+        ));
+    }
+
+    #[test]
+    fn register_sheet_title_round_trips() {
+        for module_name in ["Module-Structuring-and-Testing-Data", "Module-Onboarding"] {
+            assert_eq!(
+                module_name_for_register_sheet_title(&register_sheet_title_for_module(module_name)),
+                module_name
+            );
+        }
+    }
+
+    #[test]
+    fn onboarding_sessions_are_not_sprints() {
+        assert_eq!(
+            extract_register_day("sprint-1").unwrap(),
+            RegisterDay::Sprint(1)
+        );
+        assert_eq!(
+            extract_register_day("welcome-to-code-your-future").unwrap(),
+            RegisterDay::OnboardingSession(1)
+        );
+        assert!(extract_register_day("sprint-0").is_err());
+        assert!(extract_register_day("welcome").is_err());
     }
 }
@@ -0,0 +1,387 @@
+use std::collections::BTreeMap;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeDelta, Utc};
+use futures::future::join_all;
+use octocrab::Octocrab;
+use serde::Deserialize;
+use serde_env_field::EnvField;
+use tower_sessions::Session;
+
+use crate::{
+    Config, Error, ServerState,
+    octocrab::octocrab,
+    prs::{Pr, PrMetrics, get_prs, get_review_metrics},
+    single_flight::InFlight,
+};
+
+/// How far back compliance is tracked.
+pub const SLA_WINDOW: TimeDelta = TimeDelta::weeks(8);
+
+pub const DEFAULT_ALERT_BELOW_PERCENT: u8 = 80;
+
+/// How quickly a course promises trainees their PRs will be reviewed.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReviewSla {
+    /// How long after a PR is labelled Needs Review it should get its first review (Reviewed or Complete).
+    pub first_review_within_days: i64,
+    /// The review-sla-alerts job alerts when less than this percentage of PRs met the SLA in the previous week.
+    /// Defaults to DEFAULT_ALERT_BELOW_PERCENT.
+    #[serde(default)]
+    pub alert_below_percent: Option<u8>,
+    /// Slack incoming webhook which the review-sla-alerts job posts to.
+    #[serde(default)]
+    pub slack_webhook_url: Option<EnvField<String>>,
+}
+
+impl ReviewSla {
+    pub fn first_review_within(&self) -> TimeDelta {
+        TimeDelta::days(self.first_review_within_days)
+    }
+
+    pub fn alert_below_percent(&self) -> u8 {
+        self.alert_below_percent
+            .unwrap_or(DEFAULT_ALERT_BELOW_PERCENT)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlaOutcome {
+    Met,
+    Missed,
+    /// Still waiting for a first review, but not for longer than the SLA, so doesn't count either way yet.
+    Pending,
+}
+
+/// How a PR got on against the SLA, or None if it was never labelled Needs Review (so the clock never started).
+pub fn sla_outcome(
+    metrics: &PrMetrics,
+    within: TimeDelta,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, SlaOutcome)> {
+    let needs_review_at = metrics.first_needs_review?;
+    let first_review_at = match (metrics.first_reviewed, metrics.first_complete) {
+        (Some(reviewed), Some(complete)) => Some(reviewed.min(complete)),
+        (reviewed, complete) => reviewed.or(complete),
+    };
+    let outcome = match first_review_at {
+        Some(first_review_at) if first_review_at - needs_review_at <= within => SlaOutcome::Met,
+        Some(_) => SlaOutcome::Missed,
+        None if now - needs_review_at > within => SlaOutcome::Missed,
+        None => SlaOutcome::Pending,
+    };
+    Some((needs_review_at, outcome))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlaCompliance {
+    pub met: usize,
+    pub missed: usize,
+}
+
+impl SlaCompliance {
+    fn record(&mut self, outcome: SlaOutcome) {
+        match outcome {
+            SlaOutcome::Met => self.met += 1,
+            SlaOutcome::Missed => self.missed += 1,
+            SlaOutcome::Pending => {}
+        }
+    }
+
+    /// None if no PRs have come due yet.
+    pub fn percent(&self) -> Option<u8> {
+        let total = self.met + self.missed;
+        if total == 0 {
+            None
+        } else {
+            Some((self.met * 100 / total) as u8)
+        }
+    }
+
+    pub fn is_below(&self, percent: u8) -> bool {
+        self.percent().is_some_and(|actual| actual < percent)
+    }
+}
+
+/// A PR which has been waiting for its first review for longer than the SLA.
+#[derive(Clone, Debug)]
+pub struct OverduePr {
+    pub module: String,
+    pub pr: Pr,
+    pub needs_review_at: DateTime<Utc>,
+}
+
+impl OverduePr {
+    pub fn days_waiting(&self, now: DateTime<Utc>) -> i64 {
+        (now - self.needs_review_at).num_days()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReviewSlaReport {
+    pub by_module: BTreeMap<String, SlaCompliance>,
+    /// Keyed by the Monday of the week the PR was labelled Needs Review.
+    pub by_week: BTreeMap<NaiveDate, SlaCompliance>,
+    /// Oldest first.
+    pub overdue: Vec<OverduePr>,
+}
+
+impl ReviewSlaReport {
+    fn record(&mut self, module: &str, metrics: &PrMetrics, within: TimeDelta, now: DateTime<Utc>) {
+        let Some((needs_review_at, outcome)) = sla_outcome(metrics, within, now) else {
+            return;
+        };
+        if now - needs_review_at > SLA_WINDOW {
+            return;
+        }
+        self.by_module
+            .entry(module.to_owned())
+            .or_default()
+            .record(outcome);
+        self.by_week
+            .entry(week_starting(needs_review_at.date_naive()))
+            .or_default()
+            .record(outcome);
+        if outcome == SlaOutcome::Missed
+            && metrics.first_reviewed.is_none()
+            && metrics.first_complete.is_none()
+        {
+            self.overdue.push(OverduePr {
+                module: module.to_owned(),
+                pr: metrics.pr.clone(),
+                needs_review_at,
+            });
+        }
+    }
+
+    pub fn overall(&self) -> SlaCompliance {
+        self.by_module
+            .values()
+            .fold(SlaCompliance::default(), |total, compliance| {
+                SlaCompliance {
+                    met: total.met + compliance.met,
+                    missed: total.missed + compliance.missed,
+                }
+            })
+    }
+
+    /// Compliance for the last full week before `now`, which is what alerts are based on.
+    pub fn previous_week(&self, now: DateTime<Utc>) -> (NaiveDate, SlaCompliance) {
+        let week = week_starting(now.date_naive()) - Days::new(7);
+        (week, self.by_week.get(&week).copied().unwrap_or_default())
+    }
+}
+
+pub(crate) fn week_starting(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+/// Review SLA compliance for PRs in the course's modules labelled Needs Review in the last SLA_WINDOW.
+pub async fn review_sla_report(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    config: &Config,
+    course: &str,
+    sla: &ReviewSla,
+    now: DateTime<Utc>,
+) -> Result<ReviewSlaReport, Error> {
+    let module_names = config
+        .get_course_module_names(course)
+        .ok_or_else(|| Error::UserFacing(format!("Unknown course {}", course)))?;
+    let module_futures = module_names.iter().map(async |module_name| {
+        let prs = get_prs(octocrab, in_flight, &config.github_org, module_name, true).await?;
+        // Adding a label updates a PR, so anything last touched before the window can't have been labelled Needs Review in it.
+        let metrics_futures = prs
+            .into_iter()
+            .filter(|pr| now - pr.updated_at <= SLA_WINDOW)
+            .map(|pr| get_review_metrics(octocrab, &config.github_org, pr));
+        let metrics = join_all(metrics_futures)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, Error>((module_name, metrics))
+    });
+    let mut report = ReviewSlaReport::default();
+    for result in join_all(module_futures).await {
+        let (module_name, metrics) = result?;
+        for metrics in &metrics {
+            report.record(module_name, metrics, sla.first_review_within(), now);
+        }
+    }
+    report
+        .overdue
+        .sort_by_key(|overdue| overdue.needs_review_at);
+    Ok(report)
+}
+
+pub fn review_sla_alert_text(
+    course_name: &str,
+    sla: &ReviewSla,
+    week: NaiveDate,
+    compliance: &SlaCompliance,
+    report: &ReviewSlaReport,
+) -> String {
+    let mut text = format!(
+        "Only {}% of {} PRs marked Needs Review in the week of {} got a first review within {} days ({} of {}), below the {}% target.",
+        compliance.percent().unwrap_or_default(),
+        course_name,
+        week,
+        sla.first_review_within_days,
+        compliance.met,
+        compliance.met + compliance.missed,
+        sla.alert_below_percent(),
+    );
+    if !report.overdue.is_empty() {
+        text.push_str(&format!(
+            "\n{} PRs are still waiting longer than that for a first review:",
+            report.overdue.len()
+        ));
+        for overdue in &report.overdue {
+            text.push_str(&format!(
+                "\n• <{}|{}#{}> - needs review since {}",
+                overdue.pr.url,
+                overdue.pr.repo_name,
+                overdue.pr.number,
+                overdue.needs_review_at.date_naive(),
+            ));
+        }
+    }
+    text
+}
+
+pub async fn get_review_sla(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Html<String>, Error> {
+    let sla = server_state
+        .config
+        .courses
+        .get(&course)
+        .ok_or_else(|| Error::UserFacing(format!("Unknown course {}", course)))?
+        .review_sla
+        .clone()
+        .ok_or_else(|| {
+            Error::UserFacing(format!(
+                "Course {} doesn't have a review SLA set up",
+                course
+            ))
+        })?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let now = Utc::now();
+    let report = review_sla_report(
+        &octocrab,
+        &server_state.in_flight,
+        &server_state.config,
+        &course,
+        &sla,
+        now,
+    )
+    .await?;
+    Ok(Html(
+        ReviewSlaTemplate {
+            course,
+            overall: report.overall(),
+            report,
+            sla,
+            window_weeks: SLA_WINDOW.num_weeks(),
+            now,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "review-sla.html")]
+struct ReviewSlaTemplate {
+    course: String,
+    sla: ReviewSla,
+    report: ReviewSlaReport,
+    overall: SlaCompliance,
+    window_weeks: i64,
+    now: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    use super::{SlaOutcome, sla_outcome};
+    use crate::{
+        newtypes::GithubLogin,
+        prs::{Pr, PrMetrics, PrState},
+    };
+
+    fn metrics(
+        first_needs_review: Option<DateTime<Utc>>,
+        first_reviewed: Option<DateTime<Utc>>,
+    ) -> PrMetrics {
+        let created_at = "2026-09-01T10:00:00Z".parse().unwrap();
+        PrMetrics {
+            pr: Pr {
+                repo_name: "Module-Onboarding".to_owned(),
+                number: 1,
+                url: "https://github.com/CodeYourFuture/Module-Onboarding/pull/1".to_owned(),
+                title: "London | 26-ITP-Sep | Some One | Sprint 1 | Form controls".to_owned(),
+                author: GithubLogin::from("someone".to_owned()),
+                body: String::new(),
+                state: PrState::NeedsReview,
+                created_at,
+                updated_at: created_at,
+                is_closed: false,
+                labels: BTreeSet::new(),
+            },
+            created_at,
+            label_add_events: Vec::new(),
+            first_needs_review,
+            first_reviewed,
+            first_complete: None,
+            iterations: 0,
+        }
+    }
+
+    #[test]
+    fn outcomes() {
+        let needs_review: DateTime<Utc> = "2026-09-01T12:00:00Z".parse().unwrap();
+        let within = TimeDelta::days(5);
+        let now = needs_review + TimeDelta::days(7);
+
+        assert_eq!(sla_outcome(&metrics(None, None), within, now), None);
+        assert_eq!(
+            sla_outcome(
+                &metrics(Some(needs_review), Some(needs_review + TimeDelta::days(5))),
+                within,
+                now
+            ),
+            Some((needs_review, SlaOutcome::Met))
+        );
+        assert_eq!(
+            sla_outcome(
+                &metrics(Some(needs_review), Some(needs_review + TimeDelta::days(6))),
+                within,
+                now
+            ),
+            Some((needs_review, SlaOutcome::Missed))
+        );
+        assert_eq!(
+            sla_outcome(&metrics(Some(needs_review), None), within, now),
+            Some((needs_review, SlaOutcome::Missed))
+        );
+        assert_eq!(
+            sla_outcome(
+                &metrics(Some(needs_review), None),
+                within,
+                needs_review + TimeDelta::days(2)
+            ),
+            Some((needs_review, SlaOutcome::Pending))
+        );
+    }
+}
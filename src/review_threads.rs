@@ -0,0 +1,204 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use octocrab::Octocrab;
+use serde::Deserialize;
+
+use crate::{
+    Error,
+    course::{Submission, SubmissionState, TraineeWithSubmissions},
+    newtypes::GithubLogin,
+    pr_comments::graphql,
+    prs::{Pr, PrState},
+};
+
+/// Longer comments are cut off at this many characters, as the page only needs enough to jog a mentor's memory.
+const EXCERPT_LENGTH: usize = 140;
+
+/// The review conversations on one of a trainee's reviewed PRs.
+#[derive(Clone, Debug)]
+pub struct ReviewConversationSummary {
+    pub module_name: String,
+    pub pr: Pr,
+    pub thread_count: usize,
+    pub resolved: usize,
+    pub unresolved: usize,
+    /// The most recent comment in any of the PR's review threads.
+    pub latest_comment: Option<CommentExcerpt>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CommentExcerpt {
+    /// None if the author's account has been deleted.
+    pub author: Option<GithubLogin>,
+    pub created_at: DateTime<Utc>,
+    pub excerpt: String,
+}
+
+/// Summarises the review threads on each of the trainee's PRs which have been reviewed, in module and sprint order.
+pub async fn get_review_conversation_summaries(
+    octocrab: &Octocrab,
+    github_org: &str,
+    trainee: &TraineeWithSubmissions,
+) -> Result<Vec<ReviewConversationSummary>, Error> {
+    let reviewed_prs = trainee.modules.iter().flat_map(|(module_name, module)| {
+        module
+            .sprints
+            .iter()
+            .flat_map(|sprint| sprint.submissions.iter())
+            .filter_map(move |submission| match submission {
+                SubmissionState::Some(Submission::PullRequest { pull_request, .. })
+                    if matches!(pull_request.state, PrState::Reviewed | PrState::Complete) =>
+                {
+                    Some((module_name, pull_request))
+                }
+                _ => None,
+            })
+    });
+    join_all(reviewed_prs.map(async |(module_name, pr)| {
+        let threads = get_review_threads(octocrab, github_org, pr).await?;
+        Ok::<_, Error>(summarise(module_name, pr, threads))
+    }))
+    .await
+    .into_iter()
+    .collect()
+}
+
+fn summarise(
+    module_name: &str,
+    pr: &Pr,
+    threads: ReviewThreadConnection,
+) -> ReviewConversationSummary {
+    let resolved = threads
+        .nodes
+        .iter()
+        .filter(|thread| thread.is_resolved)
+        .count();
+    let latest_comment = threads
+        .nodes
+        .into_iter()
+        .flat_map(|thread| thread.comments.nodes)
+        .max_by_key(|comment| comment.created_at)
+        .map(|comment| CommentExcerpt {
+            author: comment.author.map(|author| GithubLogin::from(author.login)),
+            created_at: comment.created_at,
+            excerpt: excerpt(&comment.body),
+        });
+    ReviewConversationSummary {
+        module_name: module_name.to_owned(),
+        pr: pr.clone(),
+        thread_count: threads.total_count,
+        resolved,
+        // Only the first page of threads is fetched, so count anything beyond it as unresolved rather than hiding it.
+        unresolved: threads.total_count - resolved,
+        latest_comment,
+    }
+}
+
+fn excerpt(body: &str) -> String {
+    let collapsed = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= EXCERPT_LENGTH {
+        collapsed
+    } else {
+        let mut excerpt = collapsed.chars().take(EXCERPT_LENGTH).collect::<String>();
+        excerpt.push('…');
+        excerpt
+    }
+}
+
+async fn get_review_threads(
+    octocrab: &Octocrab,
+    github_org: &str,
+    pr: &Pr,
+) -> Result<ReviewThreadConnection, Error> {
+    let response: graphql::Result<ReviewThreadsResponse> = octocrab
+        .graphql(&serde_json::json!({
+            "query": REVIEW_THREADS_QUERY,
+            "variables": {"owner": github_org, "repo": pr.repo_name, "number": pr.number},
+        }))
+        .await
+        .with_context(|| format!("Failed to get review threads for {}", pr.url))?;
+    Ok(response
+        .into_result()?
+        .and_then(|data| data.repository)
+        .and_then(|repository| repository.pull_request)
+        .map(|pull_request| pull_request.review_threads)
+        .unwrap_or_default())
+}
+
+// There's no REST API for whether review threads are resolved.
+// Only the first 100 threads are fetched, and the last comment of each - nobody reviews a trainee PR in more detail than that.
+const REVIEW_THREADS_QUERY: &str = r#"
+query($owner: String!, $repo: String!, $number: Int!) {
+  repository(owner: $owner, name: $repo) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100) {
+        totalCount
+        nodes {
+          isResolved
+          comments(last: 1) {
+            nodes {
+              body
+              createdAt
+              author {
+                login
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+struct ReviewThreadsResponse {
+    // None if the repo doesn't exist.
+    repository: Option<ReviewThreadsRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThreadsRepository {
+    pull_request: Option<ReviewThreadsPullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThreadsPullRequest {
+    review_threads: ReviewThreadConnection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThreadConnection {
+    total_count: usize,
+    nodes: Vec<ReviewThread>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThread {
+    is_resolved: bool,
+    comments: ReviewThreadComments,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewThreadComments {
+    nodes: Vec<ReviewThreadComment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThreadComment {
+    body: String,
+    created_at: DateTime<Utc>,
+    // None if the author's account has been deleted.
+    author: Option<ReviewThreadCommentAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewThreadCommentAuthor {
+    login: String,
+}
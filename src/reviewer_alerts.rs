@@ -0,0 +1,89 @@
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use octocrab::Octocrab;
+
+use crate::{
+    Error,
+    github_accounts::get_trainee_logins,
+    newtypes::{GithubLogin, TeamSlug},
+    prs::get_reviewers,
+    single_flight::InFlight,
+};
+
+/// How long a reviewer can go without reviewing before volunteer coordinators are told.
+pub const INACTIVITY_DAYS: i64 = 14;
+
+/// A reviewer's recent activity, as recorded in reviewer snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewerActivity {
+    pub login: GithubLogin,
+    pub last_review: DateTime<Utc>,
+    pub reviews_days_in_last_28_days: u8,
+}
+
+impl ReviewerActivity {
+    fn is_active_at(&self, time: DateTime<Utc>) -> bool {
+        self.reviews_days_in_last_28_days > 0
+            && time - self.last_review < TimeDelta::days(INACTIVITY_DAYS)
+    }
+}
+
+/// Activity of everyone who has reviewed PRs in the given modules, other than trainees peer-reviewing each other.
+pub async fn get_reviewer_activity(
+    octocrab: &Octocrab,
+    in_flight: &InFlight,
+    github_org: &str,
+    module_names: &[String],
+    batch_github_slugs: &[TeamSlug],
+) -> Result<Vec<ReviewerActivity>, Error> {
+    let trainee_logins = get_trainee_logins(octocrab, github_org, batch_github_slugs).await?;
+    let reviewers = get_reviewers(octocrab.clone(), in_flight, github_org, module_names).await?;
+    Ok(reviewers
+        .into_iter()
+        .filter(|reviewer| !trainee_logins.contains(&reviewer.login))
+        .map(|reviewer| ReviewerActivity {
+            login: reviewer.login,
+            last_review: reviewer.last_review,
+            reviews_days_in_last_28_days: reviewer.reviews_days_in_last_28_days,
+        })
+        .collect())
+}
+
+/// Reviewers who were active when the previous snapshot was taken, but who haven't reviewed for INACTIVITY_DAYS now.
+///
+/// Comparing against a snapshot means each reviewer is only reported once, when they drift away, rather than every day until they come back.
+pub fn newly_inactive_reviewers(
+    previous_snapshot_date: NaiveDate,
+    previous: &[ReviewerActivity],
+    current: &[ReviewerActivity],
+    now: DateTime<Utc>,
+) -> Vec<ReviewerActivity> {
+    let previous_snapshot_time = previous_snapshot_date
+        .and_hms_opt(23, 59, 59)
+        .expect("Known time failed to parse")
+        .and_utc();
+    current
+        .iter()
+        .filter(|reviewer| !reviewer.is_active_at(now))
+        .filter(|reviewer| {
+            previous.iter().any(|previous| {
+                previous.login == reviewer.login && previous.is_active_at(previous_snapshot_time)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn inactivity_alert_text(course_name: &str, reviewers: &[ReviewerActivity]) -> String {
+    let mut text = format!(
+        "These {} reviewers were active recently, but haven't reviewed anything in the last {} days - it may be worth checking in with them:",
+        course_name, INACTIVITY_DAYS
+    );
+    for reviewer in reviewers {
+        text.push_str(&format!(
+            "\n• <https://github.com/{login}|@{login}> - last review {}",
+            reviewer.last_review.date_naive(),
+            login = reviewer.login,
+        ));
+    }
+    text
+}
@@ -40,6 +40,19 @@ pub(crate) async fn get_reviewer_staff_info(
     }
 }
 
+/// Like get_reviewer_staff_info, but returns None if the current user doesn't have access to the sheet.
+/// Not having access generally means the user isn't staff, so callers should hide staff-only details.
+pub(crate) async fn get_reviewer_staff_info_if_permitted(
+    client: SheetsClient,
+    sheet_id: &str,
+) -> Result<Option<BTreeMap<GithubLogin, ReviewerStaffOnlyDetails>>, Error> {
+    match get_reviewer_staff_info(client, sheet_id).await {
+        Ok(staff_details) => Ok(Some(staff_details)),
+        Err(Error::PermissionDenied(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
 fn reviewer_staff_detail_from_sheet(
     sheet: &Sheet,
 ) -> Result<BTreeMap<GithubLogin, ReviewerStaffOnlyDetails>, Error> {
@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    course::{
+        Assignment, Attendance, Submission, SubmissionState, TraineeStatus, TraineeWithSubmissions,
+    },
+    forecast::module_forecasts,
+};
+
+/// A config-defined warning sign, shown as a badge on trainees it applies to.
+///
+/// These complement the overall progress score, which can hide a sudden change in a trainee's engagement.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AtRiskRule {
+    /// Shown on the badge, e.g. "2 consecutive absences".
+    pub name: String,
+    pub condition: AtRiskCondition,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AtRiskCondition {
+    /// The trainee's most recent `count` classes were all missed.
+    ConsecutiveAbsences { count: usize },
+    /// The trainee hasn't submitted anything for the most recent sprint which expected pull requests.
+    NoSubmissionsInLastSprint,
+    /// The trainee hasn't had a mentoring check-in for more than `days` days.
+    /// If `only_when` is non-empty, the rule only applies to trainees with one of those statuses.
+    NoMentoringFor {
+        days: i64,
+        #[serde(default)]
+        only_when: Vec<TraineeStatus>,
+    },
+    /// At the rate the trainee has been submitting, they won't have submitted all of a module's mandatory PRs by the module's last sprint.
+    ProjectedToMissModuleEnd,
+}
+
+impl AtRiskRule {
+    pub fn is_triggered(&self, trainee: &TraineeWithSubmissions, today: NaiveDate) -> bool {
+        match &self.condition {
+            AtRiskCondition::ConsecutiveAbsences { count } => trailing_absences(trainee) >= *count,
+            AtRiskCondition::NoSubmissionsInLastSprint => no_submissions_in_last_sprint(trainee),
+            AtRiskCondition::NoMentoringFor { days, only_when } => {
+                if !only_when.is_empty() && !only_when.contains(&trainee.status()) {
+                    return false;
+                }
+                match &trainee.mentoring_record {
+                    Some(record) => {
+                        today.signed_duration_since(record.last_date).num_days() > *days
+                    }
+                    None => true,
+                }
+            }
+            AtRiskCondition::ProjectedToMissModuleEnd => module_forecasts(trainee)
+                .iter()
+                .any(|forecast| !forecast.is_on_pace()),
+        }
+    }
+}
+
+pub fn triggered_rules<'a>(
+    rules: &'a [AtRiskRule],
+    trainee: &TraineeWithSubmissions,
+    today: NaiveDate,
+) -> Vec<&'a AtRiskRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_triggered(trainee, today))
+        .collect()
+}
+
+/// How many classes in a row the trainee has missed, counting back from the most recent one.
+fn trailing_absences(trainee: &TraineeWithSubmissions) -> usize {
+    let mut streak = 0;
+    for submission in trainee
+        .modules
+        .values()
+        .flat_map(|module| module.sprints.iter())
+        .flat_map(|sprint| sprint.submissions.iter())
+    {
+        match submission {
+            SubmissionState::Some(Submission::Attendance(Attendance::Absent { .. })) => {
+                streak += 1;
+            }
+            SubmissionState::Some(Submission::Attendance(_)) => {
+                streak = 0;
+            }
+            // Classes which haven't happened yet (or which we have no register for) don't break a streak.
+            _ => {}
+        }
+    }
+    streak
+}
+
+fn no_submissions_in_last_sprint(trainee: &TraineeWithSubmissions) -> bool {
+    let last_due_sprint = trainee
+        .modules
+        .values()
+        .flat_map(|module| module.sprints.iter())
+        .filter(|sprint| {
+            sprint.submissions.iter().any(|submission| {
+                matches!(
+                    submission,
+                    SubmissionState::Some(Submission::PullRequest { .. })
+                        | SubmissionState::MissingButExpected(
+                            Assignment::ExpectedPullRequest { .. }
+                        )
+                        | SubmissionState::MissingStretch(Assignment::ExpectedPullRequest { .. })
+                )
+            })
+        })
+        .last();
+    match last_due_sprint {
+        Some(sprint) => !sprint.submissions.iter().any(|submission| {
+            matches!(
+                submission,
+                SubmissionState::Some(Submission::PullRequest { .. })
+            )
+        }),
+        None => false,
+    }
+}
@@ -1,10 +1,16 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use anyhow::Context;
-use chrono::Days;
+use chrono::{DateTime, Days, TimeDelta, Utc};
+use chrono_tz::Tz;
 use google_sheets4::{
     Sheets,
-    api::{CellData, ErrorValue},
+    api::{
+        AddSheetRequest, BatchUpdateSpreadsheetRequest, CellData, ErrorValue, Request,
+        SheetProperties, ValueRange,
+    },
+    common::GetToken,
+    yup_oauth2,
 };
 use http::{HeaderMap, Uri};
 use hyper_rustls::HttpsConnector;
@@ -15,7 +21,10 @@ use tracing::warn;
 
 use crate::{
     Error, ServerState,
+    auth::{AuthProvider, ExpiredAuthorisation, PermissionDenied},
     google_auth::{GoogleScope, make_redirect_uri, redirect_endpoint},
+    http_clients::SheetsHttpClient,
+    upstream::GOOGLE_SHEETS,
 };
 
 // This is documented as a union where at most one value is set, per https://developers.google.com/workspace/sheets/api/reference/rest/v4/spreadsheets/other#ExtendedValue
@@ -59,6 +68,11 @@ pub(crate) fn cell_string(cell: &CellData) -> String {
     }
 }
 
+/// The value of the cell as displayed in the Sheets UI, whatever its type.
+pub(crate) fn cell_formatted(cell: &CellData) -> String {
+    cell.formatted_value.clone().unwrap_or_default()
+}
+
 pub(crate) fn cell_bool(cell: &CellData) -> bool {
     if let ExtendedValue::Bool(value) = ExtendedValue::from(cell) {
         value
@@ -84,6 +98,40 @@ pub(crate) fn cell_date(cell: &CellData) -> Result<chrono::NaiveDate, anyhow::Er
     }
 }
 
+/// Reads a timestamp which was either written as an RFC 3339 string, or entered as a Sheets date-time.
+///
+/// Sheets date-times don't carry a time zone, so are read in the spreadsheet's time zone (see Sheet::time_zone).
+pub(crate) fn cell_datetime(
+    cell: &CellData,
+    time_zone: Tz,
+) -> Result<DateTime<Utc>, anyhow::Error> {
+    match ExtendedValue::from(cell) {
+        ExtendedValue::String(value) => Ok(DateTime::parse_from_rfc3339(&value)
+            .with_context(|| format!("Failed to parse timestamp {}", value))?
+            .to_utc()),
+        ExtendedValue::Number(value) => {
+            // UNWRAP: Statically known valid date-time.
+            let epoch = chrono::NaiveDate::from_ymd_opt(1899, 12, 30)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap();
+            // AS: Millisecond precision is plenty for a timestamp.
+            let local = epoch + TimeDelta::milliseconds((value * 86_400_000.0).round() as i64);
+            local
+                .and_local_timezone(time_zone)
+                .earliest()
+                .map(|timestamp| timestamp.to_utc())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Timestamp {} doesn't exist in {}", local, time_zone)
+                })
+        }
+        _ => Err(anyhow::anyhow!(
+            "Failed to parse cell containing {} as a timestamp",
+            cell.formatted_value.clone().unwrap_or_default()
+        )),
+    }
+}
+
 pub(crate) async fn sheets_client(
     session: &Session,
     server_state: ServerState,
@@ -112,21 +160,8 @@ pub(crate) async fn sheets_client(
     let redirect_endpoint = redirect_endpoint(&server_state);
 
     if let Some(token) = maybe_token {
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build(
-                    hyper_rustls::HttpsConnectorBuilder::new()
-                        .with_native_roots()
-                        .unwrap()
-                        .https_only()
-                        .enable_http1()
-                        .enable_http2()
-                        .build(),
-                );
-
-        let client = Sheets::new(client, token);
         Ok(SheetsClient {
-            client,
+            client: make_sheets(server_state.http_clients.sheets.clone(), token),
             original_uri,
             server_state,
         })
@@ -143,6 +178,13 @@ pub(crate) async fn sheets_client(
     }
 }
 
+fn make_sheets<A: GetToken + 'static>(
+    client: SheetsHttpClient,
+    auth: A,
+) -> Sheets<HttpsConnector<HttpConnector>> {
+    Sheets::new(client, auth)
+}
+
 #[derive(Clone)]
 pub struct SheetsClient {
     client: Sheets<HttpsConnector<HttpConnector>>,
@@ -150,26 +192,94 @@ pub struct SheetsClient {
     server_state: ServerState,
 }
 
+#[derive(Clone)]
 pub struct Sheet {
     pub title: String,
     pub rows: Vec<Vec<CellData>>,
     pub id: String,
     pub url: String,
+    /// The spreadsheet's time zone, which date-times entered in it are in.
+    pub time_zone: Tz,
+}
+
+/// Parses a spreadsheet's time zone setting, assuming UK time (which most of ours are in) if it's missing or unknown.
+fn spreadsheet_time_zone(sheet_id: &str, time_zone: Option<&str>) -> Tz {
+    let Some(time_zone) = time_zone else {
+        warn!(
+            "Fetching spreadsheet with ID {} didn't have time zone metadata - assuming UK time",
+            sheet_id
+        );
+        return chrono_tz::Europe::London;
+    };
+    time_zone.parse().unwrap_or_else(|_| {
+        warn!(
+            "Spreadsheet with ID {} has unknown time zone {} - assuming UK time",
+            sheet_id, time_zone
+        );
+        chrono_tz::Europe::London
+    })
 }
 
 impl SheetsClient {
-    pub async fn get(
-        self,
+    pub(crate) fn server_state(&self) -> &ServerState {
+        &self.server_state
+    }
+
+    /// Makes a client which authenticates as a Google service account rather than as the current user.
+    /// Used where there is no user session to take a token from.
+    pub async fn for_service_account(
+        server_state: ServerState,
+        service_account_key_path: &str,
+    ) -> Result<SheetsClient, Error> {
+        let key = yup_oauth2::read_service_account_key(service_account_key_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to read Google service account key from {}",
+                    service_account_key_path
+                )
+            })?;
+        let authenticator = yup_oauth2::ServiceAccountAuthenticator::builder(key)
+            .build()
+            .await
+            .context("Failed to build Google service account authenticator")?;
+        let original_uri = Uri::from_static("/");
+        Ok(SheetsClient {
+            client: make_sheets(server_state.http_clients.sheets.clone(), authenticator),
+            original_uri,
+            server_state,
+        })
+    }
+
+    pub async fn get(self, sheet_id: &str) -> Result<BTreeMap<String, Sheet>, Error> {
+        let flight = self
+            .server_state
+            .in_flight
+            .spreadsheets
+            .run(sheet_id, async || self.fetch(sheet_id).await.map(Arc::new))
+            .await?;
+        if flight.shared {
+            // Whoever read the sheet could, but that says nothing about this client - so check it can too.
+            self.sheet_titles(sheet_id).await?;
+        }
+        Ok(Arc::unwrap_or_clone(flight.value))
+    }
+
+    async fn fetch(
+        &self,
         sheet_id: &str,
         // ) -> Result<::sheets::Response<::sheets::types::Spreadsheet>, Error> {
     ) -> Result<BTreeMap<String, Sheet>, Error> {
-        let result = self
-            .client
-            .spreadsheets()
-            .get(sheet_id)
-            .include_grid_data(true)
-            .doit()
-            .await;
+        let result = GOOGLE_SHEETS
+            .call(
+                self.client
+                    .spreadsheets()
+                    .get(sheet_id)
+                    .include_grid_data(true)
+                    .doit(),
+                is_outage,
+            )
+            .await?;
         match result {
             Ok((_, spreadsheet)) => {
                 let mut sheets = BTreeMap::new();
@@ -187,6 +297,13 @@ impl SheetsClient {
                     );
                     return Ok(sheets);
                 };
+                let time_zone = spreadsheet_time_zone(
+                    sheet_id,
+                    spreadsheet
+                        .properties
+                        .as_ref()
+                        .and_then(|properties| properties.time_zone.as_deref()),
+                );
                 for (sheet_index, sheet) in spreadsheet
                     .sheets
                     .unwrap_or_default()
@@ -237,6 +354,7 @@ impl SheetsClient {
                                         title: title.clone(),
                                         url: url.clone(),
                                         id: id.clone(),
+                                        time_zone,
                                     },
                                 );
                             } else {
@@ -250,29 +368,224 @@ impl SheetsClient {
                 }
                 Ok(sheets)
             }
-            Err(
-                ::google_sheets4::Error::MissingAPIKey | ::google_sheets4::Error::MissingToken(..),
-            ) => Err(Error::Redirect(
-                make_redirect_uri(
+            Err(err) => Err(self.convert_error(err, sheet_id, "read").await),
+        }
+    }
+
+    /// Lists the titles of all of the sheets (tabs) in a spreadsheet, including empty ones.
+    pub async fn sheet_titles(&self, sheet_id: &str) -> Result<Vec<String>, Error> {
+        let result = GOOGLE_SHEETS
+            .call(self.client.spreadsheets().get(sheet_id).doit(), is_outage)
+            .await?;
+        match result {
+            Ok((_, spreadsheet)) => Ok(spreadsheet
+                .sheets
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|sheet| sheet.properties?.title)
+                .collect()),
+            Err(err) => Err(self.convert_error(err, sheet_id, "read").await),
+        }
+    }
+
+    pub async fn add_sheet(&self, sheet_id: &str, title: &str) -> Result<(), Error> {
+        let request = BatchUpdateSpreadsheetRequest {
+            requests: Some(vec![Request {
+                add_sheet: Some(AddSheetRequest {
+                    properties: Some(SheetProperties {
+                        title: Some(title.to_owned()),
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let result = GOOGLE_SHEETS
+            .call(
+                self.client
+                    .spreadsheets()
+                    .batch_update(request, sheet_id)
+                    .doit(),
+                is_outage,
+            )
+            .await?;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(self
+                .convert_error(err, sheet_id, "edit")
+                .await
+                .with_context(|| format!("Failed to add sheet {} to {}", title, sheet_id))),
+        }
+    }
+
+    /// Overwrites the cells in range (in A1 notation, e.g. "Sheet1!A1:G1") with the given rows.
+    pub async fn write_rows(
+        &self,
+        sheet_id: &str,
+        range: &str,
+        rows: Vec<Vec<String>>,
+    ) -> Result<(), Error> {
+        let value_range = ValueRange {
+            range: Some(range.to_owned()),
+            values: Some(
+                rows.into_iter()
+                    .map(|row| row.into_iter().map(Value::String).collect())
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        let result = GOOGLE_SHEETS
+            .call(
+                self.client
+                    .spreadsheets()
+                    .values_update(value_range, sheet_id, range)
+                    .value_input_option("RAW")
+                    .doit(),
+                is_outage,
+            )
+            .await?;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(self
+                .convert_error(err, sheet_id, "edit")
+                .await
+                .with_context(|| format!("Failed to write range {} in {}", range, sheet_id))),
+        }
+    }
+
+    /// Adds rows after the last non-empty row of the table in range (in A1 notation, e.g. "'Sheet1'").
+    /// Unlike reading and then rewriting the sheet, concurrent appends don't overwrite each other.
+    pub async fn append_rows(
+        &self,
+        sheet_id: &str,
+        range: &str,
+        rows: Vec<Vec<String>>,
+    ) -> Result<(), Error> {
+        let value_range = ValueRange {
+            range: Some(range.to_owned()),
+            values: Some(
+                rows.into_iter()
+                    .map(|row| row.into_iter().map(Value::String).collect())
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        let result = GOOGLE_SHEETS
+            .call(
+                self.client
+                    .spreadsheets()
+                    .values_append(value_range, sheet_id, range)
+                    .value_input_option("RAW")
+                    .insert_data_option("INSERT_ROWS")
+                    .doit(),
+                is_outage,
+            )
+            .await?;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(self
+                .convert_error(err, sheet_id, "edit")
+                .await
+                .with_context(|| format!("Failed to append to range {} in {}", range, sheet_id))),
+        }
+    }
+
+    /// Reads the formatted values of the cells in range (in A1 notation), e.g. as written by write_rows.
+    pub async fn read_rows(&self, sheet_id: &str, range: &str) -> Result<Vec<Vec<String>>, Error> {
+        let result = GOOGLE_SHEETS
+            .call(
+                self.client
+                    .spreadsheets()
+                    .values_get(sheet_id, range)
+                    .doit(),
+                is_outage,
+            )
+            .await?;
+        match result {
+            Ok((_, value_range)) => Ok(value_range
+                .values
+                .unwrap_or_default()
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|value| match value {
+                            Value::String(value) => value,
+                            value => value.to_string(),
+                        })
+                        .collect()
+                })
+                .collect()),
+            Err(err) => Err(self
+                .convert_error(err, sheet_id, "read")
+                .await
+                .with_context(|| format!("Failed to read range {} in {}", range, sheet_id))),
+        }
+    }
+
+    async fn convert_error(
+        &self,
+        err: ::google_sheets4::Error,
+        sheet_id: &str,
+        needed_scope: &'static str,
+    ) -> Error {
+        match err {
+            ::google_sheets4::Error::MissingAPIKey | ::google_sheets4::Error::MissingToken(..) => {
+                match make_redirect_uri(
                     &self.server_state,
-                    self.original_uri,
+                    self.original_uri.clone(),
                     &redirect_endpoint(&self.server_state),
                     GoogleScope::Sheets,
                 )
-                .await?,
-            )),
-            Err(err) => {
+                .await
+                {
+                    Ok(redirect_uri) => Error::Redirect(redirect_uri),
+                    Err(err) => err,
+                }
+            }
+            err => {
                 // TODO: Upgrade to a let guard when https://github.com/rust-lang/rust/issues/51114 stabilises.
                 if let ::google_sheets4::Error::BadRequest(ref details) = err
                     && let Value::Object(object) = details
                     && object.get("error").and_then(|error| error.get("code"))
                         == Some(&Value::Number(serde_json::Number::from_u128(403).unwrap()))
                 {
-                    Err(Error::PotentiallyIgnorablePermissions(err.into()))
+                    Error::PermissionDenied(PermissionDenied {
+                        provider: AuthProvider::GoogleSheets,
+                        resource: format!(
+                            "the Google Sheet https://docs.google.com/spreadsheets/d/{sheet_id}"
+                        ),
+                        needed_scope,
+                        who_to_ask: self.server_state.config.admins.clone(),
+                        source: err.into(),
+                    })
+                } else if let ::google_sheets4::Error::BadRequest(ref details) = err
+                    && details.get("error").and_then(|error| error.get("code"))
+                        == Some(&Value::Number(serde_json::Number::from_u128(401).unwrap()))
+                {
+                    warn!("Google Sheets rejected our token: {:?}", err);
+                    ExpiredAuthorisation {
+                        provider: AuthProvider::GoogleSheets,
+                    }
+                    .into()
                 } else {
-                    Err(Error::Fatal(err.into()))
+                    Error::Fatal(err.into())
                 }
             }
         }
     }
 }
+
+/// Whether an error means Sheets itself is having problems, as opposed to e.g. us not having permission.
+fn is_outage(err: &::google_sheets4::Error) -> bool {
+    match err {
+        ::google_sheets4::Error::HttpError(_) | ::google_sheets4::Error::Io(_) => true,
+        ::google_sheets4::Error::Failure(response) => response.status().is_server_error(),
+        ::google_sheets4::Error::BadRequest(details) => details
+            .get("error")
+            .and_then(|error| error.get("code"))
+            .and_then(Value::as_u64)
+            .is_some_and(|code| code >= 500),
+        _ => false,
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    collections::BTreeSet,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+/// How many consecutive words make up a shingle.
+const SHINGLE_WORDS: usize = 5;
+
+/// Hashes of every run of SHINGLE_WORDS words, ignoring case, punctuation and formatting.
+pub fn shingles(text: &str) -> BTreeSet<u64> {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    words
+        .windows(SHINGLE_WORDS)
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// From 0 (nothing in common) to 1 (the same).
+pub fn jaccard(left: &BTreeSet<u64>, right: &BTreeSet<u64>) -> f64 {
+    let union = left.union(right).count();
+    if union == 0 {
+        return 0.0;
+    }
+    left.intersection(right).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::{jaccard, shingles};
+
+    #[test]
+    fn shingles_ignore_formatting() {
+        assert_eq!(
+            shingles("## I changed the colour of the button, and the font."),
+            shingles("i changed THE colour of the **button** and the font")
+        );
+    }
+
+    #[test]
+    fn similarity_of_unrelated_text_is_low() {
+        let left =
+            shingles("I changed the colour of the button and the font to make it accessible");
+        let right = shingles("This PR adds a form with validation for the email and name fields");
+        assert_eq!(jaccard(&left, &right), 0.0);
+        assert_eq!(jaccard(&left, &left), 1.0);
+    }
+}
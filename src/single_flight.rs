@@ -0,0 +1,139 @@
+//! Sharing one upstream fetch between everyone who asks for the same thing at the same time.
+//!
+//! When two people load the same batch at once, each would otherwise make every GitHub and Sheets call it needs.
+//! Nothing is kept once a fetch finishes - this only merges fetches which overlap, so data is never staler than it was.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use futures::channel::oneshot;
+use octocrab::models::issues::Issue;
+
+use crate::{Error, newtypes::GithubLogin, prs::Pr, sheets::Sheet};
+
+/// Every kind of fetch we share, kept in ServerState.
+///
+/// Command-line tools which don't have a ServerState (and only make one fetch at a time anyway) can use InFlight::default().
+#[derive(Clone, Default)]
+pub struct InFlight {
+    /// `org/repo` -> its issues.
+    pub issues: SingleFlight<Arc<Vec<Issue>>>,
+    /// `org/repo?state=...` -> its PRs.
+    pub prs: SingleFlight<Arc<Vec<Pr>>>,
+    /// Spreadsheet ID -> its sheets.
+    pub spreadsheets: SingleFlight<Arc<BTreeMap<String, Sheet>>>,
+    /// `org/repo` -> the Codewars usernames trainees gave in its issues.
+    pub codewars_issue_usernames: SingleFlight<Arc<BTreeMap<GithubLogin, String>>>,
+}
+
+/// Resource key -> everyone waiting for the fetch for it to finish.
+type Waiters<V> = HashMap<String, Vec<oneshot::Sender<V>>>;
+
+pub struct SingleFlight<V> {
+    in_flight: Arc<Mutex<Waiters<V>>>,
+}
+
+/// The result of a fetch, and whether it came from someone else's.
+pub struct Flight<V> {
+    pub value: V,
+    /// True if another caller made the fetch. They may have had access we don't, so callers must check their own
+    /// (which is usually much cheaper than the fetch) before using the value.
+    pub shared: bool,
+}
+
+impl<V: Clone> SingleFlight<V> {
+    pub fn new() -> Self {
+        SingleFlight {
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Runs fetch, unless a fetch for the same key is already running, in which case waits for that one instead.
+    ///
+    /// The key must identify everything the result depends on, e.g. a repo and which PRs were asked for.
+    pub async fn run(
+        &self,
+        key: &str,
+        fetch: impl AsyncFn() -> Result<V, Error>,
+    ) -> Result<Flight<V>, Error> {
+        let waiting = {
+            // UNWRAP: We never panic while holding the lock.
+            let mut in_flight = self.in_flight.lock().unwrap();
+            if let Some(waiters) = in_flight.get_mut(key) {
+                let (sender, receiver) = oneshot::channel();
+                waiters.push(sender);
+                Some(receiver)
+            } else {
+                in_flight.insert(key.to_owned(), Vec::new());
+                None
+            }
+        };
+        if let Some(receiver) = waiting {
+            return match receiver.await {
+                Ok(value) => Ok(Flight {
+                    value,
+                    shared: true,
+                }),
+                // The fetch we waited for failed. Its error may have been down to whoever made it (e.g. their token expired),
+                // so rather than passing it on, try for ourselves.
+                Err(oneshot::Canceled) => Ok(Flight {
+                    value: fetch().await?,
+                    shared: false,
+                }),
+            };
+        }
+
+        let mut leader = Leader {
+            in_flight: &self.in_flight,
+            key,
+            value: None,
+        };
+        let value = fetch().await?;
+        leader.value = Some(value.clone());
+        Ok(Flight {
+            value,
+            shared: false,
+        })
+    }
+}
+
+impl<V: Clone> Default for SingleFlight<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Clone for SingleFlight<V> {
+    fn clone(&self) -> Self {
+        SingleFlight {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+/// Ends the fetch for key when dropped, however the fetch ended (including by being cancelled),
+/// giving everyone waiting the value if there is one.
+struct Leader<'a, V: Clone> {
+    in_flight: &'a Mutex<Waiters<V>>,
+    key: &'a str,
+    value: Option<V>,
+}
+
+impl<V: Clone> Drop for Leader<'_, V> {
+    fn drop(&mut self) {
+        // Publish while still holding the lock we remove the key under,
+        // so nobody can join this fetch after it has been published and be left waiting.
+        // UNWRAP: We never panic while holding the lock.
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let waiters = in_flight.remove(self.key).unwrap_or_default();
+        if let Some(value) = &self.value {
+            for waiter in waiters {
+                // Waiters which have since given up don't need the value.
+                let _ = waiter.send(value.clone());
+            }
+        }
+        // Otherwise dropping the senders tells waiters the fetch failed.
+    }
+}
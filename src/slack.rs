@@ -1,37 +1,39 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::Context;
+use email_address::EmailAddress;
 use futures::future::join_all;
 use http::Uri;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use slack_with_types::{
     client::RateLimiter, newtypes::UserGroupId, usergroups::UserGroup, users::UserInfo,
 };
 use tower_sessions::Session;
+use tracing::warn;
 use uuid::Uuid;
 
-use crate::{Error, ServerState};
+use crate::{Error, ServerState, upstream::SLACK};
 
 pub(crate) const SLACK_ACCESS_TOKEN_SESSION_KEY: &str = "slack_access_token";
 
+/// Adding to these means everyone who has already signed in to Slack needs to sign in again before using the new scopes.
+const SLACK_SCOPES: [&str; 6] = [
+    "usergroups:read",
+    "usergroups:write",
+    "users:read",
+    "users:read.email",
+    "channels:read",
+    "groups:read",
+];
+
 pub(crate) async fn slack_client(
     session: &Session,
     server_state: ServerState,
     original_uri: Uri,
 ) -> Result<slack_with_types::client::Client, Error> {
-    let maybe_token: Option<String> = session
-        .get(SLACK_ACCESS_TOKEN_SESSION_KEY)
-        .await
-        .context("Session load error")?;
-    if let Some(access_token) = maybe_token {
-        let rate_limiter = server_state
-            .slack_rate_limiters
-            .get_with(access_token.clone(), async { RateLimiter::new() })
-            .await;
-        Ok(slack_with_types::client::Client::new(
-            reqwest::Client::new(),
-            rate_limiter,
-            access_token,
-        ))
+    if let Some(client) = slack_client_if_signed_in(session, &server_state).await? {
+        Ok(client)
     } else {
         let state = Uuid::new_v4();
         server_state
@@ -42,11 +44,90 @@ pub(crate) async fn slack_client(
         let redirect_uri = make_slack_redirect_uri(&server_state.config.public_base_url);
         // TODO: Generalise requesting scopes
         Err(Error::Redirect(
-            format!("https://slack.com/oauth/v2/authorize?scope={},{},{}&client_id={}&redirect_uri={}&state={}", "usergroups:read", "users:read", "users:read.email", client_id, redirect_uri, state).parse().context("Statically known correct Slack auth Uri couldn't be constructed")?
+            format!("https://slack.com/oauth/v2/authorize?scope={}&client_id={}&redirect_uri={}&state={}", SLACK_SCOPES.join(","), client_id, redirect_uri, state).parse().context("Statically known correct Slack auth Uri couldn't be constructed")?
         ))
     }
 }
 
+/// Like slack_client, but returns None rather than redirecting if the user hasn't signed in to Slack.
+/// For pages where Slack data is a nice-to-have.
+pub(crate) async fn slack_client_if_signed_in(
+    session: &Session,
+    server_state: &ServerState,
+) -> Result<Option<slack_with_types::client::Client>, Error> {
+    let maybe_token: Option<String> = session
+        .get(SLACK_ACCESS_TOKEN_SESSION_KEY)
+        .await
+        .context("Session load error")?;
+    let Some(access_token) = maybe_token else {
+        return Ok(None);
+    };
+    let rate_limiter = server_state
+        .slack_rate_limiters
+        .get_with(access_token.clone(), async { RateLimiter::new() })
+        .await;
+    Ok(Some(slack_with_types::client::Client::new(
+        server_state.http_clients.slack.clone(),
+        rate_limiter,
+        access_token,
+    )))
+}
+
+#[derive(Serialize)]
+struct LookupUserByEmailRequest {
+    email: String,
+}
+
+#[derive(Deserialize)]
+struct LookupUserByEmailResponse {
+    user: SlackUserIds,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SlackUserIds {
+    pub(crate) id: String,
+    pub(crate) team_id: String,
+}
+
+/// Finds a link which opens the Slack profile of the user with the given email.
+/// Returns None if there's no such user (or Slack couldn't tell us), as this is only ever a convenience.
+pub(crate) async fn find_slack_profile_url(
+    client: &slack_with_types::client::Client,
+    email: &EmailAddress,
+) -> Option<String> {
+    let SlackUserIds { id, team_id } = find_slack_user(client, email).await?;
+    Some(format!("slack://user?team={}&id={}", team_id, id))
+}
+
+/// Finds the Slack user with the given email.
+/// Returns None if there's no such user (or Slack couldn't tell us).
+pub(crate) async fn find_slack_user(
+    client: &slack_with_types::client::Client,
+    email: &EmailAddress,
+) -> Option<SlackUserIds> {
+    let request = LookupUserByEmailRequest {
+        email: email.to_string(),
+    };
+    // Slack errors don't tell us whether Slack is down, so only count timeouts against it.
+    let response: Result<LookupUserByEmailResponse, _> = match SLACK
+        .call(client.post("users.lookupByEmail", &request), |_| false)
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            warn!("Failed to look up Slack user by email {}: {}", email, err);
+            return None;
+        }
+    };
+    match response {
+        Ok(LookupUserByEmailResponse { user }) => Some(user),
+        Err(err) => {
+            warn!("Failed to look up Slack user by email {}: {:?}", email, err);
+            None
+        }
+    }
+}
+
 pub(crate) fn make_slack_redirect_uri(public_base_uri: &str) -> Uri {
     format!("{}/api/oauth-callbacks/slack", public_base_uri,)
         .parse()
@@ -71,9 +152,11 @@ pub(crate) async fn list_groups_with_members(
         team_id: None,
     };
 
-    let groups_list: slack_with_types::usergroups::ListResponse = client
-        .post("usergroups.list", &list_groups_request)
-        .await
+    let groups_list: slack_with_types::usergroups::ListResponse = SLACK
+        .call(client.post("usergroups.list", &list_groups_request), |_| {
+            false
+        })
+        .await?
         .context("Failed to list Slack usergroups")?;
 
     let groups_and_users = join_all(groups_list.usergroups.into_iter().map(
@@ -85,9 +168,12 @@ pub(crate) async fn list_groups_with_members(
             let list_users_request = slack_with_types::usergroups::ListUsersRequest {
                 usergroup: usergroup.id.clone(),
             };
-            let users_list: slack_with_types::usergroups::ListUsersResponse = client
-                .post("usergroups.users.list", &list_users_request)
-                .await
+            let users_list: slack_with_types::usergroups::ListUsersResponse = SLACK
+                .call(
+                    client.post("usergroups.users.list", &list_users_request),
+                    |_| false,
+                )
+                .await?
                 .with_context(|| {
                     format!(
                         "Failed to list users in group {}",
@@ -111,9 +197,9 @@ pub(crate) async fn list_groups_with_members(
             user: user_id.clone(),
         };
 
-        let user: slack_with_types::users::GetUserInfoResponse = client
-            .post("users.info", &get_user_request)
-            .await
+        let user: slack_with_types::users::GetUserInfoResponse = SLACK
+            .call(client.post("users.info", &get_user_request), |_| false)
+            .await?
             .with_context(|| format!("Failed to get user with ID {}", get_user_request.user))?;
         Ok((user_id, user.user))
     }))
@@ -146,3 +232,172 @@ pub(crate) async fn list_groups_with_members(
 
     Ok(groups)
 }
+
+#[derive(Serialize)]
+struct ConversationMembersRequest {
+    channel: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct ConversationMembersResponse {
+    members: Vec<String>,
+    #[serde(default)]
+    response_metadata: Option<ResponseMetadata>,
+}
+
+#[derive(Deserialize)]
+struct ResponseMetadata {
+    #[serde(default)]
+    next_cursor: String,
+}
+
+/// Lists the user IDs of everyone in a channel (public or private, as long as the signed-in user can see it).
+pub(crate) async fn list_channel_members(
+    client: &slack_with_types::client::Client,
+    channel_id: &str,
+) -> Result<BTreeSet<String>, Error> {
+    let mut members = BTreeSet::new();
+    let mut cursor = None;
+    loop {
+        let request = ConversationMembersRequest {
+            channel: channel_id.to_owned(),
+            cursor: cursor.take(),
+            limit: 1000,
+        };
+        let response: ConversationMembersResponse = SLACK
+            .call(client.post("conversations.members", &request), |_| false)
+            .await?
+            .with_context(|| format!("Failed to list members of Slack channel {}", channel_id))?;
+        members.extend(response.members);
+        match response.response_metadata {
+            Some(ResponseMetadata { next_cursor }) if !next_cursor.is_empty() => {
+                cursor = Some(next_cursor);
+            }
+            _ => break,
+        }
+    }
+    Ok(members)
+}
+
+#[derive(Serialize)]
+struct ListUsergroupUsersRequest {
+    usergroup: String,
+}
+
+#[derive(Deserialize)]
+struct ListUsergroupUsersResponse {
+    users: Vec<String>,
+}
+
+/// Finds the usergroup with the given handle (without the @), and the IDs of its members.
+pub(crate) async fn find_usergroup_by_handle(
+    client: &slack_with_types::client::Client,
+    handle: &str,
+) -> Result<Option<(String, BTreeSet<String>)>, Error> {
+    let list_groups_request = slack_with_types::usergroups::ListRequest {
+        include_count: None,
+        include_disabled: Some(true),
+        include_users: None,
+        team_id: None,
+    };
+    let groups_list: slack_with_types::usergroups::ListResponse = SLACK
+        .call(client.post("usergroups.list", &list_groups_request), |_| {
+            false
+        })
+        .await?
+        .context("Failed to list Slack usergroups")?;
+    let Some(usergroup) = groups_list
+        .usergroups
+        .into_iter()
+        .find(|usergroup| usergroup.handle == handle)
+    else {
+        return Ok(None);
+    };
+    let request = ListUsergroupUsersRequest {
+        usergroup: usergroup.id.as_str().to_owned(),
+    };
+    let users: ListUsergroupUsersResponse = SLACK
+        .call(client.post("usergroups.users.list", &request), |_| false)
+        .await?
+        .with_context(|| format!("Failed to list users in group {}", request.usergroup))?;
+    Ok(Some((request.usergroup, users.users.into_iter().collect())))
+}
+
+#[derive(Serialize)]
+struct CreateUsergroupRequest {
+    name: String,
+    handle: String,
+}
+
+#[derive(Deserialize)]
+struct CreateUsergroupResponse {
+    usergroup: CreatedUsergroup,
+}
+
+#[derive(Deserialize)]
+struct CreatedUsergroup {
+    id: String,
+}
+
+/// Creates a usergroup, returning its ID.
+pub(crate) async fn create_usergroup(
+    client: &slack_with_types::client::Client,
+    name: &str,
+    handle: &str,
+) -> Result<String, Error> {
+    let request = CreateUsergroupRequest {
+        name: name.to_owned(),
+        handle: handle.to_owned(),
+    };
+    let response: CreateUsergroupResponse = SLACK
+        .call(client.post("usergroups.create", &request), |_| false)
+        .await?
+        .with_context(|| format!("Failed to create Slack usergroup @{}", handle))?;
+    Ok(response.usergroup.id)
+}
+
+#[derive(Serialize)]
+struct UpdateUsergroupUsersRequest {
+    usergroup: String,
+    /// Comma-separated user IDs.
+    users: String,
+}
+
+#[derive(Deserialize)]
+struct UpdateUsergroupUsersResponse {}
+
+/// Replaces the members of a usergroup with exactly the given users.
+pub(crate) async fn set_usergroup_users(
+    client: &slack_with_types::client::Client,
+    usergroup_id: &str,
+    user_ids: &BTreeSet<String>,
+) -> Result<(), Error> {
+    let request = UpdateUsergroupUsersRequest {
+        usergroup: usergroup_id.to_owned(),
+        users: user_ids.iter().cloned().collect::<Vec<_>>().join(","),
+    };
+    let _: UpdateUsergroupUsersResponse = SLACK
+        .call(client.post("usergroups.users.update", &request), |_| false)
+        .await?
+        .with_context(|| format!("Failed to update users in Slack usergroup {}", usergroup_id))?;
+    Ok(())
+}
+
+/// Posts to a Slack channel via an incoming webhook.
+pub async fn post_to_slack_webhook(
+    client: &reqwest::Client,
+    webhook_url: &str,
+    text: &str,
+) -> Result<(), Error> {
+    client
+        .post(webhook_url)
+        .json(&json!({ "text": text }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("Failed to post to Slack webhook")?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use futures::future::join_all;
+use http::HeaderMap;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    course::Batch,
+    frontend::get_course_and_batch,
+    newtypes::GithubLogin,
+    slack::{find_slack_user, list_channel_members, slack_client},
+};
+
+/// A trainee who isn't in one of their batch's Slack channels.
+#[derive(Clone, Debug)]
+pub struct MissingFromChannel {
+    pub github_login: GithubLogin,
+    pub name: String,
+    /// None if we couldn't find a Slack account with the trainee's email, so couldn't check.
+    pub slack_user_id: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChannelAudit {
+    pub channel_id: String,
+    pub member_count: usize,
+    pub missing: Vec<MissingFromChannel>,
+}
+
+/// Checks which of the batch's trainees are in each of the given Slack channels.
+/// Trainees are matched to Slack accounts by the email in the GitHub email mapping sheet.
+pub async fn audit_slack_channels(
+    client: &slack_with_types::client::Client,
+    batch: &Batch,
+    channel_ids: &[String],
+) -> Result<Vec<ChannelAudit>, Error> {
+    let slack_user_ids = join_all(
+        batch
+            .trainees
+            .iter()
+            .map(|trainee| find_slack_user(client, &trainee.trainee.email)),
+    )
+    .await
+    .into_iter()
+    .map(|user| user.map(|user| user.id))
+    .collect::<Vec<_>>();
+
+    let channel_members = join_all(
+        channel_ids
+            .iter()
+            .map(|channel_id| list_channel_members(client, channel_id)),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<BTreeSet<String>>, Error>>()?;
+
+    Ok(channel_ids
+        .iter()
+        .zip(channel_members)
+        .map(|(channel_id, members)| ChannelAudit {
+            channel_id: channel_id.clone(),
+            member_count: members.len(),
+            missing: batch
+                .trainees
+                .iter()
+                .zip(&slack_user_ids)
+                .filter(|(_, slack_user_id)| {
+                    slack_user_id
+                        .as_ref()
+                        .is_none_or(|slack_user_id| !members.contains(slack_user_id))
+                })
+                .map(|(trainee, slack_user_id)| MissingFromChannel {
+                    github_login: trainee.trainee.github_login.clone(),
+                    name: trainee.trainee.display_name().to_owned(),
+                    slack_user_id: slack_user_id.clone(),
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+pub async fn get_slack_channel_audit(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    let channel_ids = server_state
+        .config
+        .courses
+        .get(course.as_str())
+        .and_then(|course_info| course_info.batches.get(batch_github_slug.as_str()))
+        .map(|course_schedule| course_schedule.slack_channels.clone())
+        .ok_or_else(|| Error::UserFacing(format!("Batch not found: {batch_github_slug}")))?;
+    let client = slack_client(&session, server_state.clone(), original_uri.clone()).await?;
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let channels = audit_slack_channels(&client, &batch, &channel_ids).await?;
+    Ok(Html(
+        SlackChannelAuditTemplate {
+            course_name: course.name,
+            batch_name: batch.name,
+            channels,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "slack-channel-audit.html")]
+struct SlackChannelAuditTemplate {
+    course_name: String,
+    batch_name: String,
+    channels: Vec<ChannelAudit>,
+}
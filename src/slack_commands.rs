@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use axum::{Json, body::Bytes, extract::State};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use http::HeaderMap;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use tracing::error;
+
+use crate::{
+    Error, ServerState,
+    course::{Assignment, TraineeInBatch, TraineeWithSubmissions, find_trainee_in_batches},
+    github_accounts::{Trainee, get_trainees},
+    newtypes::GithubLogin,
+    octocrab::octocrab_for_token,
+    risk_rules::{AtRiskRule, triggered_rules},
+    sheets::SheetsClient,
+};
+
+// See https://api.slack.com/authentication/verifying-requests-from-slack
+const SIGNATURE_HEADER: &str = "x-slack-signature";
+const TIMESTAMP_HEADER: &str = "x-slack-request-timestamp";
+const MAX_REQUEST_AGE_SECONDS: i64 = 5 * 60;
+
+// Slack truncates long messages, and a long list isn't useful in a summary anyway.
+const MAX_MISSING_ASSIGNMENTS_TO_LIST: usize = 10;
+
+#[derive(Deserialize)]
+pub struct SlashCommand {
+    command: String,
+    user_id: String,
+    text: String,
+    response_url: String,
+}
+
+pub async fn handle_slash_command(
+    State(server_state): State<ServerState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, Error> {
+    verify_slack_signature(&server_state, &headers, &body)?;
+    let command: SlashCommand =
+        serde_urlencoded::from_bytes(&body).context("Failed to parse Slack command")?;
+
+    // The signature only proves the command came from Slack - anyone in the workspace (including trainees) can run it.
+    if !server_state
+        .config
+        .slack_command_users
+        .contains(&command.user_id)
+    {
+        return Ok(Json(ephemeral_message(
+            "Sorry, only staff can use this command. Ask an admin to add you to the trainee tracker's Slack command users.",
+        )));
+    }
+
+    match command.command.as_str() {
+        "/trainee" => {
+            let query = command.text.trim().to_owned();
+            if query.is_empty() {
+                return Ok(Json(ephemeral_message(
+                    "Usage: `/trainee <name or GitHub username>`",
+                )));
+            }
+            // Slack expects a response within 3 seconds, and looking up a trainee takes longer than that.
+            // So we acknowledge now, and post the real answer to the response_url when we have it.
+            let acknowledgement = ephemeral_message(&format!("Looking up {}...", query));
+            tokio::spawn(respond_with_trainee_summary(
+                server_state,
+                query,
+                command.response_url,
+            ));
+            Ok(Json(acknowledgement))
+        }
+        other => Err(Error::UserFacing(format!(
+            "Unrecognised Slack command {}",
+            other
+        ))),
+    }
+}
+
+fn verify_slack_signature(
+    server_state: &ServerState,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), Error> {
+    let Some(signing_secret) = &server_state.config.slack_signing_secret else {
+        return Err(Error::Fatal(anyhow::anyhow!(
+            "Received Slack command but no Slack signing secret is configured"
+        )));
+    };
+    let Some(timestamp) = headers
+        .get(TIMESTAMP_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Err(Error::UserFacing(format!(
+            "Missing {} header",
+            TIMESTAMP_HEADER
+        )));
+    };
+    let Some(signature) = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Err(Error::UserFacing(format!(
+            "Missing {} header",
+            SIGNATURE_HEADER
+        )));
+    };
+    let Ok(timestamp_seconds) = timestamp.parse::<i64>() else {
+        return Err(Error::UserFacing(format!(
+            "Invalid {} header",
+            TIMESTAMP_HEADER
+        )));
+    };
+    // Guard against replay attacks.
+    if (Utc::now().timestamp() - timestamp_seconds).abs() > MAX_REQUEST_AGE_SECONDS {
+        return Err(Error::UserFacing(
+            "Slack request timestamp was too far from the current time".to_owned(),
+        ));
+    }
+    if is_valid_slack_signature(signing_secret, timestamp, body, signature) {
+        Ok(())
+    } else {
+        Err(Error::UserFacing(
+            "Invalid Slack request signature".to_owned(),
+        ))
+    }
+}
+
+fn is_valid_slack_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("v0=")
+        .and_then(|signature| hex::decode(signature).ok())
+    else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(b"v0:");
+    mac.update(timestamp.as_bytes());
+    mac.update(b":");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn ephemeral_message(text: &str) -> Value {
+    json!({
+        "response_type": "ephemeral",
+        "text": text,
+    })
+}
+
+async fn respond_with_trainee_summary(
+    server_state: ServerState,
+    query: String,
+    response_url: String,
+) {
+    let message = match trainee_summary(&server_state, &query).await {
+        Ok(message) => message,
+        Err(Error::UserFacing(message)) => ephemeral_message(&message),
+        Err(err) => {
+            error!(
+                "Failed to look up trainee {} for Slack command: {:?}",
+                query, err
+            );
+            ephemeral_message(
+                "Something went wrong looking up that trainee - please try again, or look in the trainee tracker directly.",
+            )
+        }
+    };
+    let result = server_state
+        .http_clients
+        .slack
+        .post(&response_url)
+        .json(&message)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(err) = result {
+        error!("Failed to respond to Slack command: {:?}", err);
+    }
+}
+
+async fn trainee_summary(server_state: &ServerState, query: &str) -> Result<Value, Error> {
+    let Some(service_credentials) = &server_state.config.service_credentials else {
+        return Err(Error::UserFacing(
+            "The trainee tracker isn't configured with service credentials, so can't look up trainees from Slack.".to_owned(),
+        ));
+    };
+    let octocrab = octocrab_for_token(service_credentials.github_token.to_string())?;
+    let sheets_client = SheetsClient::for_service_account(
+        server_state.clone(),
+        &service_credentials.google_service_account_key_path,
+    )
+    .await?;
+
+    let trainees = get_trainees(
+        sheets_client.clone(),
+        &server_state.config.github_email_mapping_sheet_id,
+    )
+    .await?;
+    let trainee = find_trainee(&trainees, query)?;
+
+    let Some(TraineeInBatch {
+        course_name,
+        batch_github_slug,
+        batch_name,
+        trainee: trainee_with_submissions,
+    }) = find_trainee_in_batches(
+        &octocrab,
+        sheets_client,
+        &server_state.config,
+        &trainee.github_login,
+    )
+    .await?
+    else {
+        return Err(Error::UserFacing(format!(
+            "Found trainee {} (@{}) but they're not in any batch the tracker knows about.",
+            trainee.display_name(),
+            trainee.github_login
+        )));
+    };
+    let trainee_url = format!(
+        "{}/courses/{}/batches/{}/trainees/{}",
+        server_state.config.public_base_url, course_name, batch_github_slug, trainee.github_login
+    );
+    let triggered_rules = triggered_rules(
+        &server_state.config.at_risk_rules,
+        &trainee_with_submissions,
+        Utc::now().date_naive(),
+    );
+    Ok(summary_message(
+        &trainee_with_submissions,
+        &batch_name,
+        &triggered_rules,
+        &trainee_url,
+    ))
+}
+
+fn find_trainee<'a>(
+    trainees: &'a BTreeMap<GithubLogin, Trainee>,
+    query: &str,
+) -> Result<&'a Trainee, Error> {
+    let query = query.trim_start_matches('@');
+    if let Some(trainee) = trainees.get(&GithubLogin::from(query.to_owned())) {
+        return Ok(trainee);
+    }
+    let query_lower = query.to_lowercase();
+    let matches: Vec<_> = trainees
+        .values()
+        .filter(|trainee| {
+            trainee.name.to_lowercase().contains(&query_lower)
+                || trainee
+                    .preferred_name
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase().contains(&query_lower))
+        })
+        .collect();
+    match matches.as_slice() {
+        [trainee] => Ok(*trainee),
+        [] => Err(Error::UserFacing(format!(
+            "Couldn't find a trainee matching '{}'",
+            query
+        ))),
+        more_than_one => Err(Error::UserFacing(format!(
+            "Found more than one trainee matching '{}' - try again with their GitHub username: {}",
+            query,
+            more_than_one
+                .iter()
+                .map(|trainee| format!("{} (@{})", trainee.display_name(), trainee.github_login))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+fn summary_message(
+    trainee: &TraineeWithSubmissions,
+    batch_name: &str,
+    triggered_rules: &[&AtRiskRule],
+    trainee_url: &str,
+) -> Value {
+    let attendance = trainee.attendance();
+    let mut overview = format!(
+        "*{}* (<https://github.com/{login}|@{login}>) - {} - {}\n*Status:* {} ({}%)\n*Attendance:* {} / {}",
+        trainee.trainee.display_name(),
+        trainee.trainee.region,
+        batch_name,
+        trainee.status().display_text(),
+        trainee.progress_score() / 100,
+        attendance.numerator,
+        attendance.denominator,
+        login = trainee.trainee.github_login,
+    );
+    if !triggered_rules.is_empty() {
+        overview.push_str(&format!(
+            "\n*Warnings:* {}",
+            triggered_rules
+                .iter()
+                .map(|rule| rule.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let missing = trainee.missing_mandatory_pull_requests();
+    let mut missing_text = if missing.is_empty() {
+        "*Missing assignments:* None 🎉".to_owned()
+    } else {
+        format!("*Missing assignments ({}):*", missing.len())
+    };
+    for assignment in missing.iter().take(MAX_MISSING_ASSIGNMENTS_TO_LIST) {
+        if let Assignment::ExpectedPullRequest {
+            title, html_url, ..
+        } = assignment
+        {
+            missing_text.push_str(&format!("\n• <{}|{}>", html_url, title));
+        }
+    }
+    if missing.len() > MAX_MISSING_ASSIGNMENTS_TO_LIST {
+        missing_text.push_str(&format!(
+            "\n…and {} more",
+            missing.len() - MAX_MISSING_ASSIGNMENTS_TO_LIST
+        ));
+    }
+
+    let link_text = format!("<{}|View in the trainee tracker>", trainee_url);
+
+    json!({
+        "response_type": "ephemeral",
+        "text": format!("{} - {}", trainee.trainee.name, trainee.status().display_text()),
+        "blocks": [
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": overview },
+            },
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": missing_text },
+            },
+            {
+                "type": "context",
+                "elements": [
+                    { "type": "mrkdwn", "text": link_text },
+                ],
+            },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_valid_slack_signature;
+
+    // Example taken from https://api.slack.com/authentication/verifying-requests-from-slack
+    const SIGNING_SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+    const TIMESTAMP: &str = "1531420618";
+    const BODY: &str = "token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+    const SIGNATURE: &str = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+
+    #[test]
+    fn test_valid_signature() {
+        assert!(is_valid_slack_signature(
+            SIGNING_SECRET,
+            TIMESTAMP,
+            BODY.as_bytes(),
+            SIGNATURE
+        ));
+    }
+
+    #[test]
+    fn test_tampered_body() {
+        let body = BODY.replace("text=", "text=someone");
+        assert!(!is_valid_slack_signature(
+            SIGNING_SECRET,
+            TIMESTAMP,
+            body.as_bytes(),
+            SIGNATURE
+        ));
+    }
+
+    #[test]
+    fn test_malformed_signature() {
+        assert!(!is_valid_slack_signature(
+            SIGNING_SECRET,
+            TIMESTAMP,
+            BODY.as_bytes(),
+            "a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503"
+        ));
+    }
+}
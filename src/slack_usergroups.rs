@@ -0,0 +1,223 @@
+use std::collections::BTreeSet;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    response::{Html, Redirect},
+};
+use futures::future::join_all;
+use http::HeaderMap;
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    audit_log::{AuditEvent, record_audit_event},
+    course::Batch,
+    frontend::{get_course_and_batch, record_store},
+    impersonation::{forbid_while_viewing_as, require_admin},
+    newtypes::GithubLogin,
+    slack::{
+        create_usergroup, find_slack_user, find_usergroup_by_handle, set_usergroup_users,
+        slack_client,
+    },
+};
+
+/// What provisioning a batch's Slack usergroup would change.
+#[derive(Clone, Debug)]
+pub struct UsergroupDiff {
+    pub handle: String,
+    /// None if the usergroup doesn't exist yet, and will be created.
+    pub usergroup_id: Option<String>,
+    /// Trainees who will be added.
+    pub to_add: Vec<SlackTrainee>,
+    /// Slack user IDs of members who aren't trainees in the batch, and will be removed.
+    pub to_remove: Vec<String>,
+    /// Trainees who are already members.
+    pub unchanged: Vec<SlackTrainee>,
+    /// Trainees with no Slack account for their email, who can't be added.
+    pub unmatched: Vec<SlackTrainee>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SlackTrainee {
+    pub github_login: GithubLogin,
+    pub name: String,
+    pub slack_user_id: Option<String>,
+}
+
+impl UsergroupDiff {
+    pub fn has_changes(&self) -> bool {
+        self.usergroup_id.is_none() || !self.to_add.is_empty() || !self.to_remove.is_empty()
+    }
+
+    /// Everyone who should be in the usergroup afterwards.
+    fn desired_members(&self) -> BTreeSet<String> {
+        self.to_add
+            .iter()
+            .chain(&self.unchanged)
+            .filter_map(|trainee| trainee.slack_user_id.clone())
+            .collect()
+    }
+}
+
+async fn usergroup_diff(
+    client: &slack_with_types::client::Client,
+    batch: &Batch,
+    handle: &str,
+) -> Result<UsergroupDiff, Error> {
+    let trainees = join_all(batch.trainees.iter().map(|trainee| async {
+        SlackTrainee {
+            github_login: trainee.trainee.github_login.clone(),
+            name: trainee.trainee.display_name().to_owned(),
+            slack_user_id: find_slack_user(client, &trainee.trainee.email)
+                .await
+                .map(|user| user.id),
+        }
+    }))
+    .await;
+    let (usergroup_id, current_members) = match find_usergroup_by_handle(client, handle).await? {
+        Some((usergroup_id, members)) => (Some(usergroup_id), members),
+        None => (None, BTreeSet::new()),
+    };
+
+    let trainee_ids = trainees
+        .iter()
+        .filter_map(|trainee| trainee.slack_user_id.clone())
+        .collect::<BTreeSet<_>>();
+    let mut diff = UsergroupDiff {
+        handle: handle.to_owned(),
+        usergroup_id,
+        to_add: Vec::new(),
+        to_remove: current_members.difference(&trainee_ids).cloned().collect(),
+        unchanged: Vec::new(),
+        unmatched: Vec::new(),
+    };
+    for trainee in trainees {
+        match &trainee.slack_user_id {
+            None => diff.unmatched.push(trainee),
+            Some(id) if current_members.contains(id) => diff.unchanged.push(trainee),
+            Some(_) => diff.to_add.push(trainee),
+        }
+    }
+    Ok(diff)
+}
+
+fn usergroup_handle(
+    server_state: &ServerState,
+    course: &str,
+    batch_github_slug: &str,
+) -> Result<String, Error> {
+    let course_schedule = server_state
+        .config
+        .courses
+        .get(course)
+        .and_then(|course_info| course_info.batches.get(batch_github_slug))
+        .ok_or_else(|| Error::UserFacing(format!("Batch not found: {batch_github_slug}")))?;
+    Ok(course_schedule
+        .slack_usergroup_handle
+        .clone()
+        .unwrap_or_else(|| batch_github_slug.to_owned()))
+}
+
+#[derive(Deserialize)]
+pub struct SlackUsergroupParams {
+    #[serde(default)]
+    updated: bool,
+}
+
+/// Previews the changes provisioning would make, with a button to make them.
+pub async fn get_slack_usergroup(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+    Query(params): Query<SlackUsergroupParams>,
+) -> Result<Html<String>, Error> {
+    require_admin(&session, &server_state, original_uri.clone()).await?;
+    let handle = usergroup_handle(&server_state, &course, &batch_github_slug)?;
+    let client = slack_client(&session, server_state.clone(), original_uri.clone()).await?;
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    let diff = usergroup_diff(&client, &batch, &handle).await?;
+    Ok(Html(
+        SlackUsergroupTemplate {
+            course_name: course.name,
+            batch_name: batch.name,
+            batch_github_slug,
+            diff,
+            updated: params.updated,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+/// Makes the batch's usergroup contain exactly the batch's trainees, creating it if needed.
+pub async fn post_slack_usergroup(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let admin = require_admin(&session, &server_state, original_uri.clone()).await?;
+    let handle = usergroup_handle(&server_state, &course, &batch_github_slug)?;
+    let client = slack_client(&session, server_state.clone(), original_uri.clone()).await?;
+    let (course, batch, _) = get_course_and_batch(
+        &session,
+        headers,
+        &server_state,
+        original_uri,
+        course,
+        &batch_github_slug,
+    )
+    .await?;
+    // Recompute rather than trusting the preview, so we never apply a stale diff.
+    let diff = usergroup_diff(&client, &batch, &handle).await?;
+    if diff.has_changes() {
+        record_audit_event(
+            &record_store(&server_state).await?,
+            &AuditEvent {
+                recorded_at: chrono::Utc::now(),
+                actor: admin,
+                action: "provision slack usergroup",
+                detail: format!(
+                    "@{}: adding {}, removing {}",
+                    handle,
+                    diff.to_add.len(),
+                    diff.to_remove.len()
+                ),
+            },
+        )
+        .await?;
+        let usergroup_id = match &diff.usergroup_id {
+            Some(usergroup_id) => usergroup_id.clone(),
+            None => create_usergroup(&client, &batch.name, &handle).await?,
+        };
+        set_usergroup_users(&client, &usergroup_id, &diff.desired_members()).await?;
+    }
+    Ok(Redirect::to(&format!(
+        "/admin/courses/{}/batches/{}/slack-usergroup?updated=true",
+        course.name, batch_github_slug
+    )))
+}
+
+#[derive(Template)]
+#[template(path = "slack-usergroup.html")]
+struct SlackUsergroupTemplate {
+    course_name: String,
+    batch_name: String,
+    batch_github_slug: String,
+    diff: UsergroupDiff,
+    updated: bool,
+}
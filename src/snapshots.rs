@@ -0,0 +1,398 @@
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate};
+
+use crate::{
+    Error,
+    burndown::{BurndownPoint, SprintBurndown},
+    course::{Assignment, Batch, Course, Submission, SubmissionState},
+    funnel::FunnelPoint,
+    newtypes::GithubLogin,
+    prs::PrState,
+    reviewer_alerts::ReviewerActivity,
+    sheets::SheetsClient,
+};
+
+/// Writes the batch's submission matrix into a new tab of the snapshot sheet, named after the batch and date.
+///
+/// Reports (e.g. in Looker) read these tabs, so their layout should only change deliberately.
+/// Re-running on the same day overwrites that day's tab.
+pub async fn write_batch_snapshot(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course: &Course,
+    batch: &Batch,
+    date: NaiveDate,
+) -> Result<String, Error> {
+    let title = format!("{} {} {}", course.name, batch.github_team_slug, date);
+    let existing_titles = sheets_client.sheet_titles(snapshot_sheet_id).await?;
+    if !existing_titles.contains(&title) {
+        sheets_client.add_sheet(snapshot_sheet_id, &title).await?;
+    }
+    sheets_client
+        .write_rows(
+            snapshot_sheet_id,
+            &format!("'{}'", title),
+            submission_matrix(course, batch),
+        )
+        .await?;
+    Ok(title)
+}
+
+fn reviewer_snapshot_title_prefix(course_name: &str) -> String {
+    format!("{} reviewers ", course_name)
+}
+
+/// Writes each reviewer's recent activity into a new tab of the snapshot sheet, so later runs can tell who has stopped reviewing.
+/// Re-running on the same day overwrites that day's tab.
+pub async fn write_reviewer_snapshot(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course_name: &str,
+    reviewers: &[ReviewerActivity],
+    date: NaiveDate,
+) -> Result<String, Error> {
+    let title = format!("{}{}", reviewer_snapshot_title_prefix(course_name), date);
+    let existing_titles = sheets_client.sheet_titles(snapshot_sheet_id).await?;
+    if !existing_titles.contains(&title) {
+        sheets_client.add_sheet(snapshot_sheet_id, &title).await?;
+    }
+    let mut rows = vec![vec![
+        "GitHub".to_owned(),
+        "Last review".to_owned(),
+        "Review days in last 28 days".to_owned(),
+    ]];
+    rows.extend(reviewers.iter().map(|reviewer| {
+        vec![
+            reviewer.login.to_string(),
+            reviewer.last_review.to_rfc3339(),
+            reviewer.reviews_days_in_last_28_days.to_string(),
+        ]
+    }));
+    sheets_client
+        .write_rows(snapshot_sheet_id, &format!("'{}'", title), rows)
+        .await?;
+    Ok(title)
+}
+
+/// The most recent reviewer snapshot for the course taken before `before`, if there is one.
+pub async fn latest_reviewer_snapshot(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course_name: &str,
+    before: NaiveDate,
+) -> Result<Option<(NaiveDate, Vec<ReviewerActivity>)>, Error> {
+    let prefix = reviewer_snapshot_title_prefix(course_name);
+    let latest = sheets_client
+        .sheet_titles(snapshot_sheet_id)
+        .await?
+        .into_iter()
+        .filter_map(|title| {
+            let date = title.strip_prefix(&prefix)?.parse::<NaiveDate>().ok()?;
+            (date < before).then_some((date, title))
+        })
+        .max();
+    let Some((date, title)) = latest else {
+        return Ok(None);
+    };
+    let rows = sheets_client
+        .read_rows(snapshot_sheet_id, &format!("'{}'", title))
+        .await?;
+    let reviewers = rows
+        .into_iter()
+        .skip(1)
+        .map(|row| {
+            let [login, last_review, reviews_days_in_last_28_days] = row.as_slice() else {
+                return Err(anyhow::anyhow!("Expected 3 columns, got {}", row.len()));
+            };
+            Ok(ReviewerActivity {
+                login: GithubLogin::from(login.clone()),
+                last_review: DateTime::parse_from_rfc3339(last_review)
+                    .context("Failed to parse last review time")?
+                    .to_utc(),
+                reviews_days_in_last_28_days: reviews_days_in_last_28_days
+                    .parse()
+                    .context("Failed to parse review days")?,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .with_context(|| format!("Failed to read reviewer snapshot {}", title))?;
+    Ok(Some((date, reviewers)))
+}
+
+fn burndown_title(course_name: &str, batch_github_slug: &str) -> String {
+    format!("{} {} burndown", course_name, batch_github_slug)
+}
+
+const BURNDOWN_HEADINGS: [&str; 7] = [
+    "Date", "Module", "Sprint", "Expected", "Received", "Reviewed", "Complete",
+];
+
+/// Records the day's burndown counts in the batch's burndown tab of the snapshot sheet.
+/// Unlike the dated matrix tabs, this is one small tab per batch with a row per day, so it can cheaply be run daily and read back for the batch page.
+/// Re-running on the same day replaces that day's row.
+pub async fn write_burndown_point(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course_name: &str,
+    batch_github_slug: &str,
+    point: &BurndownPoint,
+) -> Result<String, Error> {
+    let title = burndown_title(course_name, batch_github_slug);
+    let range = format!("'{}'", title);
+    let mut rows = if sheets_client
+        .sheet_titles(snapshot_sheet_id)
+        .await?
+        .contains(&title)
+    {
+        sheets_client.read_rows(snapshot_sheet_id, &range).await?
+    } else {
+        sheets_client.add_sheet(snapshot_sheet_id, &title).await?;
+        Vec::new()
+    };
+    if rows.is_empty() {
+        rows.push(BURNDOWN_HEADINGS.map(str::to_owned).to_vec());
+    }
+    let date = point.date.to_string();
+    rows.retain(|row| row.first() != Some(&date));
+    rows.push(vec![
+        date,
+        point.module_name.clone(),
+        point.sprint_number.to_string(),
+        point.expected.to_string(),
+        point.received.to_string(),
+        point.reviewed.to_string(),
+        point.complete.to_string(),
+    ]);
+    sheets_client
+        .write_rows(snapshot_sheet_id, &range, rows)
+        .await?;
+    Ok(title)
+}
+
+/// Reads back the recorded burndown for one sprint of a batch.
+/// Returns None if the batch has no burndown tab (e.g. because the snapshot job hasn't run for it yet).
+pub async fn read_sprint_burndown(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course_name: &str,
+    batch_github_slug: &str,
+    (module_name, sprint_number, start): (String, usize, NaiveDate),
+) -> Result<Option<SprintBurndown>, Error> {
+    let title = burndown_title(course_name, batch_github_slug);
+    if !sheets_client
+        .sheet_titles(snapshot_sheet_id)
+        .await?
+        .contains(&title)
+    {
+        return Ok(None);
+    }
+    let rows = sheets_client
+        .read_rows(snapshot_sheet_id, &format!("'{}'", title))
+        .await?;
+    let mut points = rows
+        .into_iter()
+        .skip(1)
+        .map(|row| {
+            let [
+                date,
+                module_name,
+                sprint_number,
+                expected,
+                received,
+                reviewed,
+                complete,
+            ] = row.as_slice()
+            else {
+                return Err(anyhow::anyhow!("Expected 7 columns, got {}", row.len()));
+            };
+            Ok(BurndownPoint {
+                date: date.parse().context("Failed to parse date")?,
+                module_name: module_name.clone(),
+                sprint_number: sprint_number.parse().context("Failed to parse sprint")?,
+                expected: expected.parse().context("Failed to parse expected count")?,
+                received: received.parse().context("Failed to parse received count")?,
+                reviewed: reviewed.parse().context("Failed to parse reviewed count")?,
+                complete: complete.parse().context("Failed to parse complete count")?,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .with_context(|| format!("Failed to read burndown {}", title))?;
+    points.retain(|point| point.module_name == module_name && point.sprint_number == sprint_number);
+    points.sort_by_key(|point| point.date);
+    Ok(Some(SprintBurndown {
+        module_name,
+        sprint_number,
+        start,
+        points,
+    }))
+}
+
+fn funnel_title(course_name: &str, batch_github_slug: &str) -> String {
+    format!("{} {} funnel", course_name, batch_github_slug)
+}
+
+const FUNNEL_HEADINGS: [&str; 6] = [
+    "Date",
+    "Module",
+    "Trainees",
+    "Started",
+    "Active",
+    "Completed",
+];
+
+/// Records the day's funnel counts in the batch's funnel tab of the snapshot sheet, with a row per module.
+/// Re-running on the same day replaces that day's rows.
+pub async fn write_funnel_points(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course_name: &str,
+    batch_github_slug: &str,
+    date: NaiveDate,
+    points: &[FunnelPoint],
+) -> Result<String, Error> {
+    let title = funnel_title(course_name, batch_github_slug);
+    let range = format!("'{}'", title);
+    let mut rows = if sheets_client
+        .sheet_titles(snapshot_sheet_id)
+        .await?
+        .contains(&title)
+    {
+        sheets_client.read_rows(snapshot_sheet_id, &range).await?
+    } else {
+        sheets_client.add_sheet(snapshot_sheet_id, &title).await?;
+        Vec::new()
+    };
+    if rows.is_empty() {
+        rows.push(FUNNEL_HEADINGS.map(str::to_owned).to_vec());
+    }
+    let date = date.to_string();
+    rows.retain(|row| row.first() != Some(&date));
+    rows.extend(points.iter().map(|point| {
+        vec![
+            point.date.to_string(),
+            point.module_name.clone(),
+            point.trainees.to_string(),
+            point.started.to_string(),
+            point.active.to_string(),
+            point.completed.to_string(),
+        ]
+    }));
+    sheets_client
+        .write_rows(snapshot_sheet_id, &range, rows)
+        .await?;
+    Ok(title)
+}
+
+/// Reads back every recorded funnel point for a batch, oldest first.
+/// Returns None if the batch has no funnel tab (e.g. because the snapshot job hasn't run for it yet).
+pub async fn read_funnel(
+    sheets_client: &SheetsClient,
+    snapshot_sheet_id: &str,
+    course_name: &str,
+    batch_github_slug: &str,
+) -> Result<Option<Vec<FunnelPoint>>, Error> {
+    let title = funnel_title(course_name, batch_github_slug);
+    if !sheets_client
+        .sheet_titles(snapshot_sheet_id)
+        .await?
+        .contains(&title)
+    {
+        return Ok(None);
+    }
+    let rows = sheets_client
+        .read_rows(snapshot_sheet_id, &format!("'{}'", title))
+        .await?;
+    let mut points = rows
+        .into_iter()
+        .skip(1)
+        .map(|row| {
+            let [date, module_name, trainees, started, active, completed] = row.as_slice() else {
+                return Err(anyhow::anyhow!("Expected 6 columns, got {}", row.len()));
+            };
+            Ok(FunnelPoint {
+                date: date.parse().context("Failed to parse date")?,
+                module_name: module_name.clone(),
+                trainees: trainees.parse().context("Failed to parse trainee count")?,
+                started: started.parse().context("Failed to parse started count")?,
+                active: active.parse().context("Failed to parse active count")?,
+                completed: completed
+                    .parse()
+                    .context("Failed to parse completed count")?,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .with_context(|| format!("Failed to read funnel {}", title))?;
+    // Stable, so modules stay in course order within each day.
+    points.sort_by_key(|point| point.date);
+    Ok(Some(points))
+}
+
+/// One row per trainee, one column per assignment, in course order.
+pub fn submission_matrix(course: &Course, batch: &Batch) -> Vec<Vec<String>> {
+    let mut headings = vec![
+        "Name".to_owned(),
+        "GitHub".to_owned(),
+        "Region".to_owned(),
+        "Status".to_owned(),
+        "Progress".to_owned(),
+    ];
+    for (module_name, module) in &course.modules {
+        for (sprint_index, sprint) in module.sprints.iter().enumerate() {
+            for assignment in &sprint.assignments {
+                let assignment_name = match assignment {
+                    Assignment::Attendance { .. } => "Attendance",
+                    Assignment::ExpectedPullRequest { title, .. }
+                    | Assignment::ProjectRepo { title, .. } => title,
+                };
+                headings.push(format!(
+                    "{} sprint {}: {}",
+                    module_name,
+                    sprint_index + 1,
+                    assignment_name
+                ));
+            }
+        }
+    }
+
+    let mut rows = vec![headings];
+    for trainee in &batch.trainees {
+        let mut row = vec![
+            trainee.trainee.name.clone(),
+            trainee.trainee.github_login.to_string(),
+            trainee.trainee.region.to_string(),
+            trainee.status().display_text().to_owned(),
+            format!("{}%", trainee.progress_score() / 100),
+        ];
+        for module in trainee.modules.values() {
+            for sprint in &module.sprints {
+                row.extend(sprint.submissions.iter().map(submission_cell));
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+fn submission_cell(submission: &SubmissionState) -> String {
+    match submission {
+        SubmissionState::Some(Submission::PullRequest { pull_request, .. }) => {
+            let state = match pull_request.state {
+                PrState::NeedsReview => "Needs review",
+                PrState::Reviewed => "Reviewed",
+                PrState::Complete => "Complete",
+                PrState::Unknown => "Unknown",
+            };
+            format!("#{} {}", pull_request.number, state)
+        }
+        SubmissionState::Some(submission @ Submission::Attendance(_)) => submission.display_text(),
+        SubmissionState::Some(submission @ Submission::ProjectRepo(project_repo)) => format!(
+            "{}/{} ({})",
+            project_repo.owner,
+            project_repo.repo,
+            submission.display_text()
+        ),
+        SubmissionState::MissingButExpected(_) => "Missing".to_owned(),
+        SubmissionState::MissingStretch(_) => "Missing (stretch)".to_owned(),
+        SubmissionState::MissingButNotExpected(_) => String::new(),
+    }
+}
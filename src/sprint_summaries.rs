@@ -0,0 +1,224 @@
+use anyhow::Context;
+use chrono::{Days, NaiveDate, TimeDelta};
+use futures::future::join_all;
+use octocrab::Octocrab;
+use octocrab::params::State;
+
+use crate::{
+    Error,
+    course::{Assignment, Batch, Course, ONBOARDING_MODULE_NAME, Submission, SubmissionState},
+    octocrab::all_pages,
+    prs::{BallInCourt, PrState, get_ball_in_court, get_review_metrics},
+};
+
+/// Classes are weekly, so a sprint ends the day before the following week's class.
+const SPRINT_LENGTH: Days = Days::new(6);
+
+/// How a batch did on one sprint of one module, posted as an issue in the module repo when the sprint ends.
+pub struct SprintSummary {
+    pub course_name: String,
+    pub batch_name: String,
+    pub module_name: String,
+    pub sprint_number: usize,
+    pub trainee_count: usize,
+    pub expected_pull_requests: usize,
+    pub submitted_pull_requests: usize,
+    pub awaiting_review: usize,
+    /// Open PRs which have had changes requested, and the trainee hasn't responded since.
+    pub awaiting_trainee: usize,
+    /// Open PRs where the trainee has responded to requested changes, and needs a reviewer to look again.
+    pub awaiting_re_review: usize,
+    /// Mean time from a PR being marked as needing review to its first review, for PRs which have been reviewed.
+    pub average_review_latency: Option<TimeDelta>,
+}
+
+/// The (module name, sprint index) of each sprint which ends on `date` in its latest region.
+pub fn sprints_ending_on(course: &Course, date: NaiveDate) -> Vec<(String, usize)> {
+    let mut sprints = Vec::new();
+    for (module_name, module) in &course.modules {
+        // There's no repo to post induction summaries in.
+        if module_name == ONBOARDING_MODULE_NAME {
+            continue;
+        }
+        for (sprint_index, sprint) in module.sprints.iter().enumerate() {
+            let end = sprint
+                .dates
+                .values()
+                .max()
+                .and_then(|last_class| last_class.checked_add_days(SPRINT_LENGTH));
+            if end == Some(date) {
+                sprints.push((module_name.clone(), sprint_index));
+            }
+        }
+    }
+    sprints
+}
+
+pub async fn summarise_sprint(
+    octocrab: &Octocrab,
+    github_org: &str,
+    course: &Course,
+    batch: &Batch,
+    module_name: &str,
+    sprint_index: usize,
+) -> Result<SprintSummary, Error> {
+    let mut expected_pull_requests = 0;
+    let mut pull_requests = Vec::new();
+    for trainee in &batch.trainees {
+        let Some(sprint) = trainee
+            .modules
+            .get(module_name)
+            .and_then(|module| module.sprints.get(sprint_index))
+        else {
+            continue;
+        };
+        for submission in &sprint.submissions {
+            match submission {
+                SubmissionState::Some(Submission::PullRequest { pull_request, .. }) => {
+                    expected_pull_requests += 1;
+                    pull_requests.push(pull_request.clone());
+                }
+                SubmissionState::MissingButExpected(assignment)
+                | SubmissionState::MissingButNotExpected(assignment)
+                    if matches!(assignment, Assignment::ExpectedPullRequest { .. }) =>
+                {
+                    expected_pull_requests += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let awaiting_review = pull_requests
+        .iter()
+        .filter(|pr| pr.state == PrState::NeedsReview)
+        .count();
+    let submitted_pull_requests = pull_requests.len();
+
+    let balls_in_court = join_all(
+        pull_requests
+            .iter()
+            .map(|pr| get_ball_in_court(octocrab, github_org, pr)),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    let awaiting_trainee = balls_in_court
+        .iter()
+        .filter(|ball_in_court| **ball_in_court == Some(BallInCourt::Trainee))
+        .count();
+    let awaiting_re_review = balls_in_court
+        .iter()
+        .filter(|ball_in_court| **ball_in_court == Some(BallInCourt::Reviewer))
+        .count();
+
+    let metrics = join_all(
+        pull_requests
+            .into_iter()
+            .map(|pr| get_review_metrics(octocrab, github_org, pr)),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    let latencies = metrics
+        .iter()
+        .filter_map(|metric| {
+            let first_review = match (metric.first_reviewed, metric.first_complete) {
+                (Some(reviewed), Some(complete)) => reviewed.min(complete),
+                (Some(reviewed), None) => reviewed,
+                (None, Some(complete)) => complete,
+                (None, None) => return None,
+            };
+            Some(first_review - metric.first_needs_review.unwrap_or(metric.created_at))
+        })
+        .collect::<Vec<_>>();
+    let average_review_latency = if latencies.is_empty() {
+        None
+    } else {
+        Some(latencies.iter().sum::<TimeDelta>() / latencies.len() as i32)
+    };
+
+    Ok(SprintSummary {
+        course_name: course.name.clone(),
+        batch_name: batch.name.clone(),
+        module_name: module_name.to_owned(),
+        sprint_number: sprint_index + 1,
+        trainee_count: batch.trainees.len(),
+        expected_pull_requests,
+        submitted_pull_requests,
+        awaiting_review,
+        awaiting_trainee,
+        awaiting_re_review,
+        average_review_latency,
+    })
+}
+
+impl SprintSummary {
+    pub fn title(&self) -> String {
+        format!(
+            "{} sprint {} summary for {}",
+            self.module_name, self.sprint_number, self.batch_name
+        )
+    }
+
+    pub fn body(&self, github_org: &str) -> String {
+        let average_review_latency = match self.average_review_latency {
+            Some(latency) if latency.num_hours() >= 48 => format!("{} days", latency.num_days()),
+            Some(latency) => format!("{} hours", latency.num_hours()),
+            None => "No PRs reviewed yet".to_owned(),
+        };
+        format!(
+            "Sprint {} of {} has ended for {} ({} trainees).\n\n\
+             * PRs submitted: {} of {} expected\n\
+             * PRs awaiting review: {}\n\
+             * PRs with changes requested, waiting on the trainee: {}\n\
+             * PRs where the trainee has responded to changes, waiting on a reviewer: {}\n\
+             * Average time to first review: {}\n\n\
+             @{}/{}-mentors - PRs awaiting review are listed [here](https://github.com/{}/{}/pulls?q=is%3Apr+is%3Aopen+label%3A%22Needs+Review%22).",
+            self.sprint_number,
+            self.module_name,
+            self.batch_name,
+            self.trainee_count,
+            self.submitted_pull_requests,
+            self.expected_pull_requests,
+            self.awaiting_review,
+            self.awaiting_trainee,
+            self.awaiting_re_review,
+            average_review_latency,
+            github_org,
+            self.course_name,
+            github_org,
+            self.module_name,
+        )
+    }
+}
+
+/// Posts the summary as an issue in the module repo, unless it has already been posted.
+/// Returns the URL of the new issue.
+pub async fn post_sprint_summary(
+    octocrab: &Octocrab,
+    github_org: &str,
+    summary: &SprintSummary,
+) -> Result<Option<String>, Error> {
+    let title = summary.title();
+    let existing_issues = all_pages("issues", octocrab, async || {
+        octocrab
+            .issues(github_org, &summary.module_name)
+            .list()
+            .state(State::All)
+            .send()
+            .await
+    })
+    .await?;
+    if existing_issues.iter().any(|issue| issue.title == title) {
+        return Ok(None);
+    }
+    let issue = octocrab
+        .issues(github_org, &summary.module_name)
+        .create(title)
+        .body(summary.body(github_org))
+        .send()
+        .await
+        .context("Failed to create sprint summary issue")?;
+    Ok(Some(issue.html_url.to_string()))
+}
@@ -0,0 +1,68 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+
+use crate::{Error, newtypes::GithubLogin, persistence::RecordStore};
+
+const NOTES_TAB: &str = "Staff notes";
+const NOTES_HEADINGS: [&str; 4] = ["Trainee", "Author", "Recorded", "Note"];
+
+/// A quick observation about a trainee, which doesn't warrant a full mentoring record.
+#[derive(Clone, Debug)]
+pub struct StaffNote {
+    pub trainee: GithubLogin,
+    pub author: GithubLogin,
+    pub recorded_at: DateTime<Utc>,
+    pub text: String,
+}
+
+pub async fn add_staff_note(store: &RecordStore, note: &StaffNote) -> Result<(), Error> {
+    if note.text.trim().is_empty() {
+        return Err(Error::UserFacing("Notes can't be empty".to_owned()));
+    }
+    store
+        .append(
+            NOTES_TAB,
+            &NOTES_HEADINGS,
+            vec![
+                note.trainee.to_string(),
+                note.author.to_string(),
+                note.recorded_at.to_rfc3339(),
+                note.text.clone(),
+            ],
+        )
+        .await
+}
+
+/// Notes about the trainee, newest first.
+pub async fn get_staff_notes(
+    store: &RecordStore,
+    trainee: &GithubLogin,
+) -> Result<Vec<StaffNote>, Error> {
+    let mut notes = get_all_staff_notes(store).await?;
+    notes.retain(|note| note.trainee == *trainee);
+    notes.reverse();
+    Ok(notes)
+}
+
+/// Every note about any trainee, oldest first.
+pub async fn get_all_staff_notes(store: &RecordStore) -> Result<Vec<StaffNote>, Error> {
+    Ok(store
+        .read(NOTES_TAB)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let [trainee, author, recorded_at, text] = row.as_slice() else {
+                return Err(anyhow::anyhow!("Expected 4 columns, got {}", row.len()));
+            };
+            Ok(StaffNote {
+                trainee: GithubLogin::from(trainee.clone()),
+                author: GithubLogin::from(author.clone()),
+                recorded_at: DateTime::parse_from_rfc3339(recorded_at)
+                    .context("Failed to parse recorded time")?
+                    .to_utc(),
+                text: text.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .context("Failed to read staff notes")?)
+}
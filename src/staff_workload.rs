@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use http::HeaderMap;
+use octocrab::{Octocrab, models::Author};
+use tower_sessions::Session;
+
+use crate::{
+    Config, Error, ServerState,
+    assessments::REINVITE_AUDIT_ACTION,
+    audit_log::count_audit_actions_since,
+    github_accounts::get_trainees,
+    mentoring::get_mentoring_sessions,
+    newtypes::GithubLogin,
+    octocrab::{all_pages, octocrab},
+    persistence::RecordStore,
+    prs::get_reviewers,
+    register::normalise_name,
+    reviewer_staff_info::get_reviewer_staff_info_if_permitted,
+    sheets::{SheetsClient, sheets_client},
+    staff_notes::get_all_staff_notes,
+};
+
+/// How far back the dashboard counts.
+pub const WORKLOAD_WINDOW: TimeDelta = TimeDelta::days(28);
+
+/// What one member of staff has done recently, across the places it gets recorded.
+#[derive(Clone, Debug, Default)]
+pub struct StaffWorkload {
+    /// Mentoring records only have names, so staff whose names we can't match to a GitHub login are listed by name alone.
+    pub github_login: Option<GithubLogin>,
+    pub name: Option<String>,
+    /// Logged in the mentoring records sheet.
+    pub mentoring_sessions: usize,
+    /// Distinct PRs reviewed in the course's modules.
+    pub prs_reviewed: usize,
+    /// Staff notes written about trainees, which is where interventions get recorded.
+    pub interventions: usize,
+    /// Trainees re-invited to timed assessments.
+    pub nudges: usize,
+}
+
+impl StaffWorkload {
+    pub fn total(&self) -> usize {
+        self.mentoring_sessions + self.prs_reviewed + self.interventions + self.nudges
+    }
+}
+
+/// The course's mentors' workload over the last WORKLOAD_WINDOW, busiest first.
+///
+/// Also returns which sources couldn't be counted (e.g. because there's no record store configured), so gaps aren't mistaken for idleness.
+pub async fn staff_workload(
+    octocrab: &Octocrab,
+    sheets_client: SheetsClient,
+    config: &Config,
+    record_store: Option<&RecordStore>,
+    course: &str,
+    now: DateTime<Utc>,
+) -> Result<(Vec<StaffWorkload>, Vec<String>), Error> {
+    let since = now - WORKLOAD_WINDOW;
+    let module_names = config
+        .get_course_module_names(course)
+        .ok_or_else(|| Error::UserFacing(format!("Unknown course {}", course)))?;
+    let mut unavailable = Vec::new();
+
+    let mentors_team = format!("{}-mentors", course);
+    let mentors = all_pages("mentors team members", octocrab, async || {
+        octocrab
+            .teams(&config.github_org)
+            .members(&mentors_team)
+            .send()
+            .await
+    })
+    .await?;
+    let mut workloads: BTreeMap<GithubLogin, StaffWorkload> = mentors
+        .into_iter()
+        .map(|Author { login, .. }| {
+            let login = GithubLogin::from(login);
+            (
+                login.clone(),
+                StaffWorkload {
+                    github_login: Some(login),
+                    ..StaffWorkload::default()
+                },
+            )
+        })
+        .collect();
+
+    // Names come from the staff info sheet where we can read it, falling back to the GitHub email mapping.
+    let mut names: BTreeMap<GithubLogin, String> =
+        get_trainees(sheets_client.clone(), &config.github_email_mapping_sheet_id)
+            .await?
+            .into_iter()
+            .map(|(login, account)| (login, account.name))
+            .collect();
+    if let Some(staff_details) = get_reviewer_staff_info_if_permitted(
+        sheets_client.clone(),
+        &config.reviewer_staff_info_sheet_id,
+    )
+    .await?
+    {
+        names.extend(
+            staff_details
+                .into_iter()
+                .map(|(login, details)| (login, details.name)),
+        );
+    }
+
+    for reviewer in get_reviewers(
+        octocrab.clone(),
+        &sheets_client.server_state().in_flight,
+        &config.github_org,
+        &module_names,
+    )
+    .await?
+    {
+        // Trainees review each other's PRs too, but that isn't staff workload.
+        if let Some(workload) = workloads.get_mut(&reviewer.login) {
+            workload.prs_reviewed = reviewer
+                .prs
+                .iter()
+                .filter(|reviewed| reviewed.latest_review_time >= since)
+                .count();
+        }
+    }
+
+    let logins_by_name: BTreeMap<String, GithubLogin> = workloads
+        .keys()
+        .filter_map(|login| Some((normalise_name(names.get(login)?), login.clone())))
+        .collect();
+    let mut unmatched: BTreeMap<String, StaffWorkload> = BTreeMap::new();
+    for session in get_mentoring_sessions(sheets_client, &config.mentoring_records_sheet_id).await?
+    {
+        if session.date < since.date_naive() || session.staff.is_empty() {
+            continue;
+        }
+        let normalised = normalise_name(&session.staff);
+        let workload = match logins_by_name.get(&normalised) {
+            // UNWRAP: logins_by_name is built from workloads' keys.
+            Some(login) => workloads.get_mut(login).unwrap(),
+            None => unmatched
+                .entry(normalised)
+                .or_insert_with(|| StaffWorkload {
+                    name: Some(session.staff.clone()),
+                    ..StaffWorkload::default()
+                }),
+        };
+        workload.mentoring_sessions += 1;
+    }
+
+    match record_store {
+        Some(store) => {
+            for note in get_all_staff_notes(store).await? {
+                if note.recorded_at >= since
+                    && let Some(workload) = workloads.get_mut(&note.author)
+                {
+                    workload.interventions += 1;
+                }
+            }
+            for (actor, count) in count_audit_actions_since(store, REINVITE_AUDIT_ACTION, since).await?
+            {
+                if let Some(workload) = workloads.get_mut(&actor) {
+                    workload.nudges = count;
+                }
+            }
+        }
+        None => unavailable.push(
+            "Interventions and nudges aren't counted, as the tracker isn't configured to store records."
+                .to_owned(),
+        ),
+    }
+
+    let mut workloads = workloads
+        .into_values()
+        .map(|mut workload| {
+            workload.name = workload
+                .github_login
+                .as_ref()
+                .and_then(|login| names.get(login).cloned());
+            workload
+        })
+        .chain(unmatched.into_values())
+        .collect::<Vec<_>>();
+    workloads.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.name.cmp(&b.name)));
+    Ok((workloads, unavailable))
+}
+
+/// Who on the course's staff has been doing what recently, so team leads can spread the load.
+pub async fn get_staff_workload(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path(course): Path<String>,
+) -> Result<Html<String>, Error> {
+    let sheets_client = sheets_client(
+        &session,
+        server_state.clone(),
+        headers,
+        original_uri.clone(),
+    )
+    .await?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    let record_store = RecordStore::from_config(&server_state).await?;
+    let (workloads, unavailable) = staff_workload(
+        &octocrab,
+        sheets_client,
+        &server_state.config,
+        record_store.as_ref(),
+        &course,
+        Utc::now(),
+    )
+    .await?;
+    Ok(Html(
+        StaffWorkloadTemplate {
+            course,
+            workloads,
+            unavailable,
+            window_days: WORKLOAD_WINDOW.num_days(),
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "staff-workload.html")]
+struct StaffWorkloadTemplate {
+    course: String,
+    workloads: Vec<StaffWorkload>,
+    unavailable: Vec<String>,
+    window_days: i64,
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::Serialize;
+
+use crate::{Error, ServerState};
+
+/// Set at build time by GitHub Actions, which builds our releases.
+pub const GIT_COMMIT: Option<&str> = option_env!("GITHUB_SHA");
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const UPSTREAM_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// When we last tried to load a course's data from upstream, and whether it worked.
+///
+/// Only success or failure is recorded, because the status page is public and error messages may contain private data.
+#[derive(Clone, Debug, Serialize)]
+pub struct RefreshStatus {
+    pub last_attempt: DateTime<Utc>,
+    pub last_attempt_succeeded: bool,
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+pub(crate) async fn record_refresh<T>(
+    server_state: &ServerState,
+    course: &str,
+    result: &Result<T, Error>,
+) {
+    let succeeded = match result {
+        Ok(_) => true,
+        // The user needs to log in - that says nothing about whether we can load data.
+        Err(Error::Redirect(_)) => return,
+        Err(_) => false,
+    };
+    let now = Utc::now();
+    let previous_success = server_state
+        .course_refreshes
+        .get(course)
+        .await
+        .and_then(|status| status.last_success);
+    server_state
+        .course_refreshes
+        .insert(
+            course.to_owned(),
+            RefreshStatus {
+                last_attempt: now,
+                last_attempt_succeeded: succeeded,
+                last_success: if succeeded {
+                    Some(now)
+                } else {
+                    previous_success
+                },
+            },
+        )
+        .await;
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UpstreamHealth {
+    pub name: &'static str,
+    pub healthy: bool,
+    pub detail: String,
+}
+
+/// Checks whether each API we depend on is reachable.
+///
+/// These checks are unauthenticated, so they tell us whether the service is up, not whether our credentials work.
+pub async fn check_upstreams(client: &reqwest::Client) -> Vec<UpstreamHealth> {
+    let checks = [
+        ("GitHub", "https://www.githubstatus.com/api/v2/status.json"),
+        (
+            "Google Sheets",
+            "https://sheets.googleapis.com/$discovery/rest?version=v4",
+        ),
+        ("Slack", "https://slack.com/api/api.test"),
+    ];
+    join_all(checks.into_iter().map(|(name, url)| async move {
+        match client.get(url).timeout(UPSTREAM_CHECK_TIMEOUT).send().await {
+            Ok(response) if response.status().is_success() => {
+                let detail = if name == "GitHub" {
+                    github_status_description(response).await
+                } else {
+                    "Reachable".to_owned()
+                };
+                UpstreamHealth {
+                    name,
+                    healthy: true,
+                    detail,
+                }
+            }
+            Ok(response) => UpstreamHealth {
+                name,
+                healthy: false,
+                detail: format!("Responded with status {}", response.status()),
+            },
+            Err(err) => UpstreamHealth {
+                name,
+                healthy: false,
+                detail: if err.is_timeout() {
+                    "Timed out".to_owned()
+                } else {
+                    "Unreachable".to_owned()
+                },
+            },
+        }
+    }))
+    .await
+}
+
+// GitHub's status page can report partial outages (e.g. just Actions) while still responding.
+async fn github_status_description(response: reqwest::Response) -> String {
+    #[derive(serde::Deserialize)]
+    struct StatusPage {
+        status: StatusPageStatus,
+    }
+    #[derive(serde::Deserialize)]
+    struct StatusPageStatus {
+        description: String,
+    }
+    match response.json::<StatusPage>().await {
+        Ok(status_page) => status_page.status.description,
+        Err(_) => "Reachable".to_owned(),
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+
+use askama::Template;
+use axum::{
+    extract::{OriginalUri, Path, State},
+    response::Html,
+};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    config::CourseSchedule,
+    course::{Assignment, AssignmentOptionality, Course, ONBOARDING_MODULE_NAME},
+    newtypes::Region,
+    octocrab::octocrab,
+    review_sla::week_starting,
+};
+
+/// A period when trainees in the batch sit a timed assessment, e.g. the end of module test.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AssessmentWindow {
+    pub name: String,
+    pub start: NaiveDate,
+    /// Inclusive.
+    pub end: NaiveDate,
+}
+
+impl AssessmentWindow {
+    pub fn is_open(&self, today: NaiveDate) -> bool {
+        self.start <= today && today <= self.end
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TimelineSprint {
+    /// 1-based, as in sprint labels.
+    pub number: usize,
+    /// Each region's class, after any deadline extensions. Regions with no class that sprint are left out.
+    pub class_dates: BTreeMap<Region, NaiveDate>,
+    pub mandatory_assignments: usize,
+    pub stretch_assignments: usize,
+}
+
+impl TimelineSprint {
+    /// Whether any region has a class for this sprint in the week (Monday to Sunday) containing today.
+    pub fn is_this_week(&self, today: NaiveDate) -> bool {
+        let week_start = week_starting(today);
+        let week_end = week_start + Days::new(6);
+        self.class_dates
+            .values()
+            .any(|date| week_start <= *date && *date <= week_end)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TimelineModule {
+    pub name: String,
+    pub sprints: Vec<TimelineSprint>,
+}
+
+impl TimelineModule {
+    /// The first class of the module in any region.
+    pub fn start(&self) -> Option<NaiveDate> {
+        self.sprints
+            .iter()
+            .flat_map(|sprint| sprint.class_dates.values())
+            .min()
+            .copied()
+    }
+
+    /// The last class of the module in any region.
+    pub fn end(&self) -> Option<NaiveDate> {
+        self.sprints
+            .iter()
+            .flat_map(|sprint| sprint.class_dates.values())
+            .max()
+            .copied()
+    }
+
+    pub fn is_current(&self, today: NaiveDate) -> bool {
+        self.start().is_some_and(|start| start <= today)
+            && self.end().is_some_and(|end| today <= end)
+    }
+}
+
+/// The key dates of a batch, in order, for volunteers getting to know where a batch is up to.
+pub struct BatchTimeline {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Induction (if the batch has any) first, then each module in course order.
+    pub modules: Vec<TimelineModule>,
+    pub assessment_windows: Vec<AssessmentWindow>,
+    pub today: NaiveDate,
+}
+
+impl BatchTimeline {
+    pub fn new(schedule: &CourseSchedule, course: &Course, today: NaiveDate) -> Self {
+        let induction = (!schedule.onboarding_sessions.is_empty()).then(|| TimelineModule {
+            name: ONBOARDING_MODULE_NAME.to_owned(),
+            sprints: schedule
+                .onboarding_sessions
+                .iter()
+                .enumerate()
+                .map(|(sprint_index, dates)| TimelineSprint {
+                    number: sprint_index + 1,
+                    class_dates: dates.clone(),
+                    mandatory_assignments: 0,
+                    stretch_assignments: 0,
+                })
+                .collect(),
+        });
+        let modules = schedule.sprints.iter().map(|(module_name, sprints)| {
+            let course_sprints = course
+                .modules
+                .get(module_name)
+                .map(|module| &module.sprints);
+            TimelineModule {
+                name: module_name.clone(),
+                sprints: (0..sprints.len())
+                    .map(|sprint_index| {
+                        let assignments = course_sprints
+                            .and_then(|sprints| sprints.get(sprint_index))
+                            .map(|sprint| sprint.assignments.as_slice())
+                            .unwrap_or_default();
+                        let count = |optionality: AssignmentOptionality| {
+                            assignments
+                                .iter()
+                                .filter(|assignment| {
+                                    !matches!(assignment, Assignment::Attendance { .. })
+                                        && assignment.optionality() == optionality
+                                })
+                                .count()
+                        };
+                        TimelineSprint {
+                            number: sprint_index + 1,
+                            class_dates: schedule.class_dates(module_name, sprint_index),
+                            mandatory_assignments: count(AssignmentOptionality::Mandatory),
+                            stretch_assignments: count(AssignmentOptionality::Stretch),
+                        }
+                    })
+                    .collect(),
+            }
+        });
+        let mut assessment_windows = schedule.assessment_windows.clone();
+        assessment_windows.sort_by_key(|window| window.start);
+        BatchTimeline {
+            start: schedule.start,
+            end: schedule.end,
+            modules: induction.into_iter().chain(modules).collect(),
+            assessment_windows,
+            today,
+        }
+    }
+
+    /// Sprints with a class this week, with the name of their module.
+    pub fn this_week(&self) -> Vec<(&str, &TimelineSprint)> {
+        self.modules
+            .iter()
+            .flat_map(|module| {
+                module
+                    .sprints
+                    .iter()
+                    .filter(|sprint| sprint.is_this_week(self.today))
+                    .map(|sprint| (module.name.as_str(), sprint))
+            })
+            .collect()
+    }
+
+    pub fn open_assessment_windows(&self) -> Vec<&AssessmentWindow> {
+        self.assessment_windows
+            .iter()
+            .filter(|window| window.is_open(self.today))
+            .collect()
+    }
+}
+
+pub async fn get_batch_timeline(
+    session: Session,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+    Path((course, batch_github_slug)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    let course_schedule = server_state
+        .config
+        .get_course_schedule_with_register_sheet_id(course.clone(), &batch_github_slug)
+        .ok_or_else(|| Error::UserFacing(format!("Unknown batch {course}/{batch_github_slug}")))?;
+    let octocrab = octocrab(&session, &server_state, original_uri).await?;
+    // Assignments only come from GitHub, so this is needed for the counts of what's due each sprint.
+    let course = course_schedule
+        .with_assignments(
+            &octocrab,
+            &server_state.in_flight,
+            &server_state.config.github_org,
+        )
+        .await?;
+    let timeline = BatchTimeline::new(
+        &course_schedule.course_schedule,
+        &course,
+        chrono::Utc::now().date_naive(),
+    );
+    Ok(Html(
+        BatchTimelineTemplate {
+            course_name: course.name,
+            batch_github_slug,
+            timeline,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "timeline.html")]
+struct BatchTimelineTemplate {
+    course_name: String,
+    batch_github_slug: String,
+    timeline: BatchTimeline,
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use maplit::btreemap;
+
+    use super::TimelineSprint;
+    use crate::newtypes::Region;
+
+    #[test]
+    fn sprint_is_this_week_in_any_region() {
+        let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+        let sprint = TimelineSprint {
+            number: 1,
+            class_dates: btreemap! {
+                Region("London".to_owned()) => date("2026-10-17"),
+                Region("Cape Town".to_owned()) => date("2026-10-19"),
+            },
+            mandatory_assignments: 3,
+            stretch_assignments: 1,
+        };
+        // Thursday, before London's Saturday class.
+        assert!(sprint.is_this_week(date("2026-10-15")));
+        // The Monday of Cape Town's class.
+        assert!(sprint.is_this_week(date("2026-10-19")));
+        assert!(!sprint.is_this_week(date("2026-10-11")));
+        assert!(!sprint.is_this_week(date("2026-10-26")));
+    }
+}
@@ -0,0 +1,84 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{OriginalUri, State},
+    response::Redirect,
+};
+use chrono::{DateTime, Utc};
+use http::HeaderMap;
+use moka::future::Cache;
+use tower_sessions::Session;
+
+use crate::{
+    Error, ServerState,
+    github_accounts::{Trainee, fetch_trainees},
+    impersonation::forbid_while_viewing_as,
+    newtypes::GithubLogin,
+    sheets::{SheetsClient, sheets_client},
+};
+
+pub const DEFAULT_TRAINEE_DIRECTORY_CACHE_MINUTES: u64 = 10;
+
+/// The trainees in each GitHub email mapping sheet, shared between everyone who reads it, so that the whole sheet isn't
+/// fetched and parsed for every page, region lookup, or report.
+#[derive(Clone)]
+pub struct TraineeDirectory {
+    /// Sheet ID -> when it was read, and its trainees.
+    sheets: Cache<String, (DateTime<Utc>, Arc<BTreeMap<GithubLogin, Trainee>>)>,
+}
+
+impl TraineeDirectory {
+    pub fn new(time_to_live: Duration) -> Self {
+        TraineeDirectory {
+            sheets: Cache::builder().time_to_live(time_to_live).build(),
+        }
+    }
+
+    pub async fn get(
+        &self,
+        client: SheetsClient,
+        sheet_id: &str,
+    ) -> Result<Arc<BTreeMap<GithubLogin, Trainee>>, Error> {
+        if let Some((_, trainees)) = self.sheets.get(sheet_id).await {
+            // Whoever filled the cache could read the sheet, but that says nothing about this client,
+            // so check it can too - which is much cheaper than reading the whole sheet again.
+            client.sheet_titles(sheet_id).await?;
+            return Ok(trainees);
+        }
+        let trainees = Arc::new(fetch_trainees(client, sheet_id).await?);
+        self.sheets
+            .insert(sheet_id.to_owned(), (Utc::now(), trainees.clone()))
+            .await;
+        Ok(trainees)
+    }
+
+    /// When the sheet was last read, if it's still cached.
+    pub async fn loaded_at(&self, sheet_id: &str) -> Option<DateTime<Utc>> {
+        self.sheets
+            .get(sheet_id)
+            .await
+            .map(|(loaded_at, _)| loaded_at)
+    }
+
+    /// Makes the next read of the sheet fetch it again, e.g. because someone has just been added to it.
+    pub async fn invalidate(&self, sheet_id: &str) {
+        self.sheets.invalidate(sheet_id).await;
+    }
+}
+
+/// Re-reads the GitHub email mapping sheet next time it's needed, rather than waiting for the cached copy to expire.
+pub async fn post_reload_trainee_directory(
+    session: Session,
+    headers: HeaderMap,
+    State(server_state): State<ServerState>,
+    OriginalUri(original_uri): OriginalUri,
+) -> Result<Redirect, Error> {
+    forbid_while_viewing_as(&session).await?;
+    let sheet_id = &server_state.config.github_email_mapping_sheet_id;
+    let sheets_client =
+        sheets_client(&session, server_state.clone(), headers, original_uri).await?;
+    // Only staff can read the sheet, so only they can make everyone wait for it to be read again.
+    sheets_client.sheet_titles(sheet_id).await?;
+    server_state.trainee_directory.invalidate(sheet_id).await;
+    Ok(Redirect::to("/status"))
+}
@@ -0,0 +1,214 @@
+//! Timeouts and circuit breakers for the APIs we depend on.
+//!
+//! Without these, a slow or broken upstream makes every page which uses it hang for minutes.
+//! Instead, each call is given a timeout, and after repeated failures we stop calling the upstream for a while,
+//! and tell users that it's down rather than making them wait.
+//!
+//! We don't fall back to serving previously cached data while an upstream is down: pages show an error saying which upstream is unavailable.
+//! Calls which are cached (e.g. the trainee directory) keep serving their cached value until it expires either way.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many failures in a row it takes to stop calling an upstream.
+const FAILURE_THRESHOLD: u32 = 5;
+/// How long to stop calling an upstream for, before letting a request through to see if it has recovered.
+const OPEN_DURATION: Duration = Duration::from_secs(30);
+
+pub static GITHUB: CircuitBreaker = CircuitBreaker::new("GitHub");
+pub static GOOGLE_SHEETS: CircuitBreaker = CircuitBreaker::new("Google Sheets");
+pub static GOOGLE_DIRECTORY: CircuitBreaker = CircuitBreaker::new("Google Groups");
+pub static SLACK: CircuitBreaker = CircuitBreaker::new("Slack");
+
+pub struct CircuitBreaker {
+    name: &'static str,
+    state: Mutex<BreakerState>,
+}
+
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct UpstreamUnavailable {
+    pub upstream: &'static str,
+    pub reason: UnavailableReason,
+}
+
+#[derive(Debug)]
+pub enum UnavailableReason {
+    TimedOut,
+    RepeatedFailures,
+}
+
+impl std::fmt::Display for UpstreamUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.reason {
+            UnavailableReason::TimedOut => write!(
+                f,
+                "{} took too long to respond - it may be having problems. Please try again in a few minutes.",
+                self.upstream
+            ),
+            UnavailableReason::RepeatedFailures => write!(
+                f,
+                "{} seems to be down - please try again in a few minutes.",
+                self.upstream
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UpstreamUnavailable {}
+
+impl CircuitBreaker {
+    pub const fn new(name: &'static str) -> CircuitBreaker {
+        CircuitBreaker {
+            name,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+            }),
+        }
+    }
+
+    /// Runs a call to this upstream with a timeout, unless the upstream has recently been failing.
+    ///
+    /// is_outage decides which errors mean the upstream is unhealthy - e.g. a permissions error is the caller's problem, not the upstream's.
+    pub async fn call<T, E>(
+        &self,
+        future: impl Future<Output = Result<T, E>>,
+        is_outage: impl FnOnce(&E) -> bool,
+    ) -> Result<Result<T, E>, UpstreamUnavailable> {
+        self.check()?;
+        match tokio::time::timeout(REQUEST_TIMEOUT, future).await {
+            Ok(Ok(value)) => {
+                self.record(true);
+                Ok(Ok(value))
+            }
+            Ok(Err(err)) => {
+                self.record(!is_outage(&err));
+                Ok(Err(err))
+            }
+            Err(_elapsed) => {
+                self.record(false);
+                Err(self.unavailable(UnavailableReason::TimedOut))
+            }
+        }
+    }
+
+    fn check(&self) -> Result<(), UpstreamUnavailable> {
+        // UNWRAP: We never panic while holding the lock.
+        let state = self.state.lock().unwrap();
+        match state.open_until {
+            Some(open_until) if Instant::now() < open_until => {
+                Err(self.unavailable(UnavailableReason::RepeatedFailures))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn record(&self, succeeded: bool) {
+        // UNWRAP: We never panic while holding the lock.
+        let mut state = self.state.lock().unwrap();
+        if succeeded {
+            state.consecutive_failures = 0;
+            state.open_until = None;
+        } else {
+            state.consecutive_failures += 1;
+            if state.consecutive_failures >= FAILURE_THRESHOLD {
+                warn!(
+                    "{} failed {} times in a row - not calling it for {:?}",
+                    self.name, state.consecutive_failures, OPEN_DURATION
+                );
+                state.open_until = Some(Instant::now() + OPEN_DURATION);
+            }
+        }
+    }
+
+    fn unavailable(&self, reason: UnavailableReason) -> UpstreamUnavailable {
+        UpstreamUnavailable {
+            upstream: self.name,
+            reason,
+        }
+    }
+}
+
+pub(crate) type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Applies a CircuitBreaker to every HTTP request made through a tower service (e.g. an Octocrab client).
+/// Server errors and transport errors count as failures.
+#[derive(Clone)]
+pub(crate) struct CircuitBreakerLayer(pub &'static CircuitBreaker);
+
+impl<S> tower::Layer<S> for CircuitBreakerLayer {
+    type Service = CircuitBreakerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreakerService {
+            inner,
+            breaker: self.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct CircuitBreakerService<S> {
+    inner: S,
+    breaker: &'static CircuitBreaker,
+}
+
+impl<S, Req, B> tower::Service<Req> for CircuitBreakerService<S>
+where
+    S: tower::Service<Req, Response = http::Response<B>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = http::Response<B>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        let breaker = self.breaker;
+        if let Err(err) = breaker.check() {
+            return Box::pin(async move { Err(err.into()) });
+        }
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let future = async {
+                match future.await {
+                    Ok(response) if response.status().is_server_error() => {
+                        Err(RequestFailure::ServerError(response))
+                    }
+                    Ok(response) => Ok(response),
+                    Err(err) => Err(RequestFailure::Transport(err.into())),
+                }
+            };
+            match breaker.call(future, |_| true).await? {
+                Ok(response) => Ok(response),
+                // Hand server errors back to the caller (e.g. so that retry logic can see them), having counted them as failures.
+                Err(RequestFailure::ServerError(response)) => Ok(response),
+                Err(RequestFailure::Transport(err)) => Err(err),
+            }
+        })
+    }
+}
+
+enum RequestFailure<B> {
+    ServerError(http::Response<B>),
+    Transport(BoxError),
+}